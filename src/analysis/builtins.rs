@@ -0,0 +1,394 @@
+use std::{collections::HashMap, sync::OnceLock};
+
+use derive_more::Display;
+use serde::Deserialize;
+
+use crate::ast::{visit::*, *};
+
+use super::{calls::arity_message, ClassTable, Diagnostic, Project, Resolution, ValueType};
+
+/// The most recent version of LeekScript.
+pub const LATEST_VERSION: u32 = 4;
+
+/// The LeekScript versions in which a builtin can be used.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Availability {
+    pub introduced: u32,
+    pub deprecated: Option<u32>,
+    pub removed: Option<u32>,
+    /// What to use instead once it is deprecated
+    pub replacement: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BuiltinParameter {
+    pub name: String,
+    pub type_: ValueType,
+}
+
+/// A function given by the fight engine.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BuiltinFunction {
+    pub name: String,
+    pub parameters: Vec<BuiltinParameter>,
+    /// How many parameters have to be given, the others can be left out
+    pub required: usize,
+    pub return_type: ValueType,
+    /// The operations a call costs
+    pub ops: u32,
+    pub availability: Availability,
+}
+
+/// A constant given by the fight engine.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BuiltinConstant {
+    pub name: String,
+    pub type_: ValueType,
+    pub availability: Availability,
+}
+
+#[derive(Debug, Clone, PartialEq, Display)]
+pub enum BuiltinsError {
+    #[display(fmt = "invalid builtins: {}", _0)]
+    Toml(String),
+    #[display(fmt = "invalid type `{}` in `{}`", type_, name)]
+    InvalidType { name: String, type_: String },
+    #[display(fmt = "invalid parameter `{}` of `{}`", parameter, name)]
+    InvalidParameter { name: String, parameter: String },
+    #[display(fmt = "`{}` has more optional parameters than parameters", _0)]
+    TooManyOptional(String),
+    #[display(fmt = "`{}` is declared twice", _0)]
+    Duplicate(String),
+}
+
+impl std::error::Error for BuiltinsError {}
+
+/// The content of a catalogue like the bundled `data/builtins.toml`.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Catalogue {
+    #[serde(default)]
+    function: Vec<FunctionEntry>,
+    #[serde(default)]
+    constant: Vec<ConstantEntry>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FunctionEntry {
+    name: String,
+    /// Written `type name`
+    #[serde(default)]
+    parameters: Vec<String>,
+    #[serde(default)]
+    optional: usize,
+    #[serde(rename = "return")]
+    return_type: String,
+    ops: u32,
+    #[serde(default = "first_version")]
+    introduced: u32,
+    deprecated: Option<u32>,
+    removed: Option<u32>,
+    replacement: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConstantEntry {
+    name: String,
+    #[serde(rename = "type")]
+    type_: String,
+    #[serde(default = "first_version")]
+    introduced: u32,
+    deprecated: Option<u32>,
+    removed: Option<u32>,
+    replacement: Option<String>,
+}
+
+fn first_version() -> u32 {
+    1
+}
+
+fn parse_type(name: &str, type_: &str) -> Result<ValueType, BuiltinsError> {
+    match Type::parse(Span::new_extra(type_, "")) {
+        Ok((rest, parsed)) if rest.is_empty() => Ok(ValueType::from_annotation(&parsed.node)),
+        _ => Err(BuiltinsError::InvalidType { name: name.to_string(), type_: type_.to_string() }),
+    }
+}
+
+/// The functions and constants known to exist without being declared.
+#[derive(Debug, Clone, Default)]
+pub struct Builtins {
+    functions: Vec<BuiltinFunction>,
+    constants: Vec<BuiltinConstant>,
+    function_names: HashMap<String, usize>,
+    constant_names: HashMap<String, usize>,
+}
+
+impl Builtins {
+    /// The catalogue of the LeekWars API bundled with the crate.
+    pub fn leekwars() -> &'static Builtins {
+        static BUILTINS: OnceLock<Builtins> = OnceLock::new();
+        BUILTINS.get_or_init(|| {
+            Builtins::from_toml(include_str!("../../data/builtins.toml")).expect("the bundled builtins are valid")
+        })
+    }
+
+    pub fn from_toml(text: &str) -> Result<Self, BuiltinsError> {
+        let catalogue: Catalogue = toml::from_str(text).map_err(|error| BuiltinsError::Toml(error.message().to_string()))?;
+        let mut builtins = Builtins::default();
+
+        for entry in catalogue.function {
+            let mut parameters = Vec::new();
+            for parameter in &entry.parameters {
+                let invalid = || BuiltinsError::InvalidParameter { name: entry.name.clone(), parameter: parameter.clone() };
+                let (type_, name) = parameter.trim().rsplit_once(' ').ok_or_else(invalid)?;
+                parameters.push(BuiltinParameter { name: name.to_string(), type_: parse_type(&entry.name, type_.trim())? });
+            }
+            let required = parameters.len().checked_sub(entry.optional)
+                .ok_or_else(|| BuiltinsError::TooManyOptional(entry.name.clone()))?;
+            let function = BuiltinFunction {
+                return_type: parse_type(&entry.name, &entry.return_type)?,
+                name: entry.name,
+                parameters,
+                required,
+                ops: entry.ops,
+                availability: Availability {
+                    introduced: entry.introduced,
+                    deprecated: entry.deprecated,
+                    removed: entry.removed,
+                    replacement: entry.replacement,
+                },
+            };
+            builtins.check_unique(&function.name)?;
+            builtins.function_names.insert(function.name.clone(), builtins.functions.len());
+            builtins.functions.push(function);
+        }
+
+        for entry in catalogue.constant {
+            let constant = BuiltinConstant {
+                type_: parse_type(&entry.name, &entry.type_)?,
+                name: entry.name,
+                availability: Availability {
+                    introduced: entry.introduced,
+                    deprecated: entry.deprecated,
+                    removed: entry.removed,
+                    replacement: entry.replacement,
+                },
+            };
+            builtins.check_unique(&constant.name)?;
+            builtins.constant_names.insert(constant.name.clone(), builtins.constants.len());
+            builtins.constants.push(constant);
+        }
+
+        Ok(builtins)
+    }
+
+    fn check_unique(&self, name: &str) -> Result<(), BuiltinsError> {
+        match self.function_names.contains_key(name) || self.constant_names.contains_key(name) {
+            true => Err(BuiltinsError::Duplicate(name.to_string())),
+            false => Ok(()),
+        }
+    }
+
+    pub fn function(&self, name: &str) -> Option<&BuiltinFunction> {
+        self.function_names.get(name).map(|&index| &self.functions[index])
+    }
+
+    pub fn constant(&self, name: &str) -> Option<&BuiltinConstant> {
+        self.constant_names.get(name).map(|&index| &self.constants[index])
+    }
+
+    pub fn functions(&self) -> impl Iterator<Item = &BuiltinFunction> {
+        self.functions.iter()
+    }
+
+    pub fn constants(&self) -> impl Iterator<Item = &BuiltinConstant> {
+        self.constants.iter()
+    }
+}
+
+/// Checks the names a project uses without declaring them against `builtins` as they are in
+/// LeekScript `version`: calls to unknown functions or with a wrong number of arguments, and
+/// functions or constants missing from that version or deprecated in it.
+pub fn check_builtins(project: &Project, builtins: &Builtins, version: u32) -> Vec<Diagnostic> {
+    let files: Vec<&File> = project.files.iter().map(|source| &source.file).collect();
+    let resolution = Resolution::resolve_files(&files);
+    let (table, _) = ClassTable::build(project);
+    let mut diagnostics = Vec::new();
+
+    for (index, source) in project.files.iter().enumerate() {
+        let undeclared = resolution.unresolved()
+            .filter(|reference| reference.file == index)
+            .filter_map(|reference| reference.location)
+            .map(|location| location.start.offset)
+            .collect();
+        let mut checker = BuiltinChecker {
+            builtins,
+            version,
+            table: &table,
+            undeclared,
+            class: None,
+            called: None,
+            diagnostics: Vec::new(),
+        };
+        checker.visit_file(&source.file);
+        diagnostics.extend(checker.diagnostics.into_iter().map(|diagnostic| diagnostic.in_file(&source.path)));
+    }
+    diagnostics
+}
+
+struct BuiltinChecker<'a> {
+    builtins: &'a Builtins,
+    version: u32,
+    table: &'a ClassTable<'a>,
+    /// Where the names that are not declared by the project start
+    undeclared: Vec<usize>,
+    class: Option<&'a str>,
+    /// The name of the call being visited, already checked
+    called: Option<Location>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl BuiltinChecker<'_> {
+    fn undeclared(&self, name: &MetaNode<Identifier>) -> bool {
+        let member = self.class.is_some_and(|class| {
+            self.table.find_member(class, &name.node.name, false).is_some() || self.table.find_member(class, &name.node.name, true).is_some()
+        });
+        !member && name.location.is_some_and(|location| self.undeclared.contains(&location.start.offset))
+    }
+
+    fn check_availability(&mut self, name: &MetaNode<Identifier>, availability: &Availability) {
+        let instead = match &availability.replacement {
+            Some(replacement) => format!(", use `{}` instead", replacement),
+            None => String::new(),
+        };
+        if availability.introduced > self.version {
+            let message = format!("`{}` is not available before LeekScript {}", name.node.name, availability.introduced);
+            self.diagnostics.push(Diagnostic::error(message, name.location));
+        } else if let Some(removed) = availability.removed.filter(|&removed| removed <= self.version) {
+            let message = format!("`{}` was removed in LeekScript {}{}", name.node.name, removed, instead);
+            self.diagnostics.push(Diagnostic::error(message, name.location));
+        } else if let Some(deprecated) = availability.deprecated.filter(|&deprecated| deprecated <= self.version) {
+            let message = format!("`{}` is deprecated since LeekScript {}{}", name.node.name, deprecated, instead);
+            self.diagnostics.push(Diagnostic::warning(message, name.location));
+        }
+    }
+
+    fn check_call(&mut self, name: &MetaNode<Identifier>, arguments: usize) {
+        let builtins = self.builtins;
+        let Some(function) = builtins.function(&name.node.name) else {
+            let message = match builtins.constant(&name.node.name) {
+                Some(_) => format!("`{}` is a constant, not a function", name.node.name),
+                None => format!("unknown function `{}`", name.node.name),
+            };
+            self.diagnostics.push(Diagnostic::error(message, name.location));
+            return;
+        };
+
+        self.check_availability(name, &function.availability);
+        let (required, total) = (function.required, function.parameters.len());
+        if arguments < required || arguments > total {
+            let message = arity_message(&name.node.name, required, total, arguments);
+            self.diagnostics.push(Diagnostic::error(message, name.location));
+        }
+    }
+}
+
+impl<'a> Visitor<'a> for BuiltinChecker<'a> {
+    fn visit_class(&mut self, class: &'a Class) {
+        let outer = self.class.replace(&class.name.node.name);
+        walk_class(self, class);
+        self.class = outer;
+    }
+
+    fn visit_member(&mut self, member: &'a Member) {
+        if let (PrimaryExpr::IdentifierOrMember(object), Some(MemberRight::Call(arguments))) =
+            (&member.left, member.right.first().map(|right| right.node.as_ref()))
+        {
+            if let IdentifierOrMember::Identifier(name) = object.node.as_ref() {
+                if self.undeclared(name) {
+                    self.check_call(name, arguments.len());
+                    self.called = name.location;
+                }
+            }
+        }
+        walk_member(self, member);
+    }
+
+    fn visit_identifier(&mut self, name: &'a MetaNode<Identifier>) {
+        if self.called.is_some() && self.called == name.location {
+            self.called = None;
+        } else if self.undeclared(name) {
+            if let Some(function) = self.builtins.function(&name.node.name) {
+                self.check_availability(name, &function.availability);
+            } else if let Some(constant) = self.builtins.constant(&name.node.name) {
+                self.check_availability(name, &constant.availability);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::project::tests::project;
+
+    use super::*;
+
+    fn messages(files: &[(&str, &str)], version: u32) -> Vec<String> {
+        check_builtins(&project(files), Builtins::leekwars(), version).into_iter().map(|d| d.to_string()).collect()
+    }
+
+    #[test]
+    fn test_bundled() {
+        let builtins = Builtins::leekwars();
+        let get_cell = builtins.function("getCell").unwrap();
+        assert_eq!((get_cell.required, get_cell.parameters.len()), (0, 1));
+        assert_eq!(get_cell.parameters[0].type_, ValueType::Integer);
+        assert_eq!(get_cell.return_type, ValueType::Integer.nullable());
+        assert_eq!(builtins.function("getLeek").unwrap().availability.replacement.as_deref(), Some("getEntity"));
+        assert_eq!(builtins.constant("PI").unwrap().type_, ValueType::Real);
+        assert!(builtins.function("PI").is_none());
+    }
+
+    #[test]
+    fn test_invalid() {
+        let error = |text| Builtins::from_toml(text).unwrap_err().to_string();
+        assert_eq!(error("function = [{ name = \"f\", return = \"integer<\", ops = 1 }]"), "invalid type `integer<` in `f`");
+        assert_eq!(error("function = [{ name = \"f\", parameters = [\"x\"], return = \"any\", ops = 1 }]"), "invalid parameter `x` of `f`");
+        assert_eq!(error("function = [{ name = \"f\", optional = 1, return = \"any\", ops = 1 }]"), "`f` has more optional parameters than parameters");
+        assert_eq!(
+            error("function = [{ name = \"f\", return = \"any\", ops = 1 }]\nconstant = [{ name = \"f\", type = \"any\" }]"),
+            "`f` is declared twice"
+        );
+        assert!(error("function = [{ name = \"f\", return = \"any\" }]").starts_with("invalid builtins: missing field `ops`"));
+    }
+
+    #[test]
+    fn test_check() {
+        let source = "var e = getNearestEnemy();\nmoveToward(e);\nuseWeapon();\ngetCell(e, 1);\nfoo(e);\nPI();\n\
+            getLeek();\nvar m = mapKeys;\ngetInstructionsCount();";
+        assert_eq!(messages(&[("main", source)], 3), vec![
+            "main:3:1: error: `useWeapon` takes 1 argument but 0 were given",
+            "main:4:1: error: `getCell` takes 0 to 1 arguments but 2 were given",
+            "main:5:1: error: unknown function `foo`",
+            "main:6:1: error: `PI` is a constant, not a function",
+            "main:7:1: warning: `getLeek` is deprecated since LeekScript 2, use `getEntity` instead",
+            "main:8:9: error: `mapKeys` is not available before LeekScript 4",
+            "main:9:1: error: `getInstructionsCount` was removed in LeekScript 2, use `getOperations` instead",
+        ]);
+        assert_eq!(messages(&[("main", "getLeek(); mapKeys([:]);")], 4), vec![
+            "main:1:1: error: `getLeek` was removed in LeekScript 4, use `getEntity` instead",
+        ]);
+    }
+
+    #[test]
+    fn test_declared_names() {
+        // Functions of the project, including the ones named like a builtin, and methods
+        let files = [
+            ("main", "include('lib'); helper(1, 2); function getCell() { return 1; } getCell(1, 2);"),
+            ("lib", "function helper(a, b) {} class A { m() { n(); } static n() {} }"),
+        ];
+        assert!(messages(&files, LATEST_VERSION).is_empty());
+    }
+}
@@ -0,0 +1,373 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    ast::{scan::code_end, visit::*, *},
+    lint::{apply_fixes, Edit, Fix},
+};
+
+use super::{includes, parse_source, resolve_include, BindingKind, Project, ProjectError, Resolution, ScopeId, ROOT_SCOPE, TOP_LEVEL_SCOPE};
+
+/// A project printed as a single file.
+#[derive(Debug, Clone)]
+pub struct Bundle {
+    pub file: File,
+    /// The top-level names given another name because an earlier file already declares them
+    pub renames: Vec<BundleRename>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BundleRename {
+    pub path: String,
+    pub name: String,
+    pub new_name: String,
+}
+
+/// A top-level name used or declared by a file.
+struct Occurrence {
+    offset: usize,
+    name: String,
+    /// Whether the file declares the name itself
+    own: bool,
+    reach: Reach,
+}
+
+/// The declarations an occurrence can refer to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Reach {
+    /// Any, from the top-level code and the anonymous functions in it
+    TopLevel,
+    /// Functions, classes and globals, from named functions and methods
+    Globals,
+    /// Classes, from types
+    Classes,
+}
+
+/// A top-level declaration of a file.
+struct Declaration {
+    name: String,
+    kind: BindingKind,
+    /// Whether it is a variable of the top-level code rather than a global
+    top_level: bool,
+}
+
+impl Declaration {
+    fn reached(&self, reach: Reach) -> bool {
+        match reach {
+            Reach::TopLevel => true,
+            Reach::Globals => !self.top_level,
+            Reach::Classes => self.kind == BindingKind::Class,
+        }
+    }
+}
+
+/// Puts the files of a project in a single file: each file comes once, in place of the first
+/// `include` statement met for it, with comments marking where it begins and ends. The other
+/// `include` statements are removed. A top-level name declared by several files keeps its name
+/// in the first one to be complete, counting the files it includes, and gets a new one in the
+/// others, along with the uses each file makes of its own declaration. The uses of a name a file
+/// does not declare go to the declaration of the closest file it includes, or else of the first
+/// file declaring it.
+pub fn bundle(project: &Project) -> Result<Bundle, ProjectError> {
+    let order = dependency_order(project);
+
+    // What each file declares and uses at the top level, from the file alone
+    let mut declared: Vec<Vec<Declaration>> = Vec::new();
+    let mut occurrences: Vec<Vec<Occurrence>> = Vec::new();
+    let mut used: HashSet<String> = HashSet::new();
+    for source in &project.files {
+        let resolution = Resolution::resolve(&source.file);
+        used.extend(resolution.bindings.iter().map(|binding| binding.name.clone()));
+        used.extend(resolution.references.iter().map(|reference| reference.name.clone()));
+
+        let is_top_level = |scope: ScopeId| scope == ROOT_SCOPE || scope == TOP_LEVEL_SCOPE;
+        let mut file_occurrences = Vec::new();
+        let mut file_declared = Vec::new();
+        for binding in resolution.bindings.iter().filter(|binding| is_top_level(binding.scope)) {
+            file_declared.push(Declaration {
+                name: binding.name.clone(),
+                kind: binding.kind,
+                top_level: binding.scope == TOP_LEVEL_SCOPE,
+            });
+            if let Some(location) = binding.location {
+                file_occurrences.push(Occurrence {
+                    offset: location.start.offset,
+                    name: binding.name.clone(),
+                    own: true,
+                    reach: Reach::TopLevel,
+                });
+            }
+        }
+        for reference in &resolution.references {
+            let (Some(location), own) = (reference.location, reference.binding) else { continue };
+            let own = match own {
+                Some(binding) if is_top_level(resolution.bindings[binding].scope) => true,
+                Some(_) => continue,
+                None => false,
+            };
+            file_occurrences.push(Occurrence {
+                offset: location.start.offset,
+                name: reference.name.clone(),
+                own,
+                reach: match sees_top_level(&resolution, reference.scope) {
+                    true => Reach::TopLevel,
+                    false => Reach::Globals,
+                },
+            });
+        }
+        let mut types = TypeNames { resolution: &resolution, occurrences: Vec::new() };
+        types.visit_file(&source.file);
+        file_occurrences.extend(types.occurrences);
+
+        declared.push(file_declared);
+        occurrences.push(file_occurrences);
+    }
+
+    // The name each file gives to its declarations in the bundle
+    let mut names: Vec<HashMap<String, String>> = vec![HashMap::new(); project.files.len()];
+    let mut taken: HashSet<String> = HashSet::new();
+    let mut renames = Vec::new();
+    for &index in &order {
+        for Declaration { name, .. } in &declared[index] {
+            if names[index].contains_key(name) {
+                continue;
+            }
+            let new_name = match taken.contains(name) {
+                false => name.clone(),
+                true => (2..).map(|n| format!("{}_{}", name, n)).find(|new_name| !used.contains(new_name)).unwrap(),
+            };
+            if &new_name != name {
+                renames.push(BundleRename {
+                    path: project.files[index].path.clone(),
+                    name: name.clone(),
+                    new_name: new_name.clone(),
+                });
+                used.insert(new_name.clone());
+            }
+            taken.insert(name.clone());
+            names[index].insert(name.clone(), new_name);
+        }
+    }
+
+    let mut edits = vec![Vec::new(); project.files.len()];
+    for &index in &order {
+        let reached = reached_files(project, index);
+
+        for occurrence in &occurrences[index] {
+            let declarer = match occurrence.own {
+                true => Some(index),
+                // The closest included file first, then any other one
+                false => order.iter().rev().filter(|file| reached.contains(file))
+                    .chain(&order)
+                    .copied()
+                    .find(|&file| {
+                        declared[file].iter()
+                            .any(|declaration| declaration.name == occurrence.name && declaration.reached(occurrence.reach))
+                    }),
+            };
+            let Some(new_name) = declarer.and_then(|file| names[file].get(&occurrence.name)) else { continue };
+            if *new_name != occurrence.name {
+                edits[index].push(Edit {
+                    start: occurrence.offset,
+                    end: occurrence.offset + occurrence.name.len(),
+                    text: new_name.clone(),
+                });
+            }
+        }
+        // A declaration is also a reference for some kinds of names
+        edits[index].sort_by_key(|edit| edit.start);
+        edits[index].dedup_by_key(|edit| edit.start);
+    }
+
+    let text = bundled_file(project, 0, &edits, &mut HashSet::new());
+    let file = parse_source(&project.entry().path, &text)?;
+    Ok(Bundle { file, renames })
+}
+
+/// The text of the file `index` with its renames made, and the files it includes first in place
+/// of its `include` statements.
+fn bundled_file(project: &Project, index: usize, renames: &[Vec<Edit>], emitted: &mut HashSet<usize>) -> String {
+    emitted.insert(index);
+    let source = &project.files[index];
+    let original = source.file.to_string();
+    let mut edits = renames[index].clone();
+    for statement in &source.file.statements {
+        let (GlobalStatement::IncludeStatement(include), Some(location)) = (statement.node.as_ref(), statement.location) else {
+            continue;
+        };
+        let mut edit = remove_line(&original, location.start.offset, location.end.offset);
+        let path = resolve_include(&source.path, &include.path.node.decoded());
+        let included = project.files.iter().position(|file| file.path == path);
+        if let Some(included) = included.filter(|included| !emitted.contains(included)) {
+            edit.text = bundled_file(project, included, renames, emitted);
+            // The included file takes lines of its own
+            if edit.start > 0 && !original[..edit.start].ends_with('\n') {
+                edit.start = original[..edit.start].trim_end_matches([' ', '\t']).len();
+                edit.text.insert(0, '\n');
+            }
+            if !original[..edit.end].ends_with('\n') {
+                let rest = original[edit.end..].trim_start_matches([' ', '\t']);
+                edit.end = original.len() - rest.len() + usize::from(rest.starts_with('\n'));
+            }
+        }
+        edits.push(edit);
+    }
+
+    let mut text = format!("// begin {}\n", source.path);
+    text.push_str(&apply_fixes(&original, &[Fix { location: None, edits }]));
+    if !text.ends_with('\n') {
+        text.push('\n');
+    }
+    text.push_str(&format!("// end {}\n", source.path));
+    text
+}
+
+/// The files of the project, each one after the files it includes, which is the order their
+/// declarations get their names in.
+fn dependency_order(project: &Project) -> Vec<usize> {
+    fn visit(project: &Project, index: usize, seen: &mut HashSet<usize>, order: &mut Vec<usize>) {
+        if !seen.insert(index) {
+            return;
+        }
+        let source = &project.files[index];
+        for include in includes(&source.file) {
+            let path = resolve_include(&source.path, &include.path.node.decoded());
+            if let Some(included) = project.files.iter().position(|file| file.path == path) {
+                visit(project, included, seen, order);
+            }
+        }
+        order.push(index);
+    }
+
+    let mut order = Vec::new();
+    visit(project, 0, &mut HashSet::new(), &mut order);
+    order
+}
+
+/// The files `index` includes, directly or not.
+fn reached_files(project: &Project, index: usize) -> HashSet<usize> {
+    let mut reached = HashSet::new();
+    let mut pending = vec![index];
+    while let Some(index) = pending.pop() {
+        let source = &project.files[index];
+        for include in includes(&source.file) {
+            let path = resolve_include(&source.path, &include.path.node.decoded());
+            if let Some(included) = project.files.iter().position(|file| file.path == path) {
+                if reached.insert(included) {
+                    pending.push(included);
+                }
+            }
+        }
+    }
+    reached.remove(&index);
+    reached
+}
+
+/// Whether the code in `scope` sees the top-level variables, which named functions do not.
+fn sees_top_level(resolution: &Resolution, scope: ScopeId) -> bool {
+    let mut current = Some(scope);
+    while let Some(scope) = current {
+        if scope == TOP_LEVEL_SCOPE {
+            return true;
+        }
+        current = resolution.scopes[scope].parent;
+    }
+    false
+}
+
+/// Removes the code from `start` to `end`, with its line when nothing else is on it.
+fn remove_line(source: &str, start: usize, end: usize) -> Edit {
+    let end = start + code_end(&source[start..end]);
+    let line_start = source[..start].rfind('\n').map_or(0, |index| index + 1);
+    let line_end = source[end..].find('\n').map_or(source.len(), |index| end + index + 1);
+    match source[line_start..start].trim().is_empty() && source[end..line_end].trim().is_empty() {
+        true => Edit { start: line_start, end: line_end, text: String::new() },
+        false => Edit { start, end, text: String::new() },
+    }
+}
+
+/// The class names used as types.
+struct TypeNames<'a> {
+    resolution: &'a Resolution,
+    occurrences: Vec<Occurrence>,
+}
+
+impl<'ast> Visitor<'ast> for TypeNames<'_> {
+    fn visit_type(&mut self, type_: &'ast MetaNode<Type>) {
+        let name = &type_.node.type_;
+        if let Some(location) = name.location {
+            let own = self.resolution.lookup(ROOT_SCOPE, &name.node.name)
+                .is_some_and(|binding| self.resolution.bindings[binding].kind == BindingKind::Class);
+            self.occurrences.push(Occurrence {
+                offset: location.start.offset,
+                name: name.node.name.clone(),
+                own,
+                reach: Reach::Classes,
+            });
+        }
+        for generic in &type_.node.generics {
+            self.visit_type(generic);
+        }
+        if let Some(alternative) = &type_.node.alternative {
+            self.visit_type(alternative);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::project::tests::project;
+
+    use super::*;
+
+    #[test]
+    fn test_order() {
+        let files = [
+            ("main", "include('a');\ninclude('lib/b');\n\nf(); // go\n"),
+            ("a", "include('lib/b'); // b first\nfunction f() { return g(); }"),
+            ("lib/b", "include('../a');\nfunction g() { return 1; }"),
+        ];
+        let bundle = bundle(&project(&files)).unwrap();
+        assert_eq!(
+            bundle.file.to_string(),
+            "// begin main\n// begin a\n// begin lib/b\nfunction g() { return 1; }\n// end lib/b\n\
+             // b first\nfunction f() { return g(); }\n// end a\n\nf(); // go\n// end main\n"
+        );
+        assert!(bundle.renames.is_empty());
+    }
+
+    #[test]
+    fn test_code_before_include() {
+        let files = [
+            ("main", "debug('a');\ninclude('lib');\ndebug('c'); include('other');\n"),
+            ("lib", "debug('b');"),
+            ("other", "include('lib');\ndebug('d');"),
+        ];
+        assert_eq!(
+            bundle(&project(&files)).unwrap().file.to_string(),
+            "// begin main\ndebug('a');\n// begin lib\ndebug('b');\n// end lib\n\
+             debug('c');\n// begin other\ndebug('d');\n// end other\n// end main\n"
+        );
+    }
+
+    #[test]
+    fn test_collisions() {
+        let files = [
+            ("main", "include('a');\ninclude('b');\nvar count = size();\nfunction f() { return size() + count_2; }"),
+            ("a", "var count = 1;\nfunction size() { return count; }\nclass Item {}"),
+            ("b", "include('a');\nvar count = 2;\nfunction size() { return 2; }\nclass Item {}\n\
+                   Item item = new Item(); function g() { return size() + count; }"),
+        ];
+        let bundle = bundle(&project(&files)).unwrap();
+        assert_eq!(
+            bundle.file.to_string(),
+            "// begin main\n// begin a\nvar count = 1;\nfunction size() { return count; }\nclass Item {}\n// end a\n\
+             // begin b\nvar count_3 = 2;\nfunction size_2() { return 2; }\nclass Item_2 {}\n\
+             Item_2 item = new Item_2(); function g() { return size_2() + count; }\n// end b\n\
+             var count_4 = size_2();\nfunction f() { return size_2() + count_2; }\n// end main\n"
+        );
+        let renames: Vec<_> = bundle.renames.iter()
+            .map(|rename| format!("{}: {} -> {}", rename.path, rename.name, rename.new_name))
+            .collect();
+        assert_eq!(renames, ["b: size -> size_2", "b: Item -> Item_2", "b: count -> count_3", "main: count -> count_4"]);
+    }
+}
@@ -0,0 +1,586 @@
+use std::{collections::{BTreeSet, HashMap}, fmt::Write};
+
+use derive_more::Display;
+use serde::Serialize;
+
+use crate::ast::{visit::*, *};
+
+use super::{Binding, BindingKind, ClassTable, Project, Resolution};
+
+pub type NodeId = usize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Display)]
+#[serde(rename_all = "lowercase")]
+pub enum CallableKind {
+    /// The top-level code of a file
+    #[display(fmt = "main")] Main,
+    #[display(fmt = "function")] Function,
+    #[display(fmt = "method")] Method,
+    #[display(fmt = "constructor")] Constructor,
+    #[display(fmt = "closure")] Closure,
+}
+
+/// Code that can be run on its own and call other code.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Callable {
+    /// The name of a function, `Class.method` for methods and constructors, where it is written
+    /// for closures, and the path of the file for its top-level code.
+    pub name: String,
+    pub kind: CallableKind,
+    pub file: String,
+    pub location: Option<Location>,
+}
+
+/// Code calling itself, directly or through others.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Recursion {
+    Direct(NodeId),
+    /// Callables calling each other, in the order of the graph
+    Mutual(Vec<NodeId>),
+}
+
+/// Who calls whom in a project.
+///
+/// Calls are found through the names they use, so a function or method is also called wherever
+/// it is used as a value, and a closure by the code that creates it. Only the callees invoked
+/// right where they are named count for recursion. Methods are only known when reached through
+/// `this`, `super` or a class name, and a call through `this` may also run the methods
+/// overriding the called one.
+#[derive(Debug, Clone, Default)]
+pub struct CallGraph {
+    pub nodes: Vec<Callable>,
+    /// The callees of each node, sorted
+    calls: Vec<Vec<NodeId>>,
+    /// The callees each node invokes itself rather than creates or uses as a value, sorted
+    invocations: Vec<Vec<NodeId>>,
+}
+
+impl CallGraph {
+    pub fn build(project: &Project) -> Self {
+        let files: Vec<&File> = project.files.iter().map(|source| &source.file).collect();
+        let resolution = Resolution::resolve_files(&files);
+        let (table, _) = ClassTable::build(project);
+        let mut builder = GraphBuilder {
+            graph: CallGraph::default(),
+            resolution: &resolution,
+            table: &table,
+            functions: HashMap::new(),
+            methods: HashMap::new(),
+            constructors: HashMap::new(),
+            file: 0,
+            path: "",
+            class: None,
+            current: Vec::new(),
+            invoked: None,
+        };
+
+        let mut mains = Vec::new();
+        for (index, source) in project.files.iter().enumerate() {
+            mains.push(builder.graph.add(Callable {
+                name: source.path.clone(),
+                kind: CallableKind::Main,
+                file: source.path.clone(),
+                location: None,
+            }));
+            for statement in &source.file.statements {
+                match statement.node.as_ref() {
+                    GlobalStatement::FunctionDefinition(function) => {
+                        let id = builder.graph.add(Callable {
+                            name: function.name.node.name.clone(),
+                            kind: CallableKind::Function,
+                            file: source.path.clone(),
+                            location: function.name.location,
+                        });
+                        if let Some(location) = function.name.location {
+                            builder.functions.insert((index, location.start.offset), id);
+                        }
+                    }
+                    GlobalStatement::ClassDefinition(class) => builder.declare_members(class, &source.path),
+                    _ => {}
+                }
+            }
+        }
+
+        for (index, source) in project.files.iter().enumerate() {
+            builder.file = index;
+            builder.path = &source.path;
+            builder.current = vec![mains[index]];
+            builder.invoked = None;
+            builder.visit_file(&source.file);
+        }
+
+        let mut graph = builder.graph;
+        for callees in graph.calls.iter_mut().chain(&mut graph.invocations) {
+            callees.sort_unstable();
+            callees.dedup();
+        }
+        graph
+    }
+
+    fn add(&mut self, callable: Callable) -> NodeId {
+        self.nodes.push(callable);
+        self.calls.push(Vec::new());
+        self.invocations.push(Vec::new());
+        self.nodes.len() - 1
+    }
+
+    /// The first callable of that name.
+    pub fn find(&self, name: &str) -> Option<NodeId> {
+        self.nodes.iter().position(|node| node.name == name)
+    }
+
+    pub fn callees(&self, node: NodeId) -> &[NodeId] {
+        &self.calls[node]
+    }
+
+    pub fn callers(&self, node: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        (0..self.nodes.len()).filter(move |&caller| self.calls[caller].contains(&node))
+    }
+
+    /// Everything a call to `node` may run, itself included only when it is recursive.
+    pub fn transitive_callees(&self, node: NodeId) -> BTreeSet<NodeId> {
+        let mut reached = BTreeSet::new();
+        let mut pending = self.calls[node].clone();
+        while let Some(callee) = pending.pop() {
+            if reached.insert(callee) {
+                pending.extend(&self.calls[callee]);
+            }
+        }
+        reached
+    }
+
+    /// The callables invoking themselves, alone or in groups invoking each other.
+    pub fn recursion(&self) -> Vec<Recursion> {
+        let mut recursions: Vec<_> = self.components().into_iter()
+            .filter_map(|mut component| match component.as_slice() {
+                [node] => self.invocations[*node].contains(node).then_some(Recursion::Direct(*node)),
+                _ => {
+                    component.sort_unstable();
+                    Some(Recursion::Mutual(component))
+                }
+            })
+            .collect();
+        recursions.sort_by_key(|recursion| match recursion {
+            Recursion::Direct(node) => *node,
+            Recursion::Mutual(nodes) => nodes[0],
+        });
+        recursions
+    }
+
+    /// The strongly connected components of the invocations, with Tarjan's algorithm.
+    fn components(&self) -> Vec<Vec<NodeId>> {
+        struct Tarjan<'g> {
+            graph: &'g CallGraph,
+            index: Vec<Option<usize>>,
+            low: Vec<usize>,
+            stack: Vec<NodeId>,
+            on_stack: Vec<bool>,
+            next: usize,
+            components: Vec<Vec<NodeId>>,
+        }
+
+        impl Tarjan<'_> {
+            fn visit(&mut self, node: NodeId) {
+                self.index[node] = Some(self.next);
+                self.low[node] = self.next;
+                self.next += 1;
+                self.stack.push(node);
+                self.on_stack[node] = true;
+
+                for &callee in &self.graph.invocations[node] {
+                    match self.index[callee] {
+                        None => {
+                            self.visit(callee);
+                            self.low[node] = self.low[node].min(self.low[callee]);
+                        }
+                        Some(index) if self.on_stack[callee] => self.low[node] = self.low[node].min(index),
+                        Some(_) => {}
+                    }
+                }
+
+                if Some(self.low[node]) == self.index[node] {
+                    let mut component = Vec::new();
+                    while let Some(member) = self.stack.pop() {
+                        self.on_stack[member] = false;
+                        component.push(member);
+                        if member == node {
+                            break;
+                        }
+                    }
+                    self.components.push(component);
+                }
+            }
+        }
+
+        let count = self.nodes.len();
+        let mut tarjan = Tarjan {
+            graph: self,
+            index: vec![None; count],
+            low: vec![0; count],
+            stack: Vec::new(),
+            on_stack: vec![false; count],
+            next: 0,
+            components: Vec::new(),
+        };
+        for node in 0..count {
+            if tarjan.index[node].is_none() {
+                tarjan.visit(node);
+            }
+        }
+        tarjan.components
+    }
+
+    /// The graph in the Graphviz format, for `dot -Tsvg`. Recursive callables are drawn in red,
+    /// and the callees only created or used as values with dashed edges.
+    pub fn to_dot(&self) -> String {
+        let recursive = self.recursive();
+        let mut dot = String::new();
+        writeln!(dot, "digraph calls {{").unwrap();
+        writeln!(dot, "    node [shape=box, fontname=monospace];").unwrap();
+        for (id, node) in self.nodes.iter().enumerate() {
+            let label = match node.kind {
+                CallableKind::Main => format!("{} (main)", node.name),
+                _ => node.name.clone(),
+            };
+            let color = if recursive.contains(&id) { ", color=red" } else { "" };
+            writeln!(dot, "    n{} [label=\"{}\"{}];", id, label.replace('\\', "\\\\").replace('"', "\\\""), color).unwrap();
+        }
+        for (caller, callees) in self.calls.iter().enumerate() {
+            for callee in callees {
+                let style = if self.invocations[caller].contains(callee) { "" } else { " [style=dashed]" };
+                writeln!(dot, "    n{} -> n{}{};", caller, callee, style).unwrap();
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// The graph as JSON: every callable with where it is, what it calls directly and
+    /// everything it may run.
+    pub fn to_json(&self) -> String {
+        #[derive(Serialize)]
+        struct Node<'g> {
+            id: NodeId,
+            name: &'g str,
+            kind: CallableKind,
+            file: &'g str,
+            line: Option<u32>,
+            column: Option<u32>,
+            calls: &'g [NodeId],
+            reaches: BTreeSet<NodeId>,
+            recursive: bool,
+        }
+
+        let recursive = self.recursive();
+        let nodes: Vec<_> = self.nodes.iter().enumerate()
+            .map(|(id, node)| Node {
+                id,
+                name: &node.name,
+                kind: node.kind,
+                file: &node.file,
+                line: node.location.map(|location| location.start.line),
+                column: node.location.map(|location| location.start.column),
+                calls: &self.calls[id],
+                reaches: self.transitive_callees(id),
+                recursive: recursive.contains(&id),
+            })
+            .collect();
+        serde_json::to_string_pretty(&nodes).expect("the graph can be serialized")
+    }
+
+    fn recursive(&self) -> BTreeSet<NodeId> {
+        self.recursion().into_iter()
+            .flat_map(|recursion| match recursion {
+                Recursion::Direct(node) => vec![node],
+                Recursion::Mutual(nodes) => nodes,
+            })
+            .collect()
+    }
+}
+
+struct GraphBuilder<'a> {
+    graph: CallGraph,
+    resolution: &'a Resolution,
+    table: &'a ClassTable<'a>,
+    /// The functions by the file and offset of their name
+    functions: HashMap<(usize, usize), NodeId>,
+    /// The methods by class, name and whether they are static
+    methods: HashMap<(String, String, bool), Vec<NodeId>>,
+    constructors: HashMap<String, Vec<NodeId>>,
+    file: usize,
+    path: &'a str,
+    class: Option<&'a str>,
+    /// The callables whose code is being visited, innermost last
+    current: Vec<NodeId>,
+    /// The offset of the name or closure being called by the member visited
+    invoked: Option<usize>,
+}
+
+impl<'a> GraphBuilder<'a> {
+    fn declare_members(&mut self, class: &Class, path: &str) {
+        let class_name = &class.name.node.name;
+        for member in class.members() {
+            match member {
+                ClassMember::Method(method) => {
+                    let id = self.graph.add(Callable {
+                        name: format!("{}.{}", class_name, method.name.node.name),
+                        kind: CallableKind::Method,
+                        file: path.to_string(),
+                        location: method.name.location,
+                    });
+                    let key = (class_name.clone(), method.name.node.name.clone(), method.visibility.node.is_static());
+                    self.methods.entry(key).or_default().push(id);
+                }
+                ClassMember::Constructor(constructor) => {
+                    let id = self.graph.add(Callable {
+                        name: format!("{}.constructor", class_name),
+                        kind: CallableKind::Constructor,
+                        file: path.to_string(),
+                        location: constructor.parameters.location,
+                    });
+                    self.constructors.entry(class_name.clone()).or_default().push(id);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Adds the edges to `callees`, which only count for recursion when `invoked`.
+    fn call(&mut self, callees: impl IntoIterator<Item = NodeId>, invoked: bool) {
+        if let Some(&caller) = self.current.last() {
+            let start = self.graph.calls[caller].len();
+            self.graph.calls[caller].extend(callees);
+            if invoked {
+                let callees = self.graph.calls[caller][start..].to_vec();
+                self.graph.invocations[caller].extend(callees);
+            }
+        }
+    }
+
+    /// Runs `f` with the code visited belonging to `node`.
+    fn inside(&mut self, node: Option<NodeId>, f: impl FnOnce(&mut Self)) {
+        let pushed = node.is_some();
+        self.current.extend(node);
+        f(self);
+        if pushed {
+            self.current.pop();
+        }
+    }
+
+    /// The binding of `name` when it is of the kind given.
+    fn binding(&self, name: &MetaNode<Identifier>, kind: BindingKind) -> Option<&'a Binding> {
+        let resolution = self.resolution;
+        let reference = resolution.reference_at(self.file, name.location?.start.offset)?;
+        let binding = &resolution.bindings[reference.binding?];
+        (binding.kind == kind).then_some(binding)
+    }
+
+    fn methods(&self, class: &str, name: &str, static_: bool) -> Vec<NodeId> {
+        self.methods.get(&(class.to_string(), name.to_string(), static_)).cloned().unwrap_or_default()
+    }
+
+    fn access(&mut self, object: &IdentifierOrMember, name: &str, invoked: bool) {
+        if let IdentifierOrMember::Identifier(class) = object {
+            if self.binding(class, BindingKind::Class).is_none() {
+                return;
+            }
+        }
+        let Ok(resolved) = self.table.resolve_access(self.class, object, name) else { return };
+        let static_ = resolved.member.visibility().is_static();
+        let mut callees = self.methods(resolved.owner.name(), name, static_);
+
+        // The object may be of a subclass overriding the method
+        if let (IdentifierOrMember::This, Some(class), false) = (object, self.class, static_) {
+            let overriding: Vec<_> = self.table.classes()
+                .filter(|entry| entry.name() != class && self.table.is_subclass_of(entry.name(), class))
+                .flat_map(|entry| self.methods(entry.name(), name, false))
+                .collect();
+            callees.extend(overriding);
+        }
+        self.call(callees, invoked);
+    }
+
+    fn construct(&mut self, class: &MetaNode<Identifier>) {
+        if self.binding(class, BindingKind::Class).is_none() {
+            return;
+        }
+        let constructors = self.table.ancestors(&class.node.name)
+            .find_map(|entry| self.constructors.get(entry.name()))
+            .cloned()
+            .unwrap_or_default();
+        self.call(constructors, true);
+    }
+}
+
+impl<'a> Visitor<'a> for GraphBuilder<'a> {
+    fn visit_function_definition(&mut self, function: &'a FunctionDefinition) {
+        let node = function.name.location.and_then(|location| self.functions.get(&(self.file, location.start.offset)).copied());
+        self.inside(node, |builder| walk_function_definition(builder, function));
+    }
+
+    fn visit_class(&mut self, class: &'a Class) {
+        let outer = self.class.replace(&class.name.node.name);
+        walk_class(self, class);
+        self.class = outer;
+    }
+
+    fn visit_method(&mut self, method: &'a Method) {
+        let class = self.class.unwrap_or_default();
+        let node = self.methods.get(&(class.to_string(), method.name.node.name.clone(), method.visibility.node.is_static()))
+            .and_then(|nodes| nodes.iter().copied().find(|&node| self.graph.nodes[node].location == method.name.location));
+        self.inside(node, |builder| walk_method(builder, method));
+    }
+
+    fn visit_constructor(&mut self, constructor: &'a Constructor) {
+        let class = self.class.unwrap_or_default();
+        let node = self.constructors.get(class)
+            .and_then(|nodes| nodes.iter().copied().find(|&node| self.graph.nodes[node].location == constructor.parameters.location));
+        self.inside(node, |builder| walk_constructor(builder, constructor));
+    }
+
+    fn visit_anonymous_function(&mut self, function: &'a AnonymousFunction) {
+        let location = match function {
+            AnonymousFunction::ArrowFunction(arrow) => arrow.args.location,
+            AnonymousFunction::Function(function) => function.args.location,
+        };
+        let name = match location {
+            Some(location) => format!("closure at {}:{}", self.path, location.start),
+            None => format!("closure in {}", self.path),
+        };
+        let node = self.graph.add(Callable { name, kind: CallableKind::Closure, file: self.path.to_string(), location });
+        let invoked = location.is_some_and(|location| self.invoked == Some(location.start.offset));
+        self.call([node], invoked);
+        self.inside(Some(node), |builder| walk_anonymous_function(builder, function));
+    }
+
+    fn visit_expression(&mut self, expression: &'a Expression) {
+        if let Expression::Unary(unary) = expression {
+            if matches!(unary.op.node.as_ref(), UnaryOp::New) {
+                if let Expression::Member(Member { left: PrimaryExpr::IdentifierOrMember(class), .. })
+                | Expression::Primary(PrimaryExpr::IdentifierOrMember(class)) = unary.expr.as_ref()
+                {
+                    if let IdentifierOrMember::Identifier(class) = class.node.as_ref() {
+                        self.construct(class);
+                    }
+                }
+            }
+        }
+        walk_expression(self, expression);
+    }
+
+    fn visit_member(&mut self, member: &'a Member) {
+        let called = |index: usize| matches!(member.right.get(index).map(|right| right.node.as_ref()), Some(MemberRight::Call(_)));
+        if let (PrimaryExpr::IdentifierOrMember(object), Some(first)) = (&member.left, member.right.first()) {
+            if let MemberRight::Dot(name) | MemberRight::OptionalDot(name) = first.node.as_ref() {
+                if let IdentifierOrMember::Identifier(name) = name.node.as_ref() {
+                    self.access(object.node.as_ref(), &name.node.name, called(1));
+                }
+            }
+        }
+        if called(0) {
+            self.invoked = match &member.left {
+                PrimaryExpr::IdentifierOrMember(callee) => match callee.node.as_ref() {
+                    IdentifierOrMember::Identifier(name) => name.location.map(|location| location.start.offset),
+                    _ => None,
+                },
+                PrimaryExpr::ParenthesizedExpr(callee) => match callee.node.as_ref() {
+                    Expression::AnonyFunc(AnonymousFunction::ArrowFunction(arrow)) => arrow.args.location.map(|location| location.start.offset),
+                    Expression::AnonyFunc(AnonymousFunction::Function(function)) => function.args.location.map(|location| location.start.offset),
+                    _ => None,
+                },
+                _ => None,
+            };
+        }
+        walk_member(self, member);
+    }
+
+    fn visit_identifier(&mut self, name: &'a MetaNode<Identifier>) {
+        if let Some(binding) = self.binding(name, BindingKind::Function) {
+            let node = binding.location.and_then(|location| self.functions.get(&(binding.file, location.start.offset)).copied());
+            let invoked = name.location.is_some_and(|location| self.invoked == Some(location.start.offset));
+            self.call(node, invoked);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::project::tests::project;
+
+    use super::*;
+
+    fn names(graph: &CallGraph, nodes: impl IntoIterator<Item = NodeId>) -> Vec<&str> {
+        nodes.into_iter().map(|node| graph.nodes[node].name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_calls() {
+        let project = project(&[
+            ("main", "include('lib');\nvar f = function() { return a(); };\nf();\nvar x = new B();"),
+            ("lib", "function a() { return arrayMap([1], b); }\nfunction b(x) { return x; }\n\
+                class A { constructor() { this.m(); } m() {} static s() { return A.s; } }\n\
+                class B extends A { m() { super.m(); } }"),
+        ]);
+        let graph = CallGraph::build(&project);
+        let callees = |name| names(&graph, graph.callees(graph.find(name).unwrap()).iter().copied());
+
+        assert_eq!(callees("main"), vec!["A.constructor", "closure at main:2:17"]);
+        assert_eq!(callees("closure at main:2:17"), vec!["a"]);
+        assert_eq!(callees("a"), vec!["b"]);
+        assert_eq!(callees("A.constructor"), vec!["A.m", "B.m"]);
+        assert_eq!(callees("B.m"), vec!["A.m"]);
+        assert_eq!(callees("A.s"), vec!["A.s"]);
+        assert_eq!(names(&graph, graph.callers(graph.find("b").unwrap())), vec!["a"]);
+        assert_eq!(
+            names(&graph, graph.transitive_callees(graph.find("main").unwrap())),
+            vec!["a", "b", "A.constructor", "A.m", "B.m", "closure at main:2:17"]
+        );
+    }
+
+    #[test]
+    fn test_recursion() {
+        let project = project(&[
+            ("main", "include('lib'); function even(n) { return n == 0 || odd(n - 1); } function f() { return f(); }"),
+            ("lib", "function odd(n) { return n != 0 && even(n - 1); } function g() { return odd(1); }"),
+        ]);
+        let graph = CallGraph::build(&project);
+        let recursion: Vec<_> = graph.recursion().into_iter()
+            .map(|recursion| match recursion {
+                Recursion::Direct(node) => format!("direct {}", graph.nodes[node].name),
+                Recursion::Mutual(nodes) => format!("mutual {}", names(&graph, nodes).join(" ")),
+            })
+            .collect();
+        assert_eq!(recursion, vec!["mutual even odd", "direct f"]);
+        assert!(graph.transitive_callees(graph.find("g").unwrap()).contains(&graph.find("even").unwrap()));
+    }
+
+    #[test]
+    fn test_recursion_through_values() {
+        let project = project(&[(
+            "main",
+            "function f() { return () => f(); }\nfunction g() { return (() => g())(); }\n\
+            function h() { return arrayMap([1], h); }\nclass A { static s() { return A.s; } static t() { return A.t(); } }",
+        )]);
+        let graph = CallGraph::build(&project);
+        let recursive = names(&graph, graph.recursive());
+        assert_eq!(recursive, vec!["g", "A.t", "closure at main:2:24"]);
+        assert_eq!(names(&graph, graph.callees(graph.find("h").unwrap()).iter().copied()), vec!["h"]);
+    }
+
+    #[test]
+    fn test_export() {
+        let graph = CallGraph::build(&project(&[("main", "function f() { return f(); } f();")]));
+        assert_eq!(
+            graph.to_dot(),
+            "digraph calls {\n    node [shape=box, fontname=monospace];\n    \
+            n0 [label=\"main (main)\"];\n    n1 [label=\"f\", color=red];\n    n0 -> n1;\n    n1 -> n1;\n}\n"
+        );
+        let json: serde_json::Value = serde_json::from_str(&graph.to_json()).unwrap();
+        assert_eq!(json[1]["name"], "f");
+        assert_eq!(json[1]["kind"], "function");
+        assert_eq!(json[1]["line"], 1);
+        assert_eq!(json[0]["calls"], serde_json::json!([1]));
+        assert_eq!(json[0]["reaches"], serde_json::json!([1]));
+        assert_eq!(json[1]["recursive"], true);
+    }
+}
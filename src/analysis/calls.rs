@@ -0,0 +1,291 @@
+use std::collections::HashMap;
+
+use crate::ast::{visit::*, *};
+
+use super::{infer_type, Binding, BindingKind, ClassTable, Diagnostic, Project, Resolution, ValueType};
+
+/// What the called function, method or constructor takes.
+#[derive(Debug, Clone, Copy)]
+struct Signature<'a> {
+    parameters: &'a [MetaNode<Parameter>],
+}
+
+impl Signature<'_> {
+    fn required(&self) -> usize {
+        self.parameters.iter().filter(|parameter| parameter.node.default_value.is_none()).count()
+    }
+
+    fn accepts(&self, arguments: usize) -> bool {
+        self.required() <= arguments && arguments <= self.parameters.len()
+    }
+}
+
+/// How many arguments a function taking from `required` to `total` of them expects, and how
+/// many it was given.
+pub(crate) fn arity_message(name: &str, required: usize, total: usize, given: usize) -> String {
+    let expected = match required == total {
+        true => format!("{} argument{}", total, if total == 1 { "" } else { "s" }),
+        false => format!("{} to {} arguments", required, total),
+    };
+    let given = match given {
+        1 => "1 was given".to_string(),
+        _ => format!("{} were given", given),
+    };
+    format!("`{}` takes {} but {}", name, expected, given)
+}
+
+/// Checks the calls to the functions of a project, to the methods reached through `this`,
+/// `super` or a class name, and the `new` of its classes: the number of arguments has to be
+/// one the definition takes, and the arguments whose type is known have to fit the declared
+/// type of their parameter.
+pub fn check_calls(project: &Project) -> Vec<Diagnostic> {
+    let files: Vec<&File> = project.files.iter().map(|source| &source.file).collect();
+    let resolution = Resolution::resolve_files(&files);
+    let (table, _) = ClassTable::build(project);
+
+    let mut functions = HashMap::new();
+    for (index, file) in files.iter().enumerate() {
+        for statement in &file.statements {
+            if let GlobalStatement::FunctionDefinition(function) = statement.node.as_ref() {
+                if let Some(location) = function.name.location {
+                    functions.insert((index, location.start.offset), function);
+                }
+            }
+        }
+    }
+
+    let mut diagnostics = Vec::new();
+    for (index, source) in project.files.iter().enumerate() {
+        let mut checker = CallChecker {
+            resolution: &resolution,
+            table: &table,
+            functions: &functions,
+            file: index,
+            class: None,
+            diagnostics: Vec::new(),
+        };
+        checker.visit_file(&source.file);
+        diagnostics.extend(checker.diagnostics.into_iter().map(|diagnostic| diagnostic.in_file(&source.path)));
+    }
+    diagnostics
+}
+
+struct CallChecker<'a> {
+    resolution: &'a Resolution,
+    table: &'a ClassTable<'a>,
+    /// The functions of the project by the file and offset of their name
+    functions: &'a HashMap<(usize, usize), &'a FunctionDefinition>,
+    file: usize,
+    class: Option<&'a str>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> CallChecker<'a> {
+    /// What `name` refers to when it is of the kind given.
+    fn binding(&self, name: &MetaNode<Identifier>, kind: BindingKind) -> Option<&'a Binding> {
+        let resolution = self.resolution;
+        let reference = resolution.reference_at(self.file, name.location?.start.offset)?;
+        let binding = &resolution.bindings[reference.binding?];
+        (binding.kind == kind).then_some(binding)
+    }
+
+    fn check_function(&mut self, name: &MetaNode<Identifier>, arguments: &[MetaNode<Expression>]) {
+        let Some(binding) = self.binding(name, BindingKind::Function) else { return };
+        let Some(location) = binding.location else { return };
+        let Some(function) = self.functions.get(&(binding.file, location.start.offset)) else { return };
+        self.check(&name.node.name, name.location, &[Signature { parameters: &function.args.node.params }], arguments);
+    }
+
+    fn check_method(&mut self, object: &IdentifierOrMember, name: &MetaNode<Identifier>, arguments: &[MetaNode<Expression>]) {
+        if let IdentifierOrMember::Identifier(class) = object {
+            if self.binding(class, BindingKind::Class).is_none() {
+                return;
+            }
+        }
+        let Ok(resolved) = self.table.resolve_access(self.class, object, &name.node.name) else { return };
+        let static_ = resolved.member.visibility().is_static();
+
+        // Methods of the same name taking other arguments are overloads
+        let mut signatures = Vec::new();
+        for owner in self.table.ancestors(resolved.owner.name()) {
+            for member in owner.class.members() {
+                if let ClassMember::Method(method) = member {
+                    if method.name.node.name == name.node.name && method.visibility.node.is_static() == static_ {
+                        signatures.push(Signature { parameters: &method.parameters.node.params });
+                    }
+                }
+            }
+        }
+        let described = format!("{}.{}", resolved.owner.name(), name.node.name);
+        self.check(&described, name.location, &signatures, arguments);
+    }
+
+    fn check_new(&mut self, class: &MetaNode<Identifier>, arguments: &[MetaNode<Expression>]) {
+        if self.binding(class, BindingKind::Class).is_none() {
+            return;
+        }
+        // The constructors of the closest class declaring some, none takes no argument
+        let constructors = self.table.ancestors(&class.node.name)
+            .map(|entry| {
+                entry.class.members()
+                    .filter_map(|member| match member {
+                        ClassMember::Constructor(constructor) => {
+                            Some(Signature { parameters: &constructor.parameters.node.params })
+                        }
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .find(|constructors| !constructors.is_empty())
+            .unwrap_or_else(|| vec![Signature { parameters: &[] }]);
+        self.check(&format!("new {}", class.node.name), class.location, &constructors, arguments);
+    }
+
+    fn check(&mut self, name: &str, location: Option<Location>, signatures: &[Signature], arguments: &[MetaNode<Expression>]) {
+        let matching: Vec<_> = signatures.iter().filter(|signature| signature.accepts(arguments.len())).collect();
+        match (signatures, matching.as_slice()) {
+            (_, [signature]) => self.check_types(name, signature, arguments),
+            ([signature], []) => {
+                let message = arity_message(name, signature.required(), signature.parameters.len(), arguments.len());
+                self.diagnostics.push(Diagnostic::error(message, location));
+            }
+            (_, []) => {
+                let plural = if arguments.len() == 1 { "" } else { "s" };
+                let message = format!("no overload of `{}` takes {} argument{}", name, arguments.len(), plural);
+                self.diagnostics.push(Diagnostic::error(message, location));
+            }
+            // Nothing tells which one is called
+            _ => {}
+        }
+    }
+
+    fn check_types(&mut self, name: &str, signature: &Signature, arguments: &[MetaNode<Expression>]) {
+        for (position, (parameter, argument)) in signature.parameters.iter().zip(arguments).enumerate() {
+            let Some(type_) = &parameter.node.type_ else { continue };
+            let expected = ValueType::from_annotation(&type_.node);
+            let given = infer_type(&argument.node);
+            if !self.fits(&given, &expected) {
+                let message = format!(
+                    "argument {} of `{}` should be of type `{}` but is of type `{}`",
+                    position + 1, name, expected, given
+                );
+                self.diagnostics.push(Diagnostic::error(message, argument.location));
+            }
+        }
+    }
+
+    /// Whether a value of type `given` can be passed where `expected` is declared.
+    fn fits(&self, given: &ValueType, expected: &ValueType) -> bool {
+        match (given, expected) {
+            (ValueType::Any, _) | (_, ValueType::Any) => true,
+            (ValueType::Union(types), _) => types.iter().all(|given| self.fits(given, expected)),
+            (_, ValueType::Union(types)) => types.iter().any(|expected| self.fits(given, expected)),
+            (ValueType::Integer, ValueType::Real) => true,
+            (ValueType::Class(_), ValueType::Object) => true,
+            (ValueType::Class(given), ValueType::Class(expected)) => {
+                given == expected || self.table.is_subclass_of(given, expected) || self.table.get(expected).is_none()
+            }
+            (given, expected) => given == expected,
+        }
+    }
+}
+
+impl<'a> Visitor<'a> for CallChecker<'a> {
+    fn visit_class(&mut self, class: &'a Class) {
+        let outer = self.class.replace(&class.name.node.name);
+        walk_class(self, class);
+        self.class = outer;
+    }
+
+    fn visit_expression(&mut self, expression: &'a Expression) {
+        if let Expression::Unary(unary) = expression {
+            if matches!(unary.op.node.as_ref(), UnaryOp::New) {
+                let (class, arguments) = match unary.expr.as_ref() {
+                    Expression::Member(Member { left: PrimaryExpr::IdentifierOrMember(class), right }) => {
+                        match right.first().map(|right| right.node.as_ref()) {
+                            Some(MemberRight::Call(arguments)) => (Some(class), arguments.as_slice()),
+                            _ => (None, &[][..]),
+                        }
+                    }
+                    Expression::Primary(PrimaryExpr::IdentifierOrMember(class)) => (Some(class), &[][..]),
+                    _ => (None, &[][..]),
+                };
+                if let Some(IdentifierOrMember::Identifier(class)) = class.map(|class| class.node.as_ref()) {
+                    self.check_new(class, arguments);
+                }
+            }
+        }
+        walk_expression(self, expression);
+    }
+
+    fn visit_member(&mut self, member: &'a Member) {
+        if let PrimaryExpr::IdentifierOrMember(object) = &member.left {
+            let right = |index: usize| member.right.get(index).map(|right| right.node.as_ref());
+            match (object.node.as_ref(), right(0), right(1)) {
+                (IdentifierOrMember::Identifier(name), Some(MemberRight::Call(arguments)), _) => {
+                    self.check_function(name, arguments);
+                }
+                (object, Some(MemberRight::Dot(name)), Some(MemberRight::Call(arguments))) => {
+                    if let IdentifierOrMember::Identifier(name) = name.node.as_ref() {
+                        self.check_method(object, name, arguments);
+                    }
+                }
+                _ => {}
+            }
+        }
+        walk_member(self, member);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::project::tests::project;
+
+    use super::*;
+
+    fn messages(files: &[(&str, &str)]) -> Vec<String> {
+        check_calls(&project(files)).into_iter().map(|d| d.to_string()).collect()
+    }
+
+    #[test]
+    fn test_functions() {
+        let files = [
+            ("main", "include('lib');\nf(1);\nf(1, 2, 3);\ng();\ng(1, 2);\nvar f2 = f; f2();"),
+            ("lib", "function f(a, b) {}\nfunction g(a, b = 1) {}"),
+        ];
+        assert_eq!(messages(&files), vec![
+            "main:2:1: error: `f` takes 2 arguments but 1 was given",
+            "main:3:1: error: `f` takes 2 arguments but 3 were given",
+            "main:4:1: error: `g` takes 1 to 2 arguments but 0 were given",
+        ]);
+        // A variable hiding the function
+        assert!(messages(&[("main", "function f(a) {} function h(f) { f(); }")]).is_empty());
+    }
+
+    #[test]
+    fn test_classes() {
+        let source = "class A { constructor(integer x) {} static s(a) {} m() {} m(a, b) {} }\n\
+            class B extends A { n() { this.m(1); super.m(); A.s(); } }\n\
+            class C {}\n\
+            var a = new A(), b = new B(1), c = new B('x'), d = new C(1), e = new C; A.s(1);";
+        assert_eq!(messages(&[("main", source)]), vec![
+            "main:2:32: error: no overload of `A.m` takes 1 argument",
+            "main:2:51: error: `A.s` takes 1 argument but 0 were given",
+            "main:4:13: error: `new A` takes 1 argument but 0 were given",
+            "main:4:42: error: argument 1 of `new B` should be of type `integer` but is of type `string`",
+            "main:4:56: error: `new C` takes 0 arguments but 1 was given",
+        ]);
+    }
+
+    #[test]
+    fn test_types() {
+        let source = "class A {} class B extends A {}\n\
+            function f(real x, A? a, integer|string y) {}\n\
+            f(1, new B(), 'a');\nf(1.5, null, 2);\nf(true, 1, null);\nf(x, y, z);";
+        assert_eq!(messages(&[("main", source)]), vec![
+            "main:5:3: error: argument 1 of `f` should be of type `real` but is of type `boolean`",
+            "main:5:9: error: argument 2 of `f` should be of type `A?` but is of type `integer`",
+            "main:5:12: error: argument 3 of `f` should be of type `integer|string` but is of type `null`",
+        ]);
+    }
+}
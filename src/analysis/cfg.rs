@@ -0,0 +1,714 @@
+use std::fmt::{Display, Write};
+
+use crate::ast::*;
+
+use super::evaluate;
+
+pub type BlockId = usize;
+
+/// What a basic block runs, in order.
+#[derive(Debug, Clone, Copy)]
+pub enum CfgNode<'a> {
+    /// A statement without inner control flow: an expression, a declaration, a jump.
+    Statement(&'a Statement),
+    /// A variable declaration run on its own, like the initialization of a `for (;;)` loop.
+    Declaration(&'a VarDeclaration),
+    /// The variables of a `for-in` loop, given the next key and value at each turn.
+    Element(&'a VarDeclaration),
+    /// An expression run on its own, like the increment of a `for` loop or the value of an arrow function.
+    Expression(&'a Expression),
+    /// The condition deciding between the `True` and `False` edges leaving the block.
+    Condition(&'a Expression),
+}
+
+impl Display for CfgNode<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CfgNode::Statement(statement) => write!(f, "{}", statement.to_string().trim()),
+            CfgNode::Declaration(declaration) | CfgNode::Element(declaration) => {
+                write!(f, "{}", declaration.to_string().trim())
+            }
+            CfgNode::Expression(expression) => write!(f, "{}", expression.to_string().trim()),
+            CfgNode::Condition(expression) => write!(f, "{} ?", expression.to_string().trim()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, derive_more::Display)]
+pub enum EdgeKind {
+    #[display(fmt = "")] Normal,
+    #[display(fmt = "true")] True,
+    #[display(fmt = "false")] False,
+    #[display(fmt = "break")] Break,
+    #[display(fmt = "continue")] Continue,
+    #[display(fmt = "return")] Return,
+    #[display(fmt = "throw")] Throw,
+    /// From the blocks of a `try` to its `catch` clauses.
+    #[display(fmt = "exception")] Exception,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Edge {
+    pub target: BlockId,
+    pub kind: EdgeKind,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct BasicBlock<'a> {
+    pub nodes: Vec<CfgNode<'a>>,
+    pub successors: Vec<Edge>,
+}
+
+/// The control-flow graph of a function or of the top-level code of a file. The `entry` block
+/// starts the code and every way out of it leads to the empty `exit` block, falling off the end
+/// being the `Normal` edge. Code after a jump lands in blocks that have no predecessors.
+///
+/// A condition that is a constant only gets the edge it takes. Anonymous functions are not part
+/// of the graph of the code defining them.
+#[derive(Debug, Clone)]
+pub struct ControlFlowGraph<'a> {
+    pub blocks: Vec<BasicBlock<'a>>,
+    pub entry: BlockId,
+    pub exit: BlockId,
+}
+
+impl<'a> ControlFlowGraph<'a> {
+    pub fn function(function: &'a FunctionDefinition) -> Self {
+        Self::block(&function.body.node)
+    }
+
+    pub fn method(method: &'a Method) -> Self {
+        match method.body.block() {
+            Some(body) => Self::block(&body.node),
+            None => Builder::new().finish(),
+        }
+    }
+
+    pub fn constructor(constructor: &'a Constructor) -> Self {
+        Self::block(&constructor.body.node)
+    }
+
+    pub fn anonymous_function(function: &'a AnonymousFunction) -> Self {
+        match function {
+            AnonymousFunction::Function(function) => Self::block(&function.body.node),
+            AnonymousFunction::ArrowFunction(arrow) => match arrow.body.node.as_ref() {
+                FunctionBodyOrExpression::BlockStatement(body) => Self::block(&body.node),
+                FunctionBodyOrExpression::Expression(expression) => {
+                    let mut builder = Builder::new();
+                    builder.push(CfgNode::Expression(&expression.node));
+                    builder.jump(builder.exit, EdgeKind::Return);
+                    builder.finish()
+                }
+            },
+        }
+    }
+
+    /// The top-level statements of a file, leaving out the functions and classes it defines.
+    pub fn file(file: &'a File) -> Self {
+        let mut builder = Builder::new();
+        for statement in &file.statements {
+            if let GlobalStatement::Statement(statement) = statement.node.as_ref() {
+                builder.statement(statement);
+            }
+        }
+        builder.finish()
+    }
+
+    pub fn block(block: &'a BlockStatement) -> Self {
+        let mut builder = Builder::new();
+        builder.block(block);
+        builder.finish()
+    }
+
+    pub fn predecessors(&self, block: BlockId) -> impl Iterator<Item = (BlockId, Edge)> + '_ {
+        self.blocks.iter().enumerate().flat_map(move |(id, source)| {
+            source.successors.iter().filter(move |edge| edge.target == block).map(move |edge| (id, *edge))
+        })
+    }
+
+    /// Which blocks can be reached from the entry, by block id.
+    pub fn reachable(&self) -> Vec<bool> {
+        let mut reached = vec![false; self.blocks.len()];
+        let mut pending = vec![self.entry];
+        while let Some(block) = pending.pop() {
+            if !std::mem::replace(&mut reached[block], true) {
+                pending.extend(self.blocks[block].successors.iter().map(|edge| edge.target));
+            }
+        }
+        reached
+    }
+
+    /// Whether the end of the code can be reached without a `return` or `throw`.
+    pub fn falls_through(&self) -> bool {
+        let reachable = self.reachable();
+        self.predecessors(self.exit).any(|(block, edge)| edge.kind == EdgeKind::Normal && reachable[block])
+    }
+
+    /// The graph in the Graphviz format, for `dot -Tsvg`.
+    pub fn to_dot(&self, name: &str) -> String {
+        let mut dot = String::new();
+        writeln!(dot, "digraph \"{}\" {{", escape(name)).unwrap();
+        writeln!(dot, "    node [shape=box, fontname=monospace];").unwrap();
+        for (id, block) in self.blocks.iter().enumerate() {
+            let mut label = match id {
+                id if id == self.entry => "entry\\l".to_string(),
+                id if id == self.exit => "exit\\l".to_string(),
+                _ => String::new(),
+            };
+            for node in &block.nodes {
+                label.push_str(&escape(&node.to_string()));
+                label.push_str("\\l");
+            }
+            writeln!(dot, "    b{} [label=\"{}\"];", id, label).unwrap();
+        }
+        for (id, block) in self.blocks.iter().enumerate() {
+            for edge in &block.successors {
+                match edge.kind {
+                    EdgeKind::Normal => writeln!(dot, "    b{} -> b{};", id, edge.target),
+                    kind => writeln!(dot, "    b{} -> b{} [label=\"{}\"];", id, edge.target, kind),
+                }
+                .unwrap();
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\l")
+}
+
+struct Loop {
+    continue_target: BlockId,
+    break_target: BlockId,
+}
+
+/// A `finally` block the code being built is in, through `try` or `catch`.
+#[derive(Clone, Copy)]
+struct Finally<'a> {
+    block: &'a BlockStatement,
+    /// How many loops it is in, the loops entered after it being inside of it
+    loops: usize,
+}
+
+/// The `catch` clauses of a `try` the code being built is in.
+struct Handler {
+    catches: Vec<BlockId>,
+    /// How many `finally` blocks it is in, the ones entered after it being left by a `throw`
+    finally: usize,
+}
+
+struct Builder<'a> {
+    blocks: Vec<BasicBlock<'a>>,
+    /// The block receiving the next nodes, `None` right after a jump
+    current: Option<BlockId>,
+    loops: Vec<Loop>,
+    finally: Vec<Finally<'a>>,
+    handlers: Vec<Handler>,
+    /// The copies of `finally` blocks run by a jump, which are outside of the `try` they leave
+    copies: Vec<BlockId>,
+    exit: BlockId,
+}
+
+impl<'a> Builder<'a> {
+    fn new() -> Self {
+        Builder {
+            blocks: vec![BasicBlock::default(), BasicBlock::default()],
+            current: Some(0),
+            loops: Vec::new(),
+            finally: Vec::new(),
+            handlers: Vec::new(),
+            copies: Vec::new(),
+            exit: 1,
+        }
+    }
+
+    fn finish(mut self) -> ControlFlowGraph<'a> {
+        self.goto(self.exit, EdgeKind::Normal);
+        ControlFlowGraph { blocks: self.blocks, entry: 0, exit: self.exit }
+    }
+
+    fn new_block(&mut self) -> BlockId {
+        self.blocks.push(BasicBlock::default());
+        self.blocks.len() - 1
+    }
+
+    /// The block receiving the next nodes, a new one without predecessors after a jump.
+    fn current(&mut self) -> BlockId {
+        match self.current {
+            Some(current) => current,
+            None => {
+                let block = self.new_block();
+                self.current = Some(block);
+                block
+            }
+        }
+    }
+
+    fn push(&mut self, node: CfgNode<'a>) {
+        let current = self.current();
+        self.blocks[current].nodes.push(node);
+    }
+
+    fn edge(&mut self, from: BlockId, to: BlockId, kind: EdgeKind) {
+        self.blocks[from].successors.push(Edge { target: to, kind });
+    }
+
+    /// Ends the current block with an edge to `target`, if the current point can be reached.
+    fn goto(&mut self, target: BlockId, kind: EdgeKind) {
+        if let Some(current) = self.current {
+            self.edge(current, target, kind);
+        }
+    }
+
+    fn jump(&mut self, target: BlockId, kind: EdgeKind) {
+        let current = self.current();
+        self.edge(current, target, kind);
+        self.current = None;
+    }
+
+    /// Continues in `block`, coming from the current one.
+    fn enter(&mut self, block: BlockId) {
+        self.goto(block, EdgeKind::Normal);
+        self.current = Some(block);
+    }
+
+    /// Ends the current block with `condition`, giving the blocks where it is true and false.
+    fn branch(&mut self, condition: &'a Expression) -> (BlockId, BlockId) {
+        self.push(CfgNode::Condition(condition));
+        let from = self.current();
+        let (when_true, when_false) = (self.new_block(), self.new_block());
+        let value = evaluate(condition).map(|value| value.is_truthy());
+        if value != Some(false) {
+            self.edge(from, when_true, EdgeKind::True);
+        }
+        if value != Some(true) {
+            self.edge(from, when_false, EdgeKind::False);
+        }
+        self.current = None;
+        (when_true, when_false)
+    }
+
+    fn in_loop(&mut self, continue_target: BlockId, break_target: BlockId, body: &'a Statement) {
+        self.loops.push(Loop { continue_target, break_target });
+        self.statement(body);
+        self.loops.pop();
+    }
+
+    fn block(&mut self, block: &'a BlockStatement) {
+        if let BlockStatement::StatementList(statements) = block {
+            for statement in statements {
+                self.statement(&statement.node);
+            }
+        }
+    }
+
+    fn statement(&mut self, statement: &'a Statement) {
+        match statement {
+            Statement::Block(block) => self.block(block),
+            Statement::IfStatement(if_statement) => {
+                let (then_block, else_block) = self.branch(&if_statement.condition.node.expression.node);
+                self.current = Some(then_block);
+                self.statement(&if_statement.block.node);
+                let then_end = self.current;
+
+                self.current = Some(else_block);
+                if let Some(else_statement) = &if_statement.else_block {
+                    self.statement(&else_statement.node);
+                }
+                let after = self.new_block();
+                self.goto(after, EdgeKind::Normal);
+                if let Some(then_end) = then_end {
+                    self.edge(then_end, after, EdgeKind::Normal);
+                }
+                self.current = Some(after);
+            }
+            Statement::WhileStatement(while_statement) => {
+                let condition = self.new_block();
+                self.enter(condition);
+                let (body, after) = self.branch(&while_statement.condition.node);
+                self.current = Some(body);
+                self.in_loop(condition, after, &while_statement.block.node);
+                self.goto(condition, EdgeKind::Normal);
+                self.current = Some(after);
+            }
+            Statement::DoWhileStatement(do_while) => {
+                let body = self.new_block();
+                let condition = self.new_block();
+                self.enter(body);
+                let after = self.new_block();
+                self.in_loop(condition, after, &do_while.statement.node);
+                self.enter(condition);
+                let (again, exit) = self.branch(&do_while.condition.node);
+                self.current = Some(again);
+                self.goto(body, EdgeKind::Normal);
+                self.current = Some(exit);
+                self.enter(after);
+            }
+            Statement::ForStatement(for_statement) => self.for_statement(for_statement),
+            Statement::TryStatement(try_statement) => self.try_statement(try_statement),
+            Statement::ReturnStatement(_) => {
+                self.push(CfgNode::Statement(statement));
+                self.leave(self.exit, EdgeKind::Return, 0);
+            }
+            Statement::ThrowStatement(_) => {
+                self.push(CfgNode::Statement(statement));
+                self.throw();
+            }
+            Statement::BreakStatement(jump) => {
+                self.push(CfgNode::Statement(statement));
+                // A jump outside of a loop is an error reported elsewhere, it ends the code here
+                let (target, kind) = match (jump, self.loops.last()) {
+                    (BreakStatement::Break(_), Some(loop_)) => (loop_.break_target, EdgeKind::Break),
+                    (BreakStatement::Continue(_), Some(loop_)) => (loop_.continue_target, EdgeKind::Continue),
+                    (_, None) => {
+                        self.current = None;
+                        return;
+                    }
+                };
+                // Only the `finally` blocks inside the loop are left
+                let loops = self.loops.len();
+                let kept = self.finally.iter().take_while(|finally| finally.loops < loops).count();
+                self.leave(target, kind, kept);
+            }
+            Statement::Empty(_) => {}
+            _ => self.push(CfgNode::Statement(statement)),
+        }
+    }
+
+    fn for_statement(&mut self, for_statement: &'a ForStatement) {
+        match for_statement.for_header.node.as_ref() {
+            ForHeader::ForIter(header) => {
+                match header.init.as_ref().map(|init| init.node.as_ref()) {
+                    Some(VarDecOrExpr::VarDeclaration(declaration)) => self.push(CfgNode::Declaration(declaration)),
+                    Some(VarDecOrExpr::Expression(expression)) => self.push(CfgNode::Expression(expression)),
+                    None => {}
+                }
+                let condition = self.new_block();
+                let increment = self.new_block();
+                self.enter(condition);
+                let (body, after) = match &header.condition {
+                    Some(condition) => self.branch(&condition.node),
+                    None => {
+                        let body = self.new_block();
+                        self.enter(body);
+                        (body, self.new_block())
+                    }
+                };
+                self.current = Some(body);
+                self.in_loop(increment, after, &for_statement.block.node);
+                self.enter(increment);
+                if let Some(expression) = &header.increment {
+                    self.push(CfgNode::Expression(&expression.node));
+                }
+                self.goto(condition, EdgeKind::Normal);
+                self.current = Some(after);
+            }
+            ForHeader::ForIn(header) => {
+                self.push(CfgNode::Expression(&header.iterable.node));
+                let next = self.new_block();
+                let (body, after) = (self.new_block(), self.new_block());
+                self.enter(next);
+                self.edge(next, body, EdgeKind::True);
+                self.edge(next, after, EdgeKind::False);
+
+                self.current = Some(body);
+                if let Some(key) = &header.key {
+                    self.push(CfgNode::Element(&key.node));
+                }
+                self.push(CfgNode::Element(&header.var.node));
+                self.in_loop(next, after, &for_statement.block.node);
+                self.goto(next, EdgeKind::Normal);
+                self.current = Some(after);
+            }
+        }
+    }
+
+    /// The blocks created from `start` on, but the copies of `finally` blocks.
+    fn blocks_since(&self, start: BlockId) -> Vec<BlockId> {
+        (start..self.blocks.len()).filter(|block| !self.copies.contains(block)).collect()
+    }
+
+    /// Jumps to `target`, running a copy of each `finally` block left on the way: all of them
+    /// but the `kept` outermost ones. Gives the block jumping to `target`, if it can be reached.
+    fn leave(&mut self, target: BlockId, kind: EdgeKind, kept: usize) -> Option<BlockId> {
+        let saved = self.finally.clone();
+        let mut edge = kind;
+        for index in (kept.min(saved.len())..saved.len()).rev() {
+            if self.current.is_none() {
+                break;
+            }
+            // A jump in the copy only runs the blocks outside of it
+            self.finally.truncate(index);
+            let copy = self.new_block();
+            self.goto(copy, edge);
+            self.current = Some(copy);
+            self.block(saved[index].block);
+            self.copies.extend(copy..self.blocks.len());
+            edge = EdgeKind::Normal;
+        }
+        let from = self.current;
+        if from.is_some() {
+            self.jump(target, kind);
+        }
+        self.finally = saved;
+        from
+    }
+
+    /// Throws to the `catch` clauses around, or out of the function when there are none.
+    fn throw(&mut self) {
+        match self.handlers.last() {
+            Some(handler) => {
+                let (catches, kept) = (handler.catches.clone(), handler.finally);
+                if let Some(from) = self.leave(catches[0], EdgeKind::Throw, kept) {
+                    for &catch in &catches[1..] {
+                        self.edge(from, catch, EdgeKind::Throw);
+                    }
+                }
+            }
+            None => {
+                self.leave(self.exit, EdgeKind::Throw, 0);
+            }
+        }
+    }
+
+    /// Any block of the `try` may throw into each `catch`. Every way out of the `try` and the
+    /// `catch` clauses goes through `finally`: the normal end runs it before the code after, and
+    /// a `return`, `throw`, `break` or `continue` runs a copy of it before jumping. So does an
+    /// exception thrown by a `try` without `catch`, or by a `catch`, before leaving the function.
+    /// A `throw` in the `try` goes to the `catch` clauses, through the `finally` blocks inside.
+    fn try_statement(&mut self, try_statement: &'a TryStatement) {
+        let finally = try_statement.finally.as_ref().map(|finally| Finally {
+            block: &finally.node,
+            loops: self.loops.len(),
+        });
+        self.finally.extend(finally);
+
+        // Made first, for the `throw` statements of the `try` to jump to
+        let catches: Vec<BlockId> = try_statement.catches.iter().map(|_| self.new_block()).collect();
+        if !catches.is_empty() {
+            self.handlers.push(Handler { catches: catches.clone(), finally: self.finally.len() });
+        }
+        let start = self.new_block();
+        self.enter(start);
+        self.block(&try_statement.block.node);
+        if !catches.is_empty() {
+            self.handlers.pop();
+        }
+        let try_blocks = self.blocks_since(start);
+
+        let mut ends = vec![self.current];
+        let mut uncaught = Vec::new();
+        for (catch, &catch_block) in try_statement.catches.iter().zip(&catches) {
+            for &block in &try_blocks {
+                self.edge(block, catch_block, EdgeKind::Exception);
+            }
+            let catch_start = self.blocks.len();
+            self.current = Some(catch_block);
+            self.block(&catch.node.block.node);
+            ends.push(self.current);
+            uncaught.push(catch_block);
+            uncaught.extend(self.blocks_since(catch_start));
+        }
+        if try_statement.catches.is_empty() {
+            uncaught = try_blocks;
+        }
+
+        if finally.is_some() {
+            self.finally.pop();
+        }
+        let after = self.new_block();
+        for end in ends.into_iter().flatten() {
+            self.edge(end, after, EdgeKind::Normal);
+        }
+        if let Some(finally) = finally {
+            let handler = self.new_block();
+            for block in uncaught {
+                self.edge(block, handler, EdgeKind::Exception);
+            }
+            self.current = Some(handler);
+            self.block(finally.block);
+            // The exception goes on through the outer `finally` blocks
+            self.throw();
+        }
+        self.current = Some(after);
+        if let Some(finally) = &try_statement.finally {
+            self.block(&finally.node);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::parse_file;
+
+    use super::*;
+
+    fn graph(file: &File) -> ControlFlowGraph<'_> {
+        match file.statements[0].node.as_ref() {
+            GlobalStatement::FunctionDefinition(function) => ControlFlowGraph::function(function),
+            _ => ControlFlowGraph::file(file),
+        }
+    }
+
+    /// The edges as `from -kind-> to`, between the labels of the blocks.
+    fn edges(graph: &ControlFlowGraph) -> Vec<String> {
+        let label = |id: BlockId| match id {
+            id if id == graph.entry => "entry".to_string(),
+            id if id == graph.exit => "exit".to_string(),
+            id => graph.blocks[id].nodes.iter().map(|node| node.to_string()).collect::<Vec<_>>().join(" "),
+        };
+        let reachable = graph.reachable();
+        graph.blocks.iter().enumerate()
+            .filter(|(id, _)| reachable[*id])
+            .flat_map(|(id, block)| block.successors.iter().map(move |edge| (id, edge)))
+            .map(|(id, edge)| format!("[{}] -{}-> [{}]", label(id), edge.kind, label(edge.target)))
+            .collect()
+    }
+
+    #[test]
+    fn test_if() {
+        let file = parse_file("function f(a) { if (a) { g(); } else return 1; h(); }");
+        let graph = graph(&file);
+        assert_eq!(edges(&graph), [
+            "[entry] -true-> [g();]",
+            "[entry] -false-> [return 1;]",
+            "[g();] --> [h();]",
+            "[return 1;] -return-> [exit]",
+            "[h();] --> [exit]",
+        ]);
+        assert_eq!(graph.blocks[graph.entry].nodes.len(), 1);
+        assert!(graph.falls_through());
+    }
+
+    #[test]
+    fn test_loops() {
+        let file = parse_file("function f() { while (a) { if (b) break; continue; } return 0; }");
+        assert_eq!(edges(&graph(&file)), [
+            "[entry] --> [(a) ?]",
+            "[(a) ?] -true-> [b ?]",
+            "[(a) ?] -false-> [return 0;]",
+            "[b ?] -true-> [break;]",
+            "[b ?] -false-> []",
+            "[return 0;] -return-> [exit]",
+            "[break;] -break-> [return 0;]",
+            "[] --> [continue;]",
+            "[continue;] -continue-> [(a) ?]",
+        ]);
+
+        let file = parse_file("for (var i = 0; i < 10; i++) { f(i); } do { g(); } while (true);");
+        assert_eq!(edges(&graph(&file)), [
+            "[entry] --> [i < 10 ?]",
+            "[i < 10 ?] -true-> [f(i);]",
+            "[i < 10 ?] -false-> []",
+            "[i++] --> [i < 10 ?]",
+            "[f(i);] --> [i++]",
+            "[] --> [g();]",
+            "[g();] --> [(true) ?]",
+            "[(true) ?] -true-> []",
+            "[] --> [g();]",
+        ]);
+        assert!(!graph(&file).falls_through());
+    }
+
+    #[test]
+    fn test_unreachable() {
+        let file = parse_file("function f() { return 1; g(); }");
+        let graph = graph(&file);
+        let reachable = graph.reachable();
+        let unreachable: Vec<_> = graph.blocks.iter().enumerate()
+            .filter(|(id, _)| !reachable[*id])
+            .map(|(_, block)| block.nodes[0].to_string())
+            .collect();
+        assert_eq!(unreachable, ["g();"]);
+        assert!(!graph.falls_through());
+    }
+
+    #[test]
+    fn test_finally() {
+        let file = parse_file("function f() { try { return 1; } finally { g(); } h(); }");
+        assert_eq!(edges(&graph(&file)), [
+            "[entry] --> [return 1;]",
+            "[return 1;] -return-> [g();]",
+            "[return 1;] -exception-> [g();]",
+            "[g();] -return-> [exit]",
+            "[g();] -throw-> [exit]",
+        ]);
+
+        // Uncaught exceptions and jumps out of a loop run it as well
+        let file = parse_file("function f() { while (a) { try { g(); break; } catch { h(); } finally { i(); } } }");
+        assert_eq!(edges(&graph(&file)), [
+            "[entry] --> [(a) ?]",
+            "[(a) ?] -true-> []",
+            "[(a) ?] -false-> []",
+            "[] --> [g(); break;]",
+            "[] --> [exit]",
+            "[h();] --> [i();]",
+            "[h();] -exception-> [i();]",
+            "[g(); break;] -break-> [i();]",
+            "[g(); break;] -exception-> [h();]",
+            "[i();] -break-> []",
+            "[i();] --> [(a) ?]",
+            "[i();] -throw-> [exit]",
+        ]);
+    }
+
+    #[test]
+    fn test_throw() {
+        // A `catch` gets the exception, after the `finally` blocks inside its `try`
+        let file = parse_file("function f() { try { try { throw 1; } finally { g(); } } catch { h(); } }");
+        assert_eq!(edges(&graph(&file)), [
+            "[entry] --> []",
+            "[h();] --> []",
+            "[] --> [throw 1;]",
+            "[] -exception-> [h();]",
+            "[throw 1;] -throw-> [g();]",
+            "[throw 1;] -exception-> [g();]",
+            "[throw 1;] -exception-> [h();]",
+            "[g();] -throw-> [h();]",
+            "[g();] -throw-> [h();]",
+            "[g();] -exception-> [h();]",
+            "[] --> [exit]",
+        ]);
+        assert!(graph(&file).falls_through());
+
+        let file = parse_file("function f() { try { g(); } catch { throw 1; } }");
+        assert_eq!(edges(&graph(&file)), [
+            "[entry] --> [g();]",
+            "[throw 1;] -throw-> [exit]",
+            "[g();] -exception-> [throw 1;]",
+            "[g();] --> []",
+            "[] --> [exit]",
+        ]);
+    }
+
+    #[test]
+    fn test_jump_outside_loop() {
+        let file = parse_file("function f() { break; } function g() { if (a) continue; return 1; }");
+        let GlobalStatement::FunctionDefinition(function) = file.statements[0].node.as_ref() else { unreachable!() };
+        let graph = ControlFlowGraph::function(function);
+        assert_eq!(edges(&graph), [] as [String; 0]);
+        assert!(!graph.falls_through());
+
+        let GlobalStatement::FunctionDefinition(function) = file.statements[1].node.as_ref() else { unreachable!() };
+        let graph = ControlFlowGraph::function(function);
+        assert_eq!(edges(&graph), [
+            "[entry] -true-> [continue;]",
+            "[entry] -false-> []",
+            "[] --> [return 1;]",
+            "[return 1;] -return-> [exit]",
+        ]);
+        assert!(!graph.falls_through());
+    }
+
+    #[test]
+    fn test_dot() {
+        let file = parse_file("if (a == \"x\") f();");
+        let dot = ControlFlowGraph::file(&file).to_dot("main");
+        assert_eq!(dot, "digraph \"main\" {\n    node [shape=box, fontname=monospace];\n    \
+            b0 [label=\"entry\\la == \\\"x\\\" ?\\l\"];\n    b1 [label=\"exit\\l\"];\n    b2 [label=\"f();\\l\"];\n    \
+            b3 [label=\"\"];\n    b4 [label=\"\"];\n    b0 -> b2 [label=\"true\"];\n    b0 -> b3 [label=\"false\"];\n    \
+            b2 -> b4;\n    b3 -> b4;\n    b4 -> b1;\n}\n");
+    }
+}
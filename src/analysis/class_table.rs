@@ -0,0 +1,453 @@
+use std::collections::HashMap;
+
+use crate::ast::{visit::*, *};
+
+use super::{Diagnostic, Project};
+
+/// A class of the project together with the file that declares it.
+#[derive(Debug, Clone, Copy)]
+pub struct ClassEntry<'a> {
+    pub class: &'a Class,
+    pub file: &'a str,
+}
+
+impl<'a> ClassEntry<'a> {
+    pub fn name(&self) -> &'a str {
+        &self.class.name.node.name
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum ClassMemberRef<'a> {
+    Field(&'a Field),
+    Method(&'a Method),
+}
+
+impl<'a> ClassMemberRef<'a> {
+    pub fn visibility(&self) -> &'a Visibility {
+        match self {
+            ClassMemberRef::Field(field) => &field.visibility.node,
+            ClassMemberRef::Method(method) => &method.visibility.node,
+        }
+    }
+}
+
+/// A member found in a class or one of its ancestors.
+#[derive(Debug, Clone, Copy)]
+pub struct ResolvedMember<'a> {
+    pub owner: ClassEntry<'a>,
+    pub member: ClassMemberRef<'a>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AccessError {
+    UnknownClass(String),
+    NoSuperclass(String),
+    UnknownMember { class: String, member: String },
+    Private { class: String, member: String },
+    Protected { class: String, member: String },
+}
+
+impl std::fmt::Display for AccessError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AccessError::UnknownClass(class) => write!(f, "unknown class `{}`", class),
+            AccessError::NoSuperclass(class) => write!(f, "class `{}` has no superclass", class),
+            AccessError::UnknownMember { class, member } => write!(f, "class `{}` has no member `{}`", class, member),
+            AccessError::Private { class, member } => write!(f, "member `{}` of `{}` is private", member, class),
+            AccessError::Protected { class, member } => write!(f, "member `{}` of `{}` is protected", member, class),
+        }
+    }
+}
+
+/// Every class of a project, linked to its superclass.
+#[derive(Debug, Default)]
+pub struct ClassTable<'a> {
+    classes: HashMap<&'a str, ClassEntry<'a>>,
+    /// Superclasses that exist in the project, cycles removed.
+    parents: HashMap<&'a str, &'a str>,
+}
+
+impl<'a> ClassTable<'a> {
+    /// Builds the table, reporting duplicate classes and missing or cyclic superclasses.
+    pub fn build(project: &'a Project) -> (Self, Vec<Diagnostic>) {
+        let mut table = ClassTable::default();
+        let mut diagnostics = Vec::new();
+
+        for source in &project.files {
+            for statement in &source.file.statements {
+                let GlobalStatement::ClassDefinition(class) = statement.node.as_ref() else { continue };
+                let entry = ClassEntry { class, file: &source.path };
+
+                if let Some(previous) = table.classes.insert(entry.name(), entry) {
+                    table.classes.insert(entry.name(), previous);
+                    diagnostics.push(Diagnostic::error(
+                        format!("class `{}` is already defined in `{}`", entry.name(), previous.file),
+                        class.name.location,
+                    ).in_file(entry.file));
+                }
+            }
+        }
+
+        let mut classes: Vec<_> = table.classes.values().copied().collect();
+        classes.sort_by_key(|entry| entry.name());
+
+        for entry in &classes {
+            let Some(extends) = &entry.class.extends else { continue };
+            match table.classes.get_key_value(extends.node.name.as_str()) {
+                Some((parent, _)) => {
+                    table.parents.insert(entry.name(), parent);
+                }
+                None => diagnostics.push(Diagnostic::error(
+                    format!("superclass `{}` of `{}` does not exist", extends.node, entry.name()),
+                    extends.location,
+                ).in_file(entry.file)),
+            }
+        }
+
+        for entry in &classes {
+            if table.parents.contains_key(entry.name()) && table.is_in_cycle(entry.name()) {
+                let extends = entry.class.extends.as_ref().unwrap();
+                diagnostics.push(Diagnostic::error(
+                    format!("class `{}` inherits from itself", entry.name()),
+                    extends.location,
+                ).in_file(entry.file));
+            }
+        }
+        // Break the cycles so that walking up the hierarchy always ends
+        for entry in &classes {
+            if table.is_in_cycle(entry.name()) {
+                table.parents.remove(entry.name());
+            }
+        }
+
+        (table, diagnostics)
+    }
+
+    fn is_in_cycle(&self, name: &str) -> bool {
+        let mut current = name;
+        for _ in 0..=self.parents.len() {
+            match self.parents.get(current) {
+                Some(parent) if *parent == name => return true,
+                Some(parent) => current = parent,
+                None => return false,
+            }
+        }
+        false
+    }
+
+    /// Every class, in no particular order.
+    pub fn classes(&self) -> impl Iterator<Item = ClassEntry<'a>> + '_ {
+        self.classes.values().copied()
+    }
+
+    pub fn get(&self, name: &str) -> Option<ClassEntry<'a>> {
+        self.classes.get(name).copied()
+    }
+
+    pub fn parent(&self, name: &str) -> Option<ClassEntry<'a>> {
+        self.parents.get(name).and_then(|parent| self.get(parent))
+    }
+
+    /// The class itself followed by its superclasses, closest first.
+    pub fn ancestors(&self, name: &str) -> impl Iterator<Item = ClassEntry<'a>> + '_ {
+        std::iter::successors(self.get(name), |entry| self.parent(entry.name()))
+    }
+
+    pub fn is_subclass_of(&self, class: &str, ancestor: &str) -> bool {
+        self.ancestors(class).any(|entry| entry.name() == ancestor)
+    }
+
+    /// Looks for a member in `class` and its ancestors. Static members are only found when
+    /// `static_` is set, instance members only when it is not.
+    pub fn find_member(&self, class: &str, name: &str, static_: bool) -> Option<ResolvedMember<'a>> {
+        self.ancestors(class).find_map(|owner| {
+            owner.class.members().find_map(|member| {
+                let member = match member {
+                    ClassMember::Field(field) if field.name.node.name == name => ClassMemberRef::Field(field),
+                    ClassMember::Method(method) if method.name.node.name == name => ClassMemberRef::Method(method),
+                    _ => return None,
+                };
+                (member.visibility().is_static() == static_).then_some(ResolvedMember { owner, member })
+            })
+        })
+    }
+
+    /// Resolves `object.name`, accessed from the code of the class `from` if any, and checks that
+    /// the member is visible from there.
+    pub fn resolve_access(
+        &self,
+        from: Option<&str>,
+        object: &IdentifierOrMember,
+        name: &str,
+    ) -> Result<ResolvedMember<'a>, AccessError> {
+        let (class, static_) = match (object, from) {
+            (IdentifierOrMember::This, Some(from)) => (self.get(from), false),
+            (IdentifierOrMember::Super, Some(from)) => match self.parent(from) {
+                Some(parent) => (Some(parent), false),
+                None => return Err(AccessError::NoSuperclass(from.to_string())),
+            },
+            (IdentifierOrMember::Identifier(class), _) => match self.get(&class.node.name) {
+                Some(class) => (Some(class), true),
+                None => return Err(AccessError::UnknownClass(class.node.name.clone())),
+            },
+            (object, _) => return Err(AccessError::UnknownClass(object.to_string())),
+        };
+        let class = class.ok_or_else(|| AccessError::UnknownClass(from.unwrap_or_default().to_string()))?;
+
+        // `this.x` may also name a static member
+        let resolved = self.find_member(class.name(), name, static_)
+            .or_else(|| if static_ { None } else { self.find_member(class.name(), name, true) })
+            .ok_or_else(|| AccessError::UnknownMember {
+                class: class.name().to_string(),
+                member: name.to_string(),
+            })?;
+
+        let owner = resolved.owner.name();
+        let error = match resolved.member.visibility().privacy.as_ref().map(|privacy| privacy.node.as_ref()) {
+            Some(Privacy::Private) if from != Some(owner) => Some(AccessError::Private {
+                class: owner.to_string(),
+                member: name.to_string(),
+            }),
+            Some(Privacy::Protected) if !from.is_some_and(|from| self.is_subclass_of(from, owner)) => {
+                Some(AccessError::Protected {
+                    class: owner.to_string(),
+                    member: name.to_string(),
+                })
+            }
+            _ => None,
+        };
+
+        match error {
+            Some(error) => Err(error),
+            None => Ok(resolved),
+        }
+    }
+}
+
+/// Builds the class table of a project and checks every `this.x`, `super.x` and `Class.x`
+/// access against it, as well as the methods overriding another one.
+pub fn check_classes(project: &Project) -> Vec<Diagnostic> {
+    let (table, mut diagnostics) = ClassTable::build(project);
+
+    for source in &project.files {
+        let mut checker = AccessChecker {
+            table: &table,
+            class: None,
+            locals: Vec::new(),
+            diagnostics: Vec::new(),
+        };
+        checker.visit_file(&source.file);
+        diagnostics.extend(checker.diagnostics.into_iter().map(|diagnostic| diagnostic.in_file(&source.path)));
+    }
+
+    let mut classes: Vec<_> = table.classes.values().copied().collect();
+    classes.sort_by_key(|entry| entry.name());
+    for entry in classes {
+        diagnostics.extend(check_overrides(&table, entry));
+    }
+
+    diagnostics
+}
+
+fn check_overrides(table: &ClassTable, entry: ClassEntry) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let Some(parent) = table.parent(entry.name()) else { return diagnostics };
+
+    for member in entry.class.members() {
+        let ClassMember::Method(method) = member else { continue };
+        let static_ = method.visibility.node.is_static();
+        let Some(ResolvedMember { owner, member: ClassMemberRef::Method(overridden) }) =
+            table.find_member(parent.name(), &method.name.node.name, static_)
+        else {
+            continue;
+        };
+
+        let count = method.parameters.node.params.len();
+        let expected = overridden.parameters.node.params.len();
+        if count != expected {
+            diagnostics.push(Diagnostic::warning(
+                format!(
+                    "method `{}` of `{}` takes {} parameters but overrides `{}.{}` which takes {}",
+                    method.name.node, entry.name(), count, owner.name(), overridden.name.node, expected
+                ),
+                method.name.location,
+            ).in_file(entry.file));
+        }
+    }
+
+    diagnostics
+}
+
+struct AccessChecker<'t, 'a> {
+    table: &'t ClassTable<'a>,
+    class: Option<&'a str>,
+    /// Variables declared in the enclosing functions, which hide classes of the same name.
+    locals: Vec<String>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'ast, 't, 'a> Visitor<'ast> for AccessChecker<'t, 'a>
+where
+    'ast: 'a,
+{
+    fn visit_class(&mut self, class: &'ast Class) {
+        let outer = self.class.replace(&class.name.node.name);
+        walk_class(self, class);
+        self.class = outer;
+    }
+
+    fn visit_function_definition(&mut self, function: &'ast FunctionDefinition) {
+        let depth = self.locals.len();
+        walk_function_definition(self, function);
+        self.locals.truncate(depth);
+    }
+
+    fn visit_method(&mut self, method: &'ast Method) {
+        let depth = self.locals.len();
+        walk_method(self, method);
+        self.locals.truncate(depth);
+    }
+
+    fn visit_constructor(&mut self, constructor: &'ast Constructor) {
+        let depth = self.locals.len();
+        walk_constructor(self, constructor);
+        self.locals.truncate(depth);
+    }
+
+    fn visit_anonymous_function(&mut self, function: &'ast AnonymousFunction) {
+        let depth = self.locals.len();
+        walk_anonymous_function(self, function);
+        self.locals.truncate(depth);
+    }
+
+    fn visit_var_declaration(&mut self, declaration: &'ast VarDeclaration) {
+        self.locals.extend(declaration.values.iter().map(|(name, _)| name.node.name.clone()));
+        walk_var_declaration(self, declaration);
+    }
+
+    fn visit_parameter(&mut self, parameter: &'ast MetaNode<Parameter>) {
+        self.locals.push(parameter.node.name.node.name.clone());
+        walk_parameter(self, parameter);
+    }
+
+    fn visit_member(&mut self, member: &'ast Member) {
+        if let (PrimaryExpr::IdentifierOrMember(object), Some(first)) = (&member.left, member.right.first()) {
+            if let MemberRight::Dot(name) | MemberRight::OptionalDot(name) = first.node.as_ref() {
+                let object = object.node.as_ref();
+                let checked = match object {
+                    IdentifierOrMember::This | IdentifierOrMember::Super => self.class.is_some(),
+                    IdentifierOrMember::Identifier(class) => {
+                        self.table.get(&class.node.name).is_some() && !self.locals.contains(&class.node.name)
+                    }
+                    IdentifierOrMember::Class => false,
+                };
+
+                if let (true, IdentifierOrMember::Identifier(name)) = (checked, name.node.as_ref()) {
+                    if let Err(error) = self.table.resolve_access(self.class, object, &name.node.name) {
+                        self.diagnostics.push(Diagnostic::error(error.to_string(), name.location));
+                    }
+                }
+            }
+        }
+        walk_member(self, member);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::project::tests::project;
+
+    use super::*;
+
+    fn messages(files: &[(&str, &str)]) -> Vec<String> {
+        check_classes(&project(files)).into_iter().map(|d| d.message).collect()
+    }
+
+    #[test]
+    fn test_hierarchy() {
+        let project = project(&[
+            ("main", "include('lib'); class C extends B {}"),
+            ("lib", "class A {} class B extends A {}"),
+        ]);
+        let (table, diagnostics) = ClassTable::build(&project);
+
+        assert!(diagnostics.is_empty());
+        assert_eq!(table.parent("C").unwrap().name(), "B");
+        assert_eq!(table.get("A").unwrap().file, "lib");
+        let ancestors: Vec<_> = table.ancestors("C").map(|entry| entry.name()).collect();
+        assert_eq!(ancestors, ["C", "B", "A"]);
+        assert!(table.is_subclass_of("C", "A"));
+        assert!(!table.is_subclass_of("A", "C"));
+    }
+
+    #[test]
+    fn test_missing_and_cyclic_superclasses() {
+        assert_eq!(messages(&[("main", "class A extends Missing {}")]), [
+            "superclass `Missing` of `A` does not exist",
+        ]);
+        assert_eq!(messages(&[("main", "class A extends B {} class B extends A {} class C extends C {} class D extends A {}")]), [
+            "class `A` inherits from itself",
+            "class `B` inherits from itself",
+            "class `C` inherits from itself",
+        ]);
+        assert_eq!(messages(&[("main", "include('a'); class A {}"), ("a", "class A {}")]), [
+            "class `A` is already defined in `main`",
+        ]);
+    }
+
+    #[test]
+    fn test_member_resolution() {
+        let project = project(&[(
+            "main",
+            "class A { x; static y; m() {} } class B extends A { n() { this.x; super.m(); B.y; } }",
+        )]);
+        let (table, _) = ClassTable::build(&project);
+
+        let resolved = table.resolve_access(Some("B"), &IdentifierOrMember::This, "x").unwrap();
+        assert_eq!(resolved.owner.name(), "A");
+        assert!(matches!(resolved.member, ClassMemberRef::Field(_)));
+
+        let resolved = table.resolve_access(Some("B"), &IdentifierOrMember::Super, "m").unwrap();
+        assert!(matches!(resolved.member, ClassMemberRef::Method(_)));
+
+        assert_eq!(
+            table.resolve_access(Some("A"), &IdentifierOrMember::Super, "m").unwrap_err(),
+            AccessError::NoSuperclass("A".to_string()),
+        );
+        assert!(check_classes(&project).is_empty());
+    }
+
+    #[test]
+    fn test_unknown_members() {
+        assert_eq!(messages(&[("main", "class A { m() { this.y; A.x(); } }")]), [
+            "class `A` has no member `y`",
+            "class `A` has no member `x`",
+        ]);
+        // A local variable hides the class
+        assert!(messages(&[("main", "class A {} function f(A) { return A.x; }")]).is_empty());
+        assert_eq!(messages(&[("main", "class A {} function f(A) {} A.x;")]).len(), 1);
+    }
+
+    #[test]
+    fn test_visibility() {
+        let source = "class A { private x; protected y; private static z; m() { this.x; A.z; } } \
+            class B extends A { n() { this.x; this.y; A.z; } } \
+            class C { o(a) { A.z; } }";
+        assert_eq!(messages(&[("main", source)]), [
+            "member `x` of `A` is private",
+            "member `z` of `A` is private",
+            "member `z` of `A` is private",
+        ]);
+        assert_eq!(messages(&[("main", "class A { protected static y; } var v = A.y;")]), [
+            "member `y` of `A` is protected",
+        ]);
+    }
+
+    #[test]
+    fn test_overrides() {
+        assert_eq!(messages(&[("main", "class A { m(a) {} n() {} } class B extends A { m(a, b) {} n() {} }")]), [
+            "method `m` of `B` takes 2 parameters but overrides `A.m` which takes 1",
+        ]);
+    }
+}
@@ -0,0 +1,291 @@
+use crate::ast::*;
+
+/// A value known without running the program.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Constant {
+    Null,
+    Boolean(bool),
+    Integer(i64),
+    Real(f64),
+    String(String),
+}
+
+impl Constant {
+    pub fn from_literal(literal: &Literal) -> Option<Self> {
+        match literal {
+            Literal::Null => Some(Constant::Null),
+            Literal::Boolean(value) => Some(Constant::Boolean(*value)),
+            Literal::Number(number) => Some(match number.value {
+                NumberValue::Integer(value) => Constant::Integer(value),
+                NumberValue::Float(value) => Constant::Real(value),
+            }),
+            Literal::String(string) if string.invalid_escapes().is_empty() => Some(Constant::String(string.decoded())),
+            _ => None,
+        }
+    }
+
+    /// Whether the value counts as true in a condition.
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            Constant::Null => false,
+            Constant::Boolean(value) => *value,
+            Constant::Integer(value) => *value != 0,
+            Constant::Real(value) => *value != 0.0,
+            Constant::String(value) => !value.is_empty(),
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        matches!(self, Constant::Integer(0)) || matches!(self, Constant::Real(value) if *value == 0.0)
+    }
+
+    fn as_real(&self) -> Option<f64> {
+        match self {
+            Constant::Integer(value) => Some(*value as f64),
+            Constant::Real(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+/// A binary operator that can be applied to constants.
+pub trait Operator {
+    /// The result of `left op right`, `None` when it is an error, overflows or depends on the runtime.
+    fn apply(&self, left: &Constant, right: &Constant) -> Option<Constant>;
+
+    /// The result of `left op ...` when it does not depend on the right side.
+    fn short_circuit(&self, _left: &Constant) -> Option<Constant> {
+        None
+    }
+}
+
+/// Integer operations overflow into reals at runtime, so they are only folded when they fit.
+fn arithmetic(
+    left: &Constant,
+    right: &Constant,
+    integer: impl Fn(i64, i64) -> Option<i64>,
+    real: impl Fn(f64, f64) -> f64,
+) -> Option<Constant> {
+    match (left, right) {
+        (Constant::Integer(left), Constant::Integer(right)) => integer(*left, *right).map(Constant::Integer),
+        _ => {
+            let value = real(left.as_real()?, right.as_real()?);
+            value.is_finite().then_some(Constant::Real(value))
+        }
+    }
+}
+
+fn integers(left: &Constant, right: &Constant, f: impl Fn(i64, i64) -> i64) -> Option<Constant> {
+    match (left, right) {
+        (Constant::Integer(left), Constant::Integer(right)) => Some(Constant::Integer(f(*left, *right))),
+        _ => None,
+    }
+}
+
+impl Operator for AddOp {
+    fn apply(&self, left: &Constant, right: &Constant) -> Option<Constant> {
+        match (self, left, right) {
+            (AddOp::Add, Constant::String(left), Constant::String(right)) => Some(Constant::String(format!("{}{}", left, right))),
+            (AddOp::Add, Constant::String(left), Constant::Integer(right)) => Some(Constant::String(format!("{}{}", left, right))),
+            (AddOp::Add, Constant::Integer(left), Constant::String(right)) => Some(Constant::String(format!("{}{}", left, right))),
+            (AddOp::Add, left, right) => arithmetic(left, right, i64::checked_add, |a, b| a + b),
+            (AddOp::Sub, left, right) => arithmetic(left, right, i64::checked_sub, |a, b| a - b),
+        }
+    }
+}
+
+impl Operator for MultOp {
+    fn apply(&self, left: &Constant, right: &Constant) -> Option<Constant> {
+        match self {
+            MultOp::Mul => arithmetic(left, right, i64::checked_mul, |a, b| a * b),
+            // Dividing always gives a real
+            MultOp::Div if !right.is_zero() => {
+                let value = left.as_real()? / right.as_real()?;
+                value.is_finite().then_some(Constant::Real(value))
+            }
+            MultOp::Mod if !right.is_zero() => arithmetic(left, right, i64::checked_rem, |a, b| a % b),
+            _ => None,
+        }
+    }
+}
+
+impl Operator for ShiftOp {
+    fn apply(&self, left: &Constant, right: &Constant) -> Option<Constant> {
+        // Like in Java, only the low 6 bits of the shift count are used
+        integers(left, right, |left, right| match self {
+            ShiftOp::Left => left.wrapping_shl(right as u32),
+            ShiftOp::Right => left.wrapping_shr(right as u32),
+            ShiftOp::UnsignedRight => (left as u64).wrapping_shr(right as u32) as i64,
+        })
+    }
+}
+
+impl Operator for BinAndOp {
+    fn apply(&self, left: &Constant, right: &Constant) -> Option<Constant> {
+        integers(left, right, |left, right| left & right)
+    }
+}
+
+impl Operator for BinOrOp {
+    fn apply(&self, left: &Constant, right: &Constant) -> Option<Constant> {
+        integers(left, right, |left, right| left | right)
+    }
+}
+
+impl Operator for BinXorOp {
+    fn apply(&self, left: &Constant, right: &Constant) -> Option<Constant> {
+        integers(left, right, |left, right| left ^ right)
+    }
+}
+
+impl Operator for RelationOp {
+    fn apply(&self, left: &Constant, right: &Constant) -> Option<Constant> {
+        let ordering = match (left, right) {
+            (Constant::Integer(left), Constant::Integer(right)) => left.partial_cmp(right),
+            (Constant::String(left), Constant::String(right)) => left.partial_cmp(right),
+            (Constant::Boolean(left), Constant::Boolean(right)) => left.partial_cmp(right),
+            (Constant::Null, Constant::Null) => Some(std::cmp::Ordering::Equal),
+            (left, right) => match (left.as_real(), right.as_real()) {
+                (Some(left), Some(right)) => left.partial_cmp(&right),
+                _ => None,
+            },
+        };
+        let value = match (self, ordering) {
+            (RelationOp::Eq, ordering) => ordering?.is_eq(),
+            (RelationOp::NotEq, ordering) => ordering?.is_ne(),
+            // Strings and booleans are only compared for equality here
+            (_, _) if left.as_real().is_none() || right.as_real().is_none() => return None,
+            (RelationOp::Less, ordering) => ordering?.is_lt(),
+            (RelationOp::LessEq, ordering) => ordering?.is_le(),
+            (RelationOp::Greater, ordering) => ordering?.is_gt(),
+            (RelationOp::GreaterEq, ordering) => ordering?.is_ge(),
+        };
+        Some(Constant::Boolean(value))
+    }
+}
+
+impl Operator for LogicalAndOp {
+    fn apply(&self, left: &Constant, right: &Constant) -> Option<Constant> {
+        Some(Constant::Boolean(left.is_truthy() && right.is_truthy()))
+    }
+
+    fn short_circuit(&self, left: &Constant) -> Option<Constant> {
+        (!left.is_truthy()).then_some(Constant::Boolean(false))
+    }
+}
+
+impl Operator for LogicalOrOp {
+    fn apply(&self, left: &Constant, right: &Constant) -> Option<Constant> {
+        Some(Constant::Boolean(left.is_truthy() || right.is_truthy()))
+    }
+
+    fn short_circuit(&self, left: &Constant) -> Option<Constant> {
+        left.is_truthy().then_some(Constant::Boolean(true))
+    }
+}
+
+impl Operator for LogicalXorOp {
+    fn apply(&self, left: &Constant, right: &Constant) -> Option<Constant> {
+        Some(Constant::Boolean(left.is_truthy() != right.is_truthy()))
+    }
+}
+
+pub fn apply_unary(op: &UnaryOp, value: &Constant) -> Option<Constant> {
+    match (op, value) {
+        (UnaryOp::Not(_), value) => Some(Constant::Boolean(!value.is_truthy())),
+        (UnaryOp::Plus, Constant::Integer(_) | Constant::Real(_)) => Some(value.clone()),
+        (UnaryOp::Neg, Constant::Integer(value)) => value.checked_neg().map(Constant::Integer),
+        (UnaryOp::Neg, Constant::Real(value)) => Some(Constant::Real(-value)),
+        (UnaryOp::BitNot, Constant::Integer(value)) => Some(Constant::Integer(!value)),
+        _ => None,
+    }
+}
+
+/// The value of an expression made of literals.
+pub fn evaluate(expression: &Expression) -> Option<Constant> {
+    evaluate_with(expression, &|_| None)
+}
+
+/// The value of an expression made of literals and of the names `lookup` knows the value of.
+pub fn evaluate_with(expression: &Expression, lookup: &dyn Fn(&str) -> Option<Constant>) -> Option<Constant> {
+    let evaluate = |expression: &Expression| evaluate_with(expression, lookup);
+    match expression {
+        Expression::TernaryExpression(ternary) => match evaluate(&ternary.condition.node)?.is_truthy() {
+            true => evaluate(&ternary.true_expr.node),
+            false => evaluate(&ternary.false_expr.node),
+        },
+        Expression::LogicalOr(chain) => evaluate_chain(chain, lookup),
+        Expression::LogicalXor(chain) => evaluate_chain(chain, lookup),
+        Expression::LogicalAnd(chain) => evaluate_chain(chain, lookup),
+        Expression::Relation(chain) => evaluate_chain(chain, lookup),
+        Expression::Shift(chain) => evaluate_chain(chain, lookup),
+        Expression::BinOr(chain) => evaluate_chain(chain, lookup),
+        Expression::BinXor(chain) => evaluate_chain(chain, lookup),
+        Expression::BinAnd(chain) => evaluate_chain(chain, lookup),
+        Expression::Add(chain) => evaluate_chain(chain, lookup),
+        Expression::Mult(chain) => evaluate_chain(chain, lookup),
+        Expression::Unary(unary) => apply_unary(&unary.op.node, &evaluate(&unary.expr)?),
+        Expression::Primary(PrimaryExpr::Literal(literal)) => Constant::from_literal(literal),
+        Expression::Primary(PrimaryExpr::ParenthesizedExpr(expression)) => evaluate(&expression.node),
+        _ => lookup(&expression.as_identifier()?.node.name),
+    }
+}
+
+/// The value of `left op1 right1 op2 right2...`, applied from left to right.
+pub fn evaluate_chain<Op: Operator + ParseInto, E: ParseInto>(
+    chain: &BinExpr<Op, E>,
+    lookup: &dyn Fn(&str) -> Option<Constant>,
+) -> Option<Constant> {
+    let mut value = evaluate_with(&chain.left, lookup)?;
+    for (op, right) in &chain.right {
+        if let Some(result) = op.node.short_circuit(&value) {
+            return Some(result);
+        }
+        value = op.node.apply(&value, &evaluate_with(&right.node, lookup)?)?;
+    }
+    Some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::parse_file;
+
+    use super::*;
+
+    fn eval(input: &str) -> Option<Constant> {
+        let file = parse_file(&format!("{};", input));
+        let GlobalStatement::Statement(Statement::ExpressionStatement(statement)) = file.statements[0].node.as_ref() else {
+            panic!("not an expression");
+        };
+        evaluate(&statement.expression.node)
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        assert_eq!(eval("1 + 2 * 3"), Some(Constant::Integer(7)));
+        assert_eq!(eval("7 / 2"), Some(Constant::Real(3.5)));
+        assert_eq!(eval("-7 % 3"), Some(Constant::Integer(-1)));
+        assert_eq!(eval("1.5 + 1"), Some(Constant::Real(2.5)));
+        assert_eq!(eval("'a' + 'b' + 1"), Some(Constant::String("ab1".to_string())));
+        assert_eq!(eval("9223372036854775807 + 1"), None);
+        assert_eq!(eval("1 / 0"), None);
+        assert_eq!(eval("a + 1"), None);
+    }
+
+    #[test]
+    fn test_bits() {
+        assert_eq!(eval("(1 << 4) | 0x0f & ~1"), Some(Constant::Integer(30)));
+        assert_eq!(eval("-8 >> 1"), Some(Constant::Integer(-4)));
+        assert_eq!(eval("-1 >>> 60"), Some(Constant::Integer(15)));
+    }
+
+    #[test]
+    fn test_logic() {
+        assert_eq!(eval("1 < 2 && 2 <= 2"), Some(Constant::Boolean(true)));
+        assert_eq!(eval("'a' == 'a' xor null == null"), Some(Constant::Boolean(false)));
+        assert_eq!(eval("false and f()"), Some(Constant::Boolean(false)));
+        assert_eq!(eval("!0 || f()"), Some(Constant::Boolean(true)));
+        assert_eq!(eval("'a' < 'b'"), None);
+        assert_eq!(eval("1 ? 'x' : f()"), Some(Constant::String("x".to_string())));
+    }
+}
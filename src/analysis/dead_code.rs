@@ -0,0 +1,335 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::ast::{visit::*, *};
+
+use super::{evaluate, includes, resolve_include, Diagnostic, Project};
+
+/// Reports the code of a project that never runs: statements following a `return`, `throw`,
+/// `break`, `continue` or infinite loop, `if` branches ruled out by a constant condition, and
+/// the functions and classes that can not be reached from the top-level code of the files.
+pub fn find_dead_code(project: &Project) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    for source in &project.files {
+        diagnostics.extend(find_unreachable_code(&source.file).into_iter().map(|diagnostic| diagnostic.in_file(&source.path)));
+    }
+    diagnostics.extend(find_unused_declarations(project));
+    diagnostics
+}
+
+/// Reports the statements and `if` branches of a file that can never run.
+pub fn find_unreachable_code(file: &File) -> Vec<Diagnostic> {
+    let mut finder = UnreachableFinder { diagnostics: Vec::new() };
+    finder.visit_file(file);
+    finder.diagnostics
+}
+
+struct UnreachableFinder {
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl UnreachableFinder {
+    /// Reports the first statement following one that never completes.
+    fn check_sequence<'a>(&mut self, statements: impl Iterator<Item = (&'a Statement, Option<Location>)>) {
+        let mut completes = true;
+        for (statement, location) in statements {
+            if matches!(statement, Statement::Empty(_)) {
+                continue;
+            }
+            if !completes {
+                self.diagnostics.push(Diagnostic::warning("unreachable code", location));
+                return;
+            }
+            completes = can_complete(statement);
+        }
+    }
+
+    fn check_branches(&mut self, statement: &Statement) {
+        if let Statement::IfStatement(if_statement) = statement {
+            match evaluate(&if_statement.condition.node.expression.node).map(|condition| condition.is_truthy()) {
+                Some(true) => {
+                    if let Some(else_block) = &if_statement.else_block {
+                        self.diagnostics.push(Diagnostic::warning(
+                            "the condition is always true, this `else` branch never runs",
+                            else_block.location,
+                        ));
+                    }
+                }
+                Some(false) => self.diagnostics.push(Diagnostic::warning(
+                    "the condition is always false, this branch never runs",
+                    if_statement.block.location,
+                )),
+                None => {}
+            }
+        }
+    }
+}
+
+impl<'ast> Visitor<'ast> for UnreachableFinder {
+    fn visit_file(&mut self, file: &'ast File) {
+        // Function and class definitions are hoisted, so only the statements are in sequence
+        self.check_sequence(file.statements.iter().filter_map(|statement| match statement.node.as_ref() {
+            GlobalStatement::Statement(node) => Some((node, statement.location)),
+            _ => None,
+        }));
+        walk_file(self, file);
+    }
+
+    fn visit_block(&mut self, block: &'ast BlockStatement) {
+        if let BlockStatement::StatementList(statements) = block {
+            self.check_sequence(statements.iter().map(|statement| (statement.node.as_ref(), statement.location)));
+        }
+        walk_block(self, block);
+    }
+
+    fn visit_global_statement(&mut self, statement: &'ast MetaNode<GlobalStatement>) {
+        if let GlobalStatement::Statement(node) = statement.node.as_ref() {
+            self.check_branches(node);
+        }
+        walk_global_statement(self, statement);
+    }
+
+    fn visit_statement(&mut self, statement: &'ast MetaNode<Statement>) {
+        self.check_branches(&statement.node);
+        walk_statement(self, statement);
+    }
+}
+
+/// Whether running `statement` can go on with the statement after it.
+fn can_complete(statement: &Statement) -> bool {
+    match statement {
+        Statement::ReturnStatement(_) | Statement::ThrowStatement(_) | Statement::BreakStatement(_) => false,
+        Statement::Block(block) => block_can_complete(block),
+        Statement::IfStatement(if_statement) => match &if_statement.else_block {
+            Some(else_block) => can_complete(&if_statement.block.node) || can_complete(&else_block.node),
+            None => true,
+        },
+        Statement::WhileStatement(while_statement) => {
+            !is_always_true(&while_statement.condition.node) || jumps_out(&while_statement.block.node, is_break)
+        }
+        Statement::DoWhileStatement(do_while) => {
+            let body = &do_while.statement.node;
+            let reaches_condition = can_complete(body) || jumps_out(body, is_continue);
+            (reaches_condition && !is_always_true(&do_while.condition.node)) || jumps_out(body, is_break)
+        }
+        Statement::ForStatement(for_statement) => match for_statement.for_header.node.as_ref() {
+            ForHeader::ForIter(ForIter { condition, .. }) => {
+                let infinite = condition.as_ref().is_none_or(|condition| is_always_true(&condition.node));
+                !infinite || jumps_out(&for_statement.block.node, is_break)
+            }
+            ForHeader::ForIn(_) => true,
+        },
+        Statement::TryStatement(try_statement) => match &try_statement.finally {
+            Some(finally) if !block_can_complete(&finally.node) => false,
+            _ => {
+                block_can_complete(&try_statement.block.node)
+                    || try_statement.catches.iter().any(|catch| block_can_complete(&catch.node.block.node))
+            }
+        },
+        _ => true,
+    }
+}
+
+fn block_can_complete(block: &BlockStatement) -> bool {
+    match block {
+        BlockStatement::Empty(_) => true,
+        BlockStatement::StatementList(statements) => statements.iter().all(|statement| can_complete(&statement.node)),
+    }
+}
+
+fn is_always_true(condition: &Expression) -> bool {
+    evaluate(condition).is_some_and(|condition| condition.is_truthy())
+}
+
+fn is_break(statement: &BreakStatement) -> bool {
+    matches!(statement, BreakStatement::Break(_))
+}
+
+fn is_continue(statement: &BreakStatement) -> bool {
+    matches!(statement, BreakStatement::Continue(_))
+}
+
+/// Whether the body of a loop has a `break` or `continue` matching `kind` for that loop.
+fn jumps_out(body: &Statement, kind: fn(&BreakStatement) -> bool) -> bool {
+    struct JumpFinder {
+        kind: fn(&BreakStatement) -> bool,
+        found: bool,
+    }
+
+    impl<'ast> Visitor<'ast> for JumpFinder {
+        fn visit_statement(&mut self, statement: &'ast MetaNode<Statement>) {
+            match statement.node.as_ref() {
+                // Those belong to the inner loop
+                Statement::WhileStatement(_) | Statement::DoWhileStatement(_) | Statement::ForStatement(_) => {}
+                Statement::BreakStatement(jump) => self.found |= (self.kind)(jump),
+                _ => walk_statement(self, statement),
+            }
+        }
+
+        fn visit_anonymous_function(&mut self, _function: &'ast AnonymousFunction) {}
+    }
+
+    let mut finder = JumpFinder { kind, found: false };
+    match body {
+        Statement::BreakStatement(jump) => return kind(jump),
+        body => walk_statement_node(&mut finder, body),
+    }
+    finder.found
+}
+
+/// A function or a class, reached when a reached piece of code names it.
+struct Declaration<'a> {
+    /// How the declaration is reported
+    unused: &'static str,
+    name: &'a MetaNode<Identifier>,
+    file: &'a str,
+    names: HashSet<&'a str>,
+}
+
+/// Reports the functions and classes of a project that the top-level code never reaches, even
+/// through other functions and classes. Only the top-level code of the entry and of the files
+/// it includes, directly or not, is run.
+pub fn find_unused_declarations(project: &Project) -> Vec<Diagnostic> {
+    let mut declarations: HashMap<&str, Declaration> = HashMap::new();
+    let mut reached = Vec::new();
+
+    let mut run = HashSet::new();
+    let mut pending: Vec<String> = project.files.first().map(|entry| entry.path.clone()).into_iter().collect();
+    while let Some(path) = pending.pop() {
+        let Some(source) = project.get(&path) else { continue };
+        if run.insert(source.path.as_str()) {
+            pending.extend(includes(&source.file).map(|include| resolve_include(&path, &include.path.node.decoded())));
+        }
+    }
+
+    for source in &project.files {
+        for statement in &source.file.statements {
+            let (unused, name, names) = match statement.node.as_ref() {
+                GlobalStatement::FunctionDefinition(function) => ("is never called", &function.name, NameCollector::collect(|collector| {
+                    collector.visit_function_definition(function)
+                })),
+                GlobalStatement::ClassDefinition(class) => ("is never used", &class.name, NameCollector::collect(|collector| {
+                    // A subclass uses its parent
+                    if let Some(extends) = &class.extends {
+                        collector.names.insert(&extends.node.name);
+                    }
+                    collector.visit_class(class)
+                })),
+                _ if !run.contains(source.path.as_str()) => continue,
+                _ => {
+                    reached.extend(NameCollector::collect(|collector| collector.visit_global_statement(statement)));
+                    continue;
+                }
+            };
+            declarations.entry(&name.node.name).or_insert(Declaration { unused, name, file: &source.path, names });
+        }
+    }
+
+    let mut seen = HashSet::new();
+    while let Some(name) = reached.pop() {
+        if !seen.insert(name) {
+            continue;
+        }
+        if let Some(declaration) = declarations.get(name) {
+            reached.extend(declaration.names.iter().copied());
+        }
+    }
+
+    let mut unused: Vec<_> = declarations.values().filter(|declaration| !seen.contains(declaration.name.node.name.as_str())).collect();
+    unused.sort_by_key(|declaration| (declaration.file, declaration.name.location.map(|location| location.start)));
+    unused.into_iter()
+        .map(|declaration| {
+            let message = format!("`{}` {}", declaration.name.node, declaration.unused);
+            Diagnostic::warning(message, declaration.name.location).in_file(declaration.file)
+        })
+        .collect()
+}
+
+/// The names used by a piece of code.
+#[derive(Default)]
+struct NameCollector<'ast> {
+    names: HashSet<&'ast str>,
+}
+
+impl<'ast> NameCollector<'ast> {
+    fn collect(f: impl FnOnce(&mut Self)) -> HashSet<&'ast str> {
+        let mut collector = NameCollector::default();
+        f(&mut collector);
+        collector.names
+    }
+}
+
+impl<'ast> Visitor<'ast> for NameCollector<'ast> {
+    fn visit_primary(&mut self, primary: &'ast PrimaryExpr) {
+        if let PrimaryExpr::IdentifierOrMember(identifier) = primary {
+            if let IdentifierOrMember::Identifier(name) = identifier.node.as_ref() {
+                self.names.insert(&name.node.name);
+            }
+        }
+        walk_primary(self, primary);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{analysis::project::tests::project, utils::parse_file};
+
+    use super::*;
+
+    fn unreachable(input: &str) -> Vec<String> {
+        find_unreachable_code(&parse_file(input)).iter().map(|diagnostic| diagnostic.to_string()).collect()
+    }
+
+    #[test]
+    fn test_after_jumps() {
+        assert_eq!(unreachable("function f() { return 1; f(); }"), ["1:26: warning: unreachable code"]);
+        assert_eq!(unreachable("while (a) { if (b) { break; } else { continue; } c(); }"), ["1:50: warning: unreachable code"]);
+        assert_eq!(unreachable("throw 1;\n;\nf();"), ["3:1: warning: unreachable code"]);
+        assert!(unreachable("function f() { if (a) return 1; f(); } return 2; function g() {}").is_empty());
+    }
+
+    #[test]
+    fn test_after_infinite_loops() {
+        assert_eq!(unreachable("for (;;) { f(); } g();"), ["1:19: warning: unreachable code"]);
+        assert_eq!(unreachable("while (1 < 2) { while (true) { break; } } g();"), ["1:43: warning: unreachable code"]);
+        assert!(unreachable("while (true) { if (a) break; } g();").is_empty());
+        assert!(unreachable("do { if (a) break; } while (true); g();").is_empty());
+        assert!(unreachable("for (;;) { var f = function() { break; }; if (a) { break; } } g();").is_empty());
+    }
+
+    #[test]
+    fn test_constant_branches() {
+        assert_eq!(unreachable("if (1 > 2) f();"), ["1:12: warning: the condition is always false, this branch never runs"]);
+        assert_eq!(
+            unreachable("if (!false) f(); else g();"),
+            ["1:23: warning: the condition is always true, this `else` branch never runs"]
+        );
+        assert!(unreachable("if (a) f(); else g();").is_empty());
+    }
+
+    #[test]
+    fn test_unused_declarations() {
+        let project = project(&[
+            ("main", "include('lib'); var x = new A(); f(g);"),
+            ("lib", "function f(c) { c(); } function g() { return B; } function h() { h(); i(); } function i() {}\n\
+                     class A extends C {} class B {} class C {} class D { m() { return new E(); } } class E {}"),
+        ]);
+        let diagnostics: Vec<_> = find_dead_code(&project).iter().map(|diagnostic| diagnostic.to_string()).collect();
+        assert_eq!(diagnostics, [
+            "lib:1:60: warning: `h` is never called",
+            "lib:1:87: warning: `i` is never called",
+            "lib:2:50: warning: `D` is never used",
+            "lib:2:86: warning: `E` is never used",
+        ]);
+    }
+
+    #[test]
+    fn test_unused_declarations_of_files_not_included() {
+        let project = project(&[
+            ("main", "include('lib'); f();"),
+            ("lib", "function f() {} function g() {}"),
+            ("other", "include('lib'); g();"),
+        ]);
+        let diagnostics: Vec<_> = find_unused_declarations(&project).iter().map(|diagnostic| diagnostic.to_string()).collect();
+        assert_eq!(diagnostics, ["lib:1:26: warning: `g` is never called"]);
+    }
+}
@@ -0,0 +1,73 @@
+use std::fmt::Display;
+
+use derive_more::Display;
+
+use crate::ast::Location;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Display)]
+pub enum Severity {
+    #[display(fmt = "error")] Error,
+    #[display(fmt = "warning")] Warning,
+    #[display(fmt = "info")] Info,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    pub location: Option<Location>,
+    pub file: Option<String>,
+    /// The lint rule that reported it, if any
+    pub rule: Option<String>,
+}
+
+impl Diagnostic {
+    pub fn error(message: impl Into<String>, location: Option<Location>) -> Self {
+        Diagnostic {
+            severity: Severity::Error,
+            message: message.into(),
+            location,
+            file: None,
+            rule: None,
+        }
+    }
+
+    pub fn warning(message: impl Into<String>, location: Option<Location>) -> Self {
+        Diagnostic {
+            severity: Severity::Warning,
+            message: message.into(),
+            location,
+            file: None,
+            rule: None,
+        }
+    }
+
+    pub fn in_file(self, file: impl Into<String>) -> Self {
+        Diagnostic {
+            file: Some(file.into()),
+            ..self
+        }
+    }
+
+    pub fn with_rule(self, rule: impl Into<String>) -> Self {
+        Diagnostic {
+            rule: Some(rule.into()),
+            ..self
+        }
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (&self.file, &self.location) {
+            (Some(file), Some(location)) => write!(f, "{}:{}: ", file, location.start)?,
+            (Some(file), None) => write!(f, "{}: ", file)?,
+            (None, Some(location)) => write!(f, "{}: ", location.start)?,
+            (None, None) => {}
+        }
+        match &self.rule {
+            Some(rule) => write!(f, "{}[{}]: {}", self.severity, rule, self.message),
+            None => write!(f, "{}: {}", self.severity, self.message),
+        }
+    }
+}
@@ -0,0 +1,307 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::ast::{visit::*, *};
+
+use super::{Access, BindingId, CfgNode, ControlFlowGraph, Diagnostic, EdgeKind, Resolution};
+
+/// Reports the variables that can be read before any value is assigned to them, and the
+/// functions declaring a return type whose end can be reached without a `return`.
+pub fn check_flow(file: &File) -> Vec<Diagnostic> {
+    let mut checker = FlowChecker { names: Names::new(&Resolution::resolve(file)), diagnostics: Vec::new() };
+    checker.check_assignments(&ControlFlowGraph::file(file));
+    checker.visit_file(file);
+    checker.diagnostics.sort_by_key(|diagnostic| diagnostic.location.map(|location| location.start));
+    checker.diagnostics
+}
+
+/// The bindings declared and used by a file, by the offset of their name.
+struct Names {
+    declarations: HashMap<usize, BindingId>,
+    references: HashMap<usize, (BindingId, Access)>,
+}
+
+impl Names {
+    fn new(resolution: &Resolution) -> Self {
+        let offset = |location: Option<Location>| location.map(|location| location.start.offset);
+        Names {
+            declarations: resolution.bindings.iter().enumerate()
+                .filter_map(|(id, binding)| Some((offset(binding.location)?, id)))
+                .collect(),
+            references: resolution.references.iter()
+                .filter_map(|reference| Some((offset(reference.location)?, (reference.binding?, reference.access))))
+                .collect(),
+        }
+    }
+
+    fn declaration(&self, name: &MetaNode<Identifier>) -> Option<BindingId> {
+        self.declarations.get(&name.location?.start.offset).copied()
+    }
+
+    fn reference(&self, name: &MetaNode<Identifier>) -> Option<(BindingId, Access)> {
+        self.references.get(&name.location?.start.offset).copied()
+    }
+}
+
+struct FlowChecker {
+    names: Names,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl FlowChecker {
+    /// Follows the variables that may still be unassigned through the graph, until they are
+    /// known at the start of every block, then reports the reads of those variables.
+    fn check_assignments(&mut self, graph: &ControlFlowGraph) {
+        let reachable = graph.reachable();
+        let mut inputs = vec![HashSet::new(); graph.blocks.len()];
+        let mut outputs = vec![HashSet::new(); graph.blocks.len()];
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for block in (0..graph.blocks.len()).filter(|&block| reachable[block]) {
+                let mut input: HashSet<BindingId> = HashSet::new();
+                for (predecessor, edge) in graph.predecessors(block) {
+                    // An exception can leave the block before any of its assignments
+                    if edge.kind == EdgeKind::Exception {
+                        input.extend(&inputs[predecessor]);
+                    }
+                    input.extend(&outputs[predecessor]);
+                }
+                let output = self.transfer(graph, block, input.clone()).unassigned;
+                if input != inputs[block] || output != outputs[block] {
+                    (inputs[block], outputs[block]) = (input, output);
+                    changed = true;
+                }
+            }
+        }
+
+        let mut reported = HashSet::new();
+        for block in (0..graph.blocks.len()).filter(|&block| reachable[block]) {
+            for name in self.transfer(graph, block, inputs[block].clone()).reads {
+                if reported.insert(name.location.map(|location| location.start.offset)) {
+                    let message = format!("`{}` can be read before it is assigned", name.node);
+                    self.diagnostics.push(Diagnostic::warning(message, name.location));
+                }
+            }
+        }
+    }
+
+    fn transfer<'a>(&self, graph: &ControlFlowGraph<'a>, block: usize, unassigned: HashSet<BindingId>) -> Transfer<'_, 'a> {
+        let mut transfer = Transfer { names: &self.names, unassigned, conditional: 0, reads: Vec::new() };
+        for node in &graph.blocks[block].nodes {
+            match *node {
+                CfgNode::Statement(statement) => walk_statement_node(&mut transfer, statement),
+                CfgNode::Declaration(declaration) => transfer.visit_var_declaration(declaration),
+                CfgNode::Element(declaration) => {
+                    for (name, _) in &declaration.values {
+                        transfer.assign(self.names.declaration(name));
+                    }
+                }
+                CfgNode::Expression(expression) | CfgNode::Condition(expression) => transfer.visit_expression(expression),
+            }
+        }
+        transfer
+    }
+
+    fn check_return(&mut self, graph: &ControlFlowGraph, return_type: &MetaNode<Type>, what: String, location: Option<Location>) {
+        if return_type.node.type_.node.name != "void" && graph.falls_through() {
+            let message = format!("{} declares the return type `{}` but can end without returning a value",
+                what, return_type.node.to_string().trim());
+            self.diagnostics.push(Diagnostic::warning(message, location));
+        }
+    }
+}
+
+impl<'ast> Visitor<'ast> for FlowChecker {
+    fn visit_function_definition(&mut self, function: &'ast FunctionDefinition) {
+        let graph = ControlFlowGraph::function(function);
+        self.check_assignments(&graph);
+        if let Some((_, return_type)) = &function.return_type {
+            self.check_return(&graph, return_type, format!("function `{}`", function.name.node), function.name.location);
+        }
+        walk_function_definition(self, function);
+    }
+
+    fn visit_method(&mut self, method: &'ast Method) {
+        let graph = ControlFlowGraph::method(method);
+        self.check_assignments(&graph);
+        // Abstract methods have no end to reach
+        if let (Some(return_type), Some(_)) = (&method.return_type, method.body.block()) {
+            self.check_return(&graph, return_type, format!("method `{}`", method.name.node), method.name.location);
+        }
+        walk_method(self, method);
+    }
+
+    fn visit_constructor(&mut self, constructor: &'ast Constructor) {
+        self.check_assignments(&ControlFlowGraph::constructor(constructor));
+        walk_constructor(self, constructor);
+    }
+
+    fn visit_anonymous_function(&mut self, function: &'ast AnonymousFunction) {
+        let graph = ControlFlowGraph::anonymous_function(function);
+        self.check_assignments(&graph);
+        let return_type = match function {
+            AnonymousFunction::Function(function) => function.return_type.as_ref().map(|(_, return_type)| return_type),
+            AnonymousFunction::ArrowFunction(arrow) => arrow.return_type.as_ref(),
+        };
+        if let Some(return_type) = return_type {
+            self.check_return(&graph, return_type, "this function".to_string(), return_type.location);
+        }
+        walk_anonymous_function(self, function);
+    }
+}
+
+/// Runs the nodes of a block on the variables that may still be unassigned, keeping the reads
+/// of those variables.
+struct Transfer<'n, 'ast> {
+    names: &'n Names,
+    unassigned: HashSet<BindingId>,
+    /// How many operands that may not be evaluated enclose the current expression
+    conditional: usize,
+    reads: Vec<&'ast MetaNode<Identifier>>,
+}
+
+impl<'ast> Transfer<'_, 'ast> {
+    fn read(&mut self, name: &'ast MetaNode<Identifier>) {
+        if let Some((binding, _)) = self.names.reference(name) {
+            if self.unassigned.contains(&binding) {
+                self.reads.push(name);
+            }
+        }
+    }
+
+    fn assign(&mut self, binding: Option<BindingId>) {
+        if let (Some(binding), 0) = (binding, self.conditional) {
+            self.unassigned.remove(&binding);
+        }
+    }
+
+    fn conditionally(&mut self, f: impl FnOnce(&mut Self)) {
+        self.conditional += 1;
+        f(self);
+        self.conditional -= 1;
+    }
+
+    fn update(&mut self, target: &'ast Expression) {
+        match target.as_identifier() {
+            Some(name) => {
+                self.read(name);
+                self.assign(self.names.reference(name).map(|(binding, _)| binding));
+            }
+            None => self.visit_expression(target),
+        }
+    }
+}
+
+impl<'ast> Visitor<'ast> for Transfer<'_, 'ast> {
+    fn visit_var_declaration(&mut self, declaration: &'ast VarDeclaration) {
+        for (name, value) in &declaration.values {
+            let binding = self.names.declaration(name);
+            match value {
+                Some(value) => {
+                    self.visit_expression(&value.node);
+                    self.assign(binding);
+                }
+                None => self.unassigned.extend(binding),
+            }
+        }
+    }
+
+    fn visit_expression(&mut self, expression: &'ast Expression) {
+        match expression {
+            Expression::Assign(assign) => {
+                for (_, value) in &assign.right {
+                    self.visit_expression(&value.node);
+                }
+                match assign.left.as_identifier() {
+                    Some(name) => match self.names.reference(name) {
+                        Some((_, Access::ReadWrite)) => self.update(&assign.left),
+                        reference => self.assign(reference.map(|(binding, _)| binding)),
+                    },
+                    None => self.visit_expression(&assign.left),
+                }
+            }
+            Expression::TernaryExpression(ternary) => {
+                self.visit_expression(&ternary.condition.node);
+                self.conditionally(|transfer| {
+                    transfer.visit_expression(&ternary.true_expr.node);
+                    transfer.visit_expression(&ternary.false_expr.node);
+                });
+            }
+            // Only the first operand is sure to be evaluated
+            Expression::LogicalAnd(chain) => {
+                self.visit_expression(&chain.left);
+                self.conditionally(|transfer| chain.right.iter().for_each(|(_, right)| transfer.visit_expression(&right.node)));
+            }
+            Expression::LogicalOr(chain) => {
+                self.visit_expression(&chain.left);
+                self.conditionally(|transfer| chain.right.iter().for_each(|(_, right)| transfer.visit_expression(&right.node)));
+            }
+            Expression::PreUpdate(update) => self.update(&update.expr),
+            Expression::PostUpdate(update) => self.update(&update.expr),
+            _ => walk_expression(self, expression),
+        }
+    }
+
+    fn visit_primary(&mut self, primary: &'ast PrimaryExpr) {
+        match primary {
+            PrimaryExpr::IdentifierOrMember(identifier) => {
+                if let IdentifierOrMember::Identifier(name) = identifier.node.as_ref() {
+                    self.read(name);
+                }
+            }
+            _ => walk_primary(self, primary),
+        }
+    }
+
+    /// Its code runs later, with its own graph
+    fn visit_anonymous_function(&mut self, _function: &'ast AnonymousFunction) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::parse_file;
+
+    use super::*;
+
+    fn check(input: &str) -> Vec<String> {
+        check_flow(&parse_file(input)).iter().map(|diagnostic| diagnostic.to_string()).collect()
+    }
+
+    #[test]
+    fn test_definite_assignment() {
+        assert_eq!(check("var x; if (a) { x = 1; } f(x);"), ["1:28: warning: `x` can be read before it is assigned"]);
+        assert_eq!(check("var x; x += 1; var y; y++;"), [
+            "1:8: warning: `x` can be read before it is assigned",
+            "1:23: warning: `y` can be read before it is assigned",
+        ]);
+        assert_eq!(check("var x; a && (x = 1); f(x);"), ["1:24: warning: `x` can be read before it is assigned"]);
+        assert_eq!(check("function f() { var x; while (a) { f(x); x = 2; } }"), ["1:37: warning: `x` can be read before it is assigned"]);
+        assert!(check("var x; if (a) { x = 1; } else { x = 2; } f(x);").is_empty());
+        assert_eq!(check("var x; x = x + 1;").len(), 1);
+        assert!(check("var x; for (var k : var v in a) { x = k + v; } var y = 1; y++;").is_empty());
+        assert!(check("var x; return; f(x);").is_empty());
+        assert!(check("var x; var g = function() { return x; }; x = 1;").is_empty());
+    }
+
+    #[test]
+    fn test_try() {
+        assert_eq!(check("var x; try { f(); x = 1; } catch (e) { } f(x);"), ["1:44: warning: `x` can be read before it is assigned"]);
+        assert!(check("var x; try { x = f(); } catch (e) { x = 0; } f(x);").is_empty());
+    }
+
+    #[test]
+    fn test_missing_return() {
+        assert_eq!(check("function f(a) -> integer { if (a) { return 1; } }"), [
+            "1:10: warning: function `f` declares the return type `integer` but can end without returning a value",
+        ]);
+        assert_eq!(check("class A { integer m() { } abstract integer n() }"), [
+            "1:19: warning: method `m` declares the return type `integer` but can end without returning a value",
+        ]);
+        assert_eq!(check("var f = function() => integer { };"), [
+            "1:23: warning: this function declares the return type `integer` but can end without returning a value",
+        ]);
+        assert!(check("function f(a) -> integer { if (a) { return 1; } else { throw 2; } }").is_empty());
+        assert!(check("function f() -> integer { while (true) { } }").is_empty());
+        assert!(check("function f() -> void { } var g = (x) => integer x;").is_empty());
+    }
+}
@@ -0,0 +1,29 @@
+pub mod diagnostic;
+pub mod validation;
+pub mod project;
+pub mod class_table;
+pub mod scope;
+pub mod typing;
+pub mod constant;
+pub mod dead_code;
+pub mod cfg;
+pub mod flow;
+pub mod builtins;
+pub mod calls;
+pub mod call_graph;
+pub mod bundle;
+
+pub use diagnostic::*;
+pub use validation::*;
+pub use project::*;
+pub use class_table::*;
+pub use scope::*;
+pub use typing::*;
+pub use constant::*;
+pub use dead_code::*;
+pub use cfg::*;
+pub use flow::*;
+pub use builtins::*;
+pub use calls::*;
+pub use call_graph::*;
+pub use bundle::*;
@@ -0,0 +1,172 @@
+use std::{collections::HashSet, fmt::Display, fs, io};
+
+use crate::ast::*;
+
+/// A parsed source file, identified by the path it was loaded from.
+#[derive(Debug, Clone)]
+pub struct SourceFile {
+    pub path: String,
+    pub file: File,
+}
+
+/// An entry file and every file it reaches through `include`, in the order they were found.
+#[derive(Debug, Clone)]
+pub struct Project {
+    pub files: Vec<SourceFile>,
+}
+
+#[derive(Debug)]
+pub enum ProjectError {
+    Io { path: String, error: io::Error },
+    Parse { path: String, line: u32, column: usize },
+}
+
+impl Display for ProjectError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProjectError::Io { path, error } => write!(f, "{}: {}", path, error),
+            ProjectError::Parse { path, line, column } => write!(f, "{}:{}:{}: syntax error", path, line, column),
+        }
+    }
+}
+
+impl std::error::Error for ProjectError {}
+
+impl Project {
+    pub fn load(entry: &str) -> Result<Self, ProjectError> {
+        Self::load_with(entry, |path| fs::read_to_string(path))
+    }
+
+    /// Loads a project using `read` to get the content of each file, so that projects do not
+    /// need to live on disk.
+    pub fn load_with<F>(entry: &str, mut read: F) -> Result<Self, ProjectError>
+    where
+        F: FnMut(&str) -> io::Result<String>,
+    {
+        let mut files = Vec::new();
+        let mut seen = HashSet::new();
+        let mut pending = vec![normalize_path(entry)];
+
+        while let Some(path) = pending.pop() {
+            if !seen.insert(path.clone()) {
+                continue;
+            }
+
+            let source = read(&path).map_err(|error| ProjectError::Io { path: path.clone(), error })?;
+            let file = parse_source(&path, &source)?;
+
+            // Reversed so the first include is loaded first
+            for include in includes(&file).rev() {
+                pending.push(resolve_include(&path, &include.path.node.decoded()));
+            }
+            files.push(SourceFile { path, file });
+        }
+
+        Ok(Project { files })
+    }
+
+    pub fn entry(&self) -> &SourceFile {
+        &self.files[0]
+    }
+
+    pub fn get(&self, path: &str) -> Option<&SourceFile> {
+        self.files.iter().find(|file| file.path == path)
+    }
+}
+
+pub(crate) fn parse_source(path: &str, source: &str) -> Result<File, ProjectError> {
+    match File::parse(Span::new_extra(source, path)) {
+        Ok((_, file)) => Ok(*file.node),
+        Err(nom::Err::Error(error) | nom::Err::Failure(error)) => Err(ProjectError::Parse {
+            path: path.to_string(),
+            line: error.input.location_line(),
+            column: error.input.get_utf8_column(),
+        }),
+        Err(nom::Err::Incomplete(_)) => Err(ProjectError::Parse {
+            path: path.to_string(),
+            line: 0,
+            column: 0,
+        }),
+    }
+}
+
+/// The `include` statements at the top level of a file.
+pub fn includes(file: &File) -> impl DoubleEndedIterator<Item = &IncludeStatement> {
+    file.statements.iter().filter_map(|statement| match statement.node.as_ref() {
+        GlobalStatement::IncludeStatement(include) => Some(include),
+        _ => None,
+    })
+}
+
+/// Resolves the path given to `include` relatively to the directory of the including file.
+pub fn resolve_include(from: &str, include: &str) -> String {
+    if include.starts_with('/') {
+        return normalize_path(include);
+    }
+    match from.rfind('/') {
+        Some(index) => normalize_path(&format!("{}/{}", &from[..index], include)),
+        None => normalize_path(include),
+    }
+}
+
+fn normalize_path(path: &str) -> String {
+    let mut parts: Vec<&str> = Vec::new();
+    for part in path.split('/') {
+        match part {
+            "" | "." if !parts.is_empty() => {}
+            ".." if parts.last().is_some_and(|last| *last != ".." && !last.is_empty()) => {
+                parts.pop();
+            }
+            part => parts.push(part),
+        }
+    }
+    parts.join("/")
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    /// Loads a project from in-memory files, the first one being the entry.
+    pub fn project(files: &[(&str, &str)]) -> Project {
+        let sources: HashMap<_, _> = files.iter().cloned().collect();
+        Project::load_with(files[0].0, |path| {
+            sources.get(path)
+                .map(|source| source.to_string())
+                .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, path.to_string()))
+        }).unwrap()
+    }
+
+    #[test]
+    fn test_resolve_include() {
+        assert_eq!(resolve_include("main", "lib"), "lib");
+        assert_eq!(resolve_include("ai/main", "lib"), "ai/lib");
+        assert_eq!(resolve_include("ai/main", "../lib/util"), "lib/util");
+        assert_eq!(resolve_include("ai/main", "./lib"), "ai/lib");
+        assert_eq!(resolve_include("ai/main", "/lib"), "/lib");
+    }
+
+    #[test]
+    fn test_load() {
+        let project = project(&[
+            ("main", "include('a'); include('lib/b'); var x = 1;"),
+            ("a", "include('lib/b'); function a() {}"),
+            ("lib/b", "include('../a'); function b() {}"),
+        ]);
+
+        let paths: Vec<_> = project.files.iter().map(|file| file.path.as_str()).collect();
+        assert_eq!(paths, ["main", "a", "lib/b"]);
+        assert_eq!(project.entry().path, "main");
+    }
+
+    #[test]
+    fn test_load_errors() {
+        let result = Project::load_with("main", |_| Ok("var = ;".to_string()));
+        assert!(matches!(result, Err(ProjectError::Parse { .. })));
+
+        let result = Project::load_with("main", |_| Err(io::Error::new(io::ErrorKind::NotFound, "main")));
+        assert!(matches!(result, Err(ProjectError::Io { .. })));
+    }
+}
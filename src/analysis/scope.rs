@@ -0,0 +1,438 @@
+use std::collections::HashMap;
+
+use crate::ast::{visit::*, *};
+
+pub type ScopeId = usize;
+pub type BindingId = usize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BindingKind {
+    Variable,
+    Constant,
+    Parameter,
+    Global,
+    GlobalConstant,
+    Function,
+    Class,
+}
+
+impl BindingKind {
+    pub fn is_constant(&self) -> bool {
+        matches!(self, BindingKind::Constant | BindingKind::GlobalConstant)
+    }
+}
+
+/// A name introduced by a declaration.
+#[derive(Debug, Clone)]
+pub struct Binding {
+    pub name: String,
+    pub kind: BindingKind,
+    pub scope: ScopeId,
+    /// Index of the declaring file in the resolved files.
+    pub file: usize,
+    pub location: Option<Location>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    Read,
+    Write,
+    ReadWrite,
+}
+
+/// A use of a name in an expression, with the binding it refers to if one was found.
+#[derive(Debug, Clone)]
+pub struct Reference {
+    pub name: String,
+    pub binding: Option<BindingId>,
+    pub access: Access,
+    pub scope: ScopeId,
+    pub file: usize,
+    pub location: Option<Location>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Scope {
+    pub parent: Option<ScopeId>,
+    pub bindings: Vec<BindingId>,
+}
+
+/// The scopes, declarations and name uses of one or more files.
+///
+/// Functions, classes and globals live in the root scope shared by all the files, and are visible
+/// everywhere. Named functions and methods only see that root scope, while anonymous functions
+/// also see the variables around them. The top-level statements of every file share a single
+/// scope under the root one.
+#[derive(Debug, Clone)]
+pub struct Resolution {
+    pub scopes: Vec<Scope>,
+    pub bindings: Vec<Binding>,
+    pub references: Vec<Reference>,
+    /// The index of each reference by file and offset
+    reference_offsets: HashMap<(usize, usize), usize>,
+}
+
+pub const ROOT_SCOPE: ScopeId = 0;
+/// The scope of the top-level statements, shared by every file.
+pub const TOP_LEVEL_SCOPE: ScopeId = 1;
+
+impl Resolution {
+    pub fn resolve(file: &File) -> Self {
+        Self::resolve_files(&[file])
+    }
+
+    /// Resolves files sharing a single root scope, like a project and its includes.
+    pub fn resolve_files(files: &[&File]) -> Self {
+        let mut resolver = Resolver {
+            resolution: Resolution {
+                scopes: vec![Scope { parent: None, bindings: Vec::new() }],
+                bindings: Vec::new(),
+                references: Vec::new(),
+                reference_offsets: HashMap::new(),
+            },
+            scope: ROOT_SCOPE,
+            file: 0,
+        };
+
+        for (index, file) in files.iter().enumerate() {
+            resolver.file = index;
+            resolver.declare_globals(file);
+        }
+        // Top-level variables are not globals: they live in their own scope, out of reach of functions
+        resolver.scoped(ROOT_SCOPE, |resolver| {
+            for (index, file) in files.iter().enumerate() {
+                resolver.file = index;
+                resolver.visit_file(file);
+            }
+        });
+
+        let mut resolution = resolver.resolution;
+        resolution.reference_offsets = resolution.references.iter()
+            .enumerate()
+            .filter_map(|(index, reference)| Some(((reference.file, reference.location?.start.offset), index)))
+            .collect();
+        resolution
+    }
+
+    /// Finds the binding visible under `name` from `scope`.
+    pub fn lookup(&self, scope: ScopeId, name: &str) -> Option<BindingId> {
+        let mut current = Some(scope);
+        while let Some(scope) = current {
+            let found = self.scopes[scope].bindings.iter()
+                .rev()
+                .find(|&&binding| self.bindings[binding].name == name);
+            if let Some(&binding) = found {
+                return Some(binding);
+            }
+            current = self.scopes[scope].parent;
+        }
+        None
+    }
+
+    /// The reference whose name starts at `offset` of the file `file`.
+    pub fn reference_at(&self, file: usize, offset: usize) -> Option<&Reference> {
+        self.reference_offsets.get(&(file, offset)).map(|&index| &self.references[index])
+    }
+
+    pub fn references_to(&self, binding: BindingId) -> impl Iterator<Item = &Reference> {
+        self.references.iter().filter(move |reference| reference.binding == Some(binding))
+    }
+
+    pub fn unresolved(&self) -> impl Iterator<Item = &Reference> {
+        self.references.iter().filter(|reference| reference.binding.is_none())
+    }
+}
+
+struct Resolver {
+    resolution: Resolution,
+    scope: ScopeId,
+    file: usize,
+}
+
+impl Resolver {
+    fn declare_globals(&mut self, file: &File) {
+        for statement in &file.statements {
+            match statement.node.as_ref() {
+                GlobalStatement::FunctionDefinition(function) => self.declare(&function.name, BindingKind::Function),
+                GlobalStatement::ClassDefinition(class) => self.declare(&class.name, BindingKind::Class),
+                GlobalStatement::GlobalDefinition(definition) => {
+                    let kind = match definition.const_ {
+                        Some(_) => BindingKind::GlobalConstant,
+                        None => BindingKind::Global,
+                    };
+                    for (name, _) in &definition.values {
+                        self.declare(name, kind);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn declare(&mut self, name: &MetaNode<Identifier>, kind: BindingKind) {
+        let binding = self.resolution.bindings.len();
+        self.resolution.bindings.push(Binding {
+            name: name.node.name.clone(),
+            kind,
+            scope: self.scope,
+            file: self.file,
+            location: name.location,
+        });
+        self.resolution.scopes[self.scope].bindings.push(binding);
+    }
+
+    fn reference(&mut self, name: &MetaNode<Identifier>, access: Access) {
+        self.resolution.references.push(Reference {
+            name: name.node.name.clone(),
+            binding: self.resolution.lookup(self.scope, &name.node.name),
+            access,
+            scope: self.scope,
+            file: self.file,
+            location: name.location,
+        });
+    }
+
+    /// Runs `f` in a new scope whose parent is `parent`.
+    fn scoped(&mut self, parent: ScopeId, f: impl FnOnce(&mut Self)) {
+        let scope = self.resolution.scopes.len();
+        self.resolution.scopes.push(Scope { parent: Some(parent), bindings: Vec::new() });
+
+        let outer = std::mem::replace(&mut self.scope, scope);
+        f(self);
+        self.scope = outer;
+    }
+
+    fn visit_function_body(&mut self, parameters: &Parameters, body: &BlockStatement) {
+        self.visit_parameters(parameters);
+        self.visit_block(body);
+    }
+
+    fn visit_target(&mut self, target: &Expression, access: Access) {
+        match target.as_identifier() {
+            Some(name) => self.reference(name, access),
+            None => self.visit_expression(target),
+        }
+    }
+}
+
+impl<'ast> Visitor<'ast> for Resolver {
+    fn visit_global_statement(&mut self, statement: &'ast MetaNode<GlobalStatement>) {
+        match statement.node.as_ref() {
+            GlobalStatement::Statement(Statement::ForStatement(_)) => {
+                self.scoped(self.scope, |resolver| walk_global_statement(resolver, statement))
+            }
+            _ => walk_global_statement(self, statement),
+        }
+    }
+
+    fn visit_global_definition(&mut self, definition: &'ast GlobalDefinition) {
+        for (_, value) in &definition.values {
+            if let Some(value) = value {
+                self.visit_expression(&value.node);
+            }
+        }
+    }
+
+    fn visit_function_definition(&mut self, function: &'ast FunctionDefinition) {
+        self.scoped(ROOT_SCOPE, |resolver| {
+            resolver.visit_function_body(&function.args.node, &function.body.node)
+        });
+    }
+
+    fn visit_class(&mut self, class: &'ast Class) {
+        self.scoped(ROOT_SCOPE, |resolver| walk_class(resolver, class));
+    }
+
+    fn visit_constructor(&mut self, constructor: &'ast Constructor) {
+        self.scoped(ROOT_SCOPE, |resolver| {
+            resolver.visit_function_body(&constructor.parameters.node, &constructor.body.node)
+        });
+    }
+
+    fn visit_method(&mut self, method: &'ast Method) {
+        self.scoped(ROOT_SCOPE, |resolver| {
+            resolver.visit_parameters(&method.parameters.node);
+            if let Some(body) = method.body.block() {
+                resolver.visit_block(&body.node);
+            }
+        });
+    }
+
+    fn visit_field(&mut self, field: &'ast Field) {
+        if let Some(value) = &field.value {
+            self.visit_expression(&value.node);
+        }
+    }
+
+    fn visit_anonymous_function(&mut self, function: &'ast AnonymousFunction) {
+        self.scoped(self.scope, |resolver| walk_anonymous_function(resolver, function));
+    }
+
+    fn visit_parameter(&mut self, parameter: &'ast MetaNode<Parameter>) {
+        if let Some(default_value) = &parameter.node.default_value {
+            self.visit_expression(&default_value.node);
+        }
+        self.declare(&parameter.node.name, BindingKind::Parameter);
+    }
+
+    fn visit_statement(&mut self, statement: &'ast MetaNode<Statement>) {
+        match statement.node.as_ref() {
+            Statement::ForStatement(_) => self.scoped(self.scope, |resolver| walk_statement(resolver, statement)),
+            _ => walk_statement(self, statement),
+        }
+    }
+
+    fn visit_block(&mut self, block: &'ast BlockStatement) {
+        self.scoped(self.scope, |resolver| walk_block(resolver, block));
+    }
+
+    fn visit_catch(&mut self, catch: &'ast CatchClause) {
+        self.scoped(self.scope, |resolver| {
+            if let Some(binding) = &catch.binding {
+                resolver.declare(&binding.node.name, BindingKind::Parameter);
+            }
+            resolver.visit_block(&catch.block.node);
+        });
+    }
+
+    fn visit_var_declaration(&mut self, declaration: &'ast VarDeclaration) {
+        let kind = match declaration.type_.node.is_constant() {
+            true => BindingKind::Constant,
+            false => BindingKind::Variable,
+        };
+        for (name, value) in &declaration.values {
+            if let Some(value) = value {
+                self.visit_expression(&value.node);
+            }
+            self.declare(name, kind);
+        }
+    }
+
+    fn visit_for_header(&mut self, header: &'ast ForHeader) {
+        match header {
+            // The iterable is evaluated before the loop variables exist
+            ForHeader::ForIn(for_in) => {
+                self.visit_expression(&for_in.iterable.node);
+                if let Some(key) = &for_in.key {
+                    self.visit_var_declaration(&key.node);
+                }
+                self.visit_var_declaration(&for_in.var.node);
+            }
+            ForHeader::ForIter(_) => walk_for_header(self, header),
+        }
+    }
+
+    fn visit_expression(&mut self, expression: &'ast Expression) {
+        match expression {
+            Expression::Assign(assign) => {
+                // The value is evaluated before it is stored
+                for (_, value) in &assign.right {
+                    self.visit_expression(&value.node);
+                }
+                let access = match assign.right.first().map(|(op, _)| op.node.as_ref()) {
+                    Some(AssignOp::Assign) => Access::Write,
+                    _ => Access::ReadWrite,
+                };
+                self.visit_target(&assign.left, access);
+            }
+            Expression::PreUpdate(update) => self.visit_target(&update.expr, Access::ReadWrite),
+            Expression::PostUpdate(update) => self.visit_target(&update.expr, Access::ReadWrite),
+            _ => walk_expression(self, expression),
+        }
+    }
+
+    fn visit_primary(&mut self, primary: &'ast PrimaryExpr) {
+        match primary {
+            PrimaryExpr::IdentifierOrMember(identifier) => {
+                if let IdentifierOrMember::Identifier(name) = identifier.node.as_ref() {
+                    self.reference(name, Access::Read);
+                }
+            }
+            // The keys of an object are names, not variables
+            PrimaryExpr::Object(Object::Pairs { pairs, .. }) => {
+                for (key, value) in pairs {
+                    if key.node.as_identifier().is_none() {
+                        self.visit_expression(&key.node);
+                    }
+                    self.visit_expression(&value.node);
+                }
+            }
+            _ => walk_primary(self, primary),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::parse_file;
+
+    use super::*;
+
+    fn kind_of(resolution: &Resolution, reference: &Reference) -> Option<BindingKind> {
+        reference.binding.map(|binding| resolution.bindings[binding].kind)
+    }
+
+    #[test]
+    fn test_block_scopes() {
+        let resolution = Resolution::resolve(&parse_file("var a = 1; { var b = a; } b;"));
+
+        let references: Vec<_> = resolution.references.iter()
+            .map(|reference| (reference.name.as_str(), kind_of(&resolution, reference)))
+            .collect();
+        assert_eq!(references, [("a", Some(BindingKind::Variable)), ("b", None)]);
+    }
+
+    #[test]
+    fn test_functions_see_globals_only() {
+        let file = parse_file("var a; global g; function f(p) { a; g; p; h(); } function h() {}");
+        let resolution = Resolution::resolve(&file);
+
+        let kinds: Vec<_> = resolution.references.iter().map(|r| kind_of(&resolution, r)).collect();
+        assert_eq!(kinds, [None, Some(BindingKind::Global), Some(BindingKind::Parameter), Some(BindingKind::Function)]);
+    }
+
+    #[test]
+    fn test_closures_capture() {
+        let resolution = Resolution::resolve(&parse_file("var a; var f = x => a + x;"));
+        assert_eq!(resolution.unresolved().count(), 0);
+    }
+
+    #[test]
+    fn test_object_keys() {
+        let resolution = Resolution::resolve(&parse_file("var a; var o = {a: a, b: 1};"));
+        assert_eq!(resolution.references.len(), 1);
+        assert_eq!(resolution.unresolved().count(), 0);
+    }
+
+    #[test]
+    fn test_declaration_order() {
+        let resolution = Resolution::resolve(&parse_file("var a = a; for (var x in x) {} x;"));
+        assert_eq!(resolution.unresolved().map(|r| r.name.as_str()).collect::<Vec<_>>(), ["a", "x", "x"]);
+    }
+
+    #[test]
+    fn test_catch_binding() {
+        let resolution = Resolution::resolve(&parse_file("try { e; } catch (Error e) { e; } e;"));
+        let kinds: Vec<_> = resolution.references.iter().map(|r| kind_of(&resolution, r)).collect();
+        assert_eq!(kinds, [None, Some(BindingKind::Parameter), None]);
+    }
+
+    #[test]
+    fn test_access() {
+        let resolution = Resolution::resolve(&parse_file("var a; a = 1; a += 1; a++; --a; a;"));
+        let accesses: Vec<_> = resolution.references.iter().map(|r| r.access).collect();
+        assert_eq!(accesses, [Access::Write, Access::ReadWrite, Access::ReadWrite, Access::ReadWrite, Access::Read]);
+    }
+
+    #[test]
+    fn test_shared_root_scope() {
+        let main = parse_file("f(); var a = A;");
+        let lib = parse_file("function f() {} class A {}");
+        let resolution = Resolution::resolve_files(&[&main, &lib]);
+
+        assert_eq!(resolution.unresolved().count(), 0);
+        assert_eq!(resolution.bindings[resolution.lookup(ROOT_SCOPE, "f").unwrap()].file, 1);
+        assert_eq!(resolution.reference_at(0, 13).map(|r| (r.name.as_str(), r.file)), Some(("A", 0)));
+        assert!(resolution.reference_at(1, 13).is_none());
+    }
+}
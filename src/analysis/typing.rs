@@ -0,0 +1,467 @@
+use std::{collections::HashMap, fmt::Display};
+
+use crate::ast::{visit::*, *};
+
+use super::Diagnostic;
+
+/// The type of a value as far as the checker can tell. `Any` is used whenever it can not.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValueType {
+    Any,
+    Null,
+    Boolean,
+    Integer,
+    Real,
+    String,
+    Array,
+    Map,
+    Set,
+    Object,
+    Function,
+    Class(String),
+    Union(Vec<ValueType>),
+}
+
+impl ValueType {
+    pub fn from_annotation(type_: &Type) -> Self {
+        let mut value_type = match type_.type_.node.name.as_str() {
+            "any" => ValueType::Any,
+            "null" | "void" => ValueType::Null,
+            "boolean" | "bool" => ValueType::Boolean,
+            "integer" | "int" => ValueType::Integer,
+            "real" | "float" => ValueType::Real,
+            "number" => ValueType::Integer.union(ValueType::Real),
+            "string" => ValueType::String,
+            "array" | "Array" => ValueType::Array,
+            "map" | "Map" => ValueType::Map,
+            "set" | "Set" => ValueType::Set,
+            "object" | "Object" => ValueType::Object,
+            "function" | "Function" => ValueType::Function,
+            name => ValueType::Class(name.to_string()),
+        };
+        if let Some(alternative) = &type_.alternative {
+            value_type = value_type.union(ValueType::from_annotation(&alternative.node));
+        }
+        if type_.nullable {
+            value_type = value_type.nullable();
+        }
+        value_type
+    }
+
+    /// A type for values of either type. `Any` absorbs everything but `null`.
+    pub fn union(self, other: ValueType) -> Self {
+        let mut types = Vec::new();
+        for value_type in [self, other] {
+            match value_type {
+                ValueType::Union(inner) => types.extend(inner),
+                value_type => types.push(value_type),
+            }
+        }
+        if types.contains(&ValueType::Any) {
+            types.retain(|value_type| matches!(value_type, ValueType::Any | ValueType::Null));
+        }
+
+        let mut unique: Vec<ValueType> = Vec::new();
+        for value_type in types {
+            if !unique.contains(&value_type) {
+                unique.push(value_type);
+            }
+        }
+        match unique.len() {
+            1 => unique.pop().unwrap(),
+            _ => ValueType::Union(unique),
+        }
+    }
+
+    pub fn nullable(self) -> Self {
+        self.union(ValueType::Null)
+    }
+
+    /// The type without `null`, as after a `!` assertion.
+    pub fn non_null(self) -> Self {
+        match self {
+            ValueType::Null => ValueType::Any,
+            ValueType::Union(types) => types.into_iter()
+                .filter(|value_type| *value_type != ValueType::Null)
+                .reduce(ValueType::union)
+                .unwrap_or(ValueType::Any),
+            value_type => value_type,
+        }
+    }
+
+    /// Whether the value is known to possibly be `null`. `Any` is not, as nothing is known about it.
+    pub fn is_nullable(&self) -> bool {
+        match self {
+            ValueType::Null => true,
+            ValueType::Union(types) => types.contains(&ValueType::Null),
+            _ => false,
+        }
+    }
+
+    fn is_number(&self) -> bool {
+        matches!(self, ValueType::Integer | ValueType::Real)
+    }
+}
+
+impl Display for ValueType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValueType::Any => write!(f, "any"),
+            ValueType::Null => write!(f, "null"),
+            ValueType::Boolean => write!(f, "boolean"),
+            ValueType::Integer => write!(f, "integer"),
+            ValueType::Real => write!(f, "real"),
+            ValueType::String => write!(f, "string"),
+            ValueType::Array => write!(f, "Array"),
+            ValueType::Map => write!(f, "Map"),
+            ValueType::Set => write!(f, "Set"),
+            ValueType::Object => write!(f, "Object"),
+            ValueType::Function => write!(f, "Function"),
+            ValueType::Class(name) => write!(f, "{}", name),
+            ValueType::Union(types) => {
+                let non_null: Vec<_> = types.iter().filter(|value_type| **value_type != ValueType::Null).collect();
+                for (i, value_type) in non_null.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, "|")?;
+                    }
+                    write!(f, "{}", value_type)?;
+                }
+                if non_null.len() < types.len() {
+                    write!(f, "?")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Infers the type of an expression on its own, every variable being `any`.
+pub fn infer_type(expression: &Expression) -> ValueType {
+    TypeChecker::default().infer(expression)
+}
+
+/// Reports values that can be `null` where the declared type does not allow it, and member
+/// accesses on values that can be `null` without `?.` or `!`.
+pub fn check_types(file: &File) -> Vec<Diagnostic> {
+    let mut checker = TypeChecker::default();
+    checker.scopes.push(HashMap::new());
+    checker.visit_file(file);
+    checker.diagnostics
+}
+
+/// The type of the iterable of each for-in loop of a file by its offset, the variables having
+/// their declared types.
+pub fn iterable_types(file: &File) -> HashMap<usize, ValueType> {
+    let mut checker = TypeChecker::default();
+    checker.scopes.push(HashMap::new());
+    checker.visit_file(file);
+    checker.iterables
+}
+
+#[derive(Default)]
+struct TypeChecker {
+    diagnostics: Vec<Diagnostic>,
+    /// The types of the iterables of the for-in loops, by offset
+    iterables: HashMap<usize, ValueType>,
+    /// Declared types of the variables in scope, innermost last
+    scopes: Vec<HashMap<String, ValueType>>,
+}
+
+impl TypeChecker {
+    fn lookup(&self, name: &str) -> ValueType {
+        self.scopes.iter().rev()
+            .find_map(|scope| scope.get(name))
+            .cloned()
+            .unwrap_or(ValueType::Any)
+    }
+
+    fn declare(&mut self, name: &MetaNode<Identifier>, value_type: ValueType) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.node.name.clone(), value_type);
+        }
+    }
+
+    fn scoped(&mut self, f: impl FnOnce(&mut Self)) {
+        self.scopes.push(HashMap::new());
+        f(self);
+        self.scopes.pop();
+    }
+
+    fn infer(&self, expression: &Expression) -> ValueType {
+        match expression {
+            Expression::Assign(assign) => match assign.right.last() {
+                Some((op, right)) if matches!(op.node.as_ref(), AssignOp::Assign) => self.infer(&right.node),
+                _ => ValueType::Any,
+            },
+            Expression::AnonyFunc(_) => ValueType::Function,
+            Expression::TernaryExpression(ternary) => {
+                self.infer(&ternary.true_expr.node).union(self.infer(&ternary.false_expr.node))
+            }
+            Expression::LogicalOr(_)
+            | Expression::LogicalXor(_)
+            | Expression::LogicalAnd(_)
+            | Expression::Relation(_)
+            | Expression::InstanceOf(_) => ValueType::Boolean,
+            Expression::Shift(_) | Expression::BinOr(_) | Expression::BinXor(_) | Expression::BinAnd(_) => {
+                ValueType::Integer
+            }
+            Expression::Add(add) => add.right.iter().fold(self.infer(&add.left), |left, (op, right)| {
+                let right = self.infer(&right.node);
+                match op.node.as_ref() {
+                    AddOp::Add if left == ValueType::String || right == ValueType::String => ValueType::String,
+                    AddOp::Add if left == ValueType::Array && right == ValueType::Array => ValueType::Array,
+                    _ => arithmetic(left, right),
+                }
+            }),
+            Expression::Mult(mult) => mult.right.iter().fold(self.infer(&mult.left), |left, (op, right)| {
+                let right = self.infer(&right.node);
+                match op.node.as_ref() {
+                    MultOp::Div if left.is_number() && right.is_number() => ValueType::Real,
+                    _ => arithmetic(left, right),
+                }
+            }),
+            Expression::TypeConversion(conversion) => ValueType::from_annotation(&conversion.type_.node),
+            Expression::PreUpdate(update) => self.infer(&update.expr),
+            Expression::PostUpdate(update) => self.infer(&update.expr),
+            Expression::Unary(unary) => match unary.op.node.as_ref() {
+                UnaryOp::Not(_) => ValueType::Boolean,
+                UnaryOp::BitNot | UnaryOp::Typeof => ValueType::Integer,
+                UnaryOp::Plus | UnaryOp::Neg => match self.infer(&unary.expr) {
+                    value_type if value_type.is_number() => value_type,
+                    _ => ValueType::Any,
+                },
+                UnaryOp::New => match unary.expr.as_ref() {
+                    Expression::Member(Member { left: PrimaryExpr::IdentifierOrMember(class), .. })
+                    | Expression::Primary(PrimaryExpr::IdentifierOrMember(class)) => match class.node.as_ref() {
+                        IdentifierOrMember::Identifier(class) => ValueType::Class(class.node.name.clone()),
+                        _ => ValueType::Object,
+                    },
+                    _ => ValueType::Object,
+                },
+            },
+            Expression::Member(member) => self.infer_member(member),
+            Expression::Primary(primary) => self.infer_primary(primary),
+        }
+    }
+
+    fn infer_member(&self, member: &Member) -> ValueType {
+        let mut value_type = self.infer_primary(&member.left);
+        // Once a `?.` or `?[` is met, the rest of the chain is skipped when the object is null
+        let mut optional = false;
+        for right in &member.right {
+            value_type = match right.node.as_ref() {
+                MemberRight::NotNull => {
+                    optional = false;
+                    value_type.non_null()
+                }
+                right => {
+                    optional |= right.is_optional();
+                    ValueType::Any
+                }
+            };
+        }
+        match optional {
+            true => value_type.nullable(),
+            false => value_type,
+        }
+    }
+
+    fn infer_primary(&self, primary: &PrimaryExpr) -> ValueType {
+        match primary {
+            PrimaryExpr::IdentifierOrMember(identifier) => match identifier.node.as_ref() {
+                IdentifierOrMember::Identifier(name) => self.lookup(&name.node.name),
+                _ => ValueType::Any,
+            },
+            PrimaryExpr::Literal(literal) => match literal {
+                Literal::String(_) | Literal::Template(_) => ValueType::String,
+                Literal::Number(number) => match number.value {
+                    NumberValue::Integer(_) => ValueType::Integer,
+                    NumberValue::Float(_) => ValueType::Real,
+                },
+                Literal::Boolean(_) => ValueType::Boolean,
+                Literal::Null => ValueType::Null,
+            },
+            PrimaryExpr::Array(_) => ValueType::Array,
+            PrimaryExpr::Object(_) => ValueType::Object,
+            PrimaryExpr::Set(_) => ValueType::Set,
+            PrimaryExpr::Map(_) => ValueType::Map,
+            PrimaryExpr::ParenthesizedExpr(expression) => self.infer(&expression.node),
+        }
+    }
+
+    fn check_assigned(&mut self, name: &MetaNode<Identifier>, declared: &ValueType, value: &Expression) {
+        let value_type = self.infer(value);
+        if value_type.is_nullable() && !declared.is_nullable() && *declared != ValueType::Any {
+            self.diagnostics.push(Diagnostic::error(
+                format!("`{}` of type `{}` can not be null but is given a value of type `{}`", name.node, declared, value_type),
+                name.location,
+            ));
+        }
+    }
+}
+
+fn arithmetic(left: ValueType, right: ValueType) -> ValueType {
+    match (left, right) {
+        (ValueType::Integer, ValueType::Integer) => ValueType::Integer,
+        (left, right) if left.is_number() && right.is_number() => ValueType::Real,
+        _ => ValueType::Any,
+    }
+}
+
+impl<'ast> Visitor<'ast> for TypeChecker {
+    fn visit_function_definition(&mut self, function: &'ast FunctionDefinition) {
+        self.scoped(|checker| walk_function_definition(checker, function));
+    }
+
+    fn visit_method(&mut self, method: &'ast Method) {
+        self.scoped(|checker| walk_method(checker, method));
+    }
+
+    fn visit_constructor(&mut self, constructor: &'ast Constructor) {
+        self.scoped(|checker| walk_constructor(checker, constructor));
+    }
+
+    fn visit_anonymous_function(&mut self, function: &'ast AnonymousFunction) {
+        self.scoped(|checker| walk_anonymous_function(checker, function));
+    }
+
+    fn visit_block(&mut self, block: &'ast BlockStatement) {
+        self.scoped(|checker| walk_block(checker, block));
+    }
+
+    fn visit_statement(&mut self, statement: &'ast MetaNode<Statement>) {
+        match statement.node.as_ref() {
+            Statement::ForStatement(_) => self.scoped(|checker| walk_statement(checker, statement)),
+            _ => walk_statement(self, statement),
+        }
+    }
+
+    fn visit_for_header(&mut self, header: &'ast ForHeader) {
+        if let ForHeader::ForIn(for_in) = header {
+            if let Some(location) = for_in.iterable.location {
+                self.iterables.insert(location.start.offset, self.infer(&for_in.iterable.node));
+            }
+        }
+        walk_for_header(self, header);
+    }
+
+    fn visit_parameter(&mut self, parameter: &'ast MetaNode<Parameter>) {
+        walk_parameter(self, parameter);
+        let declared = parameter.node.type_.as_ref()
+            .map(|type_| ValueType::from_annotation(&type_.node))
+            .unwrap_or(ValueType::Any);
+        if let Some(default_value) = &parameter.node.default_value {
+            self.check_assigned(&parameter.node.name, &declared, &default_value.node);
+        }
+        self.declare(&parameter.node.name, declared);
+    }
+
+    fn visit_global_definition(&mut self, definition: &'ast GlobalDefinition) {
+        walk_global_definition(self, definition);
+        let declared = definition.type_.as_ref()
+            .map(|type_| ValueType::from_annotation(&type_.node))
+            .unwrap_or(ValueType::Any);
+        for (name, value) in &definition.values {
+            if let Some(value) = value {
+                self.check_assigned(name, &declared, &value.node);
+            }
+            self.declare(name, declared.clone());
+        }
+    }
+
+    fn visit_var_declaration(&mut self, declaration: &'ast VarDeclaration) {
+        walk_var_declaration(self, declaration);
+        let declared = declaration.type_.node.type_()
+            .map(|type_| ValueType::from_annotation(&type_.node))
+            .unwrap_or(ValueType::Any);
+        for (name, value) in &declaration.values {
+            if let Some(value) = value {
+                self.check_assigned(name, &declared, &value.node);
+            }
+            self.declare(name, declared.clone());
+        }
+    }
+
+    fn visit_expression(&mut self, expression: &'ast Expression) {
+        if let Expression::Assign(assign) = expression {
+            if let (Some(name), [(op, value)]) = (assign.left.as_identifier(), assign.right.as_slice()) {
+                if matches!(op.node.as_ref(), AssignOp::Assign) {
+                    let declared = self.lookup(&name.node.name);
+                    self.check_assigned(name, &declared, &value.node);
+                }
+            }
+        }
+        walk_expression(self, expression);
+    }
+
+    fn visit_member(&mut self, member: &'ast Member) {
+        let mut value_type = self.infer_primary(&member.left);
+        for right in &member.right {
+            match right.node.as_ref() {
+                MemberRight::NotNull => value_type = value_type.non_null(),
+                right_node => {
+                    if value_type.is_nullable() && !right_node.is_optional() {
+                        self.diagnostics.push(Diagnostic::warning(
+                            format!("value of type `{}` can be null here, use `?.` or `!`", value_type),
+                            right.location,
+                        ));
+                    }
+                    value_type = ValueType::Any;
+                }
+            }
+        }
+        walk_member(self, member);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::parse_file;
+
+    use super::*;
+
+    fn infer(input: &str) -> String {
+        let file = parse_file(&format!("{};", input));
+        let GlobalStatement::Statement(Statement::ExpressionStatement(statement)) = file.statements[0].node.as_ref() else {
+            panic!("not an expression");
+        };
+        infer_type(&statement.expression.node).to_string()
+    }
+
+    #[test]
+    fn test_infer() {
+        assert_eq!(infer("1 + 2 * 3"), "integer");
+        assert_eq!(infer("1 / 2"), "real");
+        assert_eq!(infer("'a' + 1"), "string");
+        assert_eq!(infer("a ? 1 : null"), "integer?");
+        assert_eq!(infer("(new A())"), "A");
+        assert_eq!(infer("(1 < 2) as integer|string"), "integer|string");
+    }
+
+    #[test]
+    fn test_optional_chaining_is_nullable() {
+        assert_eq!(infer("a.b"), "any");
+        assert_eq!(infer("a?.b"), "any?");
+        assert_eq!(infer("a?[0].b()"), "any?");
+        assert_eq!(infer("a?.b!"), "any");
+    }
+
+    #[test]
+    fn test_check_types() {
+        assert!(check_types(&parse_file("integer? a = b?.c; integer d = 1; var e = f?.g; e.h;")).is_empty());
+
+        let diagnostics = check_types(&parse_file("integer a = b?.c; a = null; function f(string s = null) {}"));
+        let messages: Vec<_> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+        assert_eq!(messages, [
+            "`a` of type `integer` can not be null but is given a value of type `any?`",
+            "`a` of type `integer` can not be null but is given a value of type `null`",
+            "`s` of type `string` can not be null but is given a value of type `null`",
+        ]);
+    }
+
+    #[test]
+    fn test_member_of_nullable() {
+        let diagnostics = check_types(&parse_file("string? s = null; s.length; s!.length; s?.length; (a?.b).c;"));
+        assert_eq!(diagnostics.len(), 2);
+    }
+}
@@ -0,0 +1,536 @@
+use std::collections::HashMap;
+
+use crate::ast::{scan::advance, visit::*, *};
+
+use super::{Access, Diagnostic, Resolution};
+
+/// Checks the rules of the language that the grammar alone does not enforce.
+pub fn validate(file: &File) -> Vec<Diagnostic> {
+    let mut validator = Validator {
+        classes: collect_classes(file),
+        ..Default::default()
+    };
+    validator.visit_file(file);
+    validator.check_constant_writes(&Resolution::resolve(file));
+    validator.diagnostics
+}
+
+struct ClassInfo {
+    abstract_: bool,
+    final_fields: Vec<FinalField>,
+}
+
+struct FinalField {
+    name: String,
+    static_: bool,
+    initialized: bool,
+}
+
+fn collect_classes(file: &File) -> HashMap<String, ClassInfo> {
+    file.statements
+        .iter()
+        .filter_map(|statement| match statement.node.as_ref() {
+            GlobalStatement::ClassDefinition(class) => Some(class),
+            _ => None,
+        })
+        .map(|class| {
+            let final_fields = class.members()
+                .filter_map(|member| match member {
+                    ClassMember::Field(field) if field.visibility.node.is_final() => Some(FinalField {
+                        name: field.name.node.name.clone(),
+                        static_: field.visibility.node.is_static(),
+                        initialized: field.value.is_some(),
+                    }),
+                    _ => None,
+                })
+                .collect();
+
+            (
+                class.name.node.name.clone(),
+                ClassInfo {
+                    abstract_: class.abstract_.is_some(),
+                    final_fields,
+                },
+            )
+        })
+        .collect()
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Initializer {
+    None,
+    Constructor,
+    StaticBlock,
+}
+
+#[derive(Default)]
+struct Validator<'ast> {
+    diagnostics: Vec<Diagnostic>,
+    classes: HashMap<String, ClassInfo>,
+    class: Option<&'ast str>,
+    initializer: Option<Initializer>,
+    /// Location of the innermost statement, for nodes that have none
+    statement: Option<Location>,
+    /// Where the next expression visited starts, when known
+    expression_start: Option<Position>,
+    /// Where the next literal visited starts, when known
+    literal_start: Option<Position>,
+}
+
+impl<'ast> Validator<'ast> {
+    fn check_default_parameters_last(&mut self, parameters: &Parameters) {
+        let mut defaulted = None;
+        for parameter in &parameters.params {
+            match (&parameter.node.default_value, defaulted) {
+                (Some(_), _) => defaulted = Some(&parameter.node.name),
+                (None, Some(previous)) => self.diagnostics.push(Diagnostic::error(
+                    format!(
+                        "parameter `{}` has no default value but follows the defaulted parameter `{}`",
+                        parameter.node.name.node, previous.node
+                    ),
+                    parameter.location,
+                )),
+                (None, None) => {}
+            }
+        }
+    }
+
+    fn check_constant_initialized(&mut self, name: &MetaNode<Identifier>, value: Option<&MetaNode<Expression>>) {
+        if value.is_none() {
+            self.diagnostics.push(Diagnostic::error(
+                format!("constant `{}` must be initialized", name.node),
+                name.location,
+            ));
+        }
+    }
+
+    fn check_constant_writes(&mut self, resolution: &Resolution) {
+        for reference in &resolution.references {
+            let Some(binding) = reference.binding else { continue };
+            if !resolution.bindings[binding].kind.is_constant() {
+                continue;
+            }
+
+            let message = match reference.access {
+                Access::Read => continue,
+                Access::Write => format!("constant `{}` can not be reassigned", reference.name),
+                Access::ReadWrite => format!("constant `{}` can not be modified", reference.name),
+            };
+            self.diagnostics.push(Diagnostic::error(message, reference.location));
+        }
+    }
+
+    /// Reports the escapes of a literal starting at `start`, given with their offset in its text.
+    fn check_escapes(&mut self, invalid: &[(usize, InvalidEscape)], literal: &dyn std::fmt::Display, start: Option<Position>) {
+        let text = literal.to_string();
+        for (offset, escape) in invalid {
+            let location = start.map(|start| {
+                let start = advance(start, &text[..*offset]);
+                Location { start, end: advance(start, &escape.sequence) }
+            });
+            self.diagnostics.push(Diagnostic::error(
+                format!("unknown escape sequence `{}` in {}", escape.sequence, text),
+                location.or(self.statement),
+            ));
+        }
+    }
+
+    fn check_class_members(&mut self, class: &Class) {
+        for member in class.members() {
+            match member {
+                ClassMember::Method(method) => self.check_method(class, method),
+                ClassMember::Field(field) => {
+                    if let Some(abstract_) = &field.visibility.node.abstract_ {
+                        self.diagnostics.push(Diagnostic::error(
+                            format!("field `{}` can not be abstract", field.name.node),
+                            abstract_.location,
+                        ));
+                    }
+                }
+                ClassMember::Constructor(constructor) => {
+                    let visibility = &constructor.visibility.node;
+                    let modifier = visibility.static_.as_ref().map(|m| ("static", m.location))
+                        .or(visibility.abstract_.as_ref().map(|m| ("abstract", m.location)))
+                        .or(visibility.final_.as_ref().map(|m| ("final", m.location)))
+                        .or(visibility.const_.as_ref().map(|m| ("const", m.location)));
+                    if let Some((modifier, location)) = modifier {
+                        self.diagnostics.push(Diagnostic::error(
+                            format!("a constructor can not be {}", modifier),
+                            location,
+                        ));
+                    }
+                }
+                ClassMember::StaticBlock(_) => {}
+            }
+        }
+    }
+
+    fn check_method(&mut self, class: &Class, method: &Method) {
+        let name = &method.name;
+        let visibility = &method.visibility.node;
+
+        if let Some(const_) = &visibility.const_ {
+            self.diagnostics.push(Diagnostic::error(
+                format!("method `{}` can not be const", name.node),
+                const_.location,
+            ));
+        }
+
+        if visibility.is_abstract() {
+            if class.abstract_.is_none() {
+                self.diagnostics.push(Diagnostic::error(
+                    format!("abstract method `{}` in non-abstract class `{}`", name.node, class.name.node),
+                    name.location,
+                ));
+            }
+            if visibility.final_.is_some() || visibility.static_.is_some() {
+                self.diagnostics.push(Diagnostic::error(
+                    format!("abstract method `{}` can not be final or static", name.node),
+                    name.location,
+                ));
+            }
+            if method.body.block().is_some() {
+                self.diagnostics.push(Diagnostic::error(
+                    format!("abstract method `{}` can not have a body", name.node),
+                    name.location,
+                ));
+            }
+        } else if method.body.block().is_none() {
+            self.diagnostics.push(Diagnostic::error(
+                format!("method `{}` has no body, it must be declared abstract", name.node),
+                name.location,
+            ));
+        }
+    }
+
+    fn check_new(&mut self, expression: &Expression) {
+        let class = match expression {
+            Expression::Member(member) => match &member.left {
+                PrimaryExpr::IdentifierOrMember(object) => match object.node.as_ref() {
+                    IdentifierOrMember::Identifier(class) => Some(class),
+                    _ => None,
+                },
+                _ => None,
+            },
+            expression => expression.as_identifier(),
+        };
+
+        if let Some(class) = class {
+            if self.classes.get(&class.node.name).is_some_and(|info| info.abstract_) {
+                self.diagnostics.push(Diagnostic::error(
+                    format!("abstract class `{}` can not be instantiated", class.node),
+                    class.location,
+                ));
+            }
+        }
+    }
+
+    fn check_assignment(&mut self, target: &Expression) {
+        self.check_optional_target(target);
+        self.check_final_assignment(target);
+    }
+
+    /// `a?.b = 1` has no meaning when `a` is null.
+    fn check_optional_target(&mut self, target: &Expression) {
+        let Expression::Member(member) = target else { return };
+        if let Some(optional) = member.right.iter().find(|right| right.node.is_optional()) {
+            self.diagnostics.push(Diagnostic::error(
+                format!("`{}` can not be assigned as it uses optional chaining", member.to_string().trim()),
+                optional.location,
+            ));
+        }
+    }
+
+    fn check_final_assignment(&mut self, target: &Expression) {
+        let Expression::Member(member) = target else { return };
+        let Some((object, field)) = member.as_field_access() else { return };
+
+        let (class, static_, initializer) = match object {
+            IdentifierOrMember::This => match self.class {
+                Some(class) => (class, false, Initializer::Constructor),
+                None => return,
+            },
+            IdentifierOrMember::Identifier(class) => (class.node.name.as_str(), true, Initializer::StaticBlock),
+            _ => return,
+        };
+
+        let Some(final_field) = self.classes.get(class)
+            .and_then(|info| info.final_fields.iter().find(|f| f.name == field.node.name && f.static_ == static_))
+        else {
+            return;
+        };
+
+        let initializing = self.class == Some(class) && self.initializer == Some(initializer);
+        if final_field.initialized || !initializing {
+            self.diagnostics.push(Diagnostic::error(
+                format!("final field `{}` of `{}` can not be reassigned", field.node, class),
+                field.location,
+            ));
+        }
+    }
+}
+
+impl<'ast> Visitor<'ast> for Validator<'ast> {
+    fn visit_global_statement(&mut self, statement: &'ast MetaNode<GlobalStatement>) {
+        self.statement = statement.location;
+        walk_global_statement(self, statement);
+    }
+
+    fn visit_statement(&mut self, statement: &'ast MetaNode<Statement>) {
+        let outer = std::mem::replace(&mut self.statement, statement.location);
+        walk_statement(self, statement);
+        self.statement = outer;
+    }
+
+    fn visit_parameters(&mut self, parameters: &'ast Parameters) {
+        self.check_default_parameters_last(parameters);
+        walk_parameters(self, parameters);
+    }
+
+    fn visit_global_definition(&mut self, definition: &'ast GlobalDefinition) {
+        if definition.const_.is_some() {
+            for (name, value) in &definition.values {
+                self.check_constant_initialized(name, value.as_ref());
+            }
+        }
+        walk_global_definition(self, definition);
+    }
+
+    fn visit_var_declaration(&mut self, declaration: &'ast VarDeclaration) {
+        if declaration.type_.node.is_constant() {
+            for (name, value) in &declaration.values {
+                self.check_constant_initialized(name, value.as_ref());
+            }
+        }
+        walk_var_declaration(self, declaration);
+    }
+
+    fn visit_for_header(&mut self, header: &'ast ForHeader) {
+        match header {
+            // The loop initializes the variables of a for-in
+            ForHeader::ForIn(for_in) => {
+                if let Some(key) = &for_in.key {
+                    walk_var_declaration(self, &key.node);
+                }
+                walk_var_declaration(self, &for_in.var.node);
+                self.visit_expression(&for_in.iterable.node);
+            }
+            ForHeader::ForIter(_) => walk_for_header(self, header),
+        }
+    }
+
+    fn visit_class(&mut self, class: &'ast Class) {
+        self.check_class_members(class);
+
+        let outer = self.class.replace(&class.name.node.name);
+        walk_class(self, class);
+        self.class = outer;
+    }
+
+    fn visit_constructor(&mut self, constructor: &'ast Constructor) {
+        let outer = self.initializer.replace(Initializer::Constructor);
+        walk_constructor(self, constructor);
+        self.initializer = outer;
+    }
+
+    fn visit_static_block(&mut self, block: &'ast StaticBlock) {
+        let outer = self.initializer.replace(Initializer::StaticBlock);
+        walk_static_block(self, block);
+        self.initializer = outer;
+    }
+
+    fn visit_method(&mut self, method: &'ast Method) {
+        let outer = self.initializer.replace(Initializer::None);
+        walk_method(self, method);
+        self.initializer = outer;
+    }
+
+    fn visit_expression_node(&mut self, expression: &'ast MetaNode<Expression>) {
+        self.expression_start = expression.location.map(|location| location.start);
+        self.visit_expression(&expression.node);
+    }
+
+    fn visit_expression(&mut self, expression: &'ast Expression) {
+        // What the expression starts with starts where it does
+        let start = self.expression_start.take();
+        match expression {
+            Expression::Primary(PrimaryExpr::Literal(_)) | Expression::Member(Member { left: PrimaryExpr::Literal(_), .. }) => {
+                self.literal_start = start;
+            }
+            Expression::Assign(_)
+            | Expression::LogicalOr(_)
+            | Expression::LogicalXor(_)
+            | Expression::LogicalAnd(_)
+            | Expression::Relation(_)
+            | Expression::InstanceOf(_)
+            | Expression::Shift(_)
+            | Expression::BinOr(_)
+            | Expression::BinXor(_)
+            | Expression::BinAnd(_)
+            | Expression::Add(_)
+            | Expression::Mult(_)
+            | Expression::TypeConversion(_)
+            | Expression::PostUpdate(_) => self.expression_start = start,
+            Expression::PreUpdate(UnaryLeft { op, .. }) => self.expression_start = after(op),
+            Expression::Unary(UnaryLeft { op, .. }) => self.expression_start = after(op),
+            _ => {}
+        }
+
+        match expression {
+            Expression::Assign(assign) => self.check_assignment(&assign.left),
+            Expression::PreUpdate(update) => self.check_assignment(&update.expr),
+            Expression::PostUpdate(update) => self.check_assignment(&update.expr),
+            Expression::Unary(unary) if matches!(unary.op.node.as_ref(), UnaryOp::New) => self.check_new(&unary.expr),
+            _ => {}
+        }
+        walk_expression(self, expression);
+    }
+
+    fn visit_literal(&mut self, literal: &'ast Literal) {
+        let start = self.literal_start.take();
+        match literal {
+            Literal::String(string) => {
+                let invalid: Vec<_> = string.invalid_escapes().into_iter().map(|escape| (1 + escape.offset, escape)).collect();
+                self.check_escapes(&invalid, string, start);
+            }
+            Literal::Template(template) => {
+                let mut invalid = Vec::new();
+                let mut offset = 1;
+                for part in &template.parts {
+                    invalid.extend(part.invalid_escapes().into_iter().map(|escape| (offset + escape.offset, escape)));
+                    offset += match part {
+                        TemplatePart::Text(text) => text.len(),
+                        TemplatePart::Hole(hole) => format!("${{{}}}", hole).len(),
+                    };
+                }
+                self.check_escapes(&invalid, template, start);
+            }
+            _ => {}
+        }
+        walk_literal(self, literal);
+    }
+}
+
+/// Where the code after `node` starts.
+fn after<T>(node: &MetaNode<T>) -> Option<Position> {
+    let trivia: String = node.post_comments.iter().map(ToString::to_string).collect();
+    node.location.map(|location| advance(location.end, &trivia))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::parse_file;
+
+    use super::*;
+
+    #[test]
+    fn test_default_parameters_last() {
+        assert!(validate(&parse_file("function f(a, b = 1, c = 2) {}")).is_empty());
+        assert!(validate(&parse_file("var f = (a, @b, c = 2) => a;")).is_empty());
+    }
+
+    #[test]
+    fn test_default_parameter_before_required() {
+        let diagnostics = validate(&parse_file("function f(a = 1, b) {}"));
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].location.unwrap().start.column, 19);
+
+        assert_eq!(validate(&parse_file("class A { constructor(a = 1, b) {} m(a = 1, b) {} }")).len(), 2);
+        assert_eq!(validate(&parse_file("var f = function(a = 1, b) { return (c = 1, d) => c; };")).len(), 2);
+    }
+
+    #[test]
+    fn test_abstract_methods() {
+        assert!(validate(&parse_file("abstract class A { abstract m(); n() {} }")).is_empty());
+
+        let diagnostics = validate(&parse_file("class A { abstract m(); }"));
+        assert_eq!(diagnostics.len(), 1);
+        assert!(diagnostics[0].message.contains("non-abstract class"));
+
+        assert_eq!(validate(&parse_file("abstract class A { abstract m() {} }")).len(), 1);
+        assert_eq!(validate(&parse_file("class A { m(); }")).len(), 1);
+        assert_eq!(validate(&parse_file("abstract class A { abstract final m(); const n() {} }")).len(), 2);
+    }
+
+    #[test]
+    fn test_instantiate_abstract_class() {
+        assert_eq!(validate(&parse_file("var a = new A(); abstract class A {}")).len(), 1);
+        assert_eq!(validate(&parse_file("abstract class A {} var a = new A;")).len(), 1);
+        assert!(validate(&parse_file("abstract class A {} class B extends A {} var b = new B();")).is_empty());
+    }
+
+    #[test]
+    fn test_final_fields() {
+        assert!(validate(&parse_file(
+            "class A { final x; static final y; constructor() { this.x = 1; } static { A.y = 2; } }"
+        )).is_empty());
+
+        assert_eq!(validate(&parse_file("class A { final x = 1; constructor() { this.x = 2; } }")).len(), 1);
+        assert_eq!(validate(&parse_file("class A { final x; m() { this.x = 2; this.x++; } }")).len(), 2);
+        assert_eq!(validate(&parse_file("class A { static const y = 1; } A.y += 1;")).len(), 1);
+    }
+
+    #[test]
+    fn test_constants() {
+        assert!(validate(&parse_file("const a = 1; let b = a; b = 2; for (const x in [a]) { b += x; }")).is_empty());
+
+        let diagnostics = validate(&parse_file("const a = 1; a = 2; a += 1; a++; --a;"));
+        let messages: Vec<_> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+        assert_eq!(messages, [
+            "constant `a` can not be reassigned",
+            "constant `a` can not be modified",
+            "constant `a` can not be modified",
+            "constant `a` can not be modified",
+        ]);
+
+        assert_eq!(validate(&parse_file("global const G = 1; function f() { G = 2; }")).len(), 1);
+        assert_eq!(validate(&parse_file("for (const x in [1]) { x = 2; }")).len(), 1);
+        // Shadowing a constant is not a reassignment
+        assert!(validate(&parse_file("const a = 1; function f() { var a = 1; a = 2; }")).is_empty());
+    }
+
+    #[test]
+    fn test_uninitialized_constants() {
+        assert_eq!(validate(&parse_file("const a;")).len(), 1);
+        assert_eq!(validate(&parse_file("global const a, b = 1;")).len(), 1);
+    }
+
+    #[test]
+    fn test_unknown_escapes() {
+        assert!(validate(&parse_file("var a = '\\n\\t\\u00e9';")).is_empty());
+
+        let diagnostics = validate(&parse_file("var a = 1;\nif (a) { a = 'x\\q'; }"));
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].message, "unknown escape sequence `\\q` in 'x\\q'");
+        let location = diagnostics[0].location.unwrap();
+        assert_eq!((location.start.line, location.start.column, location.end.column), (2, 16, 18));
+
+        let diagnostics = validate(&parse_file("var a = `\\` \\$ \\q ${'\\w'}`;\nb = -'\\x' + ('\\y').z + `${1}\n\\k`;"));
+        let positions: Vec<_> = diagnostics.iter()
+            .map(|diagnostic| diagnostic.location.unwrap().start)
+            .map(|start| (start.line, start.column))
+            .collect();
+        assert_eq!(positions, [(1, 16), (1, 22), (2, 7), (2, 15), (3, 1)]);
+    }
+
+    #[test]
+    fn test_optional_chain_targets() {
+        assert!(validate(&parse_file("a.b = c?.d; a[0] += 1; x = a?.b;")).is_empty());
+
+        let diagnostics = validate(&parse_file("a?.b = 1;\na.b?[0] += 1; a?.b++; --a?.b.c;"));
+        let messages: Vec<_> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+        assert_eq!(messages, [
+            "`a?.b` can not be assigned as it uses optional chaining",
+            "`a.b?[0]` can not be assigned as it uses optional chaining",
+            "`a?.b` can not be assigned as it uses optional chaining",
+            "`a?.b.c` can not be assigned as it uses optional chaining",
+        ]);
+        assert_eq!(diagnostics[1].location.unwrap().start.line, 2);
+        assert_eq!(diagnostics[1].location.unwrap().start.column, 4);
+    }
+
+    #[test]
+    fn test_member_modifiers() {
+        assert_eq!(validate(&parse_file("class A { abstract x; static constructor() {} }")).len(), 2);
+    }
+}
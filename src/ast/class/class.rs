@@ -2,8 +2,9 @@ use nom::{branch::alt, bytes::complete::tag, combinator::{map, opt}, multi::many
 
 use crate::{ast::*, utils::kw};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Class {
+    pub abstract_: Option<MetaNode<Abstract>>,
     pub name: MetaNode<Identifier>,
     pub extends: Option<MetaNode<Identifier>>,
     pub body: MetaNode<ClassBody>,
@@ -11,6 +12,9 @@ pub struct Class {
 
 impl std::fmt::Display for Class {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(abstract_) = &self.abstract_ {
+            write!(f, "{}", abstract_)?;
+        }
         write!(f, "class{}", self.name)?;
         if let Some(extends) = &self.extends {
             write!(f, "extends{}", extends)?;
@@ -19,17 +23,29 @@ impl std::fmt::Display for Class {
     }
 }
 
+impl Class {
+    pub fn members(&self) -> impl Iterator<Item = &ClassMember> {
+        let members = match self.body.node.as_ref() {
+            ClassBody::Empty(_) => &[][..],
+            ClassBody::Members(members) => members.as_slice(),
+        };
+        members.iter().map(|member| member.node.as_ref())
+    }
+}
+
 impl ParseInto for Class {
     type Output = Self;
 
     fn parse_inner<'a>(input: Span<'a>) -> IResult<Span<'a>, Self::Output> {
         map(
             tuple((
+                opt(Abstract::parse),
                 preceded(kw("class"), Identifier::parse),
                 opt(preceded(kw("extends"), Identifier::parse)),
                 ClassBody::parse,
             )),
-            |(name, extends, body)| Class {
+            |(abstract_, name, extends, body)| Class {
+                abstract_,
                 name,
                 extends,
                 body,
@@ -38,7 +54,7 @@ impl ParseInto for Class {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum ClassBody {
     Empty(MetaNode<Empty>), // Holds any whitespace or comments in an empty class body
     Members(Vec<MetaNode<ClassMember>>),
@@ -74,7 +90,7 @@ impl ParseInto for ClassBody {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Empty;
 impl Display for Empty {
     fn fmt(&self, _f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -131,6 +147,22 @@ mod tests {
         test_remains_same::<Class, _>("class A { }", "class A { }");
     }
 
+    #[test]
+    fn test_abstract() {
+        test_remains_same::<Class, _>(
+            "abstract class A { abstract m(); final integer x = 1; }",
+            "abstract class A { abstract m(); final integer x = 1; }",
+        );
+    }
+
+    #[test]
+    fn test_static_block() {
+        test_remains_same::<Class, _>(
+            "class A { static integer x; static { x = 1; } }",
+            "class A { static integer x; static { x = 1; } }",
+        );
+    }
+
     #[test]
     fn test_method() {
         test_remains_same::<Class, _>("class A { a() {} }", "class A { a() {} }");
@@ -2,8 +2,9 @@ use nom::{branch::alt, combinator::map, IResult};
 use derive_more::Display;
 use crate::ast::*;
 
-#[derive(Debug, Clone, Display)]
+#[derive(Debug, Clone, Display, PartialEq)]
 pub enum ClassMember {
+    StaticBlock(StaticBlock),
     Constructor(Constructor),
     Method(Method),
     Field(Field),
@@ -14,6 +15,7 @@ impl ParseInto for ClassMember {
 
     fn parse_inner<'a>(input: Span<'a>) -> IResult<Span<'a>, Self::Output> {
         alt((
+            map(StaticBlock::parse_inner, ClassMember::StaticBlock),
             map(Constructor::parse_inner, ClassMember::Constructor),
             map(Method::parse_inner, ClassMember::Method),
             map(Field::parse_inner, ClassMember::Field),
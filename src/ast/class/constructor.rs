@@ -2,7 +2,7 @@ use crate::{ast::*, utils::kw};
 use nom::{IResult, sequence::tuple, combinator::map};
 use derive_more::Display;
 
-#[derive(Debug, Clone, Display)]
+#[derive(Debug, Clone, Display, PartialEq)]
 #[display(fmt = "{}constructor{}{}", visibility, parameters, body)]
 pub struct Constructor {
     pub visibility: MetaNode<Visibility>,
@@ -48,6 +48,7 @@ mod tests {
     fn test_params() {
         test_remains_same::<Constructor, _>("public constructor(String a, Number b) {}", "public constructor(String a, Number b) {}");
         test_remains_same::<Constructor, _>("constructor(String a) {}", "constructor(String a) {}");
+        test_remains_same::<Constructor, _>("constructor(String a = 'a', @b) {}", "constructor(String a = 'a', @b) {}");
     }
 
     #[test]
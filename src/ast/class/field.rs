@@ -2,7 +2,7 @@ use nom::{bytes::complete::tag, combinator::{map, opt, peek}, sequence::{precede
 
 use crate::ast::*;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Field {
     pub visibility: MetaNode<Visibility>,
     pub type_: Option<MetaNode<Type>>,
@@ -66,5 +66,8 @@ mod tests {
         test_remains_same::<Field, _>("a = 1;", "a = 1;");
         test_remains_same::<Field, _>("a = 1", "a = 1");
         test_remains_same::<Field, _>("static Number a", "static Number a");
+        test_remains_same::<Field, _>("public static final Number a = 1;", "public static final Number a = 1;");
+        test_remains_same::<Field, _>("private const a = 1;", "private const a = 1;");
+        test_remains_same::<Field, _>("final a;", "final a;");
     }
 }
\ No newline at end of file
@@ -1,14 +1,15 @@
-use nom::{combinator::{opt, peek}, sequence::{terminated, tuple}, IResult};
+use nom::{branch::alt, combinator::{map, opt, peek}, sequence::{terminated, tuple}, IResult};
+use derive_more::Display;
 
 use crate::ast::*;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Method {
     pub visibility: MetaNode<Visibility>,
     pub return_type: Option<MetaNode<Type>>,
     pub name: MetaNode<Identifier>,
     pub parameters: MetaNode<Parameters>,
-    pub body: MetaNode<BlockStatement>,
+    pub body: MethodBody,
 }
 
 impl std::fmt::Display for Method {
@@ -32,7 +33,7 @@ impl ParseInto for Method {
             opt(terminated(Type::parse, peek(Identifier::parse))),
             Identifier::parse,
             Parameters::parse,
-            BlockStatement::parse,
+            MethodBody::parse_inner,
         ))(input)?;
 
         Ok((input, Method {
@@ -45,6 +46,33 @@ impl ParseInto for Method {
     }
 }
 
+#[derive(Debug, Clone, Display, PartialEq)]
+pub enum MethodBody {
+    Block(MetaNode<BlockStatement>),
+    /// Abstract methods have no body, only an optional `;`.
+    Declaration(MetaNode<Semi>),
+}
+
+impl MethodBody {
+    pub fn block(&self) -> Option<&MetaNode<BlockStatement>> {
+        match self {
+            MethodBody::Block(block) => Some(block),
+            MethodBody::Declaration(_) => None,
+        }
+    }
+}
+
+impl ParseInto for MethodBody {
+    type Output = Self;
+
+    fn parse_inner<'a>(input: Span<'a>) -> IResult<Span<'a>, Self::Output> {
+        alt((
+            map(BlockStatement::parse, MethodBody::Block),
+            map(Semi::parse, MethodBody::Declaration),
+        ))(input)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::utils::test_remains_same;
@@ -60,5 +88,13 @@ mod tests {
         test_remains_same::<Method, _>("public a(Number a) {}", "public a(Number a) {}");
         test_remains_same::<Method, _>("public a(Number a, String b) {}", "public a(Number a, String b) {}");
         test_remains_same::<Method, _>("public a(Number a, String b) { return; }", "public a(Number a, String b) { return; }");
+        test_remains_same::<Method, _>("a(Number a = 1, @b) {}", "a(Number a = 1, @b) {}");
+    }
+
+    #[test]
+    fn test_modifiers() {
+        test_remains_same::<Method, _>("public abstract Number a();", "public abstract Number a();");
+        test_remains_same::<Method, _>("abstract a()", "abstract a()");
+        test_remains_same::<Method, _>("public static final a() {}", "public static final a() {}");
     }
 }
\ No newline at end of file
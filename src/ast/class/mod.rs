@@ -4,10 +4,12 @@ pub mod privacy;
 pub mod constructor;
 pub mod method;
 pub mod field;
+pub mod static_block;
 
 pub use class::*;
 pub use class_member::*;
 pub use privacy::*;
 pub use constructor::*;
 pub use method::*;
-pub use field::*;
\ No newline at end of file
+pub use field::*;
+pub use static_block::*;
\ No newline at end of file
@@ -2,7 +2,7 @@ use nom::{branch::alt, combinator::{map, opt, value}, sequence::tuple, IResult};
 use derive_more::Display;
 use crate::{ast::*, utils::kw};
 
-#[derive(Debug, Clone, Display)]
+#[derive(Debug, Clone, Display, PartialEq)]
 pub enum Privacy {
     #[display(fmt = "public")] Public,
     #[display(fmt = "protected")] Protected,
@@ -21,7 +21,7 @@ impl ParseInto for Privacy {
     }
 }
 
-#[derive(Debug, Clone, Display)]
+#[derive(Debug, Clone, Display, PartialEq)]
 #[display(fmt = "static")]
 pub struct Static;
 
@@ -33,10 +33,66 @@ impl ParseInto for Static {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Display, PartialEq)]
+#[display(fmt = "abstract")]
+pub struct Abstract;
+
+impl ParseInto for Abstract {
+    type Output = Self;
+
+    fn parse_inner<'a>(input: Span<'a>) -> IResult<Span<'a>, Self::Output> {
+        value(Abstract, kw("abstract"))(input)
+    }
+}
+
+#[derive(Debug, Clone, Display, PartialEq)]
+#[display(fmt = "final")]
+pub struct Final;
+
+impl ParseInto for Final {
+    type Output = Self;
+
+    fn parse_inner<'a>(input: Span<'a>) -> IResult<Span<'a>, Self::Output> {
+        value(Final, kw("final"))(input)
+    }
+}
+
+#[derive(Debug, Clone, Display, PartialEq)]
+#[display(fmt = "const")]
+pub struct Const;
+
+impl ParseInto for Const {
+    type Output = Self;
+
+    fn parse_inner<'a>(input: Span<'a>) -> IResult<Span<'a>, Self::Output> {
+        value(Const, kw("const"))(input)
+    }
+}
+
+/// The modifiers in front of a class member, always written in the order
+/// `privacy static abstract final const`.
+#[derive(Debug, Clone, PartialEq)]
 pub struct Visibility {
     pub privacy: Option<MetaNode<Privacy>>,
     pub static_: Option<MetaNode<Static>>,
+    pub abstract_: Option<MetaNode<Abstract>>,
+    pub final_: Option<MetaNode<Final>>,
+    pub const_: Option<MetaNode<Const>>,
+}
+
+impl Visibility {
+    pub fn is_static(&self) -> bool {
+        self.static_.is_some()
+    }
+
+    pub fn is_abstract(&self) -> bool {
+        self.abstract_.is_some()
+    }
+
+    /// `const` members can not be reassigned either.
+    pub fn is_final(&self) -> bool {
+        self.final_.is_some() || self.const_.is_some()
+    }
 }
 
 impl std::fmt::Display for Visibility {
@@ -47,6 +103,15 @@ impl std::fmt::Display for Visibility {
         if let Some(static_) = &self.static_ {
             write!(f, "{}", static_)?;
         }
+        if let Some(abstract_) = &self.abstract_ {
+            write!(f, "{}", abstract_)?;
+        }
+        if let Some(final_) = &self.final_ {
+            write!(f, "{}", final_)?;
+        }
+        if let Some(const_) = &self.const_ {
+            write!(f, "{}", const_)?;
+        }
         Ok(())
     }
 }
@@ -59,8 +124,17 @@ impl ParseInto for Visibility {
             tuple((
                 opt(Privacy::parse),
                 opt(Static::parse),
+                opt(Abstract::parse),
+                opt(Final::parse),
+                opt(Const::parse),
             )),
-            |(privacy, static_)| Visibility { privacy, static_ },
+            |(privacy, static_, abstract_, final_, const_)| Visibility {
+                privacy,
+                static_,
+                abstract_,
+                final_,
+                const_,
+            },
         )(input)
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,35 @@
+use crate::ast::*;
+use nom::{IResult, sequence::pair, combinator::map};
+use derive_more::Display;
+
+/// A `static { ... }` block, run once when the class is initialized.
+#[derive(Debug, Clone, Display, PartialEq)]
+#[display(fmt = "{}{}", static_, body)]
+pub struct StaticBlock {
+    pub static_: MetaNode<Static>,
+    pub body: MetaNode<BlockStatement>,
+}
+
+impl ParseInto for StaticBlock {
+    type Output = Self;
+
+    fn parse_inner<'a>(input: Span<'a>) -> IResult<Span<'a>, Self::Output> {
+        map(
+            pair(Static::parse, BlockStatement::parse),
+            |(static_, body)| StaticBlock { static_, body },
+        )(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::test_remains_same;
+
+    use super::*;
+
+    #[test]
+    fn test_static_block() {
+        test_remains_same::<StaticBlock, _>("static {}", "static {}");
+        test_remains_same::<StaticBlock, _>("static { a = 1; }", "static { a = 1; }");
+    }
+}
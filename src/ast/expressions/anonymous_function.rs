@@ -3,12 +3,13 @@ use derive_more::Display;
 use function_definition::Arrow;
 use nom::{
     branch::alt,
-    combinator::{map, opt},
-    sequence::pair,
+    character::complete::one_of,
+    combinator::{map, not, opt},
+    sequence::{pair, preceded},
     IResult,
 };
 
-#[derive(Debug, Clone, Display)]
+#[derive(Debug, Clone, Display, PartialEq)]
 pub enum AnonymousFunction {
     ArrowFunction(ArrowFunction),
     Function(AnonymousFuncDec),
@@ -28,7 +29,7 @@ impl ParseInto for AnonymousFunction {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct ArrowFunction {
     pub args: MetaNode<AnonymousParameter>,
     pub arrow: MetaNode<Arrow>,
@@ -62,7 +63,11 @@ impl ParseInto for ArrowFunction {
                 AnonymousParameter::parse,
                 Arrow::parse,
                 alt((
-                    map(pair(Type::parse, FunctionBodyOrExpression::parse), |(t, f)| { (Some(t), f) }),
+                    // In `x => x + 1`, `x` is the body and not a return type followed by `+1`
+                    map(
+                        pair(Type::parse, preceded(not(one_of("+-*/%<>=!&|^?.[(")), FunctionBodyOrExpression::parse)),
+                        |(t, f)| { (Some(t), f) },
+                    ),
                     map(FunctionBodyOrExpression::parse, |f| (None, f)),
                 ))
             )),
@@ -76,7 +81,7 @@ impl ParseInto for ArrowFunction {
     }
 }
 
-#[derive(Debug, Clone, Display)]
+#[derive(Debug, Clone, Display, PartialEq)]
 pub enum AnonymousParameter {
     SingleParam(MetaNode<Parameter>),
     Parameters(MetaNode<Parameters>),
@@ -88,12 +93,12 @@ impl ParseInto for AnonymousParameter {
     fn parse_inner<'a>(input: Span<'a>) -> IResult<Span<'a>, Self::Output> {
         alt((
             map(Parameters::parse, AnonymousParameter::Parameters),
-            map(Parameter::parse, AnonymousParameter::SingleParam),
+            map(meta(Parameter::parse_without_default), AnonymousParameter::SingleParam),
         ))(input)
     }
 }
 
-#[derive(Debug, Clone, Display)]
+#[derive(Debug, Clone, Display, PartialEq)]
 pub enum FunctionBodyOrExpression {
     BlockStatement(MetaNode<BlockStatement>),
     Expression(MetaNode<Expression>),
@@ -113,7 +118,7 @@ impl ParseInto for FunctionBodyOrExpression {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct AnonymousFuncDec {
     pub args: MetaNode<Parameters>,
     pub return_type: Option<(MetaNode<Arrow>, MetaNode<Type>)>,
@@ -187,6 +192,22 @@ mod tests {
         test_remains_same::<AnonymousFunction, _>("(String a, String b) => String a", "(String a, String b) => String a");
     }
 
+    #[test]
+    fn test_arrow_expression_body() {
+        let parse = |input| AnonymousFunction::parse_inner(Span::new_extra(input, "test_input")).unwrap().1;
+        let Expression::AnonyFunc(AnonymousFunction::ArrowFunction(arrow)) = parse("x => x + 1") else { panic!() };
+        assert!(arrow.return_type.is_none());
+        let Expression::AnonyFunc(AnonymousFunction::ArrowFunction(arrow)) = parse("x => integer x") else { panic!() };
+        assert!(arrow.return_type.is_some());
+    }
+
+    #[test]
+    fn test_arrow_default_and_reference() {
+        test_remains_same::<AnonymousFunction, _>("(a, b = 2) => a + b", "(a, b = 2) => a + b");
+        test_remains_same::<AnonymousFunction, _>("@a => a", "@a => a");
+        test_remains_same::<AnonymousFunction, _>("(integer @a = 1) => a", "(integer @a = 1) => a");
+    }
+
     #[test]
     fn test_function() {
         test_remains_same::<AnonymousFunction, _>("function(){}", "function(){}");
@@ -200,5 +221,6 @@ mod tests {
         test_remains_same::<AnonymousFunction, _>("function(a) => String {}", "function(a) => String {}");
         test_remains_same::<AnonymousFunction, _>("function(a, b) => String {}", "function(a, b) => String {}");
         test_remains_same::<AnonymousFunction, _>("function(String a, String b) => String {}", "function(String a, String b) => String {}");
+        test_remains_same::<AnonymousFunction, _>("function(@a, b = null) {}", "function(@a, b = null) {}");
     }
 }
\ No newline at end of file
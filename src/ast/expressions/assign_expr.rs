@@ -2,7 +2,7 @@ use nom::{multi::many0, sequence::tuple};
 
 use crate::ast::*;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct AssignExpr {
     pub left: Box<Expression>,
     pub right: Vec<(MetaNode<AssignOp>, MetaNode<Expression>)>,
@@ -5,7 +5,7 @@ use combinator::map;
 use nom::*;
 use sequence::tuple;
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq)]
 pub struct BinExpr<Op: ParseInto, Expr: ParseInto> {
     marker: std::marker::PhantomData::<Expr>,
 
@@ -13,6 +13,16 @@ pub struct BinExpr<Op: ParseInto, Expr: ParseInto> {
     pub right: Vec<(MetaNode<Op>, MetaNode<Expression>)>,
 }
 
+impl<Op: ParseInto, Expr: ParseInto> BinExpr<Op, Expr> {
+    pub fn new(left: Expression, right: Vec<(MetaNode<Op>, MetaNode<Expression>)>) -> Self {
+        BinExpr {
+            marker: std::marker::PhantomData,
+            left: Box::new(left),
+            right,
+        }
+    }
+}
+
 impl<Op: Debug + ParseInto, Expr: Debug + ParseInto> Debug for BinExpr<Op, Expr> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         // Display the debug representation of the struct without the marker
@@ -60,7 +70,7 @@ Expression: From<BinExpr<Op, Expr>>,
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq)]
 pub struct UnaryLeft<Op: ParseInto, Expr: ParseInto> {
     marker: std::marker::PhantomData::<Expr>,
 
@@ -68,6 +78,16 @@ pub struct UnaryLeft<Op: ParseInto, Expr: ParseInto> {
     pub expr: Box<Expression>,
 }
 
+impl<Op: ParseInto, Expr: ParseInto> UnaryLeft<Op, Expr> {
+    pub fn new(op: MetaNode<Op>, expr: Expression) -> Self {
+        UnaryLeft {
+            marker: std::marker::PhantomData,
+            op,
+            expr: Box::new(expr),
+        }
+    }
+}
+
 impl<Op: Debug + ParseInto, Expr: Debug + ParseInto> Debug for UnaryLeft<Op, Expr> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         // Display the debug representation of the struct without the marker
@@ -109,7 +129,7 @@ Expression: From<UnaryLeft<Op, Expr>>,
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq)]
 pub struct UnaryRight<Op: ParseInto, Expr: ParseInto> {
     marker: std::marker::PhantomData::<Expr>,
 
@@ -19,7 +19,7 @@ pub type PreUpdateExpr = UnaryLeft<UpdateOp, PostUpdateExpr>;
 pub type PostUpdateExpr = UnaryRight<UpdateOp, UnaryExpr>;
 pub type UnaryExpr = UnaryLeft<UnaryOp, Member>;
 
-#[derive(Debug, Clone, Display, From)]
+#[derive(Debug, Clone, Display, From, PartialEq)]
 pub enum Expression {
     Assign(AssignExpr),
     AnonyFunc(AnonymousFunction),
@@ -54,7 +54,46 @@ impl ParseInto for Expression {
     }
 }
 
-#[derive(Debug, Clone, Display)]
+impl Expression {
+    /// The identifier this expression consists of, if it is a bare name.
+    pub fn as_identifier(&self) -> Option<&MetaNode<Identifier>> {
+        match self {
+            Expression::Primary(PrimaryExpr::IdentifierOrMember(identifier)) => match identifier.node.as_ref() {
+                IdentifierOrMember::Identifier(identifier) => Some(identifier),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// How tightly the expression binds: an operand needs parentheses when its precedence is
+    /// lower than the one of the expression it is part of, or equal for a right operand.
+    pub fn precedence(&self) -> u8 {
+        match self {
+            Expression::Assign(_) | Expression::AnonyFunc(_) => 0,
+            Expression::TernaryExpression(_) => 1,
+            Expression::LogicalOr(_) => 2,
+            Expression::LogicalXor(_) => 3,
+            Expression::LogicalAnd(_) => 4,
+            Expression::Relation(_) => 5,
+            Expression::InstanceOf(_) => 6,
+            Expression::Shift(_) => 7,
+            Expression::BinOr(_) => 8,
+            Expression::BinXor(_) => 9,
+            Expression::BinAnd(_) => 10,
+            Expression::Add(_) => 11,
+            Expression::Mult(_) => 12,
+            Expression::TypeConversion(_) => 13,
+            Expression::PreUpdate(_) => 14,
+            Expression::PostUpdate(_) => 15,
+            Expression::Unary(_) => 16,
+            Expression::Member(_) => 17,
+            Expression::Primary(_) => 18,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Display, PartialEq)]
 pub enum UnaryOp {
     Not(String),
     #[display(fmt = "+")] Plus,
@@ -79,7 +118,7 @@ impl ParseInto for UnaryOp {
     }
 }
 
-#[derive(Debug, Clone, Display)]
+#[derive(Debug, Clone, Display, PartialEq)]
 pub enum UpdateOp {
     #[display(fmt = "++")] Inc,
     #[display(fmt = "--")] Dec,
@@ -96,7 +135,7 @@ impl ParseInto for UpdateOp {
     }
 }
 
-#[derive(Debug, Clone, Display)]
+#[derive(Debug, Clone, Display, PartialEq)]
 pub enum MultOp {
     #[display(fmt = "*")] Mul,
     #[display(fmt = "/")] Div,
@@ -115,7 +154,7 @@ impl ParseInto for MultOp {
     }
 }
 
-#[derive(Debug, Clone, Display)]
+#[derive(Debug, Clone, Display, PartialEq)]
 pub enum AddOp {
     #[display(fmt = "+")] Add,
     #[display(fmt = "-")] Sub,
@@ -132,7 +171,7 @@ impl ParseInto for AddOp {
     }
 }
 
-#[derive(Debug, Clone, Display)]
+#[derive(Debug, Clone, Display, PartialEq)]
 #[display(fmt = "&")]
 pub struct BinAndOp;
 
@@ -144,7 +183,7 @@ impl ParseInto for BinAndOp {
     }
 }
 
-#[derive(Debug, Clone, Display)]
+#[derive(Debug, Clone, Display, PartialEq)]
 #[display(fmt = "^")]
 pub struct BinXorOp;
 
@@ -156,7 +195,7 @@ impl ParseInto for BinXorOp {
     }
 }
 
-#[derive(Debug, Clone, Display)]
+#[derive(Debug, Clone, Display, PartialEq)]
 #[display(fmt = "|")]
 pub struct BinOrOp;
 
@@ -168,7 +207,7 @@ impl ParseInto for BinOrOp {
     }
 }
 
-#[derive(Debug, Clone, Display)]
+#[derive(Debug, Clone, Display, PartialEq)]
 pub enum ShiftOp {
     #[display(fmt = "<<")] Left,
     #[display(fmt = ">>")] Right,
@@ -187,7 +226,7 @@ impl ParseInto for ShiftOp {
     }
 }
 
-#[derive(Debug, Clone, Display)]
+#[derive(Debug, Clone, Display, PartialEq)]
 #[display(fmt = "instanceof")]
 pub struct InstanceOfOp;
 
@@ -199,7 +238,7 @@ impl ParseInto for InstanceOfOp {
     }
 }
 
-#[derive(Debug, Clone, Display)]
+#[derive(Debug, Clone, Display, PartialEq)]
 pub enum RelationOp {
     #[display(fmt = "<")] Less,
     #[display(fmt = "<=")] LessEq,
@@ -224,7 +263,7 @@ impl ParseInto for RelationOp {
     }
 }
 
-#[derive(Debug, Clone, Display)]
+#[derive(Debug, Clone, Display, PartialEq)]
 pub enum LogicalAndOp {
     And(String),
 }
@@ -237,7 +276,7 @@ impl ParseInto for LogicalAndOp {
     }
 }
 
-#[derive(Debug, Clone, Display)]
+#[derive(Debug, Clone, Display, PartialEq)]
 pub enum LogicalXorOp {
     Xor(String),
 }
@@ -250,7 +289,7 @@ impl ParseInto for LogicalXorOp {
     }
 }
 
-#[derive(Debug, Clone, Display)]
+#[derive(Debug, Clone, Display, PartialEq)]
 pub enum LogicalOrOp {
     Or(String),
 }
@@ -263,7 +302,7 @@ impl ParseInto for LogicalOrOp {
     }
 }
 
-#[derive(Debug, Clone, Display)]
+#[derive(Debug, Clone, Display, PartialEq)]
 pub enum AssignOp {
     #[display(fmt = "=")] Assign,
     #[display(fmt = "+=")] Add,
@@ -1,9 +1,9 @@
 use derive_more::Display;
-use nom::{branch::alt, bytes::complete::tag, combinator::map, multi::{many0, separated_list0}, sequence::{delimited, pair, preceded}, IResult};
+use nom::{branch::alt, bytes::complete::tag, combinator::{map, not, verify}, multi::{many0, separated_list0}, sequence::{delimited, pair, preceded, terminated}, IResult};
 use crate::{ast::*, utils::kw};
 use nom::character::complete::char;
 
-#[derive(Debug, Clone, Display)]
+#[derive(Debug, Clone, Display, PartialEq)]
 #[display(fmt = ".")]
 pub struct Dot;
 impl ParseInto for Dot {
@@ -14,7 +14,7 @@ impl ParseInto for Dot {
     }
 }
 
-#[derive(Debug, Clone, Display)]
+#[derive(Debug, Clone, Display, PartialEq)]
 pub enum IdentifierOrMember {
     Identifier(MetaNode<Identifier>),
     #[display(fmt = "class")]
@@ -30,20 +30,30 @@ impl ParseInto for IdentifierOrMember {
 
     fn parse_inner<'a>(input: Span<'a>) -> IResult<Span<'a>, Self::Output> {
         alt((
-            map(Identifier::parse, IdentifierOrMember::Identifier),
             map(kw("class"), |_| IdentifierOrMember::Class),
             map(kw("super"), |_| IdentifierOrMember::Super),
             map(kw("this"), |_| IdentifierOrMember::This),
+            map(Identifier::parse, IdentifierOrMember::Identifier),
         ))(input)
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum MemberRight {
     Dot(MetaNode<IdentifierOrMember>),
     Bracket(MetaNode<Expression>),
     Call(Vec<MetaNode<Expression>>),
     NotNull,
+    /// `?.name`, null when the object is null
+    OptionalDot(MetaNode<IdentifierOrMember>),
+    /// `?[index]`, null when the object is null
+    OptionalBracket(MetaNode<Expression>),
+}
+
+impl MemberRight {
+    pub fn is_optional(&self) -> bool {
+        matches!(self, MemberRight::OptionalDot(_) | MemberRight::OptionalBracket(_))
+    }
 }
 
 impl Display for MemberRight {
@@ -64,6 +74,8 @@ impl Display for MemberRight {
                 write!(f, ")")
             },
             MemberRight::NotNull => write!(f, "!"),
+            MemberRight::OptionalDot(ident) => write!(f, "?.{}", ident),
+            MemberRight::OptionalBracket(expr) => write!(f, "?[{}]", expr),
         }
     }
 }
@@ -84,12 +96,19 @@ impl ParseInto for MemberRight {
                 separated_list0(char(','), Expression::parse),
                 char(')'),
             ), MemberRight::Call),
-            map(tag("!"), |_| MemberRight::NotNull),
+            // Not the start of `!=`
+            map(terminated(tag("!"), not(char('='))), |_| MemberRight::NotNull),
+            map(preceded(tag("?."), IdentifierOrMember::parse), MemberRight::OptionalDot),
+            map(delimited(
+                tag("?["),
+                Expression::parse,
+                char(']'),
+            ), MemberRight::OptionalBracket),
         ))(input)
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Member {
     pub left: PrimaryExpr,
     pub right: Vec<MetaNode<MemberRight>>,
@@ -105,6 +124,22 @@ impl Display for Member {
     }
 }
 
+impl Member {
+    /// The object and field of a simple `object.field` access such as `this.x` or `A.x`.
+    pub fn as_field_access(&self) -> Option<(&IdentifierOrMember, &MetaNode<Identifier>)> {
+        match (&self.left, self.right.as_slice()) {
+            (PrimaryExpr::IdentifierOrMember(object), [right]) => match right.node.as_ref() {
+                MemberRight::Dot(field) => match field.node.as_ref() {
+                    IdentifierOrMember::Identifier(field) => Some((object.node.as_ref(), field)),
+                    _ => None,
+                },
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
 impl ParseInto for Member {
     type Output = Expression;
 
@@ -112,7 +147,10 @@ impl ParseInto for Member {
         map(
             pair(
                 PrimaryExpr::parse_inner,
-                many0(MemberRight::parse),
+                // `a ? [b] : c` is a ternary, `?[` only indexes when it sticks to the object
+                many0(verify(MemberRight::parse, |right| {
+                    !matches!(right.node.as_ref(), MemberRight::OptionalBracket(_)) || right.pre_comments.is_empty()
+                })),
             ),
             |(left, right)| match right.len() {
                 0 => left.into(),
@@ -165,6 +203,24 @@ mod tests {
         test_remains_same::<Member, _>("a.b!", "a.b!");
         test_remains_same::<Member, _>("a[1]!", "a[1]!");
         test_remains_same::<Member, _>("a(1, 2)!", "a(1, 2)!");
+
+        let (_, expression) = Expression::parse_inner(Span::new_extra("a != b", "test_input")).unwrap();
+        assert!(matches!(expression, Expression::Relation(_)), "{:?}", expression);
+    }
+
+    #[test]
+    fn test_optional() {
+        test_remains_same::<Member, _>("a?.b", "a?.b");
+        test_remains_same::<Member, _>("a?.b?.c()", "a?.b?.c()");
+        test_remains_same::<Member, _>("a?[1]?[2]", "a?[1]?[2]");
+        test_remains_same::<Member, _>("a.b?.c!.d", "a.b?.c!.d");
+    }
+
+    #[test]
+    fn test_optional_bracket_or_ternary() {
+        test_remains_same::<Expression, _>("a ? [1] : [2]", "a ? [1] : [2]");
+        test_remains_same::<Expression, _>("a ?.5 : 1", "a ?.5 : 1");
+        test_remains_same::<Expression, _>("x ? a?[1] : b", "x ? a?[1] : b");
     }
 
     #[test]
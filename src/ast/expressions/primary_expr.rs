@@ -2,7 +2,7 @@ use derive_more::Display;
 use nom::{branch::alt, bytes::complete::tag, combinator::map, sequence::delimited, IResult};
 use crate::ast::*;
 
-#[derive(Debug, Clone, Display)]
+#[derive(Debug, Clone, Display, PartialEq)]
 pub enum PrimaryExpr {
     IdentifierOrMember(MetaNode<IdentifierOrMember>),
     Literal(Literal),
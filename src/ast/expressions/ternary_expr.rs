@@ -2,7 +2,7 @@ use derive_more::Display;
 use nom::{branch::alt, combinator::map, sequence::{preceded, tuple}, IResult};
 use crate::{ast::*, utils::kw};
 
-#[derive(Debug, Clone, Display)]
+#[derive(Debug, Clone, Display, PartialEq)]
 #[display(fmt = "{}?{}:{}", condition, true_expr, false_expr)]
 pub struct TernaryExpression {
     pub condition: MetaNode<Expression>,
@@ -2,7 +2,7 @@ use derive_more::Display;
 use nom::{combinator::{map, opt}, sequence::pair, IResult};
 use crate::{ast::*, utils::kw};
 
-#[derive(Debug, Clone, Display)]
+#[derive(Debug, Clone, Display, PartialEq)]
 #[display(fmt = "{}{}{}", expression, as_, type_)]
 pub struct TypeConversionExpr {
     pub expression: Box<Expression>,
@@ -31,7 +31,7 @@ impl ParseInto for TypeConversionExpr {
     }
 }
 
-#[derive(Debug, Clone, Display)]
+#[derive(Debug, Clone, Display, PartialEq)]
 #[display(fmt = "as")]
 pub struct As;
 
@@ -2,7 +2,7 @@ use nom::{combinator::{eof, map}, multi::many0, sequence::{pair, terminated}, IR
 
 use crate::ast::*;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct File {
     pub statements: Vec<MetaNode<GlobalStatement>>,
     pub eof: MetaNode<Empty>
@@ -20,6 +20,21 @@ impl Display for File {
 impl ParseInto for File {
     type Output = Self;
 
+    /// Keeps the comments before the first statement in the file rather than around it, so the
+    /// `File` alone prints back the whole source.
+    fn parse<'a>(input: Span<'a>) -> IResult<Span<'a>, MetaNode<Self::Output>> {
+        let source = input;
+        let (input, mut file) = meta(Self::parse_inner)(input)?;
+        let leading = std::mem::take(&mut file.pre_comments);
+        let first = match file.node.statements.first_mut() {
+            Some(statement) => &mut statement.pre_comments,
+            None => &mut file.node.eof.pre_comments,
+        };
+        first.splice(0..0, leading);
+        trivia::attach_trivia(&mut file.node, source);
+        Ok((input, file))
+    }
+
     fn parse_inner<'a>(input: Span<'a>) -> IResult<Span<'a>, Self::Output> {
         map(
             terminated(pair(many0(GlobalStatement::parse), Empty::parse), eof),
@@ -5,7 +5,7 @@ use object::Comma;
 
 use crate::ast::*;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Array {
     Empty(MetaNode<Empty>),
     Elements {
@@ -12,16 +12,16 @@ use nom::{
 };
 use derive_more::Display;
 
-const KEYWORDS: [&str; 46] = [
+pub const KEYWORDS: [&str; 48] = [
     "true", "false", "null", "undefined",
     "not", "and", "or", "is", "in", "as",
     "if", "else", "elif",
     "switch", "case", "default",
     "for", "do", "while", "break", "continue",
     "function", "return", "yield",
-    "raise", "try", "except", "finally",
-    "import", "include", "as", "with",
-    "global", "var", "const", "let", "static",
+    "raise", "throw", "try", "catch", "except", "finally",
+    "import", "include", "with",
+    "global", "var", "const", "let", "static", "final",
     "class", "extends", "implements", "async", "await",
     "public", "private", "protected", "abstract",
 ];
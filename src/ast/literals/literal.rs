@@ -7,6 +7,7 @@ use crate::ast::*;
 #[derive(Debug, Clone, Display, PartialEq)]
 pub enum Literal {
     String(StringLiteral),
+    Template(TemplateString),
     Number(NumberLiteral),
     Boolean(bool),
     #[display(fmt = "null")]
@@ -19,6 +20,7 @@ impl ParseInto for Literal {
     fn parse_inner<'a>(input: Span<'a>) -> IResult<Span<'a>, Self::Output> {
         alt((
             map(StringLiteral::parse_inner, Literal::String),
+            map(TemplateString::parse_inner, Literal::Template),
             map(NumberLiteral::parse_inner, Literal::Number),
             map(tag("true"), |_| Literal::Boolean(true)),
             map(tag("false"), |_| Literal::Boolean(false)),
@@ -12,7 +12,7 @@ use object::{Colon, Comma};
 
 use crate::ast::*;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Map {
     Empty(MetaNode<Colon>),
     Pairs {
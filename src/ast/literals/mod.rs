@@ -2,16 +2,18 @@ pub mod identifier;
 pub mod literal;
 pub mod number;
 pub mod string;
+pub mod template;
 
 pub mod array;
 pub mod set;
 pub mod map;
 pub mod object;
 
-pub use identifier::Identifier;
+pub use identifier::{Identifier, KEYWORDS};
 pub use literal::Literal;
-pub use number::NumberLiteral;
-pub use string::StringLiteral;
+pub use number::{NumberLiteral, NumberValue, NumberFormat};
+pub use string::{StringLiteral, QuoteType, InvalidEscape};
+pub use template::{TemplateString, TemplatePart};
 
 pub use array::Array;
 pub use set::Set;
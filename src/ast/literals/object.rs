@@ -9,7 +9,7 @@ use nom::{
     IResult,
 };
 
-#[derive(Debug, Clone, Display)]
+#[derive(Debug, Clone, Display, PartialEq)]
 #[display(fmt = ":")]
 pub struct Colon;
 impl ParseInto for Colon {
@@ -20,7 +20,7 @@ impl ParseInto for Colon {
     }
 }
 
-#[derive(Debug, Clone, Display)]
+#[derive(Debug, Clone, Display, PartialEq)]
 #[display(fmt = ",")]
 pub struct Comma;
 impl ParseInto for Comma {
@@ -31,7 +31,7 @@ impl ParseInto for Comma {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Object {
     Empty(MetaNode<Empty>),
     Pairs {
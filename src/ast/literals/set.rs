@@ -7,7 +7,7 @@ use crate::ast::*;
 
 use super::super::{expression::Expression, MetaNode, ParseInto};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Set {
     Empty(MetaNode<Empty>),
     Elements {
@@ -19,6 +19,114 @@ pub enum QuoteType {
     #[display(fmt = "\"")] Double,
 }
 
+/// An escape sequence of a string literal that does not mean anything.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvalidEscape {
+    /// Byte offset of the backslash in `value`
+    pub offset: usize,
+    pub sequence: String,
+}
+
+impl StringLiteral {
+    /// Builds a literal whose decoded value is `value`, escaping what has to be.
+    pub fn new(value: &str, quote_type: QuoteType) -> Self {
+        let mut escaped = String::with_capacity(value.len());
+        for c in value.chars() {
+            match c {
+                '\\' => escaped.push_str("\\\\"),
+                '\'' if quote_type == QuoteType::Single => escaped.push_str("\\'"),
+                '"' if quote_type == QuoteType::Double => escaped.push_str("\\\""),
+                '\u{8}' => escaped.push_str("\\b"),
+                '\u{c}' => escaped.push_str("\\f"),
+                '\n' => escaped.push_str("\\n"),
+                '\r' => escaped.push_str("\\r"),
+                '\t' => escaped.push_str("\\t"),
+                '\u{b}' => escaped.push_str("\\v"),
+                '\0' => escaped.push_str("\\0"),
+                c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+                c => escaped.push(c),
+            }
+        }
+        StringLiteral { value: escaped, quote_type }
+    }
+
+    /// The value of the string with its escape sequences replaced by the characters they stand for.
+    /// Unknown escapes keep the escaped character, as `\\a` gives `a`.
+    pub fn decoded(&self) -> String {
+        self.decode().0
+    }
+
+    pub fn invalid_escapes(&self) -> Vec<InvalidEscape> {
+        self.decode().1
+    }
+
+    fn decode(&self) -> (String, Vec<InvalidEscape>) {
+        decode(&self.value, "'\"")
+    }
+}
+
+/// Decodes the escape sequences of `raw`, where `quotes` are the characters that may be escaped
+/// besides the backslash and the letter escapes.
+pub(crate) fn decode(raw: &str, quotes: &str) -> (String, Vec<InvalidEscape>) {
+    let mut decoded = String::with_capacity(raw.len());
+    let mut invalid = Vec::new();
+    let mut chars = raw.char_indices();
+
+    while let Some((offset, c)) = chars.next() {
+        if c != '\\' {
+            decoded.push(c);
+            continue;
+        }
+        let Some((_, escaped)) = chars.next() else {
+            decoded.push(c);
+            break;
+        };
+        match escaped {
+            'b' => decoded.push('\u{8}'),
+            'f' => decoded.push('\u{c}'),
+            'n' => decoded.push('\n'),
+            'r' => decoded.push('\r'),
+            't' => decoded.push('\t'),
+            'v' => decoded.push('\u{b}'),
+            '0' => decoded.push('\0'),
+            '\\' => decoded.push(escaped),
+            escaped if quotes.contains(escaped) => decoded.push(escaped),
+            'u' => {
+                // Either \uXXXX or \u{X...}
+                let rest = &raw[offset + 2..];
+                let (digits, length) = match rest.strip_prefix('{') {
+                    Some(braced) => match braced.find('}') {
+                        Some(end) => (&braced[..end], end + 2),
+                        None => ("", 0),
+                    },
+                    None => (rest.get(..4).unwrap_or(""), 4),
+                };
+                let character = Some(digits)
+                    .filter(|digits| !digits.is_empty() && digits.chars().all(|c| c.is_ascii_hexdigit()))
+                    .and_then(|digits| u32::from_str_radix(digits, 16).ok())
+                    .and_then(char::from_u32);
+                match character {
+                    Some(character) => {
+                        decoded.push(character);
+                        for _ in 0..length {
+                            chars.next();
+                        }
+                    }
+                    None => {
+                        invalid.push(InvalidEscape { offset, sequence: "\\u".to_string() });
+                        decoded.push('u');
+                    }
+                }
+            }
+            escaped => {
+                invalid.push(InvalidEscape { offset, sequence: format!("\\{}", escaped) });
+                decoded.push(escaped);
+            }
+        }
+    }
+    (decoded, invalid)
+}
+
 impl ParseInto for StringLiteral {
     type Output = Self;
 
@@ -56,6 +164,7 @@ impl ParseInto for StringLiteral {
 // - \': single quote
 // - \": double quote
 // - \0: null character
+// - \uXXXX and \u{X...}: unicode code point
 fn eat_char<'a>(quote: QuoteType) -> impl Fn(Span<'a>) -> IResult<Span<'a>, String> {
     move |input: Span<'a>| {
         // Should eat the escape character and the character it escapes or the next character
@@ -79,6 +188,10 @@ mod tests {
 
     use super::*;
 
+    fn literal(input: &str) -> StringLiteral {
+        StringLiteral::parse_inner(Span::new_extra(input, "test_input")).unwrap().1
+    }
+
     #[test]
     fn test_single_quote() {
         test_remains_same::<StringLiteral, _>("'hello'", "'hello'");
@@ -111,4 +224,30 @@ mod tests {
     fn test_arbitrary_escape() {
         test_remains_same::<StringLiteral, _>("'\\a'", "'\\a'");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_decoded() {
+        let decoded = |input| literal(input).decoded();
+        assert_eq!(decoded("'a\\nb\\t\\\\'"), "a\nb\t\\");
+        assert_eq!(decoded("'\\'\\\"\\0\\b\\f\\v\\r'"), "'\"\0\u{8}\u{c}\u{b}\r");
+        assert_eq!(decoded("'\\u00e9\\u{1F600}!'"), "\u{e9}\u{1F600}!");
+    }
+
+    #[test]
+    fn test_invalid_escapes() {
+        let invalid = |input| literal(input).invalid_escapes();
+        assert!(invalid("'\\n\\u0041'").is_empty());
+        assert_eq!(invalid("'x\\a'"), [InvalidEscape { offset: 1, sequence: "\\a".to_string() }]);
+        assert_eq!(invalid("'\\u12'").len(), 1);
+        assert_eq!(invalid("'\\u{110000}'").len(), 1);
+    }
+
+    #[test]
+    fn test_new() {
+        let value = "it's \"quoted\"\n\\ \u{1}";
+        let escaped = StringLiteral::new(value, QuoteType::Single).to_string();
+        assert_eq!(escaped, "'it\\'s \"quoted\"\\n\\\\ \\u0001'");
+        assert_eq!(literal(&escaped).decoded(), value);
+        assert_eq!(StringLiteral::new(value, QuoteType::Double).decoded(), value);
+    }
+}
@@ -0,0 +1,110 @@
+use nom::{
+    branch::alt, bytes::complete::tag, character::complete::{anychar, char, one_of}, combinator::{map, not, recognize}, multi::{many0, many1}, sequence::{delimited, pair, preceded}, IResult
+};
+
+use crate::ast::*;
+
+use super::string::decode;
+
+const TEMPLATE_ESCAPES: &str = "'\"`$";
+
+/// A backquoted string whose `${...}` holes are evaluated and concatenated with the text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TemplateString {
+    pub parts: Vec<TemplatePart>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TemplatePart {
+    /// Raw source text, escapes included
+    Text(String),
+    Hole(MetaNode<Expression>),
+}
+
+impl std::fmt::Display for TemplateString {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "`")?;
+        for part in &self.parts {
+            match part {
+                TemplatePart::Text(text) => write!(f, "{}", text)?,
+                TemplatePart::Hole(expression) => write!(f, "${{{}}}", expression)?,
+            }
+        }
+        write!(f, "`")
+    }
+}
+
+impl TemplatePart {
+    /// The text with its escape sequences replaced, `None` for holes.
+    pub fn decoded(&self) -> Option<String> {
+        match self {
+            TemplatePart::Text(text) => Some(decode(text, TEMPLATE_ESCAPES).0),
+            TemplatePart::Hole(_) => None,
+        }
+    }
+
+    pub fn invalid_escapes(&self) -> Vec<InvalidEscape> {
+        match self {
+            TemplatePart::Text(text) => decode(text, TEMPLATE_ESCAPES).1,
+            TemplatePart::Hole(_) => Vec::new(),
+        }
+    }
+}
+
+impl ParseInto for TemplateString {
+    type Output = Self;
+
+    fn parse_inner<'a>(input: Span<'a>) -> IResult<Span<'a>, Self::Output> {
+        map(
+            delimited(
+                char('`'),
+                many0(alt((
+                    map(delimited(tag("${"), Expression::parse, char('}')), TemplatePart::Hole),
+                    map(
+                        recognize(many1(alt((
+                            recognize(pair(char('\\'), anychar)),
+                            recognize(preceded(not(alt((tag("${"), recognize(one_of("`\\"))))), anychar)),
+                        )))),
+                        |text: Span| TemplatePart::Text(text.to_string()),
+                    ),
+                ))),
+                char('`'),
+            ),
+            |parts| TemplateString { parts },
+        )(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::test_remains_same;
+
+    use super::*;
+
+    #[test]
+    fn test_template() {
+        test_remains_same::<TemplateString, _>("``", "``");
+        test_remains_same::<TemplateString, _>("`hello`", "`hello`");
+        test_remains_same::<TemplateString, _>("`a ${ b + 1 } c`", "`a ${ b + 1 } c`");
+        test_remains_same::<TemplateString, _>("`${a}${f(`${b}`)}`", "`${a}${f(`${b}`)}`");
+        test_remains_same::<TemplateString, _>("`\\` \\${a} $ {`", "`\\` \\${a} $ {`");
+    }
+
+    #[test]
+    fn test_holes_are_expressions() {
+        let (_, template) = TemplateString::parse_inner(Span::new_extra("`x${a * 2}y`", "test_input")).unwrap();
+        assert_eq!(template.parts.len(), 3);
+        assert!(matches!(&template.parts[1], TemplatePart::Hole(hole) if matches!(hole.node.as_ref(), Expression::Mult(_))));
+        assert_eq!(template.parts[2].decoded().as_deref(), Some("y"));
+
+        let (_, spaced) = TemplateString::parse_inner(Span::new_extra("`x${ a * 2 }y`", "test_input")).unwrap();
+        assert_eq!(Literal::Template(template.clone()), Literal::Template(spaced));
+        let (_, other) = TemplateString::parse_inner(Span::new_extra("`x${a * 3}y`", "test_input")).unwrap();
+        assert_ne!(template, other);
+        // Holes compare by their structure, not by their text
+        let (_, commented) = TemplateString::parse_inner(Span::new_extra("`x${a/* c */*2}y`", "test_input")).unwrap();
+        assert_eq!(template, commented);
+        let (_, parenthesized) = TemplateString::parse_inner(Span::new_extra("`x${(a * 2)}y`", "test_input")).unwrap();
+        assert_ne!(template, parenthesized);
+    }
+}
@@ -8,8 +8,23 @@ pub trait ParseInto: Sized where <Self as ParseInto>::Output: From<Self> {
     type Output;
 
     fn parse<'a>(input: Span<'a>) -> IResult<Span<'a>, MetaNode<Self::Output>> {
+        meta(Self::parse_inner)(input)
+    }
+
+    fn parse_inner<'a>(input: Span<'a>) -> IResult<Span<'a>, Self::Output>;
+}
+
+/// Wraps any parser so that its output is surrounded by the comments and whitespace around it,
+/// the same way `ParseInto::parse` does for `parse_inner`.
+pub fn meta<'a, O, F>(mut parser: F) -> impl FnMut(Span<'a>) -> IResult<Span<'a>, MetaNode<O>>
+where
+    F: FnMut(Span<'a>) -> IResult<Span<'a>, O>,
+{
+    move |input: Span<'a>| {
         let (input, pre_comments) = comment::parse_comment_or_whitespace(input)?;
-        let (input, node) = Self::parse_inner(input)?;
+        let start = Position::from(&input);
+        let (input, node) = parser(input)?;
+        let end = Position::from(&input);
         let (input, post_comments) = comment::parse_comment_or_whitespace(input)?;
         Ok((
             input,
@@ -17,11 +32,40 @@ pub trait ParseInto: Sized where <Self as ParseInto>::Output: From<Self> {
                 node: Box::new(node),
                 pre_comments,
                 post_comments,
+                location: Some(Location { start, end }),
             },
         ))
     }
+}
 
-    fn parse_inner<'a>(input: Span<'a>) -> IResult<Span<'a>, Self::Output>;
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct Position {
+    pub offset: usize,
+    pub line: u32,
+    pub column: u32,
+}
+
+impl From<&Span<'_>> for Position {
+    fn from(span: &Span<'_>) -> Self {
+        Position {
+            offset: span.location_offset(),
+            line: span.location_line(),
+            column: span.get_utf8_column() as u32,
+        }
+    }
+}
+
+impl Display for Position {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// Where a node was found in the source, comments excluded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Location {
+    pub start: Position,
+    pub end: Position,
 }
 
 impl<T, U, V> ParseInto for (T, U) where 
@@ -46,6 +90,28 @@ pub struct MetaNode<Node>
     pub node: Box<Node>,
     #[builder(default)] pub pre_comments: Vec<CommentOrWhitespace>,
     #[builder(default)] pub post_comments: Vec<CommentOrWhitespace>,
+    #[builder(default)] pub location: Option<Location>,
+}
+
+impl<Node> MetaNode<Node> {
+    /// A node without comments or location, for code built by a pass rather than parsed.
+    pub fn new(node: Node) -> Self {
+        MetaNode {
+            node: Box::new(node),
+            pre_comments: Vec::new(),
+            post_comments: Vec::new(),
+            location: None,
+        }
+    }
+
+    /// Same as `new`, with a space on each side.
+    pub fn spaced(node: Node) -> Self {
+        MetaNode {
+            pre_comments: vec![CommentOrWhitespace::Whitespace(" ".to_string())],
+            post_comments: vec![CommentOrWhitespace::Whitespace(" ".to_string())],
+            ..MetaNode::new(node)
+        }
+    }
 }
 
 impl<Node> PartialEq for MetaNode<Node> where Node: PartialEq {
@@ -107,7 +173,8 @@ impl Display for CommentOrWhitespace {
     }
 }
 
-mod comment;
+pub(crate) mod comment;
+pub(crate) mod scan;
 
 pub mod literals;
 pub mod expressions;
@@ -115,6 +182,9 @@ pub mod statements;
 pub mod file;
 pub mod types;
 pub mod class;
+pub mod visit;
+pub mod visit_mut;
+pub mod trivia;
 
 pub use literals::*;
 pub use expressions::*;
@@ -0,0 +1,99 @@
+//! Finds the comments of a printed source. They are trivia spread all over the tree, so the text
+//! is scanned instead, skipping over the strings.
+
+use crate::ast::{comment::parse_comment_or_whitespace, *};
+
+/// The comments and whitespace of `source`, by offset.
+pub(crate) fn trivia(source: &str) -> Vec<(usize, CommentOrWhitespace)> {
+    let mut trivia = Vec::new();
+    let mut offset = 0;
+    while let Some(c) = source[offset..].chars().next() {
+        let rest = &source[offset..];
+        if c.is_whitespace() || rest.starts_with("//") || rest.starts_with("/*") {
+            let (after, items) = parse_comment_or_whitespace(Span::new_extra(rest, "")).expect("trivia never fails");
+            let mut start = offset;
+            for item in items {
+                let length = item.to_string().len();
+                trivia.push((start, item));
+                start += length;
+            }
+            // An unterminated `/*` is not a comment
+            offset += after.location_offset().max(c.len_utf8());
+            continue;
+        }
+        offset += match c {
+            '\'' | '"' | '`' => string_length(rest, c),
+            c => c.len_utf8(),
+        };
+    }
+    trivia
+}
+
+/// Where the code of `text` ends, before the comments and whitespace following it.
+pub(crate) fn code_end(text: &str) -> usize {
+    let mut end = text.len();
+    for (offset, item) in trivia(text).into_iter().rev() {
+        if offset + item.to_string().len() != end {
+            break;
+        }
+        end = offset;
+    }
+    end
+}
+
+/// Where the code of `text` starts, after the comments and whitespace preceding it.
+pub(crate) fn code_start(text: &str) -> usize {
+    let mut start = 0;
+    for (offset, item) in trivia(text) {
+        if offset != start {
+            break;
+        }
+        start += item.to_string().len();
+    }
+    start
+}
+
+/// The position reached after `text`, when it starts at `start`.
+pub(crate) fn advance(start: Position, text: &str) -> Position {
+    let lines = text.matches('\n').count() as u32;
+    let column = match text.rfind('\n') {
+        Some(newline) => text[newline + 1..].chars().count() as u32 + 1,
+        None => start.column + text.chars().count() as u32,
+    };
+    Position { offset: start.offset + text.len(), line: start.line + lines, column }
+}
+
+/// The length of the string starting `source`, up to its closing quote.
+pub(crate) fn string_length(source: &str, quote: char) -> usize {
+    let mut chars = source.char_indices().skip(1);
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            c if c == quote => return index + c.len_utf8(),
+            _ => {}
+        }
+    }
+    source.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trivia() {
+        let comments: Vec<_> = trivia("a = '// no' + b; // yes\n/* also */ `/* no */`")
+            .into_iter()
+            .filter(|(_, item)| !matches!(item, CommentOrWhitespace::Whitespace(_)))
+            .collect();
+        assert_eq!(comments, [
+            (17, CommentOrWhitespace::SingleLineComment("// yes".to_string())),
+            (24, CommentOrWhitespace::MultiLineComment("/* also */".to_string())),
+        ]);
+        assert_eq!(code_end("return a // b\n  "), 8);
+        assert_eq!(code_end("f('x') "), 6);
+        assert_eq!(code_start(" /* a */\n, b"), 9);
+    }
+}
@@ -2,7 +2,7 @@ use nom::{sequence::{preceded, tuple}, combinator::map, IResult};
 use derive_more::Display;
 use crate::{ast::*, utils::kw};
 
-#[derive(Debug, Clone, Display)]
+#[derive(Debug, Clone, Display, PartialEq)]
 #[display(fmt = "do{}while{}{}", statement, condition, semi)]
 pub struct DoWhileStatement {
     pub statement: MetaNode<Statement>,
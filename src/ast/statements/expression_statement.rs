@@ -2,7 +2,7 @@ use nom::{combinator::map, sequence::pair, IResult};
 use derive_more::Display;
 use crate::ast::*;
 
-#[derive(Debug, Clone, Display)]
+#[derive(Debug, Clone, Display, PartialEq)]
 #[display(fmt = "{}{}", expression, semi)]
 pub struct ExpressionStatement {
     pub expression: MetaNode<Expression>,
@@ -2,7 +2,7 @@ use crate::{ast::*, utils::kw};
 use nom::{branch::alt, bytes::complete::tag, combinator::{map, opt}, sequence::{delimited, preceded, terminated, tuple}, IResult};
 use derive_more::Display;
 
-#[derive(Debug, Clone, Display)]
+#[derive(Debug, Clone, Display, PartialEq)]
 #[display(fmt = "for{}{}", for_header, block)]
 pub struct ForStatement {
     pub for_header: MetaNode<ForHeader>,
@@ -28,7 +28,7 @@ impl ParseInto for ForStatement {
     }
 }
 
-#[derive(Debug, Clone, Display)]
+#[derive(Debug, Clone, Display, PartialEq)]
 #[display(fmt = "({})")]
 pub enum ForHeader {
     ForIter(ForIter),
@@ -50,10 +50,10 @@ impl ParseInto for ForHeader {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum VarDecOrExpr {
     VarDeclaration(VarDeclaration),
-    Expression(Expression),
+    Expression(Box<Expression>),
 }
 
 impl std::fmt::Display for VarDecOrExpr {
@@ -71,12 +71,12 @@ impl ParseInto for VarDecOrExpr {
     fn parse_inner<'a>(input: Span<'a>) -> IResult<Span<'a>, Self::Output> {
         alt((
             map(VarDeclaration::parse_inner, VarDecOrExpr::VarDeclaration),
-            map(Expression::parse_inner, VarDecOrExpr::Expression),
+            map(Expression::parse_inner, |e| VarDecOrExpr::Expression(Box::new(e))),
         ))(input)
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct ForIter {
     pub init: Option<MetaNode<VarDecOrExpr>>,
     pub condition: Option<MetaNode<Expression>>,
@@ -120,7 +120,7 @@ impl ParseInto for ForIter {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct ForIn {
     pub key: Option<MetaNode<VarDeclaration>>,
     pub var: MetaNode<VarDeclaration>,
@@ -165,6 +165,8 @@ mod tests {
     #[test]
     fn test_for_in_statement() {
         test_remains_same::<ForStatement, _>("for(var a in b) {}", "for(var a in b) {}");
+        test_remains_same::<ForStatement, _>("for(const a in b) {}", "for(const a in b) {}");
+        test_remains_same::<ForStatement, _>("for(let k : const v in b) {}", "for(let k : const v in b) {}");
         test_remains_same::<ForStatement, _>("for(var a: var b in c) { var b = 2; }", "for(var a: var b in c) { var b = 2; }");
     }
 
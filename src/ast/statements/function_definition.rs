@@ -1,9 +1,9 @@
 use nom::{
     branch::alt,
     bytes::complete::tag,
-    combinator::{map, opt},
+    combinator::{map, opt, peek},
     multi::{many1, separated_list0},
-    sequence::{delimited, pair},
+    sequence::{delimited, pair, preceded, terminated, tuple},
     IResult,
 };
 
@@ -12,7 +12,7 @@ use crate::{ast::*, utils::kw};
 
 use super::MetaNode;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum BlockStatement {
     Empty(MetaNode<Empty>),
     StatementList(Vec<MetaNode<Statement>>),
@@ -48,7 +48,7 @@ impl ParseInto for BlockStatement {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Parameters {
     pub params: Vec<MetaNode<Parameter>>,
 }
@@ -72,39 +72,79 @@ impl ParseInto for Parameters {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Parameter {
     pub type_: Option<MetaNode<Type>>,
+    pub reference: Option<MetaNode<Reference>>,
     pub name: MetaNode<Identifier>,
+    pub default_value: Option<MetaNode<Expression>>,
 }
 impl std::fmt::Display for Parameter {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         if let Some(type_) = &self.type_ {
             write!(f, "{}", type_)?;
         }
-        write!(f, "{}", self.name)
+        if let Some(reference) = &self.reference {
+            write!(f, "{}", reference)?;
+        }
+        write!(f, "{}", self.name)?;
+        if let Some(default_value) = &self.default_value {
+            write!(f, "={}", default_value)?;
+        }
+        Ok(())
     }
 }
 impl ParseInto for Parameter {
     type Output = Self;
 
     fn parse_inner<'a>(input: Span<'a>) -> IResult<Span<'a>, Self::Output> {
-        use nom::sequence::pair;
-
-        alt((
-            map(pair(Type::parse, Identifier::parse), |(type_, name)| Parameter {
-                type_: Some(type_),
-                name,
-            }),
-            map(Identifier::parse, |name| Parameter {
-                type_: None,
+        map(
+            pair(
+                Parameter::parse_without_default,
+                opt(preceded(tag("="), Expression::parse)),
+            ),
+            |(parameter, default_value)| Parameter {
+                default_value,
+                ..parameter
+            },
+        )(input)
+    }
+}
+impl Parameter {
+    /// Parses `[Type] [@]name`, as used by single parameter arrow functions where a default value is not allowed.
+    pub fn parse_without_default(input: Span) -> IResult<Span, Self> {
+        map(
+            tuple((
+                opt(terminated(Type::parse, peek(alt((
+                    map(Reference::parse_inner, |_| ()),
+                    map(Identifier::parse_inner, |_| ()),
+                ))))),
+                opt(Reference::parse),
+                Identifier::parse,
+            )),
+            |(type_, reference, name)| Parameter {
+                type_,
+                reference,
                 name,
-            }),
-        ))(input)
+                default_value: None,
+            },
+        )(input)
+    }
+}
+
+/// The LeekScript 1 `@` marker of a parameter passed by reference.
+#[derive(Debug, Clone, Display, PartialEq)]
+#[display(fmt = "@")]
+pub struct Reference;
+impl ParseInto for Reference {
+    type Output = Self;
+
+    fn parse_inner<'a>(input: Span<'a>) -> IResult<Span<'a>, Self::Output> {
+        map(tag("@"), |_| Reference)(input)
     }
 }
 
-#[derive(Debug, Clone, Display)]
+#[derive(Debug, Clone, Display, PartialEq)]
 pub struct Arrow(String);
 impl ParseInto for Arrow {
     type Output = Self;
@@ -114,7 +154,7 @@ impl ParseInto for Arrow {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct FunctionDefinition {
     pub name: MetaNode<Identifier>,
     pub args: MetaNode<Parameters>,
@@ -139,7 +179,6 @@ impl ParseInto for FunctionDefinition {
     type Output = Self;
 
     fn parse_inner<'a>(input: Span<'a>) -> IResult<Span<'a>, Self::Output> {
-        use nom::sequence::tuple;
         map(
             tuple((
                 kw("function"),
@@ -192,6 +231,20 @@ mod tests {
         test_remains_same::<Parameter, _>("String a", "String a");
     }
 
+    #[test]
+    fn test_params_with_default() {
+        test_remains_same::<Parameter, _>("a = 3", "a = 3");
+        test_remains_same::<Parameter, _>("integer x = 3", "integer x = 3");
+        test_remains_same::<Parameters, _>("(a, b = [1, 2], c = b)", "(a, b = [1, 2], c = b)");
+    }
+
+    #[test]
+    fn test_params_by_reference() {
+        test_remains_same::<Parameter, _>("@a", "@a");
+        test_remains_same::<Parameter, _>("Array @a", "Array @a");
+        test_remains_same::<Parameters, _>("(@a, b)", "(@a, b)");
+    }
+
     #[test]
     fn test_arrow() {
         test_remains_same::<Arrow, _>("->", "->");
@@ -202,5 +255,6 @@ mod tests {
     fn test_function() {
         test_remains_same::<FunctionDefinition, _>("function a(){}", "function a(){}");
         test_remains_same::<FunctionDefinition, _>("function a() -> String {}", "function a() -> String {}");
+        test_remains_same::<FunctionDefinition, _>("function f(integer x = 3, @y) {}", "function f(integer x = 3, @y) {}");
     }
 }
\ No newline at end of file
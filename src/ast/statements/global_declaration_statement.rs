@@ -4,8 +4,9 @@ use crate::{ast::*, utils::kw};
 
 use super::MetaNode;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct GlobalDefinition {
+    pub const_: Option<MetaNode<Const>>,
     pub type_: Option<MetaNode<Type>>,
     pub values: Vec<(MetaNode<Identifier>, Option<MetaNode<Expression>>)>,
     pub semi: MetaNode<Semi>,
@@ -14,6 +15,9 @@ pub struct GlobalDefinition {
 impl Display for GlobalDefinition {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "global")?;
+        if let Some(const_) = &self.const_ {
+            write!(f, "{}", const_)?;
+        }
         if let Some(type_) = &self.type_ {
             write!(f, "{}", type_)?;
         }
@@ -41,12 +45,14 @@ impl ParseInto for GlobalDefinition {
         map(
             tuple((
                 kw("global"),
+                opt(Const::parse),
                 opt(terminated(Type::parse, peek(Identifier::parse))), // Lookahead to see if we have a type
                 separated_list1(tag(","), pair(Identifier::parse,
                     opt(preceded(tag("="), Expression::parse)))),
                 Semi::parse,
             )),
-            |(_, type_, values, semi)| GlobalDefinition {
+            |(_, const_, type_, values, semi)| GlobalDefinition {
+                const_,
                 type_,
                 values,
                 semi,
@@ -75,6 +81,12 @@ mod tests {
         test_remains_same::<GlobalDefinition, _>("global Number a=1;", "global Number a=1;");
     }
 
+    #[test]
+    fn test_global_const() {
+        test_remains_same::<GlobalDefinition, _>("global const a = 1;", "global const a = 1;");
+        test_remains_same::<GlobalDefinition, _>("global const Number a = 1, b = 2;", "global const Number a = 1, b = 2;");
+    }
+
     #[test]
     fn test_multiple_var_declaration() {
         test_remains_same::<GlobalDefinition, _>("global a,b;", "global a,b;");
@@ -2,7 +2,7 @@ use nom::{bytes::complete::tag, combinator::{map, opt}, sequence::{delimited, pr
 use derive_more::{From, Display};
 use crate::{ast::*, utils::kw};
 
-#[derive(Debug, Clone, Display, From)]
+#[derive(Debug, Clone, Display, From, PartialEq)]
 #[display(fmt = "({})", expression)]
 pub struct ParenthesizedExpression {
     pub expression: MetaNode<Expression>,
@@ -23,7 +23,7 @@ impl ParseInto for ParenthesizedExpression {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct IfStatement {
     pub condition: MetaNode<ParenthesizedExpression>,
     pub block: MetaNode<Statement>,
@@ -2,7 +2,7 @@ use nom::{combinator::map, sequence::{delimited, tuple}, IResult};
 use derive_more::Display;
 use crate::{ast::*, utils::kw};
 
-#[derive(Debug, Clone, Display)]
+#[derive(Debug, Clone, Display, PartialEq)]
 #[display(fmt = "{}({}){}", include_kw, path, semi)]
 pub struct IncludeStatement {
     pub include_kw: MetaNode<IncludeKw>,
@@ -31,7 +31,7 @@ impl ParseInto for IncludeStatement {
     }
 }
 
-#[derive(Debug, Clone, Display)]
+#[derive(Debug, Clone, Display, PartialEq)]
 #[display(fmt = "include")]
 pub struct IncludeKw;
 
@@ -2,8 +2,8 @@ use nom::{bytes::complete::tag, combinator::{opt, map}, IResult};
 
 use crate::ast::*;
 
-#[derive(Debug, Clone)]
-pub struct Semi(bool);
+#[derive(Debug, Clone, PartialEq)]
+pub struct Semi(pub bool);
 
 impl std::fmt::Display for Semi {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -39,6 +39,8 @@ pub mod do_while_statement;
 pub mod while_statement;
 pub mod for_statement;
 pub mod return_statement;
+pub mod try_statement;
+pub mod throw_statement;
 
 pub mod statement;
 
@@ -52,4 +54,6 @@ pub use if_statement::*;
 pub use do_while_statement::*;
 pub use while_statement::*;
 pub use for_statement::*;
-pub use return_statement::*;
\ No newline at end of file
+pub use return_statement::*;
+pub use try_statement::*;
+pub use throw_statement::*;
\ No newline at end of file
@@ -2,7 +2,7 @@ use nom::{combinator::{map, opt}, sequence::tuple, IResult};
 use derive_more::Display;
 use crate::{ast::*, utils::kw};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct ReturnStatement {
     pub expression: Option<MetaNode<Expression>>,
     pub semi: MetaNode<Semi>,
@@ -36,7 +36,7 @@ impl ParseInto for ReturnStatement {
     }
 }
 
-#[derive(Debug, Clone, Display)]
+#[derive(Debug, Clone, Display, PartialEq)]
 pub enum BreakStatement {
     #[display(fmt = "break{}", _0)]
     Break(MetaNode<Semi>),
@@ -3,7 +3,7 @@ use derive_more::Display;
 
 use crate::ast::*;
 
-#[derive(Debug, Clone, Display)]
+#[derive(Debug, Clone, Display, PartialEq)]
 pub enum Statement {
     ExpressionStatement(ExpressionStatement),
     VarDeclaration(VarDeclarationStatement),
@@ -14,6 +14,8 @@ pub enum Statement {
     ForStatement(ForStatement),
     ReturnStatement(ReturnStatement),
     BreakStatement(BreakStatement),
+    TryStatement(TryStatement),
+    ThrowStatement(ThrowStatement),
     Empty(Semi),
 }
 
@@ -30,13 +32,15 @@ impl ParseInto for Statement {
             map(ForStatement::parse_inner, Statement::ForStatement),
             map(ReturnStatement::parse_inner, Statement::ReturnStatement),
             map(BreakStatement::parse_inner, Statement::BreakStatement),
+            map(TryStatement::parse_inner, Statement::TryStatement),
+            map(ThrowStatement::parse_inner, Statement::ThrowStatement),
             map(ExpressionStatement::parse_inner, Statement::ExpressionStatement),
             map(Semi::must_parse, Statement::Empty),
         ))(input)
     }
 }
 
-#[derive(Debug, Clone, Display)]
+#[derive(Debug, Clone, Display, PartialEq)]
 pub enum GlobalStatement {
     IncludeStatement(IncludeStatement),
     Statement(Statement), // Normal statements
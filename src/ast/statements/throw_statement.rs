@@ -0,0 +1,61 @@
+use nom::{branch::alt, combinator::{map, value}, sequence::tuple, IResult};
+use derive_more::Display;
+use crate::{ast::*, utils::kw};
+
+#[derive(Debug, Clone, Copy, Display, PartialEq)]
+pub enum ThrowKw {
+    #[display(fmt = "throw")] Throw,
+    #[display(fmt = "raise")] Raise,
+}
+
+impl ParseInto for ThrowKw {
+    type Output = Self;
+
+    fn parse_inner<'a>(input: Span<'a>) -> IResult<Span<'a>, Self::Output> {
+        alt((
+            value(ThrowKw::Throw, kw("throw")),
+            value(ThrowKw::Raise, kw("raise")),
+        ))(input)
+    }
+}
+
+#[derive(Debug, Clone, Display, PartialEq)]
+#[display(fmt = "{}{}{}", keyword, expression, semi)]
+pub struct ThrowStatement {
+    pub keyword: ThrowKw,
+    pub expression: MetaNode<Expression>,
+    pub semi: MetaNode<Semi>,
+}
+
+impl ParseInto for ThrowStatement {
+    type Output = Self;
+
+    fn parse_inner<'a>(input: Span<'a>) -> IResult<Span<'a>, Self::Output> {
+        map(
+            tuple((
+                ThrowKw::parse_inner,
+                Expression::parse,
+                Semi::parse,
+            )),
+            |(keyword, expression, semi)| ThrowStatement {
+                keyword,
+                expression,
+                semi,
+            },
+        )(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::test_remains_same;
+
+    use super::*;
+
+    #[test]
+    fn test_throw() {
+        test_remains_same::<ThrowStatement, _>("throw e;", "throw e;");
+        test_remains_same::<ThrowStatement, _>("throw new Error('a')", "throw new Error('a')");
+        test_remains_same::<ThrowStatement, _>("raise 'error';", "raise 'error';");
+    }
+}
@@ -0,0 +1,163 @@
+use nom::{branch::alt, bytes::complete::tag, combinator::{map, opt, peek, value, verify}, multi::many0, sequence::{delimited, pair, preceded, terminated, tuple}, IResult};
+use derive_more::Display;
+use crate::{ast::*, utils::kw};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TryStatement {
+    pub block: MetaNode<BlockStatement>,
+    pub catches: Vec<MetaNode<CatchClause>>,
+    pub finally: Option<MetaNode<BlockStatement>>,
+}
+
+impl std::fmt::Display for TryStatement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "try{}", self.block)?;
+        for catch in &self.catches {
+            write!(f, "{}", catch)?;
+        }
+        if let Some(finally) = &self.finally {
+            write!(f, "finally{}", finally)?;
+        }
+        Ok(())
+    }
+}
+
+impl ParseInto for TryStatement {
+    type Output = Self;
+
+    fn parse_inner<'a>(input: Span<'a>) -> IResult<Span<'a>, Self::Output> {
+        // A try block must be followed by at least one catch or a finally
+        map(
+            verify(
+                tuple((
+                    preceded(kw("try"), BlockStatement::parse),
+                    many0(CatchClause::parse),
+                    opt(preceded(kw("finally"), BlockStatement::parse)),
+                )),
+                |(_, catches, finally)| !catches.is_empty() || finally.is_some(),
+            ),
+            |(block, catches, finally)| TryStatement {
+                block,
+                catches,
+                finally,
+            },
+        )(input)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Display, PartialEq)]
+pub enum CatchKw {
+    #[display(fmt = "catch")] Catch,
+    #[display(fmt = "except")] Except,
+}
+
+impl ParseInto for CatchKw {
+    type Output = Self;
+
+    fn parse_inner<'a>(input: Span<'a>) -> IResult<Span<'a>, Self::Output> {
+        alt((
+            value(CatchKw::Catch, kw("catch")),
+            value(CatchKw::Except, kw("except")),
+        ))(input)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CatchClause {
+    pub keyword: CatchKw,
+    pub binding: Option<MetaNode<CatchBinding>>,
+    pub block: MetaNode<BlockStatement>,
+}
+
+impl std::fmt::Display for CatchClause {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.keyword)?;
+        if let Some(binding) = &self.binding {
+            write!(f, "{}", binding)?;
+        }
+        write!(f, "{}", self.block)
+    }
+}
+
+impl ParseInto for CatchClause {
+    type Output = Self;
+
+    fn parse_inner<'a>(input: Span<'a>) -> IResult<Span<'a>, Self::Output> {
+        map(
+            tuple((
+                CatchKw::parse_inner,
+                opt(CatchBinding::parse),
+                BlockStatement::parse,
+            )),
+            |(keyword, binding, block)| CatchClause {
+                keyword,
+                binding,
+                block,
+            },
+        )(input)
+    }
+}
+
+/// The `(Type name)` of a catch clause, the type being optional.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CatchBinding {
+    pub type_: Option<MetaNode<Type>>,
+    pub name: MetaNode<Identifier>,
+}
+
+impl std::fmt::Display for CatchBinding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "(")?;
+        if let Some(type_) = &self.type_ {
+            write!(f, "{}", type_)?;
+        }
+        write!(f, "{})", self.name)
+    }
+}
+
+impl ParseInto for CatchBinding {
+    type Output = Self;
+
+    fn parse_inner<'a>(input: Span<'a>) -> IResult<Span<'a>, Self::Output> {
+        map(
+            delimited(
+                tag("("),
+                pair(
+                    opt(terminated(Type::parse, peek(Identifier::parse))),
+                    Identifier::parse,
+                ),
+                tag(")"),
+            ),
+            |(type_, name)| CatchBinding { type_, name },
+        )(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::test_remains_same;
+
+    use super::*;
+
+    #[test]
+    fn test_try_catch() {
+        test_remains_same::<TryStatement, _>("try {} catch {}", "try {} catch {}");
+        test_remains_same::<TryStatement, _>("try { a(); } catch (e) { b(e); }", "try { a(); } catch (e) { b(e); }");
+        test_remains_same::<TryStatement, _>("try {} catch (Error e) {}", "try {} catch (Error e) {}");
+        test_remains_same::<TryStatement, _>("try {} except (e) {}", "try {} except (e) {}");
+    }
+
+    #[test]
+    fn test_try_finally() {
+        test_remains_same::<TryStatement, _>("try {} finally {}", "try {} finally {}");
+        test_remains_same::<TryStatement, _>(
+            "try {} catch (A a) {} catch (B b) {} finally { c(); }",
+            "try {} catch (A a) {} catch (B b) {} finally { c(); }",
+        );
+    }
+
+    #[test]
+    fn test_try_alone() {
+        assert!(TryStatement::parse(Span::new_extra("try {}", "test_input")).is_err());
+    }
+}
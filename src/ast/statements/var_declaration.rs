@@ -9,34 +9,41 @@ use nom::{
     IResult,
 };
 
-#[derive(Debug, Clone, From)]
-pub struct VarType(Option<MetaNode<Type>>);
+#[derive(Debug, Clone, Display, PartialEq)]
+pub enum VarType {
+    Typed(MetaNode<Type>),
+    #[display(fmt = "var")] Var,
+    #[display(fmt = "let")] Let,
+    #[display(fmt = "const")] Const,
+}
 
-impl Display for VarType {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        if let Some(type_) = &self.0 {
-            write!(f, "{}", type_)
-        } else {
-            write!(f, "var")
+impl VarType {
+    pub fn type_(&self) -> Option<&MetaNode<Type>> {
+        match self {
+            VarType::Typed(type_) => Some(type_),
+            _ => None,
         }
     }
+
+    pub fn is_constant(&self) -> bool {
+        matches!(self, VarType::Const)
+    }
 }
 
 impl ParseInto for VarType {
     type Output = Self;
 
     fn parse_inner<'a>(input: Span<'a>) -> IResult<Span<'a>, Self::Output> {
-        map(
-            alt((
-                map(terminated(Type::parse, peek(Identifier::parse)), Some),
-                map(kw("var"), |_| None),
-            )),
-            VarType::from,
-        )(input)
+        alt((
+            map(terminated(Type::parse, peek(Identifier::parse)), VarType::Typed),
+            map(kw("var"), |_| VarType::Var),
+            map(kw("let"), |_| VarType::Let),
+            map(kw("const"), |_| VarType::Const),
+        ))(input)
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct VarDeclaration {
     pub type_: MetaNode<VarType>,
     pub values: Vec<(MetaNode<Identifier>, Option<MetaNode<Expression>>)>,
@@ -80,7 +87,7 @@ impl ParseInto for VarDeclaration {
     }
 }
 
-#[derive(Debug, Clone, Display, From)]
+#[derive(Debug, Clone, Display, From, PartialEq)]
 #[display(fmt = "{}{}", var_declaration, semi)]
 pub struct VarDeclarationStatement {
     pub var_declaration: MetaNode<VarDeclaration>,
@@ -112,6 +119,14 @@ mod tests {
         test_remains_same::<VarDeclarationStatement, _>("Number a=1;", "Number a=1;");
     }
 
+    #[test]
+    fn test_let_const_declaration() {
+        test_remains_same::<VarDeclarationStatement, _>("let a;", "let a;");
+        test_remains_same::<VarDeclarationStatement, _>("let a = 1, b;", "let a = 1, b;");
+        test_remains_same::<VarDeclarationStatement, _>("const a = 1;", "const a = 1;");
+        test_remains_same::<VarDeclarationStatement, _>("const a = 1, b = [2];", "const a = 1, b = [2];");
+    }
+
     #[test]
     fn test_multiple_var_declaration() {
         test_remains_same::<VarDeclarationStatement, _>("var a,b;", "var a,b;");
@@ -2,7 +2,7 @@ use nom::{sequence::{preceded, tuple}, combinator::map, IResult};
 use crate::{ast::*, utils::kw};
 use derive_more::Display;
 
-#[derive(Debug, Clone, Display)]
+#[derive(Debug, Clone, Display, PartialEq)]
 #[display(fmt = "while{}{}", condition, block)]
 pub struct WhileStatement {
     pub condition: MetaNode<Expression>,
@@ -0,0 +1,448 @@
+//! Where the comments between two statements or class members belong. The parser leaves them
+//! to the last node before them, wherever it is nested; `attach_trivia` then gives the node
+//! before only what is on its last line, and moves the following lines to the node after.
+
+use nom::Slice;
+
+use crate::ast::{comment::parse_comment_or_whitespace, scan::code_end, visit_mut::*, *};
+
+/// Moves the comment lines between two statements of a list, or two members of a class, to the
+/// second one. The comment lines after the last statement of the file go before its end.
+pub(crate) fn attach_trivia(file: &mut File, source: Span) {
+    let mut attacher = Attacher { source };
+    attacher.attach(&mut file.statements, Some(&mut file.eof.pre_comments));
+    walk_file(&mut attacher, file);
+}
+
+struct Attacher<'a> {
+    /// The whole source of the file
+    source: Span<'a>,
+}
+
+impl Attacher<'_> {
+    fn attach<T: Trailing>(&self, nodes: &mut [MetaNode<T>], mut after: Option<&mut Vec<CommentOrWhitespace>>) {
+        for index in 0..nodes.len() {
+            let last = index + 1 == nodes.len();
+            // The closing brace of blocks and classes has no trivia of its own
+            if last && after.is_none() {
+                break;
+            }
+            if let Some(rest) = self.detach(&mut nodes[index]) {
+                let next = match last {
+                    true => after.as_deref_mut().unwrap(),
+                    false => &mut nodes[index + 1].pre_comments,
+                };
+                next.splice(0..0, rest);
+            }
+        }
+    }
+
+    /// Cuts the trivia ending `node` after the first line break following its code, when
+    /// comments come after it, and returns what was cut.
+    fn detach<T: Trailing>(&self, node: &mut MetaNode<T>) -> Option<Vec<CommentOrWhitespace>> {
+        let location = node.location?;
+        let base = self.source.location_offset();
+        let start = location.start.offset - base;
+        let post: usize = node.post_comments.iter().map(|trivia| trivia.to_string().len()).sum();
+        let end = location.end.offset - base + post;
+        let code = start + code_end(&self.source.fragment()[start..end]);
+
+        let (_, trailing) = parse_comment_or_whitespace(self.source.slice(code..end)).ok()?;
+        let mut split = code;
+        for trivia in trailing {
+            let text = trivia.to_string();
+            match (&trivia, text.find('\n')) {
+                (CommentOrWhitespace::Whitespace(_), Some(newline)) => {
+                    split += newline + 1;
+                    break;
+                }
+                _ => split += text.len(),
+            }
+        }
+        let (_, rest) = parse_comment_or_whitespace(self.source.slice(split..end)).ok()?;
+        if !rest.iter().any(|trivia| !matches!(trivia, CommentOrWhitespace::Whitespace(_))) {
+            return None;
+        }
+
+        // All of it went to the innermost node the code ends with
+        let trailing = node.trailing(None)?;
+        if trailing.iter().map(ToString::to_string).collect::<String>() != self.source.fragment()[code..end] {
+            return None;
+        }
+        let trailing = node.trailing(Some(Position::from(&self.source.slice(split..))))?;
+        let mut kept = 0;
+        let mut index = 0;
+        while kept < split - code {
+            kept += trailing[index].to_string().len();
+            index += 1;
+        }
+        let mut rest = trailing.split_off(index);
+        // The line break ends a whitespace that goes on with the next line
+        if kept > split - code {
+            let Some(CommentOrWhitespace::Whitespace(text)) = trailing.last_mut() else { unreachable!() };
+            let next_line = text.split_off(text.len() - (kept - (split - code)));
+            rest.insert(0, CommentOrWhitespace::Whitespace(next_line));
+        }
+        Some(rest)
+    }
+}
+
+impl VisitorMut for Attacher<'_> {
+    fn visit_block(&mut self, block: &mut BlockStatement) {
+        if let BlockStatement::StatementList(statements) = block {
+            self.attach(statements, None);
+        }
+        walk_block(self, block);
+    }
+
+    fn visit_class(&mut self, class: &mut Class) {
+        if let ClassBody::Members(members) = class.body.node.as_mut() {
+            self.attach(members, None);
+        }
+        walk_class(self, class);
+    }
+}
+
+/// Reaches the trivia after the code of a node, which the parser leaves to the innermost node
+/// it ends with. `end` shortens the locations on the way, that include the trivia.
+trait Trailing {
+    fn trailing(&mut self, _end: Option<Position>) -> Option<&mut Vec<CommentOrWhitespace>> {
+        None
+    }
+}
+
+impl<T: Trailing> Trailing for MetaNode<T> {
+    fn trailing(&mut self, end: Option<Position>) -> Option<&mut Vec<CommentOrWhitespace>> {
+        if !self.post_comments.is_empty() {
+            return Some(&mut self.post_comments);
+        }
+        if let (Some(end), Some(location)) = (end, &mut self.location) {
+            location.end = location.end.min(end);
+        }
+        self.node.trailing(end)
+    }
+}
+
+/// The trivia ending a node closed by an optional `;`, or by `before` without it.
+fn ending<'a>(
+    semi: &'a mut MetaNode<Semi>,
+    before: Option<&'a mut dyn Trailing>,
+    end: Option<Position>,
+) -> Option<&'a mut Vec<CommentOrWhitespace>> {
+    match (semi.node.0, before) {
+        (true, _) | (false, None) => match semi.post_comments.is_empty() && !semi.node.0 {
+            // Without `;`, what follows the code is parsed before it
+            true => Some(&mut semi.pre_comments).filter(|trivia| !trivia.is_empty()),
+            false => semi.trailing(end),
+        },
+        (false, Some(before)) => before.trailing(end),
+    }
+}
+
+/// The last value of a declaration, or its name when it has none.
+fn last_value(
+    values: &mut [(MetaNode<Identifier>, Option<MetaNode<Expression>>)],
+) -> Option<&mut dyn Trailing> {
+    match values.last_mut()? {
+        (_, Some(value)) => Some(value),
+        (name, None) => Some(name),
+    }
+}
+
+fn last_operand<'a, Op>(
+    left: &'a mut Expression,
+    right: &'a mut [(MetaNode<Op>, MetaNode<Expression>)],
+    end: Option<Position>,
+) -> Option<&'a mut Vec<CommentOrWhitespace>> {
+    match right.last_mut() {
+        Some((_, right)) => right.trailing(end),
+        None => left.trailing(end),
+    }
+}
+
+impl Trailing for GlobalStatement {
+    fn trailing(&mut self, end: Option<Position>) -> Option<&mut Vec<CommentOrWhitespace>> {
+        match self {
+            GlobalStatement::IncludeStatement(include) => ending(&mut include.semi, None, end),
+            GlobalStatement::Statement(statement) => statement.trailing(end),
+            GlobalStatement::GlobalDefinition(definition) => {
+                ending(&mut definition.semi, last_value(&mut definition.values), end)
+            }
+            GlobalStatement::FunctionDefinition(function) => function.body.trailing(end),
+            GlobalStatement::ClassDefinition(class) => class.body.trailing(end),
+        }
+    }
+}
+
+impl Trailing for Statement {
+    fn trailing(&mut self, end: Option<Position>) -> Option<&mut Vec<CommentOrWhitespace>> {
+        match self {
+            Statement::ExpressionStatement(statement) => {
+                ending(&mut statement.semi, Some(&mut statement.expression), end)
+            }
+            Statement::VarDeclaration(statement) => {
+                ending(&mut statement.semi, Some(&mut statement.var_declaration), end)
+            }
+            Statement::IfStatement(if_statement) => match &mut if_statement.else_block {
+                Some(else_block) => else_block.trailing(end),
+                None => if_statement.block.trailing(end),
+            },
+            Statement::DoWhileStatement(do_while) => ending(&mut do_while.semi, None, end),
+            Statement::WhileStatement(while_statement) => while_statement.block.trailing(end),
+            Statement::ForStatement(for_statement) => for_statement.block.trailing(end),
+            Statement::ReturnStatement(statement) => ending(
+                &mut statement.semi,
+                statement.expression.as_mut().map(|expression| expression as &mut dyn Trailing),
+                end,
+            ),
+            Statement::BreakStatement(BreakStatement::Break(semi) | BreakStatement::Continue(semi)) => {
+                ending(semi, None, end)
+            }
+            Statement::TryStatement(try_statement) => match (&mut try_statement.finally, try_statement.catches.last_mut()) {
+                (Some(finally), _) => finally.trailing(end),
+                (None, Some(catch)) => catch.trailing(end),
+                (None, None) => try_statement.block.trailing(end),
+            },
+            Statement::ThrowStatement(statement) => ending(&mut statement.semi, Some(&mut statement.expression), end),
+            Statement::Block(_) | Statement::Empty(_) => None,
+        }
+    }
+}
+
+impl Trailing for ClassMember {
+    fn trailing(&mut self, end: Option<Position>) -> Option<&mut Vec<CommentOrWhitespace>> {
+        match self {
+            ClassMember::StaticBlock(block) => block.body.trailing(end),
+            ClassMember::Constructor(constructor) => constructor.body.trailing(end),
+            ClassMember::Method(method) => match &mut method.body {
+                MethodBody::Block(body) => body.trailing(end),
+                MethodBody::Declaration(semi) => ending(semi, None, end),
+            },
+            ClassMember::Field(field) => {
+                let before: &mut dyn Trailing = match &mut field.value {
+                    Some(value) => value,
+                    None => &mut field.name,
+                };
+                ending(&mut field.semi, Some(before), end)
+            }
+        }
+    }
+}
+
+impl Trailing for VarDeclaration {
+    fn trailing(&mut self, end: Option<Position>) -> Option<&mut Vec<CommentOrWhitespace>> {
+        last_value(&mut self.values)?.trailing(end)
+    }
+}
+
+impl Trailing for CatchClause {
+    fn trailing(&mut self, end: Option<Position>) -> Option<&mut Vec<CommentOrWhitespace>> {
+        self.block.trailing(end)
+    }
+}
+
+impl Trailing for Expression {
+    fn trailing(&mut self, end: Option<Position>) -> Option<&mut Vec<CommentOrWhitespace>> {
+        match self {
+            Expression::Assign(assign) => last_operand(&mut assign.left, &mut assign.right, end),
+            Expression::AnonyFunc(AnonymousFunction::Function(function)) => function.body.trailing(end),
+            Expression::AnonyFunc(AnonymousFunction::ArrowFunction(arrow)) => arrow.body.trailing(end),
+            Expression::TernaryExpression(ternary) => ternary.false_expr.trailing(end),
+            Expression::LogicalOr(e) => last_operand(&mut e.left, &mut e.right, end),
+            Expression::LogicalXor(e) => last_operand(&mut e.left, &mut e.right, end),
+            Expression::LogicalAnd(e) => last_operand(&mut e.left, &mut e.right, end),
+            Expression::Relation(e) => last_operand(&mut e.left, &mut e.right, end),
+            Expression::InstanceOf(e) => last_operand(&mut e.left, &mut e.right, end),
+            Expression::Shift(e) => last_operand(&mut e.left, &mut e.right, end),
+            Expression::BinOr(e) => last_operand(&mut e.left, &mut e.right, end),
+            Expression::BinXor(e) => last_operand(&mut e.left, &mut e.right, end),
+            Expression::BinAnd(e) => last_operand(&mut e.left, &mut e.right, end),
+            Expression::Add(e) => last_operand(&mut e.left, &mut e.right, end),
+            Expression::Mult(e) => last_operand(&mut e.left, &mut e.right, end),
+            Expression::TypeConversion(conversion) => conversion.type_.trailing(end),
+            Expression::PreUpdate(update) => update.expr.trailing(end),
+            Expression::Unary(unary) => unary.expr.trailing(end),
+            Expression::PostUpdate(update) => update.op.trailing(end),
+            Expression::Member(member) => match member.right.last_mut() {
+                Some(right) => right.trailing(end),
+                None => member.left.trailing(end),
+            },
+            Expression::Primary(primary) => primary.trailing(end),
+        }
+    }
+}
+
+impl Trailing for PrimaryExpr {
+    fn trailing(&mut self, end: Option<Position>) -> Option<&mut Vec<CommentOrWhitespace>> {
+        match self {
+            PrimaryExpr::IdentifierOrMember(identifier) => identifier.trailing(end),
+            _ => None,
+        }
+    }
+}
+
+impl Trailing for IdentifierOrMember {
+    fn trailing(&mut self, end: Option<Position>) -> Option<&mut Vec<CommentOrWhitespace>> {
+        match self {
+            IdentifierOrMember::Identifier(identifier) => identifier.trailing(end),
+            _ => None,
+        }
+    }
+}
+
+impl Trailing for MemberRight {
+    fn trailing(&mut self, end: Option<Position>) -> Option<&mut Vec<CommentOrWhitespace>> {
+        match self {
+            MemberRight::Dot(name) | MemberRight::OptionalDot(name) => name.trailing(end),
+            _ => None,
+        }
+    }
+}
+
+impl Trailing for FunctionBodyOrExpression {
+    fn trailing(&mut self, end: Option<Position>) -> Option<&mut Vec<CommentOrWhitespace>> {
+        match self {
+            FunctionBodyOrExpression::BlockStatement(block) => block.trailing(end),
+            FunctionBodyOrExpression::Expression(expression) => expression.trailing(end),
+        }
+    }
+}
+
+impl Trailing for Type {
+    fn trailing(&mut self, end: Option<Position>) -> Option<&mut Vec<CommentOrWhitespace>> {
+        match &mut self.alternative {
+            Some(alternative) => alternative.trailing(end),
+            None if self.generics.is_empty() && !self.nullable => self.type_.trailing(end),
+            None => None,
+        }
+    }
+}
+
+// Closed by a token of their own
+impl Trailing for BlockStatement {}
+impl Trailing for ClassBody {}
+impl Trailing for Identifier {}
+impl Trailing for UpdateOp {}
+impl Trailing for Semi {}
+
+/// The documentation written right before a node, in a `/** ... */` comment or a run of `///`
+/// lines, without the comment markers.
+fn doc_comment(trivia: &[CommentOrWhitespace]) -> Option<String> {
+    let mut lines = Vec::new();
+    for trivia in trivia.iter().rev() {
+        match trivia {
+            // A blank line separates the comment from the node
+            CommentOrWhitespace::Whitespace(text) if text.matches('\n').count() > 1 => break,
+            CommentOrWhitespace::Whitespace(_) => {}
+            CommentOrWhitespace::SingleLineComment(text) if text.starts_with("///") => {
+                let line = &text[3..];
+                lines.push(line.strip_prefix(' ').unwrap_or(line).trim_end().to_string());
+            }
+            CommentOrWhitespace::MultiLineComment(text) if lines.is_empty() && text.starts_with("/**") && text.len() > 4 => {
+                let content = &text[3..text.len() - 2];
+                let lines: Vec<_> = content.lines()
+                    .map(|line| {
+                        let line = line.trim();
+                        let line = line.strip_prefix('*').unwrap_or(line);
+                        line.strip_prefix(' ').unwrap_or(line).trim_end()
+                    })
+                    .collect();
+                let start = lines.iter().position(|line| !line.is_empty())?;
+                let end = lines.iter().rposition(|line| !line.is_empty())?;
+                return Some(lines[start..=end].join("\n"));
+            }
+            _ => break,
+        }
+    }
+    (!lines.is_empty()).then(|| lines.into_iter().rev().collect::<Vec<_>>().join("\n"))
+}
+
+impl MetaNode<GlobalStatement> {
+    /// The documentation of a function, class or global.
+    pub fn doc_comment(&self) -> Option<String> {
+        match self.node.as_ref() {
+            GlobalStatement::FunctionDefinition(_)
+            | GlobalStatement::ClassDefinition(_)
+            | GlobalStatement::GlobalDefinition(_) => doc_comment(&self.pre_comments),
+            _ => None,
+        }
+    }
+}
+
+impl MetaNode<ClassMember> {
+    /// The documentation of a field or method.
+    pub fn doc_comment(&self) -> Option<String> {
+        match self.node.as_ref() {
+            ClassMember::Field(_) | ClassMember::Method(_) => doc_comment(&self.pre_comments),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::parse_file;
+
+    use super::*;
+
+    fn text(trivia: &[CommentOrWhitespace]) -> String {
+        trivia.iter().map(|trivia| trivia.to_string()).collect()
+    }
+
+    #[test]
+    fn test_attach() {
+        let source = "var a = 1; // one\n\n// about f\nfunction f() {\n\tb() /* two */\n\t// three\n\treturn;\n\t// end\n}\n/* last */\n";
+        let file = parse_file(source);
+        assert_eq!(file.to_string(), source);
+
+        let [first, second] = &file.statements[..] else { panic!() };
+        assert_eq!(first.to_string(), "var a = 1; // one\n");
+        assert_eq!(text(&second.pre_comments), "\n// about f\n");
+        assert_eq!(first.location.unwrap().end.offset, 18);
+        assert_eq!(text(&file.eof.pre_comments), "/* last */\n");
+
+        let GlobalStatement::FunctionDefinition(function) = second.node.as_ref() else { panic!() };
+        let BlockStatement::StatementList(statements) = function.body.node.as_ref() else { panic!() };
+        assert_eq!(statements[0].to_string(), "\n\tb() /* two */\n");
+        assert_eq!(text(&statements[1].pre_comments), "\t// three\n\t");
+        // Nothing comes after the last statement of a block
+        assert_eq!(statements[1].to_string(), "\t// three\n\treturn;\n\t// end\n");
+        let end = statements[0].location.unwrap().end;
+        assert_eq!((end.offset, end.line, end.column), (60, 6, 1));
+    }
+
+    #[test]
+    fn test_attach_nested() {
+        // The comments are cut from the innermost node, and its parents end with the line
+        let source = "if (a) x = f().y // one\n// two\nz()\n// three\n/* four */ w = 1;";
+        let file = parse_file(source);
+        assert_eq!(file.to_string(), source);
+        let texts: Vec<_> = file.statements.iter().map(ToString::to_string).collect();
+        assert_eq!(texts, ["if (a) x = f().y // one\n", "// two\nz()\n", "// three\n/* four */ w = 1;"]);
+
+        let GlobalStatement::Statement(Statement::IfStatement(if_statement)) = file.statements[0].node.as_ref() else { panic!() };
+        assert_eq!(if_statement.block.location.unwrap().end.offset, 24);
+        assert_eq!(file.statements[0].location.unwrap().end.offset, 24);
+    }
+
+    #[test]
+    fn test_doc_comment() {
+        let source = "/**\n * Adds two numbers.\n *\n * @param a the first\n */\nfunction add(a, b) {}\n\
+            /// A point\n/// in space.\nclass Point {\n\t/** The x coordinate */\n\tx = 0;\n\n\t/// Moves it\n\n\
+            \tmove() {}\n\t// Not a doc\n\tstatic origin() {}\n}\n/** Not documented */\n\nfunction g() {}\n\
+            /** A constant */\nglobal G = 1;";
+        let file = parse_file(source);
+        let docs: Vec<_> = file.statements.iter().map(|statement| statement.doc_comment()).collect();
+        assert_eq!(docs, [
+            Some("Adds two numbers.\n\n@param a the first".to_string()),
+            Some("A point\nin space.".to_string()),
+            None,
+            Some("A constant".to_string()),
+        ]);
+
+        let GlobalStatement::ClassDefinition(class) = file.statements[1].node.as_ref() else { panic!() };
+        let ClassBody::Members(members) = class.body.node.as_ref() else { panic!() };
+        let docs: Vec<_> = members.iter().map(|member| member.doc_comment()).collect();
+        assert_eq!(docs, [Some("The x coordinate".to_string()), None, None]);
+    }
+}
@@ -0,0 +1,428 @@
+use crate::ast::*;
+
+/// Walks the AST in source order. Every `visit_*` method defaults to the matching `walk_*`
+/// function, so an implementation only overrides the nodes it cares about and calls the
+/// `walk_*` function itself when it still wants to go deeper.
+pub trait Visitor<'ast> {
+    fn visit_file(&mut self, file: &'ast File) {
+        walk_file(self, file)
+    }
+
+    fn visit_global_statement(&mut self, statement: &'ast MetaNode<GlobalStatement>) {
+        walk_global_statement(self, statement)
+    }
+
+    fn visit_include(&mut self, _include: &'ast IncludeStatement) {}
+
+    fn visit_global_definition(&mut self, definition: &'ast GlobalDefinition) {
+        walk_global_definition(self, definition)
+    }
+
+    fn visit_function_definition(&mut self, function: &'ast FunctionDefinition) {
+        walk_function_definition(self, function)
+    }
+
+    fn visit_class(&mut self, class: &'ast Class) {
+        walk_class(self, class)
+    }
+
+    fn visit_static_block(&mut self, block: &'ast StaticBlock) {
+        walk_static_block(self, block)
+    }
+
+    fn visit_constructor(&mut self, constructor: &'ast Constructor) {
+        walk_constructor(self, constructor)
+    }
+
+    fn visit_method(&mut self, method: &'ast Method) {
+        walk_method(self, method)
+    }
+
+    fn visit_field(&mut self, field: &'ast Field) {
+        walk_field(self, field)
+    }
+
+    fn visit_parameters(&mut self, parameters: &'ast Parameters) {
+        walk_parameters(self, parameters)
+    }
+
+    fn visit_parameter(&mut self, parameter: &'ast MetaNode<Parameter>) {
+        walk_parameter(self, parameter)
+    }
+
+    fn visit_statement(&mut self, statement: &'ast MetaNode<Statement>) {
+        walk_statement(self, statement)
+    }
+
+    fn visit_block(&mut self, block: &'ast BlockStatement) {
+        walk_block(self, block)
+    }
+
+    fn visit_catch(&mut self, catch: &'ast CatchClause) {
+        walk_catch(self, catch)
+    }
+
+    fn visit_var_declaration(&mut self, declaration: &'ast VarDeclaration) {
+        walk_var_declaration(self, declaration)
+    }
+
+    fn visit_for_header(&mut self, header: &'ast ForHeader) {
+        walk_for_header(self, header)
+    }
+
+    /// An expression with its location, which `visit_expression` then gets without it.
+    fn visit_expression_node(&mut self, expression: &'ast MetaNode<Expression>) {
+        self.visit_expression(&expression.node);
+    }
+
+    fn visit_expression(&mut self, expression: &'ast Expression) {
+        walk_expression(self, expression)
+    }
+
+    fn visit_anonymous_function(&mut self, function: &'ast AnonymousFunction) {
+        walk_anonymous_function(self, function)
+    }
+
+    fn visit_member(&mut self, member: &'ast Member) {
+        walk_member(self, member)
+    }
+
+    fn visit_primary(&mut self, primary: &'ast PrimaryExpr) {
+        walk_primary(self, primary)
+    }
+
+    fn visit_identifier(&mut self, _identifier: &'ast MetaNode<Identifier>) {}
+
+    fn visit_literal(&mut self, literal: &'ast Literal) {
+        walk_literal(self, literal)
+    }
+
+    fn visit_type(&mut self, _type: &'ast MetaNode<Type>) {}
+}
+
+pub fn walk_file<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, file: &'ast File) {
+    for statement in &file.statements {
+        v.visit_global_statement(statement);
+    }
+}
+
+pub fn walk_global_statement<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, statement: &'ast MetaNode<GlobalStatement>) {
+    match statement.node.as_ref() {
+        GlobalStatement::IncludeStatement(include) => v.visit_include(include),
+        GlobalStatement::Statement(statement) => walk_statement_node(v, statement),
+        GlobalStatement::GlobalDefinition(definition) => v.visit_global_definition(definition),
+        GlobalStatement::FunctionDefinition(function) => v.visit_function_definition(function),
+        GlobalStatement::ClassDefinition(class) => v.visit_class(class),
+    }
+}
+
+pub fn walk_global_definition<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, definition: &'ast GlobalDefinition) {
+    if let Some(type_) = &definition.type_ {
+        v.visit_type(type_);
+    }
+    for (name, value) in &definition.values {
+        v.visit_identifier(name);
+        if let Some(value) = value {
+            v.visit_expression_node(value);
+        }
+    }
+}
+
+pub fn walk_function_definition<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, function: &'ast FunctionDefinition) {
+    v.visit_identifier(&function.name);
+    v.visit_parameters(&function.args.node);
+    if let Some((_, return_type)) = &function.return_type {
+        v.visit_type(return_type);
+    }
+    v.visit_block(&function.body.node);
+}
+
+pub fn walk_class<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, class: &'ast Class) {
+    v.visit_identifier(&class.name);
+    if let Some(extends) = &class.extends {
+        v.visit_identifier(extends);
+    }
+    if let ClassBody::Members(members) = class.body.node.as_ref() {
+        for member in members {
+            match member.node.as_ref() {
+                ClassMember::StaticBlock(block) => v.visit_static_block(block),
+                ClassMember::Constructor(constructor) => v.visit_constructor(constructor),
+                ClassMember::Method(method) => v.visit_method(method),
+                ClassMember::Field(field) => v.visit_field(field),
+            }
+        }
+    }
+}
+
+pub fn walk_static_block<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, block: &'ast StaticBlock) {
+    v.visit_block(&block.body.node);
+}
+
+pub fn walk_constructor<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, constructor: &'ast Constructor) {
+    v.visit_parameters(&constructor.parameters.node);
+    v.visit_block(&constructor.body.node);
+}
+
+pub fn walk_method<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, method: &'ast Method) {
+    if let Some(return_type) = &method.return_type {
+        v.visit_type(return_type);
+    }
+    v.visit_identifier(&method.name);
+    v.visit_parameters(&method.parameters.node);
+    if let Some(body) = method.body.block() {
+        v.visit_block(&body.node);
+    }
+}
+
+pub fn walk_field<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, field: &'ast Field) {
+    if let Some(type_) = &field.type_ {
+        v.visit_type(type_);
+    }
+    v.visit_identifier(&field.name);
+    if let Some(value) = &field.value {
+        v.visit_expression_node(value);
+    }
+}
+
+pub fn walk_parameters<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, parameters: &'ast Parameters) {
+    for parameter in &parameters.params {
+        v.visit_parameter(parameter);
+    }
+}
+
+pub fn walk_parameter<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, parameter: &'ast MetaNode<Parameter>) {
+    if let Some(type_) = &parameter.node.type_ {
+        v.visit_type(type_);
+    }
+    v.visit_identifier(&parameter.node.name);
+    if let Some(default_value) = &parameter.node.default_value {
+        v.visit_expression_node(default_value);
+    }
+}
+
+pub fn walk_statement<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, statement: &'ast MetaNode<Statement>) {
+    walk_statement_node(v, &statement.node)
+}
+
+/// Top-level statements are not wrapped in their own `MetaNode`, so this does the actual work
+/// of `walk_statement` for both places.
+pub fn walk_statement_node<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, statement: &'ast Statement) {
+    match statement {
+        Statement::ExpressionStatement(statement) => v.visit_expression_node(&statement.expression),
+        Statement::VarDeclaration(statement) => v.visit_var_declaration(&statement.var_declaration.node),
+        Statement::Block(block) => v.visit_block(block),
+        Statement::IfStatement(statement) => {
+            v.visit_expression_node(&statement.condition.node.expression);
+            v.visit_statement(&statement.block);
+            if let Some(else_block) = &statement.else_block {
+                v.visit_statement(else_block);
+            }
+        }
+        Statement::DoWhileStatement(statement) => {
+            v.visit_statement(&statement.statement);
+            v.visit_expression_node(&statement.condition);
+        }
+        Statement::WhileStatement(statement) => {
+            v.visit_expression_node(&statement.condition);
+            v.visit_statement(&statement.block);
+        }
+        Statement::ForStatement(statement) => {
+            v.visit_for_header(&statement.for_header.node);
+            v.visit_statement(&statement.block);
+        }
+        Statement::ReturnStatement(statement) => {
+            if let Some(expression) = &statement.expression {
+                v.visit_expression_node(expression);
+            }
+        }
+        Statement::TryStatement(statement) => {
+            v.visit_block(&statement.block.node);
+            for catch in &statement.catches {
+                v.visit_catch(&catch.node);
+            }
+            if let Some(finally) = &statement.finally {
+                v.visit_block(&finally.node);
+            }
+        }
+        Statement::ThrowStatement(statement) => v.visit_expression_node(&statement.expression),
+        Statement::BreakStatement(_) | Statement::Empty(_) => {}
+    }
+}
+
+pub fn walk_block<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, block: &'ast BlockStatement) {
+    if let BlockStatement::StatementList(statements) = block {
+        for statement in statements {
+            v.visit_statement(statement);
+        }
+    }
+}
+
+pub fn walk_catch<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, catch: &'ast CatchClause) {
+    if let Some(binding) = &catch.binding {
+        if let Some(type_) = &binding.node.type_ {
+            v.visit_type(type_);
+        }
+        v.visit_identifier(&binding.node.name);
+    }
+    v.visit_block(&catch.block.node);
+}
+
+pub fn walk_var_declaration<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, declaration: &'ast VarDeclaration) {
+    if let Some(type_) = declaration.type_.node.type_() {
+        v.visit_type(type_);
+    }
+    for (name, value) in &declaration.values {
+        v.visit_identifier(name);
+        if let Some(value) = value {
+            v.visit_expression_node(value);
+        }
+    }
+}
+
+pub fn walk_for_header<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, header: &'ast ForHeader) {
+    match header {
+        ForHeader::ForIter(iter) => {
+            match iter.init.as_ref().map(|init| init.node.as_ref()) {
+                Some(VarDecOrExpr::VarDeclaration(declaration)) => v.visit_var_declaration(declaration),
+                Some(VarDecOrExpr::Expression(expression)) => v.visit_expression(expression),
+                None => {}
+            }
+            if let Some(condition) = &iter.condition {
+                v.visit_expression_node(condition);
+            }
+            if let Some(increment) = &iter.increment {
+                v.visit_expression_node(increment);
+            }
+        }
+        ForHeader::ForIn(for_in) => {
+            if let Some(key) = &for_in.key {
+                v.visit_var_declaration(&key.node);
+            }
+            v.visit_var_declaration(&for_in.var.node);
+            v.visit_expression_node(&for_in.iterable);
+        }
+    }
+}
+
+fn walk_bin_expr<'ast, V, Op, Expr>(v: &mut V, expression: &'ast BinExpr<Op, Expr>)
+where
+    V: Visitor<'ast> + ?Sized,
+    Op: ParseInto,
+    Expr: ParseInto,
+{
+    v.visit_expression(&expression.left);
+    for (_, right) in &expression.right {
+        v.visit_expression_node(right);
+    }
+}
+
+pub fn walk_expression<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, expression: &'ast Expression) {
+    match expression {
+        Expression::Assign(assign) => {
+            v.visit_expression(&assign.left);
+            for (_, right) in &assign.right {
+                v.visit_expression_node(right);
+            }
+        }
+        Expression::AnonyFunc(function) => v.visit_anonymous_function(function),
+        Expression::TernaryExpression(ternary) => {
+            v.visit_expression_node(&ternary.condition);
+            v.visit_expression_node(&ternary.true_expr);
+            v.visit_expression_node(&ternary.false_expr);
+        }
+        Expression::LogicalOr(e) => walk_bin_expr(v, e),
+        Expression::LogicalXor(e) => walk_bin_expr(v, e),
+        Expression::LogicalAnd(e) => walk_bin_expr(v, e),
+        Expression::Relation(e) => walk_bin_expr(v, e),
+        Expression::InstanceOf(e) => walk_bin_expr(v, e),
+        Expression::Shift(e) => walk_bin_expr(v, e),
+        Expression::BinOr(e) => walk_bin_expr(v, e),
+        Expression::BinXor(e) => walk_bin_expr(v, e),
+        Expression::BinAnd(e) => walk_bin_expr(v, e),
+        Expression::Add(e) => walk_bin_expr(v, e),
+        Expression::Mult(e) => walk_bin_expr(v, e),
+        Expression::TypeConversion(conversion) => {
+            v.visit_expression(&conversion.expression);
+            v.visit_type(&conversion.type_);
+        }
+        Expression::PreUpdate(update) => v.visit_expression(&update.expr),
+        Expression::PostUpdate(update) => v.visit_expression(&update.expr),
+        Expression::Unary(unary) => v.visit_expression(&unary.expr),
+        Expression::Member(member) => v.visit_member(member),
+        Expression::Primary(primary) => v.visit_primary(primary),
+    }
+}
+
+pub fn walk_anonymous_function<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, function: &'ast AnonymousFunction) {
+    match function {
+        AnonymousFunction::ArrowFunction(arrow) => {
+            match arrow.args.node.as_ref() {
+                AnonymousParameter::SingleParam(parameter) => v.visit_parameter(parameter),
+                AnonymousParameter::Parameters(parameters) => v.visit_parameters(&parameters.node),
+            }
+            if let Some(return_type) = &arrow.return_type {
+                v.visit_type(return_type);
+            }
+            match arrow.body.node.as_ref() {
+                FunctionBodyOrExpression::BlockStatement(block) => v.visit_block(&block.node),
+                FunctionBodyOrExpression::Expression(expression) => v.visit_expression_node(expression),
+            }
+        }
+        AnonymousFunction::Function(function) => {
+            v.visit_parameters(&function.args.node);
+            if let Some((_, return_type)) = &function.return_type {
+                v.visit_type(return_type);
+            }
+            v.visit_block(&function.body.node);
+        }
+    }
+}
+
+pub fn walk_member<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, member: &'ast Member) {
+    v.visit_primary(&member.left);
+    for right in &member.right {
+        match right.node.as_ref() {
+            MemberRight::Dot(_) | MemberRight::OptionalDot(_) | MemberRight::NotNull => {}
+            MemberRight::Bracket(index) | MemberRight::OptionalBracket(index) => v.visit_expression_node(index),
+            MemberRight::Call(arguments) => {
+                for argument in arguments {
+                    v.visit_expression_node(argument);
+                }
+            }
+        }
+    }
+}
+
+pub fn walk_literal<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, literal: &'ast Literal) {
+    if let Literal::Template(template) = literal {
+        for part in &template.parts {
+            if let TemplatePart::Hole(expression) = part {
+                v.visit_expression_node(expression);
+            }
+        }
+    }
+}
+
+pub fn walk_primary<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, primary: &'ast PrimaryExpr) {
+    match primary {
+        PrimaryExpr::IdentifierOrMember(identifier) => {
+            if let IdentifierOrMember::Identifier(identifier) = identifier.node.as_ref() {
+                v.visit_identifier(identifier);
+            }
+        }
+        PrimaryExpr::Literal(literal) => v.visit_literal(literal),
+        PrimaryExpr::Array(Array::Elements { elements, .. }) | PrimaryExpr::Set(Set::Elements { elements, .. }) => {
+            for element in elements {
+                v.visit_expression_node(element);
+            }
+        }
+        PrimaryExpr::Object(Object::Pairs { pairs, .. }) | PrimaryExpr::Map(Map::Pairs { pairs, .. }) => {
+            for (key, value) in pairs {
+                v.visit_expression_node(key);
+                v.visit_expression_node(value);
+            }
+        }
+        PrimaryExpr::Array(_) | PrimaryExpr::Set(_) | PrimaryExpr::Object(_) | PrimaryExpr::Map(_) => {}
+        PrimaryExpr::ParenthesizedExpr(expression) => v.visit_expression_node(expression),
+    }
+}
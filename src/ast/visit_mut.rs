@@ -0,0 +1,422 @@
+use crate::ast::*;
+
+/// The mutable counterpart of `Visitor`, for passes that rewrite the AST in place. A
+/// `visit_*` method may replace the node it is given, before or after walking it.
+pub trait VisitorMut {
+    fn visit_file(&mut self, file: &mut File) {
+        walk_file(self, file)
+    }
+
+    fn visit_global_statement(&mut self, statement: &mut MetaNode<GlobalStatement>) {
+        walk_global_statement(self, statement)
+    }
+
+    fn visit_include(&mut self, _include: &mut IncludeStatement) {}
+
+    fn visit_global_definition(&mut self, definition: &mut GlobalDefinition) {
+        walk_global_definition(self, definition)
+    }
+
+    fn visit_function_definition(&mut self, function: &mut FunctionDefinition) {
+        walk_function_definition(self, function)
+    }
+
+    fn visit_class(&mut self, class: &mut Class) {
+        walk_class(self, class)
+    }
+
+    fn visit_static_block(&mut self, block: &mut StaticBlock) {
+        walk_static_block(self, block)
+    }
+
+    fn visit_constructor(&mut self, constructor: &mut Constructor) {
+        walk_constructor(self, constructor)
+    }
+
+    fn visit_method(&mut self, method: &mut Method) {
+        walk_method(self, method)
+    }
+
+    fn visit_field(&mut self, field: &mut Field) {
+        walk_field(self, field)
+    }
+
+    fn visit_parameters(&mut self, parameters: &mut Parameters) {
+        walk_parameters(self, parameters)
+    }
+
+    fn visit_parameter(&mut self, parameter: &mut MetaNode<Parameter>) {
+        walk_parameter(self, parameter)
+    }
+
+    fn visit_statement(&mut self, statement: &mut MetaNode<Statement>) {
+        walk_statement(self, statement)
+    }
+
+    fn visit_block(&mut self, block: &mut BlockStatement) {
+        walk_block(self, block)
+    }
+
+    fn visit_catch(&mut self, catch: &mut CatchClause) {
+        walk_catch(self, catch)
+    }
+
+    fn visit_var_declaration(&mut self, declaration: &mut VarDeclaration) {
+        walk_var_declaration(self, declaration)
+    }
+
+    fn visit_for_header(&mut self, header: &mut ForHeader) {
+        walk_for_header(self, header)
+    }
+
+    fn visit_expression(&mut self, expression: &mut Expression) {
+        walk_expression(self, expression)
+    }
+
+    fn visit_anonymous_function(&mut self, function: &mut AnonymousFunction) {
+        walk_anonymous_function(self, function)
+    }
+
+    fn visit_member(&mut self, member: &mut Member) {
+        walk_member(self, member)
+    }
+
+    fn visit_primary(&mut self, primary: &mut PrimaryExpr) {
+        walk_primary(self, primary)
+    }
+
+    fn visit_identifier(&mut self, _identifier: &mut MetaNode<Identifier>) {}
+
+    fn visit_literal(&mut self, literal: &mut Literal) {
+        walk_literal(self, literal)
+    }
+
+    fn visit_type(&mut self, _type: &mut MetaNode<Type>) {}
+}
+
+pub fn walk_file<V: VisitorMut + ?Sized>(v: &mut V, file: &mut File) {
+    for statement in &mut file.statements {
+        v.visit_global_statement(statement);
+    }
+}
+
+pub fn walk_global_statement<V: VisitorMut + ?Sized>(v: &mut V, statement: &mut MetaNode<GlobalStatement>) {
+    match statement.node.as_mut() {
+        GlobalStatement::IncludeStatement(include) => v.visit_include(include),
+        GlobalStatement::Statement(statement) => walk_statement_node(v, statement),
+        GlobalStatement::GlobalDefinition(definition) => v.visit_global_definition(definition),
+        GlobalStatement::FunctionDefinition(function) => v.visit_function_definition(function),
+        GlobalStatement::ClassDefinition(class) => v.visit_class(class),
+    }
+}
+
+pub fn walk_global_definition<V: VisitorMut + ?Sized>(v: &mut V, definition: &mut GlobalDefinition) {
+    if let Some(type_) = &mut definition.type_ {
+        v.visit_type(type_);
+    }
+    for (name, value) in &mut definition.values {
+        v.visit_identifier(name);
+        if let Some(value) = value {
+            v.visit_expression(&mut value.node);
+        }
+    }
+}
+
+pub fn walk_function_definition<V: VisitorMut + ?Sized>(v: &mut V, function: &mut FunctionDefinition) {
+    v.visit_identifier(&mut function.name);
+    v.visit_parameters(&mut function.args.node);
+    if let Some((_, return_type)) = &mut function.return_type {
+        v.visit_type(return_type);
+    }
+    v.visit_block(&mut function.body.node);
+}
+
+pub fn walk_class<V: VisitorMut + ?Sized>(v: &mut V, class: &mut Class) {
+    v.visit_identifier(&mut class.name);
+    if let Some(extends) = &mut class.extends {
+        v.visit_identifier(extends);
+    }
+    if let ClassBody::Members(members) = class.body.node.as_mut() {
+        for member in members {
+            match member.node.as_mut() {
+                ClassMember::StaticBlock(block) => v.visit_static_block(block),
+                ClassMember::Constructor(constructor) => v.visit_constructor(constructor),
+                ClassMember::Method(method) => v.visit_method(method),
+                ClassMember::Field(field) => v.visit_field(field),
+            }
+        }
+    }
+}
+
+pub fn walk_static_block<V: VisitorMut + ?Sized>(v: &mut V, block: &mut StaticBlock) {
+    v.visit_block(&mut block.body.node);
+}
+
+pub fn walk_constructor<V: VisitorMut + ?Sized>(v: &mut V, constructor: &mut Constructor) {
+    v.visit_parameters(&mut constructor.parameters.node);
+    v.visit_block(&mut constructor.body.node);
+}
+
+pub fn walk_method<V: VisitorMut + ?Sized>(v: &mut V, method: &mut Method) {
+    if let Some(return_type) = &mut method.return_type {
+        v.visit_type(return_type);
+    }
+    v.visit_identifier(&mut method.name);
+    v.visit_parameters(&mut method.parameters.node);
+    if let MethodBody::Block(body) = &mut method.body {
+        v.visit_block(&mut body.node);
+    }
+}
+
+pub fn walk_field<V: VisitorMut + ?Sized>(v: &mut V, field: &mut Field) {
+    if let Some(type_) = &mut field.type_ {
+        v.visit_type(type_);
+    }
+    v.visit_identifier(&mut field.name);
+    if let Some(value) = &mut field.value {
+        v.visit_expression(&mut value.node);
+    }
+}
+
+pub fn walk_parameters<V: VisitorMut + ?Sized>(v: &mut V, parameters: &mut Parameters) {
+    for parameter in &mut parameters.params {
+        v.visit_parameter(parameter);
+    }
+}
+
+pub fn walk_parameter<V: VisitorMut + ?Sized>(v: &mut V, parameter: &mut MetaNode<Parameter>) {
+    if let Some(type_) = &mut parameter.node.type_ {
+        v.visit_type(type_);
+    }
+    v.visit_identifier(&mut parameter.node.name);
+    if let Some(default_value) = &mut parameter.node.default_value {
+        v.visit_expression(&mut default_value.node);
+    }
+}
+
+pub fn walk_statement<V: VisitorMut + ?Sized>(v: &mut V, statement: &mut MetaNode<Statement>) {
+    walk_statement_node(v, &mut statement.node)
+}
+
+/// Top-level statements are not wrapped in their own `MetaNode`, so this does the actual work
+/// of `walk_statement` for both places.
+pub fn walk_statement_node<V: VisitorMut + ?Sized>(v: &mut V, statement: &mut Statement) {
+    match statement {
+        Statement::ExpressionStatement(statement) => v.visit_expression(&mut statement.expression.node),
+        Statement::VarDeclaration(statement) => v.visit_var_declaration(&mut statement.var_declaration.node),
+        Statement::Block(block) => v.visit_block(block),
+        Statement::IfStatement(statement) => {
+            v.visit_expression(&mut statement.condition.node.expression.node);
+            v.visit_statement(&mut statement.block);
+            if let Some(else_block) = &mut statement.else_block {
+                v.visit_statement(else_block);
+            }
+        }
+        Statement::DoWhileStatement(statement) => {
+            v.visit_statement(&mut statement.statement);
+            v.visit_expression(&mut statement.condition.node);
+        }
+        Statement::WhileStatement(statement) => {
+            v.visit_expression(&mut statement.condition.node);
+            v.visit_statement(&mut statement.block);
+        }
+        Statement::ForStatement(statement) => {
+            v.visit_for_header(&mut statement.for_header.node);
+            v.visit_statement(&mut statement.block);
+        }
+        Statement::ReturnStatement(statement) => {
+            if let Some(expression) = &mut statement.expression {
+                v.visit_expression(&mut expression.node);
+            }
+        }
+        Statement::TryStatement(statement) => {
+            v.visit_block(&mut statement.block.node);
+            for catch in &mut statement.catches {
+                v.visit_catch(&mut catch.node);
+            }
+            if let Some(finally) = &mut statement.finally {
+                v.visit_block(&mut finally.node);
+            }
+        }
+        Statement::ThrowStatement(statement) => v.visit_expression(&mut statement.expression.node),
+        Statement::BreakStatement(_) | Statement::Empty(_) => {}
+    }
+}
+
+pub fn walk_block<V: VisitorMut + ?Sized>(v: &mut V, block: &mut BlockStatement) {
+    if let BlockStatement::StatementList(statements) = block {
+        for statement in statements {
+            v.visit_statement(statement);
+        }
+    }
+}
+
+pub fn walk_catch<V: VisitorMut + ?Sized>(v: &mut V, catch: &mut CatchClause) {
+    if let Some(binding) = &mut catch.binding {
+        if let Some(type_) = &mut binding.node.type_ {
+            v.visit_type(type_);
+        }
+        v.visit_identifier(&mut binding.node.name);
+    }
+    v.visit_block(&mut catch.block.node);
+}
+
+pub fn walk_var_declaration<V: VisitorMut + ?Sized>(v: &mut V, declaration: &mut VarDeclaration) {
+    if let VarType::Typed(type_) = declaration.type_.node.as_mut() {
+        v.visit_type(type_);
+    }
+    for (name, value) in &mut declaration.values {
+        v.visit_identifier(name);
+        if let Some(value) = value {
+            v.visit_expression(&mut value.node);
+        }
+    }
+}
+
+pub fn walk_for_header<V: VisitorMut + ?Sized>(v: &mut V, header: &mut ForHeader) {
+    match header {
+        ForHeader::ForIter(iter) => {
+            match iter.init.as_mut().map(|init| init.node.as_mut()) {
+                Some(VarDecOrExpr::VarDeclaration(declaration)) => v.visit_var_declaration(declaration),
+                Some(VarDecOrExpr::Expression(expression)) => v.visit_expression(expression),
+                None => {}
+            }
+            if let Some(condition) = &mut iter.condition {
+                v.visit_expression(&mut condition.node);
+            }
+            if let Some(increment) = &mut iter.increment {
+                v.visit_expression(&mut increment.node);
+            }
+        }
+        ForHeader::ForIn(for_in) => {
+            if let Some(key) = &mut for_in.key {
+                v.visit_var_declaration(&mut key.node);
+            }
+            v.visit_var_declaration(&mut for_in.var.node);
+            v.visit_expression(&mut for_in.iterable.node);
+        }
+    }
+}
+
+fn walk_bin_expr<V, Op, Expr>(v: &mut V, expression: &mut BinExpr<Op, Expr>)
+where
+    V: VisitorMut + ?Sized,
+    Op: ParseInto,
+    Expr: ParseInto,
+{
+    v.visit_expression(&mut expression.left);
+    for (_, right) in &mut expression.right {
+        v.visit_expression(&mut right.node);
+    }
+}
+
+pub fn walk_expression<V: VisitorMut + ?Sized>(v: &mut V, expression: &mut Expression) {
+    match expression {
+        Expression::Assign(assign) => {
+            v.visit_expression(&mut assign.left);
+            for (_, right) in &mut assign.right {
+                v.visit_expression(&mut right.node);
+            }
+        }
+        Expression::AnonyFunc(function) => v.visit_anonymous_function(function),
+        Expression::TernaryExpression(ternary) => {
+            v.visit_expression(&mut ternary.condition.node);
+            v.visit_expression(&mut ternary.true_expr.node);
+            v.visit_expression(&mut ternary.false_expr.node);
+        }
+        Expression::LogicalOr(e) => walk_bin_expr(v, e),
+        Expression::LogicalXor(e) => walk_bin_expr(v, e),
+        Expression::LogicalAnd(e) => walk_bin_expr(v, e),
+        Expression::Relation(e) => walk_bin_expr(v, e),
+        Expression::InstanceOf(e) => walk_bin_expr(v, e),
+        Expression::Shift(e) => walk_bin_expr(v, e),
+        Expression::BinOr(e) => walk_bin_expr(v, e),
+        Expression::BinXor(e) => walk_bin_expr(v, e),
+        Expression::BinAnd(e) => walk_bin_expr(v, e),
+        Expression::Add(e) => walk_bin_expr(v, e),
+        Expression::Mult(e) => walk_bin_expr(v, e),
+        Expression::TypeConversion(conversion) => {
+            v.visit_expression(&mut conversion.expression);
+            v.visit_type(&mut conversion.type_);
+        }
+        Expression::PreUpdate(update) => v.visit_expression(&mut update.expr),
+        Expression::PostUpdate(update) => v.visit_expression(&mut update.expr),
+        Expression::Unary(unary) => v.visit_expression(&mut unary.expr),
+        Expression::Member(member) => v.visit_member(member),
+        Expression::Primary(primary) => v.visit_primary(primary),
+    }
+}
+
+pub fn walk_anonymous_function<V: VisitorMut + ?Sized>(v: &mut V, function: &mut AnonymousFunction) {
+    match function {
+        AnonymousFunction::ArrowFunction(arrow) => {
+            match arrow.args.node.as_mut() {
+                AnonymousParameter::SingleParam(parameter) => v.visit_parameter(parameter),
+                AnonymousParameter::Parameters(parameters) => v.visit_parameters(&mut parameters.node),
+            }
+            if let Some(return_type) = &mut arrow.return_type {
+                v.visit_type(return_type);
+            }
+            match arrow.body.node.as_mut() {
+                FunctionBodyOrExpression::BlockStatement(block) => v.visit_block(&mut block.node),
+                FunctionBodyOrExpression::Expression(expression) => v.visit_expression(&mut expression.node),
+            }
+        }
+        AnonymousFunction::Function(function) => {
+            v.visit_parameters(&mut function.args.node);
+            if let Some((_, return_type)) = &mut function.return_type {
+                v.visit_type(return_type);
+            }
+            v.visit_block(&mut function.body.node);
+        }
+    }
+}
+
+pub fn walk_member<V: VisitorMut + ?Sized>(v: &mut V, member: &mut Member) {
+    v.visit_primary(&mut member.left);
+    for right in &mut member.right {
+        match right.node.as_mut() {
+            MemberRight::Dot(_) | MemberRight::OptionalDot(_) | MemberRight::NotNull => {}
+            MemberRight::Bracket(index) | MemberRight::OptionalBracket(index) => v.visit_expression(&mut index.node),
+            MemberRight::Call(arguments) => {
+                for argument in arguments {
+                    v.visit_expression(&mut argument.node);
+                }
+            }
+        }
+    }
+}
+
+pub fn walk_literal<V: VisitorMut + ?Sized>(v: &mut V, literal: &mut Literal) {
+    if let Literal::Template(template) = literal {
+        for part in &mut template.parts {
+            if let TemplatePart::Hole(expression) = part {
+                v.visit_expression(&mut expression.node);
+            }
+        }
+    }
+}
+
+pub fn walk_primary<V: VisitorMut + ?Sized>(v: &mut V, primary: &mut PrimaryExpr) {
+    match primary {
+        PrimaryExpr::IdentifierOrMember(identifier) => {
+            if let IdentifierOrMember::Identifier(identifier) = identifier.node.as_mut() {
+                v.visit_identifier(identifier);
+            }
+        }
+        PrimaryExpr::Literal(literal) => v.visit_literal(literal),
+        PrimaryExpr::Array(Array::Elements { elements, .. }) | PrimaryExpr::Set(Set::Elements { elements, .. }) => {
+            for element in elements {
+                v.visit_expression(&mut element.node);
+            }
+        }
+        PrimaryExpr::Object(Object::Pairs { pairs, .. }) | PrimaryExpr::Map(Map::Pairs { pairs, .. }) => {
+            for (key, value) in pairs {
+                v.visit_expression(&mut key.node);
+                v.visit_expression(&mut value.node);
+            }
+        }
+        PrimaryExpr::Array(_) | PrimaryExpr::Set(_) | PrimaryExpr::Object(_) | PrimaryExpr::Map(_) => {}
+        PrimaryExpr::ParenthesizedExpr(expression) => v.visit_expression(&mut expression.node),
+    }
+}
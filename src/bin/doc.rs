@@ -0,0 +1,45 @@
+//! Writes the documentation of an AI and the files it includes.
+//!
+//! Usage: `doc <entry file> <output directory> [--markdown]`, HTML being written by default.
+
+use std::{fs, path::Path, process::ExitCode};
+
+use leekscript_parser::{analysis::Project, doc::Documentation};
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let markdown = args.iter().any(|arg| arg == "--markdown");
+    let paths: Vec<&String> = args.iter().filter(|arg| !arg.starts_with("--")).collect();
+    let [entry, output] = paths[..] else {
+        eprintln!("usage: doc <entry file> <output directory> [--markdown]");
+        return ExitCode::FAILURE;
+    };
+
+    // Paths relative to the directory of the entry, which the pages mirror
+    let entry = Path::new(entry);
+    let directory = entry.parent().unwrap_or(Path::new(""));
+    let name = entry.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+    let project = match Project::load_with(&name, |path| fs::read_to_string(directory.join(path))) {
+        Ok(project) => project,
+        Err(error) => {
+            eprintln!("{}", error);
+            return ExitCode::FAILURE;
+        }
+    };
+    let documentation = Documentation::build(&project);
+    let pages = match markdown {
+        true => documentation.to_markdown(),
+        false => documentation.to_html(),
+    };
+
+    // The page paths never leave the output directory
+    for (path, content) in pages {
+        let path = Path::new(output).join(path);
+        let written = path.parent().map_or(Ok(()), fs::create_dir_all).and_then(|_| fs::write(&path, content));
+        if let Err(error) = written {
+            eprintln!("{}: {}", path.display(), error);
+            return ExitCode::FAILURE;
+        }
+    }
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,145 @@
+use std::fmt::Write;
+
+use super::{index_link, page_path, relative_link, INDEX, DocComment, Documentation, Item, ItemKind, Page, Token};
+
+const STYLE: &str = "body { font-family: sans-serif; max-width: 60em; margin: auto; padding: 1em; }\n\
+    pre { background: #f4f4f4; padding: 0.5em; overflow-x: auto; }\n\
+    section.member { margin-left: 1.5em; }";
+
+impl Documentation {
+    /// The HTML pages, by the path to write them to: one per file, and `_index.html`.
+    pub fn to_html(&self) -> Vec<(String, String)> {
+        let mut pages = vec![(format!("{}.html", INDEX), self.html_index())];
+        pages.extend(self.pages.iter().map(|page| (format!("{}.html", page_path(&page.path)), page_html(page))));
+        pages
+    }
+
+    fn html_index(&self) -> String {
+        let mut body = String::from("<h1>Documentation</h1>\n<ul>\n");
+        for page in &self.pages {
+            let link = format!("{}.html", page_path(&page.path));
+            writeln!(body, "<li><a href=\"{}\">{}</a>", escape(&link), escape(&page.path)).unwrap();
+            if !page.items.is_empty() {
+                body.push_str("<ul>\n");
+                for item in &page.items {
+                    writeln!(body, "<li><a href=\"{}#{}\">{} {}</a></li>", escape(&link), escape(&item.anchor), item.kind, escape(&item.name)).unwrap();
+                }
+                body.push_str("</ul>\n");
+            }
+            body.push_str("</li>\n");
+        }
+        body.push_str("</ul>\n");
+        document("Documentation", &body)
+    }
+}
+
+fn page_html(page: &Page) -> String {
+    let mut body = String::new();
+    writeln!(body, "<nav><a href=\"{}\">Index</a></nav>", escape(&index_link(&page.path, "html"))).unwrap();
+    writeln!(body, "<h1>{}</h1>", escape(&page.path)).unwrap();
+    if !page.includes.is_empty() {
+        writeln!(body, "<p>Includes: {}</p>", tokens_html(&page.includes, &page.path, ", ", "html")).unwrap();
+    }
+    for item in &page.items {
+        item_html(item, &page.path, &mut body);
+    }
+    document(&page.path, &body)
+}
+
+fn item_html(item: &Item, page: &str, body: &mut String) {
+    let (class, heading) = match item.kind {
+        ItemKind::Constructor | ItemKind::Method | ItemKind::Field => ("member", "h3"),
+        _ => ("item", "h2"),
+    };
+    writeln!(body, "<section class=\"{}\" id=\"{}\">", class, escape(&item.anchor)).unwrap();
+    writeln!(body, "<{heading}>{} {}</{heading}>", item.kind, escape(&item.name), heading = heading).unwrap();
+    writeln!(body, "{}", signature_html(&item.signature, page, "html")).unwrap();
+    if item.kind == ItemKind::Class {
+        let mut hierarchy = tokens_html(&item.ancestors, page, " &rarr; ", "html");
+        if !hierarchy.is_empty() {
+            hierarchy.push_str(" &rarr; ");
+        }
+        writeln!(body, "<p>Hierarchy: {}<strong>{}</strong></p>", hierarchy, escape(&item.name)).unwrap();
+        if !item.subclasses.is_empty() {
+            writeln!(body, "<p>Subclasses: {}</p>", tokens_html(&item.subclasses, page, ", ", "html")).unwrap();
+        }
+    }
+    doc_html(&item.doc, body);
+    for member in &item.members {
+        item_html(member, page, body);
+    }
+    body.push_str("</section>\n");
+}
+
+fn doc_html(doc: &DocComment, body: &mut String) {
+    for paragraph in doc.description.split("\n\n").filter(|paragraph| !paragraph.trim().is_empty()) {
+        writeln!(body, "<p>{}</p>", escape(paragraph.trim())).unwrap();
+    }
+    if !doc.params.is_empty() {
+        body.push_str("<h4>Parameters</h4>\n<dl>\n");
+        for (name, text) in &doc.params {
+            writeln!(body, "<dt><code>{}</code></dt><dd>{}</dd>", escape(name), escape(text)).unwrap();
+        }
+        body.push_str("</dl>\n");
+    }
+    if let Some(returns) = &doc.returns {
+        writeln!(body, "<p><strong>Returns</strong> {}</p>", escape(returns)).unwrap();
+    }
+}
+
+/// A signature as a block of code whose class names link to their documentation, which
+/// Markdown pages use as well. The pages linked to end with `extension`.
+pub(crate) fn signature_html(signature: &[Token], page: &str, extension: &str) -> String {
+    format!("<pre><code>{}</code></pre>", tokens_html(signature, page, "", extension))
+}
+
+fn tokens_html(tokens: &[Token], page: &str, separator: &str, extension: &str) -> String {
+    let tokens: Vec<_> = tokens.iter()
+        .map(|token| match token {
+            Token::Text(text) => escape(text),
+            Token::Link { text, page: target, anchor } => {
+                let link = match (anchor, target == page) {
+                    (Some(anchor), true) => format!("#{}", anchor),
+                    (Some(anchor), false) => format!("{}#{}", relative_link(page, target, extension), anchor),
+                    (None, _) => relative_link(page, target, extension),
+                };
+                format!("<a href=\"{}\">{}</a>", escape(&link), escape(text))
+            }
+        })
+        .collect();
+    tokens.join(separator)
+}
+
+fn document(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>\n{}\n</style>\n</head>\n<body>\n{}</body>\n</html>\n",
+        escape(title), STYLE, body
+    )
+}
+
+pub(crate) fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::doc::tests::documentation;
+
+    #[test]
+    fn test_html() {
+        let pages = documentation().to_html();
+        let paths: Vec<_> = pages.iter().map(|(path, _)| path.as_str()).collect();
+        assert_eq!(paths, ["_index.html", "main.html", "lib/shapes.html"]);
+
+        let main = &pages[1].1;
+        assert!(main.contains("<title>main</title>"));
+        assert!(main.contains("<p>Includes: <a href=\"lib/shapes.html\">lib/shapes</a></p>\n"));
+        assert!(main.contains("<dt><code>shape</code></dt><dd>the shape to measure</dd>"));
+
+        let shapes = &pages[2].1;
+        assert!(shapes.contains("<nav><a href=\"../_index.html\">Index</a></nav>"));
+        assert!(shapes.contains("<section class=\"member\" id=\"Shape.constructor\">\n<h3>constructor constructor</h3>\n\
+            <pre><code>constructor(Array&lt;<a href=\"#Shape\">Shape</a>?&gt; parts)</code></pre>\n"));
+        assert!(shapes.contains("<p>Hierarchy: <a href=\"#Shape\">Shape</a> &rarr; <strong>Square</strong></p>"));
+    }
+}
@@ -0,0 +1,115 @@
+use std::fmt::Write;
+
+use super::{html::{escape, signature_html}, index_link, page_path, relative_link, INDEX, DocComment, Documentation, Item, ItemKind, Page, Token};
+
+impl Documentation {
+    /// The Markdown pages, by the path to write them to: one per file, and `_index.md`.
+    /// Signatures are written in HTML so that their class names can link to other pages.
+    pub fn to_markdown(&self) -> Vec<(String, String)> {
+        let mut pages = vec![(format!("{}.md", INDEX), self.markdown_index())];
+        pages.extend(self.pages.iter().map(|page| (format!("{}.md", page_path(&page.path)), page_markdown(page))));
+        pages
+    }
+
+    fn markdown_index(&self) -> String {
+        let mut text = String::from("# Documentation\n\n");
+        for page in &self.pages {
+            let link = format!("{}.md", page_path(&page.path));
+            writeln!(text, "- [{}]({})", page.path, link).unwrap();
+            for item in &page.items {
+                writeln!(text, "  - [{} {}]({}#{})", item.kind, item.name, link, item.anchor).unwrap();
+            }
+        }
+        text
+    }
+}
+
+fn page_markdown(page: &Page) -> String {
+    let mut text = format!("[Index]({})\n\n# {}\n", index_link(&page.path, "md"), page.path);
+    if !page.includes.is_empty() {
+        writeln!(text, "\nIncludes: {}", tokens_markdown(&page.includes, &page.path, ", ")).unwrap();
+    }
+    for item in &page.items {
+        item_markdown(item, &page.path, &mut text);
+    }
+    text
+}
+
+fn item_markdown(item: &Item, page: &str, text: &mut String) {
+    let heading = match item.kind {
+        ItemKind::Constructor | ItemKind::Method | ItemKind::Field => "###",
+        _ => "##",
+    };
+    writeln!(text, "\n<a id=\"{}\"></a>\n\n{} {} {}\n", escape(&item.anchor), heading, item.kind, item.name).unwrap();
+    writeln!(text, "{}", signature_html(&item.signature, page, "md")).unwrap();
+    if item.kind == ItemKind::Class {
+        let mut hierarchy = tokens_markdown(&item.ancestors, page, " → ");
+        if !hierarchy.is_empty() {
+            hierarchy.push_str(" → ");
+        }
+        writeln!(text, "\nHierarchy: {}**{}**", hierarchy, item.name).unwrap();
+        if !item.subclasses.is_empty() {
+            writeln!(text, "\nSubclasses: {}", tokens_markdown(&item.subclasses, page, ", ")).unwrap();
+        }
+    }
+    doc_markdown(&item.doc, text);
+    for member in &item.members {
+        item_markdown(member, page, text);
+    }
+}
+
+fn doc_markdown(doc: &DocComment, text: &mut String) {
+    if !doc.description.is_empty() {
+        writeln!(text, "\n{}", doc.description).unwrap();
+    }
+    if !doc.params.is_empty() {
+        text.push_str("\n**Parameters**\n\n");
+        for (name, description) in &doc.params {
+            writeln!(text, "- `{}`: {}", name, description).unwrap();
+        }
+    }
+    if let Some(returns) = &doc.returns {
+        writeln!(text, "\n**Returns** {}", returns).unwrap();
+    }
+}
+
+fn tokens_markdown(tokens: &[Token], page: &str, separator: &str) -> String {
+    let tokens: Vec<_> = tokens.iter()
+        .map(|token| match token {
+            Token::Text(text) => text.clone(),
+            Token::Link { text, page: target, anchor } => {
+                let link = match (anchor, target == page) {
+                    (Some(anchor), true) => format!("#{}", anchor),
+                    (Some(anchor), false) => format!("{}#{}", relative_link(page, target, "md"), anchor),
+                    (None, _) => relative_link(page, target, "md"),
+                };
+                format!("[{}]({})", text, link)
+            }
+        })
+        .collect();
+    tokens.join(separator)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::doc::tests::documentation;
+
+    #[test]
+    fn test_markdown() {
+        let pages = documentation().to_markdown();
+        let paths: Vec<_> = pages.iter().map(|(path, _)| path.as_str()).collect();
+        assert_eq!(paths, ["_index.md", "main.md", "lib/shapes.md"]);
+        assert!(pages[0].1.contains("- [main](main.md)\n  - [function area](main.md#area)\n"));
+
+        let main = &pages[1].1;
+        assert!(main.starts_with("[Index](_index.md)\n\n# main\n\nIncludes: [lib/shapes](lib/shapes.md)\n"));
+        assert!(main.contains("## function area\n\n<pre><code>function area(<a href=\"lib/shapes.md#Shape\">Shape</a> shape, integer scale = 1) =&gt; real</code></pre>\n"));
+        assert!(main.contains("\nThe area of a shape.\n\n**Parameters**\n\n- `shape`: the shape to measure\n\n**Returns** its area\n"));
+
+        let shapes = &pages[2].1;
+        assert!(shapes.starts_with("[Index](../_index.md)\n"));
+        assert!(shapes.contains("\nHierarchy: [Shape](#Shape) → **Square**\n"));
+        assert!(shapes.contains("\nSubclasses: [Square](#Square)\n"));
+        assert!(shapes.contains("<a id=\"Shape.name\"></a>\n\n### field name\n"));
+    }
+}
@@ -0,0 +1,461 @@
+//! API documentation of a project, built from the doc comments of its functions, classes,
+//! globals, fields and methods, and printed as one Markdown or HTML page per file.
+
+mod markdown;
+mod html;
+
+use std::fmt::Display;
+
+use crate::{
+    analysis::{includes, resolve_include, ClassTable, Project},
+    ast::*,
+};
+
+/// A doc comment split into its description and tags.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DocComment {
+    pub description: String,
+    /// The `@param name text` tags
+    pub params: Vec<(String, String)>,
+    /// The `@return text` tag
+    pub returns: Option<String>,
+}
+
+impl DocComment {
+    /// Reads the tags starting a line. The lines following a tag continue it, and unknown tags
+    /// stay in the description.
+    pub fn parse(text: &str) -> Self {
+        enum Current {
+            Description,
+            Param,
+            Returns,
+        }
+
+        let mut doc = DocComment::default();
+        let mut description = Vec::new();
+        let mut current = Current::Description;
+        for line in text.lines() {
+            let line = line.trim();
+            let (tag, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+            let rest = rest.trim();
+            match tag {
+                "@param" => {
+                    let (name, text) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+                    doc.params.push((name.to_string(), text.trim().to_string()));
+                    current = Current::Param;
+                }
+                "@return" | "@returns" => {
+                    doc.returns = Some(rest.to_string());
+                    current = Current::Returns;
+                }
+                _ => {
+                    let continued = match current {
+                        Current::Description => None,
+                        Current::Param => doc.params.last_mut().map(|(_, text)| text),
+                        Current::Returns => doc.returns.as_mut(),
+                    };
+                    match continued {
+                        Some(text) if !line.is_empty() => {
+                            if !text.is_empty() {
+                                text.push(' ');
+                            }
+                            text.push_str(line);
+                        }
+                        _ => {
+                            current = Current::Description;
+                            description.push(line);
+                        }
+                    }
+                }
+            }
+        }
+        doc.description = description.join("\n").trim().to_string();
+        doc
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.description.is_empty() && self.params.is_empty() && self.returns.is_none()
+    }
+}
+
+/// A piece of a signature or of a list of names, linking to the page documenting it when there
+/// is one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Token {
+    Text(String),
+    Link { text: String, page: String, anchor: Option<String> },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ItemKind {
+    Function,
+    Class,
+    Global,
+    Constructor,
+    Method,
+    Field,
+}
+
+impl Display for ItemKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = match self {
+            ItemKind::Function => "function",
+            ItemKind::Class => "class",
+            ItemKind::Global => "global",
+            ItemKind::Constructor => "constructor",
+            ItemKind::Method => "method",
+            ItemKind::Field => "field",
+        };
+        write!(f, "{}", kind)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Item {
+    pub name: String,
+    pub kind: ItemKind,
+    /// Where the item is on its page, `Class.member` for members
+    pub anchor: String,
+    pub signature: Vec<Token>,
+    pub doc: DocComment,
+    /// The ancestors of a class, the root first
+    pub ancestors: Vec<Token>,
+    pub subclasses: Vec<Token>,
+    pub members: Vec<Item>,
+}
+
+/// The documentation of one file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Page {
+    pub path: String,
+    pub includes: Vec<Token>,
+    pub items: Vec<Item>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Documentation {
+    pub pages: Vec<Page>,
+}
+
+impl Documentation {
+    /// Documents every function, class and global of the project, along with the members of
+    /// its classes, whether they have a doc comment or not.
+    pub fn build(project: &Project) -> Self {
+        let (table, _) = ClassTable::build(project);
+        let builder = Builder { table: &table };
+        let pages = project.files.iter()
+            .map(|source| {
+                let includes = includes(&source.file)
+                    .map(|include| {
+                        let path = resolve_include(&source.path, &include.path.node.decoded());
+                        Token::Link { text: path.clone(), page: path, anchor: None }
+                    })
+                    .collect();
+                let items = source.file.statements.iter().flat_map(|statement| builder.items(statement)).collect();
+                Page { path: source.path.clone(), includes, items }
+            })
+            .collect();
+        Documentation { pages }
+    }
+}
+
+struct Builder<'a> {
+    table: &'a ClassTable<'a>,
+}
+
+impl Builder<'_> {
+    fn items(&self, statement: &MetaNode<GlobalStatement>) -> Vec<Item> {
+        let doc = DocComment::parse(&statement.doc_comment().unwrap_or_default());
+        match statement.node.as_ref() {
+            GlobalStatement::FunctionDefinition(function) => {
+                let name = &function.name.node.name;
+                let mut signature = vec![Token::Text(format!("function {}", name))];
+                self.parameters(&function.args.node, &mut signature);
+                if let Some((arrow, type_)) = &function.return_type {
+                    signature.push(Token::Text(format!(" {} ", arrow.node)));
+                    self.type_(&type_.node, &mut signature);
+                }
+                vec![item(name, ItemKind::Function, name, signature, doc)]
+            }
+            GlobalStatement::ClassDefinition(class) => vec![self.class(class, doc)],
+            GlobalStatement::GlobalDefinition(definition) => {
+                definition.values.iter()
+                    .map(|(name, value)| {
+                        let name = &name.node.name;
+                        let mut signature = vec![Token::Text("global ".to_string())];
+                        if definition.const_.is_some() {
+                            signature.push(Token::Text("const ".to_string()));
+                        }
+                        if let Some(type_) = &definition.type_ {
+                            self.type_(&type_.node, &mut signature);
+                            signature.push(Token::Text(" ".to_string()));
+                        }
+                        signature.push(Token::Text(name.clone()));
+                        push_value(value, &mut signature);
+                        item(name, ItemKind::Global, name, signature, doc.clone())
+                    })
+                    .collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn class(&self, class: &Class, doc: DocComment) -> Item {
+        let name = &class.name.node.name;
+        let mut signature = Vec::new();
+        if class.abstract_.is_some() {
+            signature.push(Token::Text("abstract ".to_string()));
+        }
+        signature.push(Token::Text(format!("class {}", name)));
+        if let Some(parent) = &class.extends {
+            signature.push(Token::Text(" extends ".to_string()));
+            signature.push(self.class_name(&parent.node.name));
+        }
+
+        let mut class_item = item(name, ItemKind::Class, name, signature, doc);
+        let mut ancestors: Vec<_> = self.table.ancestors(name).skip(1).map(|entry| self.class_name(entry.name())).collect();
+        ancestors.reverse();
+        class_item.ancestors = ancestors;
+        class_item.subclasses = self.table.classes()
+            .filter(|entry| entry.class.extends.as_ref().is_some_and(|parent| parent.node.name == *name))
+            .map(|entry| self.class_name(entry.name()))
+            .collect();
+
+        if let ClassBody::Members(members) = class.body.node.as_ref() {
+            for member in members {
+                let doc = DocComment::parse(&member.doc_comment().unwrap_or_default());
+                let (member_name, kind, signature) = match member.node.as_ref() {
+                    ClassMember::StaticBlock(_) => continue,
+                    ClassMember::Constructor(constructor) => {
+                        let mut signature = visibility(&constructor.visibility.node);
+                        signature.push(Token::Text("constructor".to_string()));
+                        self.parameters(&constructor.parameters.node, &mut signature);
+                        ("constructor", ItemKind::Constructor, signature)
+                    }
+                    ClassMember::Method(method) => {
+                        let mut signature = visibility(&method.visibility.node);
+                        if let Some(type_) = &method.return_type {
+                            self.type_(&type_.node, &mut signature);
+                            signature.push(Token::Text(" ".to_string()));
+                        }
+                        signature.push(Token::Text(method.name.node.name.clone()));
+                        self.parameters(&method.parameters.node, &mut signature);
+                        (method.name.node.name.as_str(), ItemKind::Method, signature)
+                    }
+                    ClassMember::Field(field) => {
+                        let mut signature = visibility(&field.visibility.node);
+                        if let Some(type_) = &field.type_ {
+                            self.type_(&type_.node, &mut signature);
+                            signature.push(Token::Text(" ".to_string()));
+                        }
+                        signature.push(Token::Text(field.name.node.name.clone()));
+                        push_value(&field.value, &mut signature);
+                        (field.name.node.name.as_str(), ItemKind::Field, signature)
+                    }
+                };
+                let anchor = format!("{}.{}", name, member_name);
+                class_item.members.push(item(member_name, kind, &anchor, signature, doc));
+            }
+        }
+        class_item
+    }
+
+    fn parameters(&self, parameters: &Parameters, signature: &mut Vec<Token>) {
+        signature.push(Token::Text("(".to_string()));
+        for (index, parameter) in parameters.params.iter().enumerate() {
+            if index > 0 {
+                signature.push(Token::Text(", ".to_string()));
+            }
+            let parameter = &parameter.node;
+            if let Some(type_) = &parameter.type_ {
+                self.type_(&type_.node, signature);
+                signature.push(Token::Text(" ".to_string()));
+            }
+            if parameter.reference.is_some() {
+                signature.push(Token::Text("@".to_string()));
+            }
+            signature.push(Token::Text(parameter.name.node.name.clone()));
+            push_value(&parameter.default_value, signature);
+        }
+        signature.push(Token::Text(")".to_string()));
+    }
+
+    /// A type written without its comments, its class names linked.
+    fn type_(&self, type_: &Type, signature: &mut Vec<Token>) {
+        signature.push(self.class_name(&type_.type_.node.name));
+        if !type_.generics.is_empty() {
+            signature.push(Token::Text("<".to_string()));
+            for (index, generic) in type_.generics.iter().enumerate() {
+                if index > 0 {
+                    signature.push(Token::Text(", ".to_string()));
+                }
+                self.type_(&generic.node, signature);
+            }
+            signature.push(Token::Text(">".to_string()));
+        }
+        if let Some(alternative) = &type_.alternative {
+            signature.push(Token::Text("|".to_string()));
+            self.type_(&alternative.node, signature);
+        }
+        if type_.nullable {
+            signature.push(Token::Text("?".to_string()));
+        }
+    }
+
+    fn class_name(&self, name: &str) -> Token {
+        match self.table.get(name) {
+            Some(entry) => Token::Link { text: name.to_string(), page: entry.file.to_string(), anchor: Some(name.to_string()) },
+            None => Token::Text(name.to_string()),
+        }
+    }
+}
+
+fn item(name: &str, kind: ItemKind, anchor: &str, signature: Vec<Token>, doc: DocComment) -> Item {
+    Item {
+        name: name.to_string(),
+        kind,
+        anchor: anchor.to_string(),
+        signature,
+        doc,
+        ancestors: Vec::new(),
+        subclasses: Vec::new(),
+        members: Vec::new(),
+    }
+}
+
+fn visibility(visibility: &Visibility) -> Vec<Token> {
+    let mut words = Vec::new();
+    if let Some(privacy) = &visibility.privacy {
+        words.push(privacy.node.to_string());
+    }
+    let flags = [
+        (visibility.static_.is_some(), "static"),
+        (visibility.abstract_.is_some(), "abstract"),
+        (visibility.final_.is_some(), "final"),
+        (visibility.const_.is_some(), "const"),
+    ];
+    words.extend(flags.iter().filter(|(set, _)| *set).map(|(_, word)| word.to_string()));
+    words.into_iter().map(|word| Token::Text(format!("{} ", word))).collect()
+}
+
+fn push_value(value: &Option<MetaNode<Expression>>, signature: &mut Vec<Token>) {
+    if let Some(value) = value {
+        signature.push(Token::Text(format!(" = {}", value.node.to_string().trim())));
+    }
+}
+
+/// The path of the index page, without its extension. The names of the generated pages start
+/// with a single `_`, which [`page_path`] never gives to a source.
+pub(crate) const INDEX: &str = "_index";
+
+/// Where the page of the source `path` goes, without its extension. The pages stay under the
+/// output directory: the `..` of the files included from above the entry become `_up`, and the
+/// parts starting with `_` get another one so as to not collide with the generated names.
+pub(crate) fn page_path(path: &str) -> String {
+    path.trim_start_matches('/')
+        .split('/')
+        .map(|part| match part {
+            ".." => "_up".to_string(),
+            part if part.starts_with('_') => format!("_{}", part),
+            part => part.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// The link from the page of `from` to the page of `to`, both being source paths.
+pub(crate) fn relative_link(from: &str, to: &str, extension: &str) -> String {
+    link(&page_path(from), &page_path(to), extension)
+}
+
+/// The link from the page of the source `from` to the index.
+pub(crate) fn index_link(from: &str, extension: &str) -> String {
+    link(&page_path(from), INDEX, extension)
+}
+
+/// The link from the page at `from` to the page at `to`.
+fn link(from: &str, to: &str, extension: &str) -> String {
+    let from: Vec<_> = from.split('/').collect();
+    let to: Vec<_> = to.split('/').collect();
+    let directories = &from[..from.len() - 1];
+    let common = directories.iter().zip(&to[..to.len() - 1]).take_while(|(a, b)| a == b).count();
+    let mut parts = vec![".."; directories.len() - common];
+    parts.extend(&to[common..]);
+    format!("{}.{}", parts.join("/"), extension)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use crate::analysis::project::tests::project;
+
+    use super::*;
+
+    pub fn documentation() -> Documentation {
+        Documentation::build(&project(&[
+            ("main", "include('lib/shapes');\n\n/**\n * The area of a shape.\n *\n * @param shape the shape\n *   to measure\n * @return its area\n */\nfunction area(Shape shape, integer scale = 1) => real { return 0; }\nfunction count() -> integer { return 1; }"),
+            ("lib/shapes", "/// Anything with an area.\nabstract class Shape {\n\t/** Its name */\n\tpublic static string name = 'shape';\n\tconstructor(Array<Shape?> parts) {}\n}\n\
+                class Square extends Shape { real side(@x) {} }\n/** Sides of a square */\nglobal const integer SIDES = 4, CORNERS;"),
+        ]))
+    }
+
+    fn text(tokens: &[Token]) -> String {
+        tokens.iter().map(|token| match token {
+            Token::Text(text) => text.clone(),
+            Token::Link { text, page, anchor } => format!("[{}]({}#{})", text, page, anchor.clone().unwrap_or_default()),
+        }).collect()
+    }
+
+    #[test]
+    fn test_doc_comment() {
+        let doc = DocComment::parse("Does things.\n\nCarefully.\n@param a the first\n  one\n@param b\n@returns nothing\n\n@since 2");
+        assert_eq!(doc.description, "Does things.\n\nCarefully.\n\n@since 2");
+        assert_eq!(doc.params, [("a".to_string(), "the first one".to_string()), ("b".to_string(), String::new())]);
+        assert_eq!(doc.returns.as_deref(), Some("nothing"));
+        assert!(DocComment::parse("").is_empty());
+    }
+
+    #[test]
+    fn test_build() {
+        let documentation = documentation();
+        let [main, shapes] = &documentation.pages[..] else { panic!() };
+        assert_eq!(text(&main.includes), "[lib/shapes](lib/shapes#)");
+
+        let area = &main.items[0];
+        assert_eq!(text(&area.signature), "function area([Shape](lib/shapes#Shape) shape, integer scale = 1) => real");
+        assert_eq!(area.doc.params, [("shape".to_string(), "the shape to measure".to_string())]);
+        assert_eq!(text(&main.items[1].signature), "function count() -> integer");
+
+        let signatures: Vec<_> = shapes.items.iter()
+            .flat_map(|item| std::iter::once(item).chain(&item.members))
+            .map(|item| format!("{} {}: {}", item.kind, item.anchor, text(&item.signature)))
+            .collect();
+        assert_eq!(signatures, [
+            "class Shape: abstract class Shape",
+            "field Shape.name: public static string name = 'shape'",
+            "constructor Shape.constructor: constructor(Array<[Shape](lib/shapes#Shape)?> parts)",
+            "class Square: class Square extends [Shape](lib/shapes#Shape)",
+            "method Square.side: real side(@x)",
+            "global SIDES: global const integer SIDES = 4",
+            "global CORNERS: global const integer CORNERS",
+        ]);
+        assert_eq!(shapes.items[0].doc.description, "Anything with an area.");
+        assert_eq!(shapes.items[0].members[0].doc.description, "Its name");
+        assert_eq!(text(&shapes.items[0].subclasses), "[Square](lib/shapes#Square)");
+        assert_eq!(text(&shapes.items[1].ancestors), "[Shape](lib/shapes#Shape)");
+        assert_eq!(shapes.items[3].doc.description, "Sides of a square");
+    }
+
+    #[test]
+    fn test_relative_link() {
+        assert_eq!(relative_link("main", "lib/shapes", "md"), "lib/shapes.md");
+        assert_eq!(relative_link("lib/shapes", "main", "md"), "../main.md");
+        assert_eq!(relative_link("lib/a/b", "lib/c", "html"), "../c.html");
+        assert_eq!(relative_link("lib/a", "lib/b", "html"), "b.html");
+        assert_eq!(relative_link("main", "../common", "md"), "_up/common.md");
+        assert_eq!(relative_link("../../a", "../b", "md"), "../b.md");
+        assert_eq!(page_path("/abs/../x"), "abs/_up/x");
+        assert_eq!(page_path("_up/_index"), "__up/__index");
+        assert_eq!(relative_link("../a", "_up/b", "md"), "../__up/b.md");
+        assert_eq!(index_link("lib/index", "md"), "../_index.md");
+    }
+}
@@ -1,2 +1,9 @@
+pub mod analysis;
 pub mod ast;
+pub mod doc;
+pub mod lint;
+pub mod lower;
+pub mod optimize;
+pub mod refactor;
+pub mod syntax;
 pub mod utils;
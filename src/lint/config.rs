@@ -0,0 +1,73 @@
+use std::collections::HashMap;
+
+use derive_more::Display;
+use serde::Deserialize;
+
+use crate::analysis::Severity;
+
+/// The content of a configuration file like:
+///
+/// ```toml
+/// [rules]
+/// null-comparison = "off"
+/// empty-block = { level = "error", allow-comments = false }
+///
+/// [rules.mixed-operators]
+/// style = "symbols"
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LintConfig {
+    #[serde(default)]
+    pub rules: HashMap<String, RuleConfig>,
+}
+
+impl LintConfig {
+    pub fn from_toml(text: &str) -> Result<Self, LintError> {
+        toml::from_str(text).map_err(|error| LintError::Toml(error.message().to_string()))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum RuleConfig {
+    Level(Level),
+    Table {
+        #[serde(default)]
+        level: Option<Level>,
+        /// The options specific to the rule
+        #[serde(flatten)]
+        options: toml::Table,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    Off,
+    Info,
+    Warning,
+    Error,
+}
+
+impl Level {
+    /// The severity of the diagnostics, `None` when the rule is turned off.
+    pub fn severity(&self) -> Option<Severity> {
+        match self {
+            Level::Off => None,
+            Level::Info => Some(Severity::Info),
+            Level::Warning => Some(Severity::Warning),
+            Level::Error => Some(Severity::Error),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Display)]
+pub enum LintError {
+    #[display(fmt = "invalid configuration: {}", _0)]
+    Toml(String),
+    #[display(fmt = "unknown rule `{}`", _0)]
+    UnknownRule(String),
+    #[display(fmt = "invalid configuration of `{}`: {}", rule, message)]
+    InvalidOption { rule: String, message: String },
+}
@@ -0,0 +1,95 @@
+use std::fmt::Display;
+
+use crate::ast::*;
+
+/// A change to the source: the text between two offsets replaced by a node printed again.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edit {
+    pub start: usize,
+    pub end: usize,
+    pub text: String,
+}
+
+impl Edit {
+    /// Prints `new` over `old` along with the comments following it, keeping the ones before it.
+    /// The edit is `None` for a node that was not parsed.
+    pub fn replace<T: Display>(old: &MetaNode<T>, new: &MetaNode<T>) -> Option<Self> {
+        let location = old.location?;
+        let trailing: usize = old.post_comments.iter().map(|trivia| trivia.to_string().len()).sum();
+        let text = new.post_comments.iter().fold(new.node.to_string(), |text, trivia| text + &trivia.to_string());
+        Some(Edit { start: location.start.offset, end: location.end.offset + trailing, text })
+    }
+
+    pub fn insert<T: Display>(offset: usize, node: &T) -> Self {
+        Edit { start: offset, end: offset, text: node.to_string() }
+    }
+}
+
+/// The edits fixing what one diagnostic reports, applied together or not at all.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fix {
+    /// Where the diagnostic is reported, for the suppression comments
+    pub location: Option<Location>,
+    pub edits: Vec<Edit>,
+}
+
+/// Applies the fixes to the source they were made for, leaving the rest of the text as it was.
+/// A fix overlapping one applied before it is left out, running the rules again on the result
+/// gives it another chance.
+pub fn apply_fixes(source: &str, fixes: &[Fix]) -> String {
+    let mut edits: Vec<&Edit> = Vec::new();
+    for fix in fixes {
+        let overlaps = fix.edits.iter().any(|edit| {
+            edits.iter().any(|applied| edit.start < applied.end && applied.start < edit.end || edit.start == applied.start)
+        });
+        if !overlaps {
+            edits.extend(&fix.edits);
+        }
+    }
+    edits.sort_by_key(|edit| edit.start);
+
+    let mut fixed = String::with_capacity(source.len());
+    let mut offset = 0;
+    for edit in edits {
+        fixed.push_str(&source[offset..edit.start]);
+        // Keep the tokens apart when a keyword replaces a symbol
+        if starts_word(&edit.text) && ends_word(&fixed) {
+            fixed.push(' ');
+        }
+        fixed.push_str(&edit.text);
+        if ends_word(&edit.text) && starts_word(&source[edit.end..]) {
+            fixed.push(' ');
+        }
+        offset = edit.end;
+    }
+    fixed.push_str(&source[offset..]);
+    fixed
+}
+
+fn is_word(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+fn starts_word(text: &str) -> bool {
+    text.chars().next().is_some_and(is_word)
+}
+
+fn ends_word(text: &str) -> bool {
+    text.chars().next_back().is_some_and(is_word)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fix(start: usize, end: usize, text: &str) -> Fix {
+        Fix { location: None, edits: vec![Edit { start, end, text: text.to_string() }] }
+    }
+
+    #[test]
+    fn test_apply_fixes() {
+        assert_eq!(apply_fixes("a&&b // c", &[fix(1, 3, "and")]), "a and b // c");
+        assert_eq!(apply_fixes("f()\ng()", &[fix(3, 3, ";"), fix(7, 7, ";")]), "f();\ng();");
+        assert_eq!(apply_fixes("a + b", &[fix(0, 3, "c"), fix(2, 5, "d")]), "c b");
+    }
+}
@@ -0,0 +1,185 @@
+//! Checks of style and likely mistakes that the language accepts. Each check is a `Rule` that can
+//! be given another severity, configured or turned off from a TOML file, and silenced for a line
+//! with a `// leeklint-disable-next-line rule-name` comment.
+
+pub mod config;
+pub mod suppression;
+pub mod rules;
+pub mod fix;
+
+pub use config::*;
+pub use suppression::*;
+pub use rules::*;
+pub use fix::*;
+
+use crate::{analysis::{Diagnostic, Severity}, ast::File};
+
+pub trait Rule {
+    /// The name used in the configuration and in suppression comments, like `empty-block`.
+    fn name(&self) -> &'static str;
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    /// Reads the options of the rule, the keys of its table in the configuration besides `level`.
+    fn configure(&mut self, options: &toml::Table) -> Result<(), String> {
+        match options.keys().next() {
+            Some(key) => Err(format!("unknown option `{}`", key)),
+            None => Ok(()),
+        }
+    }
+
+    fn check(&self, file: &File) -> Vec<Diagnostic>;
+
+    /// The fixes of the diagnostics the rule knows how to fix, as nodes edited and printed again.
+    fn fix(&self, _file: &File) -> Vec<Fix> {
+        Vec::new()
+    }
+}
+
+/// Every rule, with its default options.
+pub fn all_rules() -> Vec<Box<dyn Rule>> {
+    vec![
+        Box::new(MixedOperators::default()),
+        Box::new(NullComparison),
+        Box::new(EmptyBlock::default()),
+        Box::new(AssignmentInCondition),
+        Box::new(MissingSemicolon),
+        Box::new(ExplicitType),
+    ]
+}
+
+/// The enabled rules and the severity they report with.
+pub struct Linter {
+    rules: Vec<(Box<dyn Rule>, Severity)>,
+}
+
+impl Default for Linter {
+    fn default() -> Self {
+        Linter {
+            rules: all_rules().into_iter().map(|rule| {
+                let severity = rule.default_severity();
+                (rule, severity)
+            }).collect(),
+        }
+    }
+}
+
+impl Linter {
+    pub fn from_config(config: &LintConfig) -> Result<Self, LintError> {
+        if let Some(name) = config.rules.keys().find(|name| all_rules().iter().all(|rule| rule.name() != name.as_str())) {
+            return Err(LintError::UnknownRule(name.clone()));
+        }
+
+        let mut rules = Vec::new();
+        for mut rule in all_rules() {
+            let (level, options) = match config.rules.get(rule.name()) {
+                Some(RuleConfig::Level(level)) => (Some(*level), None),
+                Some(RuleConfig::Table { level, options }) => (*level, Some(options)),
+                None => (None, None),
+            };
+            if let Some(options) = options {
+                rule.configure(options).map_err(|message| LintError::InvalidOption { rule: rule.name().to_string(), message })?;
+            }
+            let severity = match level {
+                Some(level) => match level.severity() {
+                    Some(severity) => severity,
+                    None => continue,
+                },
+                None => rule.default_severity(),
+            };
+            rules.push((rule, severity));
+        }
+        Ok(Linter { rules })
+    }
+
+    pub fn from_toml(text: &str) -> Result<Self, LintError> {
+        Self::from_config(&LintConfig::from_toml(text)?)
+    }
+
+    /// Runs the enabled rules, leaving out the lines they are disabled for.
+    pub fn lint(&self, file: &File) -> Vec<Diagnostic> {
+        let suppressions = Suppressions::collect(file);
+        let mut diagnostics: Vec<Diagnostic> = self.rules.iter()
+            .flat_map(|(rule, severity)| {
+                rule.check(file).into_iter().map(|diagnostic| Diagnostic { severity: *severity, ..diagnostic.with_rule(rule.name()) })
+            })
+            .filter(|diagnostic| !suppressions.is_suppressed(diagnostic))
+            .collect();
+        diagnostics.sort_by_key(|diagnostic| diagnostic.location.map(|location| location.start));
+        diagnostics
+    }
+
+    /// Applies the fixes of the enabled rules to `source`, the text `file` was parsed from. Only
+    /// the fixed nodes are printed again, the rest of the source and its comments stay untouched.
+    pub fn fix(&self, source: &str, file: &File) -> String {
+        let suppressions = Suppressions::collect(file);
+        let fixes: Vec<Fix> = self.rules.iter()
+            .flat_map(|(rule, _)| {
+                rule.fix(file).into_iter().filter(|fix| !suppressions.is_disabled(Some(rule.name()), fix.location))
+            })
+            .collect();
+        apply_fixes(source, &fixes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::parse_file;
+
+    use super::*;
+
+    fn lint(linter: &Linter, input: &str) -> Vec<String> {
+        linter.lint(&parse_file(input)).iter().map(|diagnostic| diagnostic.to_string()).collect()
+    }
+
+    #[test]
+    fn test_default_rules() {
+        assert_eq!(lint(&Linter::default(), "if (a == null and b && c) {}"), [
+            "1:7: warning[null-comparison]: comparison against `null` with `==`",
+            "1:21: warning[mixed-operators]: `&&` is spelled `and` elsewhere in the file",
+            "1:27: warning[empty-block]: empty block",
+        ]);
+    }
+
+    #[test]
+    fn test_configuration() {
+        let linter = Linter::from_toml(r#"
+            [rules]
+            null-comparison = "off"
+            empty-block = { level = "error", allow-comments = false }
+
+            [rules.mixed-operators]
+            style = "keywords"
+        "#).unwrap();
+        assert_eq!(lint(&linter, "if (a == null && b) { /* nothing */ }"), [
+            "1:15: warning[mixed-operators]: `&&` is spelled `and` by the configuration",
+            "1:21: error[empty-block]: empty block",
+        ]);
+
+        assert!(matches!(Linter::from_toml("[rules]\nno-such-rule = \"off\""), Err(LintError::UnknownRule(name)) if name == "no-such-rule"));
+        assert_eq!(
+            Linter::from_toml("[rules.mixed-operators]\nstyle = \"emoji\"").err().unwrap().to_string(),
+            "invalid configuration of `mixed-operators`: `style` should be \"consistent\", \"keywords\" or \"symbols\""
+        );
+        assert!(Linter::from_toml("[rules]\nempty-block = \"loud\"").is_err());
+    }
+
+    #[test]
+    fn test_suppression() {
+        let input = "// leeklint-disable-next-line empty-block\nif (a == null) {}\n\
+                     // leeklint-disable-next-line\nwhile (x = f()) {}\nfor (;;) {}";
+        assert_eq!(lint(&Linter::default(), input), [
+            "2:7: warning[null-comparison]: comparison against `null` with `==`",
+            "5:10: warning[empty-block]: empty block",
+        ]);
+    }
+
+    #[test]
+    fn test_fix() {
+        let source = "// leeklint-disable-next-line\nvar a = 1\nvar b = not a && c // d\n";
+        let fixed = Linter::default().fix(source, &parse_file(source));
+        assert_eq!(fixed, "// leeklint-disable-next-line\nvar a = 1\nboolean b = not a and c; // d\n");
+    }
+}
@@ -0,0 +1,99 @@
+use crate::{analysis::Diagnostic, ast::{visit::*, *}, lint::Rule};
+
+/// Assignments making up the whole condition of an `if`, a loop or a ternary, like `if (a = b)`,
+/// often meant as a comparison. Wrapping the assignment of a statement in another pair of
+/// parentheses tells it is intended.
+#[derive(Debug)]
+pub struct AssignmentInCondition;
+
+impl Rule for AssignmentInCondition {
+    fn name(&self) -> &'static str {
+        "assignment-in-condition"
+    }
+
+    fn check(&self, file: &File) -> Vec<Diagnostic> {
+        let mut finder = AssignmentFinder { diagnostics: Vec::new() };
+        finder.visit_file(file);
+        finder.diagnostics
+    }
+}
+
+struct AssignmentFinder {
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl AssignmentFinder {
+    fn check(&mut self, condition: &Expression) {
+        if let Expression::Assign(assign) = condition {
+            if let Some((op, _)) = assign.right.first() {
+                let message = match op.node.as_ref() {
+                    AssignOp::Assign => "assignment in a condition, did you mean `==`?".to_string(),
+                    op => format!("assignment `{}` in a condition", op),
+                };
+                self.diagnostics.push(Diagnostic::warning(message, op.location));
+            }
+        }
+    }
+
+    /// Checks a condition whose parentheses belong to the statement.
+    fn check_parenthesized(&mut self, condition: &Expression) {
+        match condition {
+            Expression::Primary(PrimaryExpr::ParenthesizedExpr(inner)) => self.check(&inner.node),
+            condition => self.check(condition),
+        }
+    }
+
+    fn check_statement(&mut self, statement: &Statement) {
+        match statement {
+            Statement::IfStatement(if_statement) => self.check(&if_statement.condition.node.expression.node),
+            Statement::WhileStatement(while_statement) => self.check_parenthesized(&while_statement.condition.node),
+            Statement::DoWhileStatement(do_while) => self.check_parenthesized(&do_while.condition.node),
+            Statement::ForStatement(for_statement) => {
+                if let ForHeader::ForIter(ForIter { condition: Some(condition), .. }) = for_statement.for_header.node.as_ref() {
+                    self.check(&condition.node);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl<'ast> Visitor<'ast> for AssignmentFinder {
+    fn visit_global_statement(&mut self, statement: &'ast MetaNode<GlobalStatement>) {
+        if let GlobalStatement::Statement(node) = statement.node.as_ref() {
+            self.check_statement(node);
+        }
+        walk_global_statement(self, statement);
+    }
+
+    fn visit_statement(&mut self, statement: &'ast MetaNode<Statement>) {
+        self.check_statement(&statement.node);
+        walk_statement(self, statement);
+    }
+
+    fn visit_expression(&mut self, expression: &'ast Expression) {
+        if let Expression::TernaryExpression(ternary) = expression {
+            self.check_parenthesized(&ternary.condition.node);
+        }
+        walk_expression(self, expression);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::parse_file;
+
+    use super::*;
+
+    #[test]
+    fn test_assignment_in_condition() {
+        let file = parse_file("if (a = 1) {} while (b += 2) {} do {} while (c = f());\nif ((d = g())) {} var x = (y = 2) ? 1 : 0;");
+        let messages: Vec<_> = AssignmentInCondition.check(&file).iter().map(|diagnostic| diagnostic.to_string()).collect();
+        assert_eq!(messages, [
+            "1:7: warning: assignment in a condition, did you mean `==`?",
+            "1:24: warning: assignment `+=` in a condition",
+            "1:48: warning: assignment in a condition, did you mean `==`?",
+            "2:30: warning: assignment in a condition, did you mean `==`?",
+        ]);
+    }
+}
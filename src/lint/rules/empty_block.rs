@@ -0,0 +1,114 @@
+use crate::{analysis::Diagnostic, ast::{visit::*, *}, lint::Rule};
+
+/// Blocks of statements with nothing in them, like `if (a) {}` or `catch (e) {}`. The bodies of
+/// functions are left out, being often empty on purpose.
+#[derive(Debug)]
+pub struct EmptyBlock {
+    /// Whether a block holding only a comment is fine, the comment telling why it is empty
+    pub allow_comments: bool,
+}
+
+impl Default for EmptyBlock {
+    fn default() -> Self {
+        EmptyBlock { allow_comments: true }
+    }
+}
+
+impl Rule for EmptyBlock {
+    fn name(&self) -> &'static str {
+        "empty-block"
+    }
+
+    fn configure(&mut self, options: &toml::Table) -> Result<(), String> {
+        for (key, value) in options {
+            match (key.as_str(), value.as_bool()) {
+                ("allow-comments", Some(allow)) => self.allow_comments = allow,
+                ("allow-comments", None) => return Err("`allow-comments` should be a boolean".to_string()),
+                (key, _) => return Err(format!("unknown option `{}`", key)),
+            }
+        }
+        Ok(())
+    }
+
+    fn check(&self, file: &File) -> Vec<Diagnostic> {
+        let mut finder = EmptyBlockFinder { allow_comments: self.allow_comments, diagnostics: Vec::new() };
+        finder.visit_file(file);
+        finder.diagnostics
+    }
+}
+
+struct EmptyBlockFinder {
+    allow_comments: bool,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl EmptyBlockFinder {
+    fn check(&mut self, block: &BlockStatement, location: Option<Location>) {
+        let empty = match block {
+            BlockStatement::Empty(empty) => !self.allow_comments || !has_comments(&empty.pre_comments, &empty.post_comments),
+            BlockStatement::StatementList(statements) => statements.iter().all(|statement| {
+                matches!(statement.node.as_ref(), Statement::Empty(_))
+                    && (!self.allow_comments || !has_comments(&statement.pre_comments, &statement.post_comments))
+            }),
+        };
+        if empty {
+            self.diagnostics.push(Diagnostic::warning("empty block", location));
+        }
+    }
+
+    fn check_statement(&mut self, statement: &Statement, location: Option<Location>) {
+        match statement {
+            Statement::Block(block) => self.check(block, location),
+            Statement::TryStatement(try_statement) => {
+                self.check(&try_statement.block.node, try_statement.block.location);
+                for catch in &try_statement.catches {
+                    self.check(&catch.node.block.node, catch.node.block.location);
+                }
+                if let Some(finally) = &try_statement.finally {
+                    self.check(&finally.node, finally.location);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn has_comments(pre_comments: &[CommentOrWhitespace], post_comments: &[CommentOrWhitespace]) -> bool {
+    pre_comments.iter().chain(post_comments).any(|trivia| !matches!(trivia, CommentOrWhitespace::Whitespace(_)))
+}
+
+impl<'ast> Visitor<'ast> for EmptyBlockFinder {
+    fn visit_global_statement(&mut self, statement: &'ast MetaNode<GlobalStatement>) {
+        if let GlobalStatement::Statement(node) = statement.node.as_ref() {
+            self.check_statement(node, statement.location);
+        }
+        walk_global_statement(self, statement);
+    }
+
+    fn visit_statement(&mut self, statement: &'ast MetaNode<Statement>) {
+        self.check_statement(&statement.node, statement.location);
+        walk_statement(self, statement);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::parse_file;
+
+    use super::*;
+
+    fn check(rule: &EmptyBlock, input: &str) -> Vec<String> {
+        rule.check(&parse_file(input)).iter().map(|diagnostic| diagnostic.to_string()).collect()
+    }
+
+    #[test]
+    fn test_empty_block() {
+        let rule = EmptyBlock::default();
+        assert_eq!(check(&rule, "function f() {} while (a) { }\ntry { f(); } catch (e) {}"), [
+            "1:27: warning: empty block",
+            "2:24: warning: empty block",
+        ]);
+        assert!(check(&rule, "if (a) { // nothing to do\n}").is_empty());
+        assert_eq!(check(&EmptyBlock { allow_comments: false }, "if (a) { /* nothing */ }").len(), 1);
+    }
+}
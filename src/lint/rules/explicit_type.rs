@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+
+use crate::{
+    analysis::{infer_type, Access, Diagnostic, Resolution, Severity, ValueType},
+    ast::{visit::*, *},
+    lint::{Edit, Fix, Rule},
+};
+
+/// `var` declarations whose values all have the same known type, which can be written instead.
+/// Every later assignment to the variables has to keep that type.
+#[derive(Debug)]
+pub struct ExplicitType;
+
+impl Rule for ExplicitType {
+    fn name(&self) -> &'static str {
+        "explicit-type"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Info
+    }
+
+    fn check(&self, file: &File) -> Vec<Diagnostic> {
+        untyped_declarations(file).into_iter()
+            .map(|(var, value_type)| {
+                let message = format!("`var` can be replaced by `{}`", value_type);
+                Diagnostic::warning(message, var.location)
+            })
+            .collect()
+    }
+
+    fn fix(&self, file: &File) -> Vec<Fix> {
+        untyped_declarations(file).into_iter()
+            .filter_map(|(var, value_type)| {
+                let type_ = Type {
+                    type_: MetaNode::new(Identifier { name: value_type.to_string() }),
+                    generics: Vec::new(),
+                    alternative: None,
+                    nullable: false,
+                };
+                let typed = MetaNode { node: Box::new(VarType::Typed(MetaNode::new(type_))), ..var.clone() };
+                Some(Fix { location: var.location, edits: vec![Edit::replace(var, &typed)?] })
+            })
+            .collect()
+    }
+}
+
+/// The `var` keywords that can be replaced, with the type of the values.
+fn untyped_declarations(file: &File) -> Vec<(&MetaNode<VarType>, ValueType)> {
+    let resolution = Resolution::resolve(file);
+    let mut assignments = Assignments { writes: HashMap::new() };
+    assignments.visit_file(file);
+    let mut finder = DeclarationFinder { resolution: &resolution, writes: &assignments.writes, declarations: Vec::new() };
+    finder.visit_file(file);
+    finder.declarations
+}
+
+struct DeclarationFinder<'ast, 'a> {
+    resolution: &'a Resolution,
+    writes: &'a HashMap<usize, Write>,
+    declarations: Vec<(&'ast MetaNode<VarType>, ValueType)>,
+}
+
+impl DeclarationFinder<'_, '_> {
+    /// Whether every assignment to the variable declared by `name` keeps `value_type`.
+    fn keeps_type(&self, name: &MetaNode<Identifier>, value_type: &ValueType) -> bool {
+        let Some(binding) = self.resolution.bindings.iter().position(|binding| binding.location == name.location) else {
+            return false;
+        };
+        self.resolution.references_to(binding)
+            .filter(|reference| reference.access != Access::Read)
+            .all(|reference| {
+                let write = reference.location.and_then(|location| self.writes.get(&location.start.offset));
+                match write {
+                    Some(Write::Value(assigned)) => assigned == value_type,
+                    Some(Write::Update) => matches!(value_type, ValueType::Integer | ValueType::Real),
+                    None => false,
+                }
+            })
+    }
+}
+
+impl<'ast> Visitor<'ast> for DeclarationFinder<'ast, '_> {
+    fn visit_var_declaration(&mut self, declaration: &'ast VarDeclaration) {
+        if let VarType::Var = declaration.type_.node.as_ref() {
+            let types: Option<Vec<ValueType>> = declaration.values.iter()
+                .map(|(_, value)| value.as_ref().map(|value| infer_type(&value.node)))
+                .collect();
+            if let Some(value_type) = types.and_then(|types| same_type(&types)) {
+                if declaration.values.iter().all(|(name, _)| self.keeps_type(name, &value_type)) {
+                    self.declarations.push((&declaration.type_, value_type));
+                }
+            }
+        }
+        walk_var_declaration(self, declaration);
+    }
+}
+
+/// What an assignment to a variable gives it.
+enum Write {
+    /// A value of this type, from `=` or a compound assignment keeping the type of its value
+    Value(ValueType),
+    /// `++` or `--`, which keep the type of a number
+    Update,
+}
+
+/// The assignments to variables, by offset of the variable.
+struct Assignments {
+    writes: HashMap<usize, Write>,
+}
+
+impl<'ast> Visitor<'ast> for Assignments {
+    fn visit_expression(&mut self, expression: &'ast Expression) {
+        let (target, write) = match expression {
+            Expression::Assign(assign) => match assign.right.as_slice() {
+                [(op, value)] => {
+                    let value_type = infer_type(&value.node);
+                    let keeps = match op.node.as_ref() {
+                        AssignOp::Assign => true,
+                        AssignOp::Add => matches!(value_type, ValueType::Integer | ValueType::Real | ValueType::String),
+                        AssignOp::Sub | AssignOp::Mul => matches!(value_type, ValueType::Integer | ValueType::Real),
+                        _ => false,
+                    };
+                    (assign.left.as_identifier(), keeps.then_some(Write::Value(value_type)))
+                }
+                _ => (None, None),
+            },
+            Expression::PreUpdate(update) => (update.expr.as_identifier(), Some(Write::Update)),
+            Expression::PostUpdate(update) => (update.expr.as_identifier(), Some(Write::Update)),
+            _ => (None, None),
+        };
+        if let (Some(location), Some(write)) = (target.and_then(|target| target.location), write) {
+            self.writes.insert(location.start.offset, write);
+        }
+        walk_expression(self, expression);
+    }
+}
+
+/// The type shared by every value, if it is known and can be written as a single name.
+fn same_type(types: &[ValueType]) -> Option<ValueType> {
+    let first = types.first()?;
+    let known = !matches!(first, ValueType::Any | ValueType::Null | ValueType::Union(_));
+    (known && types.iter().all(|value_type| value_type == first)).then(|| first.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{lint::apply_fixes, utils::parse_file};
+
+    use super::*;
+
+    #[test]
+    fn test_explicit_type() {
+        let input = "var a = 1, b = 2; var /* c */ c = 'x'; var d = f(); var e; var g = 1, h = 1.5;\nfor (var i = 0; i < 2; i++) {}";
+        let file = parse_file(input);
+        let messages: Vec<_> = ExplicitType.check(&file).iter().map(|diagnostic| diagnostic.to_string()).collect();
+        assert_eq!(messages, [
+            "1:1: warning: `var` can be replaced by `integer`",
+            "1:19: warning: `var` can be replaced by `string`",
+            "2:6: warning: `var` can be replaced by `integer`",
+        ]);
+        assert_eq!(
+            apply_fixes(input, &ExplicitType.fix(&file)),
+            "integer a = 1, b = 2; string /* c */ c = 'x'; var d = f(); var e; var g = 1, h = 1.5;\nfor (integer i = 0; i < 2; i++) {}"
+        );
+    }
+
+    #[test]
+    fn test_assignments() {
+        let input = "var x = 1;\nx = 'str';\nvar y = 1; y = 2; y += 3; y--;\nvar z = 1; z += 0.5;\nvar s = ''; s += 'a';";
+        let file = parse_file(input);
+        assert_eq!(
+            apply_fixes(input, &ExplicitType.fix(&file)),
+            "var x = 1;\nx = 'str';\ninteger y = 1; y = 2; y += 3; y--;\nvar z = 1; z += 0.5;\nstring s = ''; s += 'a';"
+        );
+    }
+}
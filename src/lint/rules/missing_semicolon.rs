@@ -0,0 +1,101 @@
+use std::fmt::Display;
+
+use crate::{analysis::{Diagnostic, Severity}, ast::{scan::{advance, code_end}, visit::*, *}, lint::{Edit, Fix, Rule}};
+
+/// Statements ending without a `;`, where the grammar finds their end from the start of the next
+/// one. The fix adds it right after the code, before any comment.
+#[derive(Debug)]
+pub struct MissingSemicolon;
+
+impl Rule for MissingSemicolon {
+    fn name(&self) -> &'static str {
+        "missing-semicolon"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Info
+    }
+
+    fn check(&self, file: &File) -> Vec<Diagnostic> {
+        missing_semicolons(file).into_iter()
+            .map(|position| Diagnostic::warning("missing `;`", Some(Location { start: position, end: position })))
+            .collect()
+    }
+
+    fn fix(&self, file: &File) -> Vec<Fix> {
+        missing_semicolons(file).into_iter()
+            .map(|position| Fix {
+                location: Some(Location { start: position, end: position }),
+                edits: vec![Edit::insert(position.offset, &Semi(true))],
+            })
+            .collect()
+    }
+}
+
+/// Where the missing `;` go.
+fn missing_semicolons(file: &File) -> Vec<Position> {
+    let mut finder = SemicolonFinder { positions: Vec::new() };
+    finder.visit_file(file);
+    finder.positions
+}
+
+struct SemicolonFinder {
+    positions: Vec<Position>,
+}
+
+impl SemicolonFinder {
+    fn check<T: Display>(&mut self, statement: &MetaNode<T>, semi: Option<&MetaNode<Semi>>) {
+        if let (Some(MetaNode { node, .. }), Some(location)) = (semi, statement.location) {
+            if !node.0 {
+                let text = statement.node.to_string();
+                self.positions.push(advance(location.start, &text[..code_end(&text)]));
+            }
+        }
+    }
+}
+
+fn semi(statement: &Statement) -> Option<&MetaNode<Semi>> {
+    match statement {
+        Statement::ExpressionStatement(statement) => Some(&statement.semi),
+        Statement::VarDeclaration(statement) => Some(&statement.semi),
+        Statement::ReturnStatement(statement) => Some(&statement.semi),
+        Statement::BreakStatement(BreakStatement::Break(semi) | BreakStatement::Continue(semi)) => Some(semi),
+        Statement::ThrowStatement(statement) => Some(&statement.semi),
+        Statement::DoWhileStatement(statement) => Some(&statement.semi),
+        _ => None,
+    }
+}
+
+impl<'ast> Visitor<'ast> for SemicolonFinder {
+    fn visit_global_statement(&mut self, statement: &'ast MetaNode<GlobalStatement>) {
+        let semi = match statement.node.as_ref() {
+            GlobalStatement::Statement(node) => semi(node),
+            GlobalStatement::IncludeStatement(include) => Some(&include.semi),
+            GlobalStatement::GlobalDefinition(definition) => Some(&definition.semi),
+            _ => None,
+        };
+        self.check(statement, semi);
+        walk_global_statement(self, statement);
+    }
+
+    fn visit_statement(&mut self, statement: &'ast MetaNode<Statement>) {
+        self.check(statement, semi(&statement.node));
+        walk_statement(self, statement);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{lint::apply_fixes, utils::parse_file};
+
+    use super::*;
+
+    #[test]
+    fn test_missing_semicolon() {
+        let input = "var a = 1 // one\nwhile (a) { a-- }\nreturn a;";
+        let file = parse_file(input);
+        let messages: Vec<_> = MissingSemicolon.check(&file).iter().map(|diagnostic| diagnostic.to_string()).collect();
+        assert_eq!(messages, ["1:10: warning: missing `;`", "2:16: warning: missing `;`"]);
+        assert_eq!(apply_fixes(input, &MissingSemicolon.fix(&file)), "var a = 1; // one\nwhile (a) { a--; }\nreturn a;");
+    }
+}
@@ -0,0 +1,202 @@
+use crate::{analysis::Diagnostic, ast::{visit::*, *}, lint::{Edit, Fix, Rule}};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OperatorStyle {
+    /// The spelling of the first logical operator of the file
+    #[default]
+    Consistent,
+    /// `and`, `or` and `not`
+    Keywords,
+    /// `&&`, `||` and `!`
+    Symbols,
+}
+
+/// Logical operators spelled both as keywords and as symbols, like `a and b && c`.
+#[derive(Debug, Default)]
+pub struct MixedOperators {
+    pub style: OperatorStyle,
+}
+
+impl Rule for MixedOperators {
+    fn name(&self) -> &'static str {
+        "mixed-operators"
+    }
+
+    fn configure(&mut self, options: &toml::Table) -> Result<(), String> {
+        for (key, value) in options {
+            match (key.as_str(), value.as_str()) {
+                ("style", Some("consistent")) => self.style = OperatorStyle::Consistent,
+                ("style", Some("keywords")) => self.style = OperatorStyle::Keywords,
+                ("style", Some("symbols")) => self.style = OperatorStyle::Symbols,
+                ("style", _) => return Err("`style` should be \"consistent\", \"keywords\" or \"symbols\"".to_string()),
+                (key, _) => return Err(format!("unknown option `{}`", key)),
+            }
+        }
+        Ok(())
+    }
+
+    fn check(&self, file: &File) -> Vec<Diagnostic> {
+        let mut collector = OperatorCollector::default();
+        collector.visit_file(file);
+        self.misspelled(&mut collector.operators)
+            .map(|(operator, keywords, reason)| {
+                let expected = if keywords { operator.keyword } else { operator.symbol };
+                let message = format!("`{}` is spelled `{}` {}", operator.spelling(), expected, reason);
+                Diagnostic::warning(message, operator.location())
+            })
+            .collect()
+    }
+
+    fn fix(&self, file: &File) -> Vec<Fix> {
+        let mut collector = OperatorCollector::default();
+        collector.visit_file(file);
+        self.misspelled(&mut collector.operators)
+            .filter_map(|(operator, keywords, _)| {
+                Some(Fix { location: operator.location(), edits: vec![operator.respell(keywords)?] })
+            })
+            .collect()
+    }
+}
+
+impl MixedOperators {
+    /// The operators not spelled as expected, with whether keywords are and why.
+    fn misspelled<'a, 'ast>(
+        &self,
+        operators: &'a mut [Operator<'ast>],
+    ) -> impl Iterator<Item = (&'a Operator<'ast>, bool, &'static str)> {
+        operators.sort_by_key(|operator| operator.location().map(|location| location.start));
+        let expected = match self.style {
+            OperatorStyle::Consistent => operators.first().map(|first| (first.is_keyword(), "elsewhere in the file")),
+            OperatorStyle::Keywords => Some((true, "by the configuration")),
+            OperatorStyle::Symbols => Some((false, "by the configuration")),
+        };
+        operators.iter().filter_map(move |operator| match expected {
+            Some((keywords, reason)) if operator.is_keyword() != keywords => Some((operator, keywords, reason)),
+            _ => None,
+        })
+    }
+}
+
+enum OperatorNode<'a> {
+    And(&'a MetaNode<LogicalAndOp>),
+    Or(&'a MetaNode<LogicalOrOp>),
+    Not(&'a MetaNode<UnaryOp>),
+}
+
+struct Operator<'a> {
+    node: OperatorNode<'a>,
+    keyword: &'static str,
+    symbol: &'static str,
+}
+
+impl Operator<'_> {
+    fn spelling(&self) -> &str {
+        match &self.node {
+            OperatorNode::And(op) => match op.node.as_ref() {
+                LogicalAndOp::And(spelling) => spelling,
+            },
+            OperatorNode::Or(op) => match op.node.as_ref() {
+                LogicalOrOp::Or(spelling) => spelling,
+            },
+            OperatorNode::Not(op) => match op.node.as_ref() {
+                UnaryOp::Not(spelling) => spelling,
+                _ => unreachable!("only `not` is collected"),
+            },
+        }
+    }
+
+    fn location(&self) -> Option<Location> {
+        match &self.node {
+            OperatorNode::And(op) => op.location,
+            OperatorNode::Or(op) => op.location,
+            OperatorNode::Not(op) => op.location,
+        }
+    }
+
+    fn is_keyword(&self) -> bool {
+        self.spelling() == self.keyword
+    }
+
+    /// The edit spelling the operator as a keyword or as a symbol.
+    fn respell(&self, keyword: bool) -> Option<Edit> {
+        let spelling = if keyword { self.keyword } else { self.symbol }.to_string();
+        match self.node {
+            OperatorNode::And(op) => Edit::replace(op, &MetaNode { node: Box::new(LogicalAndOp::And(spelling)), ..op.clone() }),
+            OperatorNode::Or(op) => Edit::replace(op, &MetaNode { node: Box::new(LogicalOrOp::Or(spelling)), ..op.clone() }),
+            OperatorNode::Not(op) => {
+                let mut not = MetaNode { node: Box::new(UnaryOp::Not(spelling)), ..op.clone() };
+                // `not a` becomes `!a`
+                let spaces = not.post_comments.iter().all(|trivia| match trivia {
+                    CommentOrWhitespace::Whitespace(whitespace) => !whitespace.contains('\n'),
+                    _ => false,
+                });
+                if !keyword && spaces {
+                    not.post_comments.clear();
+                }
+                Edit::replace(op, &not)
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct OperatorCollector<'ast> {
+    operators: Vec<Operator<'ast>>,
+}
+
+impl<'ast> Visitor<'ast> for OperatorCollector<'ast> {
+    fn visit_expression(&mut self, expression: &'ast Expression) {
+        match expression {
+            Expression::LogicalAnd(chain) => {
+                for (op, _) in &chain.right {
+                    self.operators.push(Operator { node: OperatorNode::And(op), keyword: "and", symbol: "&&" });
+                }
+            }
+            Expression::LogicalOr(chain) => {
+                for (op, _) in &chain.right {
+                    self.operators.push(Operator { node: OperatorNode::Or(op), keyword: "or", symbol: "||" });
+                }
+            }
+            Expression::Unary(unary) => {
+                if let UnaryOp::Not(_) = unary.op.node.as_ref() {
+                    self.operators.push(Operator { node: OperatorNode::Not(&unary.op), keyword: "not", symbol: "!" });
+                }
+            }
+            _ => {}
+        }
+        walk_expression(self, expression);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{lint::apply_fixes, utils::parse_file};
+
+    use super::*;
+
+    fn check(rule: &MixedOperators, input: &str) -> Vec<String> {
+        rule.check(&parse_file(input)).iter().map(|diagnostic| diagnostic.to_string()).collect()
+    }
+
+    #[test]
+    fn test_mixed_operators() {
+        let rule = MixedOperators::default();
+        assert_eq!(check(&rule, "var x = not a || b and c;\nif (!x) {}"), [
+            "1:15: warning: `||` is spelled `or` elsewhere in the file",
+            "2:5: warning: `!` is spelled `not` elsewhere in the file",
+        ]);
+        assert!(check(&rule, "var x = !a || b && c;").is_empty());
+
+        let rule = MixedOperators { style: OperatorStyle::Symbols };
+        assert_eq!(check(&rule, "var x = a and b;"), ["1:11: warning: `and` is spelled `&&` by the configuration"]);
+    }
+
+    #[test]
+    fn test_fix() {
+        let fix = |rule: &MixedOperators, input: &str| apply_fixes(input, &rule.fix(&parse_file(input)));
+        let rule = MixedOperators::default();
+        assert_eq!(fix(&rule, "x = not a or b||c; y = !(not  b) ||/* c */d"), "x = not a or b or c; y = not(not  b) or/* c */d");
+        let rule = MixedOperators { style: OperatorStyle::Symbols };
+        assert_eq!(fix(&rule, "x = not a or\n  not\nb;"), "x = !a ||\n  !\nb;");
+    }
+}
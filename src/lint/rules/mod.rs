@@ -0,0 +1,13 @@
+pub mod mixed_operators;
+pub mod null_comparison;
+pub mod empty_block;
+pub mod assignment_in_condition;
+pub mod missing_semicolon;
+pub mod explicit_type;
+
+pub use mixed_operators::*;
+pub use null_comparison::*;
+pub use empty_block::*;
+pub use assignment_in_condition::*;
+pub use missing_semicolon::*;
+pub use explicit_type::*;
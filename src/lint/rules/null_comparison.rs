@@ -0,0 +1,59 @@
+use crate::{analysis::Diagnostic, ast::{visit::*, *}, lint::Rule};
+
+/// Comparisons against `null` with `==` or `!=`, which go through the loose equality of the
+/// language rather than testing for the absence of a value.
+#[derive(Debug)]
+pub struct NullComparison;
+
+impl Rule for NullComparison {
+    fn name(&self) -> &'static str {
+        "null-comparison"
+    }
+
+    fn check(&self, file: &File) -> Vec<Diagnostic> {
+        let mut finder = NullComparisonFinder { diagnostics: Vec::new() };
+        finder.visit_file(file);
+        finder.diagnostics
+    }
+}
+
+struct NullComparisonFinder {
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'ast> Visitor<'ast> for NullComparisonFinder {
+    fn visit_expression(&mut self, expression: &'ast Expression) {
+        if let Expression::Relation(chain) = expression {
+            let mut left = chain.left.as_ref();
+            for (op, right) in &chain.right {
+                if matches!(op.node.as_ref(), RelationOp::Eq | RelationOp::NotEq) && (is_null(left) || is_null(&right.node)) {
+                    let message = format!("comparison against `null` with `{}`", op.node);
+                    self.diagnostics.push(Diagnostic::warning(message, op.location));
+                }
+                left = &right.node;
+            }
+        }
+        walk_expression(self, expression);
+    }
+}
+
+fn is_null(expression: &Expression) -> bool {
+    matches!(expression, Expression::Primary(PrimaryExpr::Literal(Literal::Null)))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::parse_file;
+
+    use super::*;
+
+    #[test]
+    fn test_null_comparison() {
+        let diagnostics = NullComparison.check(&parse_file("if (a == null || null != f(b) || a < null) {}"));
+        let messages: Vec<_> = diagnostics.iter().map(|diagnostic| diagnostic.to_string()).collect();
+        assert_eq!(messages, [
+            "1:7: warning: comparison against `null` with `==`",
+            "1:23: warning: comparison against `null` with `!=`",
+        ]);
+    }
+}
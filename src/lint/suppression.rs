@@ -0,0 +1,72 @@
+use std::collections::HashMap;
+
+use crate::{analysis::Diagnostic, ast::*};
+
+use crate::ast::scan::trivia;
+
+const DISABLE_NEXT_LINE: &str = "leeklint-disable-next-line";
+
+/// The lines where `// leeklint-disable-next-line` comments turn rules off. A comment without
+/// rule names turns every rule off, otherwise the names are separated by commas or spaces.
+#[derive(Debug, Default)]
+pub struct Suppressions {
+    /// The rules turned off by line, `None` for all of them
+    lines: HashMap<u32, Option<Vec<String>>>,
+}
+
+impl Suppressions {
+    pub fn collect(file: &File) -> Self {
+        let mut suppressions = Suppressions::default();
+        for (line, comment) in single_line_comments(&file.to_string()) {
+            let Some(rules) = comment.trim_start_matches('/').trim().strip_prefix(DISABLE_NEXT_LINE) else {
+                continue;
+            };
+            if !rules.is_empty() && !rules.starts_with(char::is_whitespace) {
+                continue;
+            }
+            let rules: Vec<String> = rules.split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|rule| !rule.is_empty())
+                .map(str::to_string)
+                .collect();
+            let entry = suppressions.lines.entry(line + 1).or_insert(Some(Vec::new()));
+            match (entry, rules.is_empty()) {
+                (entry, true) => *entry = None,
+                (Some(names), false) => names.extend(rules),
+                (None, false) => {}
+            }
+        }
+        suppressions
+    }
+
+    pub fn is_suppressed(&self, diagnostic: &Diagnostic) -> bool {
+        self.is_disabled(diagnostic.rule.as_deref(), diagnostic.location)
+    }
+
+    /// Whether `rule` is turned off where `location` starts, `None` standing for a rule that can
+    /// only be turned off along with all the others.
+    pub fn is_disabled(&self, rule: Option<&str>, location: Option<Location>) -> bool {
+        let Some(location) = location else {
+            return false;
+        };
+        match self.lines.get(&location.start.line) {
+            Some(None) => true,
+            Some(Some(rules)) => rule.is_some_and(|rule| rules.iter().any(|disabled| disabled == rule)),
+            None => false,
+        }
+    }
+}
+
+/// The `//` comments of a source with their line.
+fn single_line_comments(source: &str) -> Vec<(u32, String)> {
+    let mut line = 1;
+    let mut counted = 0;
+    let mut comments = Vec::new();
+    for (offset, item) in trivia(source) {
+        if let CommentOrWhitespace::SingleLineComment(comment) = item {
+            line += source[counted..offset].matches('\n').count() as u32;
+            counted = offset;
+            comments.push((line, comment));
+        }
+    }
+    comments
+}
@@ -0,0 +1,472 @@
+use crate::ast::{visit_mut::*, *};
+
+use std::collections::HashMap;
+
+use crate::analysis::{iterable_types, ValueType};
+
+use super::{
+    after_space, assignment, before_space, binary, integer, is_pure, parenthesize, temporaries_declaration, variable,
+    NameGenerator,
+};
+
+/// Rewrites the syntax sugar of a file into the core language:
+///
+/// - `for (k : v in a)` into a loop over the indexes of `a`, when `a` is an array literal or
+///   a variable declared as an array: the keys of a map are not indexes, so other loops are kept
+/// - `a += b` and the other compound assignments into `a = a + (b)`
+/// - `a++` and `--a` into assignments, and `a++` whose value is used into
+///   `[__value0 = a, a = __value0 + 1][0]`
+/// - arrow functions into `function` expressions
+/// - `and`, `or`, `not` and `xor` into `&&`, `||`, `!` and `!a != !b`
+///
+/// The object and index of a target that could have side effects, like in `a[f()] += 1`, are
+/// evaluated once into globals declared at the start of the file:
+/// `a[(__index0 = f())] = a[__index0] + 1`, the target of an assignment being evaluated
+/// before its value.
+pub fn desugar(file: &mut File) {
+    let mut desugar = Desugar {
+        names: NameGenerator::new(file),
+        iterables: iterable_types(file),
+        temporaries: Vec::new(),
+    };
+    desugar.visit_file(file);
+    if !desugar.temporaries.is_empty() {
+        file.statements.insert(0, temporaries_declaration(desugar.temporaries));
+    }
+}
+
+struct Desugar {
+    names: NameGenerator,
+    /// The types of the iterables of the for-in loops, by offset
+    iterables: HashMap<usize, ValueType>,
+    /// The globals holding the values evaluated once
+    temporaries: Vec<String>,
+}
+
+impl Desugar {
+    fn lower_statement(&mut self, statement: &mut Statement) {
+        match statement {
+            // The value of an increment is not used here, so it can be a plain assignment
+            Statement::ExpressionStatement(expression_statement) => {
+                let expression = expression_statement.expression.node.as_mut();
+                walk_expression(self, expression);
+                self.lower_update(expression, false);
+                self.lower_expression(expression);
+            }
+            Statement::ForStatement(_) => {
+                walk_statement_node(self, statement);
+                if let Statement::ForStatement(for_statement) = statement {
+                    let lowered = match for_statement.for_header.node.as_ref() {
+                        ForHeader::ForIn(for_in) => for_in.iterable.location
+                            .and_then(|location| self.iterables.get(&location.start.offset))
+                            .is_some_and(|value_type| *value_type == ValueType::Array),
+                        ForHeader::ForIter(_) => false,
+                    };
+                    if lowered {
+                        *statement = self.lower_for_in(std::mem::replace(for_statement, empty_for()));
+                    }
+                }
+            }
+            _ => walk_statement_node(self, statement),
+        }
+    }
+
+    fn lower_expression(&mut self, expression: &mut Expression) {
+        match expression {
+            Expression::Assign(assign) => self.lower_compound_assignment(assign),
+            Expression::PreUpdate(_) | Expression::PostUpdate(_) => self.lower_update(expression, true),
+            Expression::AnonyFunc(AnonymousFunction::ArrowFunction(_)) => {
+                let Expression::AnonyFunc(AnonymousFunction::ArrowFunction(arrow)) = std::mem::replace(expression, null())
+                else {
+                    unreachable!()
+                };
+                *expression = Expression::AnonyFunc(AnonymousFunction::Function(arrow_to_function(arrow)));
+            }
+            Expression::LogicalAnd(and) => {
+                for (op, _) in &mut and.right {
+                    *op.node = LogicalAndOp::And("&&".to_string());
+                }
+            }
+            Expression::LogicalOr(or) => {
+                for (op, _) in &mut or.right {
+                    *op.node = LogicalOrOp::Or("||".to_string());
+                }
+            }
+            Expression::LogicalXor(_) => {
+                let Expression::LogicalXor(xor) = std::mem::replace(expression, null()) else { unreachable!() };
+                *expression = lower_xor(xor);
+            }
+            Expression::Unary(unary) => {
+                if let UnaryOp::Not(not) = unary.op.node.as_mut() {
+                    *not = "!".to_string();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// `for (k : v in a) body` becomes
+    /// `{ var it = a; for (var i = 0; i < count(it); i = i + 1) { var k = i; var v = it[i]; body } }`
+    fn lower_for_in(&mut self, for_statement: ForStatement) -> Statement {
+        let ForStatement { for_header, block } = for_statement;
+        let ForHeader::ForIn(for_in) = *for_header.node else { unreachable!() };
+
+        let iterable = self.names.fresh("__iterable");
+        let index = self.names.fresh("__index");
+
+        let mut body = Vec::new();
+        if let Some(key) = for_in.key {
+            body.push(after_space(declaration(*key.node, variable(&index))));
+        }
+        let element = Expression::Member(Member {
+            left: PrimaryExpr::IdentifierOrMember(MetaNode::new(IdentifierOrMember::Identifier(
+                MetaNode::new(Identifier { name: iterable.clone() }),
+            ))),
+            right: vec![MetaNode::new(MemberRight::Bracket(MetaNode::new(variable(&index))))],
+        });
+        body.push(after_space(declaration(*for_in.var.node, element)));
+        body.push(match block.node.to_string().ends_with(char::is_whitespace) {
+            true => after_space(*block.node),
+            false => MetaNode::spaced(*block.node),
+        });
+
+        let count = Expression::Member(Member {
+            left: PrimaryExpr::IdentifierOrMember(MetaNode::new(IdentifierOrMember::Identifier(
+                MetaNode::new(Identifier { name: "count".to_string() }),
+            ))),
+            right: vec![MetaNode::new(MemberRight::Call(vec![MetaNode::new(variable(&iterable))]))],
+        });
+        let header = ForIter {
+            init: Some(MetaNode::new(VarDecOrExpr::VarDeclaration(VarDeclaration {
+                type_: before_space(VarType::Var),
+                values: vec![(before_space(Identifier { name: index.clone() }), Some(after_space(integer(0))))],
+            }))),
+            condition: Some(after_space(Expression::Relation(binary(variable(&index), RelationOp::Less, count)))),
+            increment: Some(after_space(increment(variable(&index), variable(&index), AddOp::Add))),
+        };
+        let loop_ = Statement::ForStatement(ForStatement {
+            for_header: MetaNode::spaced(ForHeader::ForIter(header)),
+            block: MetaNode::new(Statement::Block(BlockStatement::StatementList(body))),
+        });
+
+        let iterable_declaration = Statement::VarDeclaration(VarDeclarationStatement {
+            var_declaration: MetaNode::new(VarDeclaration {
+                type_: before_space(VarType::Var),
+                values: vec![(
+                    before_space(Identifier { name: iterable }),
+                    Some(after_space(*for_in.iterable.node)),
+                )],
+            }),
+            semi: MetaNode::new(Semi(true)),
+        });
+        Statement::Block(BlockStatement::StatementList(vec![
+            after_space(iterable_declaration),
+            MetaNode::spaced(loop_),
+        ]))
+    }
+
+    /// `a++` becomes `a = a + 1`. When its value is used, a prefix update is parenthesized and
+    /// a postfix update keeps the previous value in a temporary: `[__value0 = a, a = __value0 + 1][0]`.
+    fn lower_update(&mut self, expression: &mut Expression, value_used: bool) {
+        let (target, op, postfix) = match expression {
+            Expression::PreUpdate(update) => (update.expr.as_ref(), &update.op, false),
+            Expression::PostUpdate(update) => (update.expr.as_ref(), &update.op, true),
+            _ => return,
+        };
+        // What followed a postfix operator now follows the expression
+        let trailing = op.post_comments.clone();
+        let op = match op.node.as_ref() {
+            UpdateOp::Inc => AddOp::Add,
+            UpdateOp::Dec => AddOp::Sub,
+        };
+        let (first, second) = self.evaluate_once(target.clone());
+
+        *expression = match (value_used, postfix) {
+            (false, _) => increment(first, second, op),
+            (true, false) => parenthesize(increment(first, second, op)),
+            (true, true) => {
+                let value = self.temporary("__value");
+                let sum = Expression::Add(binary(variable(&value), op, integer(1)));
+                let elements = vec![
+                    MetaNode::new(assignment(variable(&value), first)),
+                    after_space(assignment(second, sum)),
+                ];
+                let mut index = MetaNode::new(MemberRight::Bracket(MetaNode::new(integer(0))));
+                index.post_comments = trailing;
+                Expression::Member(Member {
+                    left: PrimaryExpr::Array(Array::Elements { elements, last_comma: None }),
+                    right: vec![index],
+                })
+            }
+        };
+    }
+
+    fn lower_compound_assignment(&mut self, assign: &mut AssignExpr) {
+        let [(op, value)] = assign.right.as_mut_slice() else { return };
+        if matches!(op.node.as_ref(), AssignOp::Assign) {
+            return;
+        }
+
+        let target = std::mem::replace(assign.left.as_mut(), null());
+        let (target, left) = self.evaluate_once(target);
+        *assign.left = target;
+        let right = parenthesize(std::mem::replace(value.node.as_mut(), null()));
+        let operation = match op.node.as_ref() {
+            AssignOp::Add => Expression::Add(binary(left, AddOp::Add, right)),
+            AssignOp::Sub => Expression::Add(binary(left, AddOp::Sub, right)),
+            AssignOp::Mul => Expression::Mult(binary(left, MultOp::Mul, right)),
+            AssignOp::Div => Expression::Mult(binary(left, MultOp::Div, right)),
+            AssignOp::Mod => Expression::Mult(binary(left, MultOp::Mod, right)),
+            AssignOp::Left => Expression::Shift(binary(left, ShiftOp::Left, right)),
+            AssignOp::Right => Expression::Shift(binary(left, ShiftOp::Right, right)),
+            AssignOp::UnsignedRight => Expression::Shift(binary(left, ShiftOp::UnsignedRight, right)),
+            AssignOp::And => Expression::BinAnd(binary(left, BinAndOp, right)),
+            AssignOp::Xor => Expression::BinXor(binary(left, BinXorOp, right)),
+            AssignOp::Or => Expression::BinOr(binary(left, BinOrOp, right)),
+            AssignOp::Assign => unreachable!(),
+        };
+
+        *op.node = AssignOp::Assign;
+        *value.node = operation;
+    }
+
+    /// The target of an assignment written twice, the first one evaluated before the second:
+    /// `f()[g()]` gives `(__object0 = f())[(__index0 = g())]` then `__object0[__index0]`.
+    fn evaluate_once(&mut self, target: Expression) -> (Expression, Expression) {
+        let Expression::Member(Member { left, mut right }) = target else {
+            return (target.clone(), target);
+        };
+        let Some(last) = right.pop() else {
+            let target = Expression::Member(Member { left, right });
+            return (target.clone(), target);
+        };
+        let object = match right.is_empty() {
+            true => Expression::Primary(left),
+            false => Expression::Member(Member { left, right }),
+        };
+        let (first_object, second_object) = self.hoist(object, "__object");
+        let (first_last, second_last) = match last.node.as_ref() {
+            MemberRight::Bracket(index) => {
+                let (first_index, second_index) = self.hoist(index.node.as_ref().clone(), "__index");
+                let mut first_last = last.clone();
+                *first_last.node = MemberRight::Bracket(MetaNode { node: Box::new(first_index), ..index.clone() });
+                let mut second_last = last.clone();
+                *second_last.node = MemberRight::Bracket(MetaNode { node: Box::new(second_index), ..index.clone() });
+                (first_last, second_last)
+            }
+            _ => (last.clone(), last),
+        };
+        (access(first_object, first_last), access(second_object, second_last))
+    }
+
+    /// An expression written twice, assigned to a temporary the first time unless it is pure.
+    fn hoist(&mut self, expression: Expression, base: &str) -> (Expression, Expression) {
+        if is_pure(&expression) {
+            return (expression.clone(), expression);
+        }
+        let temporary = self.temporary(base);
+        (parenthesize(assignment(variable(&temporary), expression)), variable(&temporary))
+    }
+
+    fn temporary(&mut self, base: &str) -> String {
+        let temporary = self.names.fresh(base);
+        self.temporaries.push(temporary.clone());
+        temporary
+    }
+}
+
+impl VisitorMut for Desugar {
+    fn visit_global_statement(&mut self, statement: &mut MetaNode<GlobalStatement>) {
+        match statement.node.as_mut() {
+            GlobalStatement::Statement(statement) => self.lower_statement(statement),
+            _ => walk_global_statement(self, statement),
+        }
+    }
+
+    fn visit_statement(&mut self, statement: &mut MetaNode<Statement>) {
+        self.lower_statement(&mut statement.node);
+    }
+
+    fn visit_for_header(&mut self, header: &mut ForHeader) {
+        // Like an expression statement, the increment is lowered before its value is asked for
+        if let ForHeader::ForIter(ForIter { increment: Some(increment), .. }) = header {
+            let increment = increment.node.as_mut();
+            walk_expression(self, increment);
+            self.lower_update(increment, false);
+            self.lower_expression(increment);
+        }
+        walk_for_header(self, header);
+    }
+
+    fn visit_expression(&mut self, expression: &mut Expression) {
+        walk_expression(self, expression);
+        self.lower_expression(expression);
+    }
+}
+
+fn null() -> Expression {
+    Expression::Primary(PrimaryExpr::Literal(Literal::Null))
+}
+
+fn empty_for() -> ForStatement {
+    ForStatement {
+        for_header: MetaNode::new(ForHeader::ForIter(ForIter { init: None, condition: None, increment: None })),
+        block: MetaNode::new(Statement::Empty(Semi(true))),
+    }
+}
+
+/// The declaration of a for-in variable given an initial value, keeping its `var`, `const` or type.
+fn declaration(mut declaration: VarDeclaration, value: Expression) -> Statement {
+    if let Some((_, initial)) = declaration.values.first_mut() {
+        *initial = Some(after_space(value));
+    }
+    if let Some((name, _)) = declaration.values.first_mut() {
+        name.post_comments = vec![CommentOrWhitespace::Whitespace(" ".to_string())];
+    }
+    Statement::VarDeclaration(VarDeclarationStatement {
+        var_declaration: MetaNode::new(declaration),
+        semi: MetaNode::new(Semi(true)),
+    })
+}
+
+/// `target = value + 1`, where `target` and `value` are the same place.
+fn increment(target: Expression, value: Expression, op: AddOp) -> Expression {
+    assignment(target, Expression::Add(binary(value, op, integer(1))))
+}
+
+/// `object` followed by `right`.
+fn access(object: Expression, right: MetaNode<MemberRight>) -> Expression {
+    Expression::Member(match object {
+        Expression::Primary(left) => Member { left, right: vec![right] },
+        Expression::Member(mut member) => {
+            member.right.push(right);
+            member
+        }
+        object => Member {
+            left: PrimaryExpr::ParenthesizedExpr(MetaNode::new(object)),
+            right: vec![right],
+        },
+    })
+}
+
+/// `a xor b` is true when exactly one side is truthy, which is `!a != !b`.
+fn lower_xor(xor: LogicalXorExpr) -> Expression {
+    let not = |expression: Expression| {
+        let operand = match expression {
+            Expression::Member(_) | Expression::Primary(_) => expression,
+            expression => Expression::Primary(PrimaryExpr::ParenthesizedExpr(MetaNode::new(expression))),
+        };
+        Expression::Unary(UnaryExpr::new(MetaNode::new(UnaryOp::Not("!".to_string())), operand))
+    };
+
+    xor.right.into_iter().fold(*xor.left, |left, (_, right)| {
+        Expression::Relation(binary(not(left), RelationOp::NotEq, not(*right.node)))
+    })
+}
+
+fn arrow_to_function(arrow: ArrowFunction) -> AnonymousFuncDec {
+    let args = match *arrow.args.node {
+        AnonymousParameter::Parameters(parameters) => *parameters.node,
+        AnonymousParameter::SingleParam(parameter) => {
+            // The space before `=>` would end up before `)`
+            let mut parameter = *parameter.node;
+            parameter.name.post_comments.retain(|comment| !matches!(comment, CommentOrWhitespace::Whitespace(_)));
+            Parameters { params: vec![MetaNode::new(parameter)] }
+        }
+    };
+    let body = match *arrow.body.node {
+        FunctionBodyOrExpression::BlockStatement(block) => *block.node,
+        FunctionBodyOrExpression::Expression(expression) => {
+            let return_statement = Statement::ReturnStatement(ReturnStatement {
+                expression: Some(after_space(*expression.node)),
+                semi: MetaNode::new(Semi(true)),
+            });
+            BlockStatement::StatementList(vec![MetaNode::spaced(return_statement)])
+        }
+    };
+
+    AnonymousFuncDec {
+        args: match arrow.return_type {
+            Some(_) => before_space(args),
+            None => MetaNode::new(args),
+        },
+        return_type: arrow.return_type.map(|return_type| (before_space(*arrow.arrow.node), return_type)),
+        body: after_space(body),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::parse_file;
+
+    use super::*;
+
+    fn lower(input: &str) -> String {
+        let mut file = parse_file(input);
+        desugar(&mut file);
+        let output = file.to_string();
+        // The result must still be LeekScript
+        assert_eq!(parse_file(&output).to_string(), output);
+        output
+    }
+
+    #[test]
+    fn test_for_in() {
+        assert_eq!(
+            lower("for (var x in [1, 2]) { debug(x); }"),
+            "{ var __iterable0 = [1, 2]; for (var __index0 = 0; __index0 < count(__iterable0); __index0 = __index0 + 1) \
+             { var x = __iterable0[__index0]; { debug(x); } } }"
+        );
+        assert_eq!(
+            lower("function f(__index0) { for (let k : integer v in [3] + [4]) k; }"),
+            "function f(__index0) { { var __iterable0 = [3] + [4]; for (var __index1 = 0; __index1 < count(__iterable0); \
+             __index1 = __index1 + 1) { let k = __index1; integer v = __iterable0[__index1]; k; } }}"
+        );
+        // Only arrays are indexed by their position
+        assert_eq!(lower("for (var k : var v in [1: 2]) {}"), "for (var k : var v in [1: 2]) {}");
+        assert_eq!(lower("for (var x in a) { x++; }"), "for (var x in a) { x = x + 1; }");
+        assert_eq!(lower("var a = [1]; a = [1: 2]; for (var x in a) {}"), "var a = [1]; a = [1: 2]; for (var x in a) {}");
+        // Variables declared as arrays are
+        assert!(lower("Array<integer> a = [1]; for (var x in a) {}").contains("var x = __iterable0[__index0];"));
+        assert!(lower("function f(Array a) { for (var x in a) {} }").contains("var x = __iterable0[__index0];"));
+        assert_eq!(
+            lower("function f(Map a) { for (var x in a) {} }"),
+            "function f(Map a) { for (var x in a) {} }"
+        );
+    }
+
+    #[test]
+    fn test_assignments() {
+        assert_eq!(lower("a += 1; b[0] *= c - 1; d <<= 2;"), "a = a + 1; b[0] = b[0] * (c - 1); d = d << 2;");
+        assert_eq!(lower("a++; --b; for (;; i++) {}"), "a = a + 1; b = b - 1; for (;; i = i + 1) {}");
+        assert_eq!(lower("x = a++ + --b;"), "global __value0;\nx = [__value0 = a, a = __value0 + 1][0] + (b = b - 1);");
+        assert_eq!(lower("a.b[c].d -= 2;"), "a.b[c].d = a.b[c].d - 2;");
+    }
+
+    #[test]
+    fn test_impure_targets() {
+        assert_eq!(lower("a[f()] += 1;"), "global __index0;\na[(__index0 = f())] = a[__index0] + 1;");
+        assert_eq!(
+            lower("a[i++] += 1;"),
+            "global __value0, __index0;\na[(__index0 = [__value0 = i, i = __value0 + 1][0])] = a[__index0] + 1;"
+        );
+        assert_eq!(lower("f().x++;"), "global __object0;\n(__object0 = f()).x = __object0.x + 1;");
+        assert_eq!(
+            lower("y = g()[h()]--;"),
+            "global __object0, __index0, __value0;\n\
+             y = [__value0 = (__object0 = g())[(__index0 = h())], __object0[__index0] = __value0 - 1][0];"
+        );
+    }
+
+    #[test]
+    fn test_arrow_functions() {
+        assert_eq!(lower("var f = x => x + 1;"), "var f = function(x) { return x + 1; };");
+        assert_eq!(lower("var f = (a, b) => { return a; };"), "var f = function(a, b) { return a; };");
+    }
+
+    #[test]
+    fn test_keyword_operators() {
+        assert_eq!(lower("var a = b and c or not d;"), "var a = b && c || ! d;");
+        assert_eq!(lower("var a = b xor c xor d < 1;"), "var a = !(!b != !c ) != !(d < 1);");
+    }
+}
@@ -0,0 +1,154 @@
+//! Passes rewriting newer constructs into plain LeekScript for targets that do not support them.
+//! The output of every pass prints back to valid source.
+
+pub mod template;
+pub mod optional_chaining;
+pub mod desugar;
+
+pub use template::*;
+pub use optional_chaining::*;
+pub use desugar::*;
+
+use std::collections::HashSet;
+
+use crate::ast::{visit::Visitor, *};
+
+/// Runs every pass, leaving only the core language: no template strings, optional chaining,
+/// for-in loops over arrays, compound assignments, increments, arrow functions or keyword
+/// operators.
+pub fn lower(file: &mut File) {
+    lower_templates(file);
+    lower_optional_chaining(file);
+    desugar(file);
+}
+
+/// Wraps `expression` in parentheses unless it already binds tighter than any binary operator.
+pub(crate) fn parenthesize(expression: Expression) -> Expression {
+    match expression {
+        Expression::TypeConversion(_)
+        | Expression::PreUpdate(_)
+        | Expression::PostUpdate(_)
+        | Expression::Unary(_)
+        | Expression::Member(_)
+        | Expression::Primary(_) => expression,
+        expression => Expression::Primary(PrimaryExpr::ParenthesizedExpr(MetaNode::new(expression))),
+    }
+}
+
+/// A node preceded by a space.
+pub(crate) fn after_space<T>(node: T) -> MetaNode<T> {
+    let mut node = MetaNode::spaced(node);
+    node.post_comments.clear();
+    node
+}
+
+/// A node followed by a space.
+pub(crate) fn before_space<T>(node: T) -> MetaNode<T> {
+    let mut node = MetaNode::spaced(node);
+    node.pre_comments.clear();
+    node
+}
+
+/// An operator following `left`, with a space on each side unless `left` already ends with one.
+pub(crate) fn operator<Op>(left: &Expression, op: Op) -> MetaNode<Op> {
+    match left.to_string().ends_with(char::is_whitespace) {
+        true => before_space(op),
+        false => MetaNode::spaced(op),
+    }
+}
+
+pub(crate) fn variable(name: &str) -> Expression {
+    Expression::Primary(PrimaryExpr::IdentifierOrMember(MetaNode::new(IdentifierOrMember::Identifier(
+        MetaNode::new(Identifier { name: name.to_string() }),
+    ))))
+}
+
+pub(crate) fn integer(value: i64) -> Expression {
+    Expression::Primary(PrimaryExpr::Literal(Literal::Number(NumberLiteral {
+        value: NumberValue::Integer(value),
+        format: NumberFormat::Decimal,
+        raw: value.to_string(),
+    })))
+}
+
+pub(crate) fn assignment(target: Expression, value: Expression) -> Expression {
+    let op = operator(&target, AssignOp::Assign);
+    Expression::Assign(AssignExpr {
+        left: Box::new(target),
+        right: vec![(op, MetaNode::new(value))],
+    })
+}
+
+/// `left op right` with a single operator.
+pub(crate) fn binary<Op: ParseInto, Expr: ParseInto>(left: Expression, op: Op, right: Expression) -> BinExpr<Op, Expr> {
+    let op = operator(&left, op);
+    BinExpr::new(left, vec![(op, MetaNode::new(right))])
+}
+
+/// Whether evaluating `expression` twice has the same effect as once: a name or a literal,
+/// followed by field accesses and indexes that are themselves pure.
+pub(crate) fn is_pure(expression: &Expression) -> bool {
+    let (left, right) = match expression {
+        Expression::Primary(left) => (left, &[][..]),
+        Expression::Member(member) => (&member.left, &member.right[..]),
+        _ => return false,
+    };
+    let pure_left = match left {
+        PrimaryExpr::IdentifierOrMember(_) => true,
+        PrimaryExpr::Literal(literal) => !matches!(literal, Literal::Template(_)),
+        _ => false,
+    };
+    pure_left && right.iter().all(|right| match right.node.as_ref() {
+        MemberRight::Dot(_) | MemberRight::NotNull => true,
+        MemberRight::Bracket(index) => is_pure(&index.node),
+        _ => false,
+    })
+}
+
+/// `global __object0, __object1;` on its own line.
+pub(crate) fn temporaries_declaration(names: Vec<String>) -> MetaNode<GlobalStatement> {
+    let values = names.into_iter()
+        .map(|name| {
+            let mut name = MetaNode::new(Identifier { name });
+            name.pre_comments.push(CommentOrWhitespace::Whitespace(" ".to_string()));
+            (name, None)
+        })
+        .collect();
+    let mut semi = MetaNode::new(Semi(true));
+    semi.post_comments.push(CommentOrWhitespace::Whitespace("\n".to_string()));
+    MetaNode::new(GlobalStatement::GlobalDefinition(GlobalDefinition { const_: None, type_: None, values, semi }))
+}
+
+/// Gives names that are not used anywhere in a file, for the variables a pass introduces.
+pub(crate) struct NameGenerator {
+    used: HashSet<String>,
+}
+
+impl NameGenerator {
+    pub(crate) fn new(file: &File) -> Self {
+        let mut collector = NameCollector::default();
+        collector.visit_file(file);
+        NameGenerator { used: collector.names }
+    }
+
+    /// `base` followed by the smallest number giving an unused name.
+    pub(crate) fn fresh(&mut self, base: &str) -> String {
+        let name = (0..)
+            .map(|i| format!("{}{}", base, i))
+            .find(|name| !self.used.contains(name))
+            .unwrap();
+        self.used.insert(name.clone());
+        name
+    }
+}
+
+#[derive(Default)]
+struct NameCollector {
+    names: HashSet<String>,
+}
+
+impl<'ast> Visitor<'ast> for NameCollector {
+    fn visit_identifier(&mut self, identifier: &'ast MetaNode<Identifier>) {
+        self.names.insert(identifier.node.name.clone());
+    }
+}
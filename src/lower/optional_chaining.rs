@@ -0,0 +1,149 @@
+use crate::ast::{visit_mut::*, *};
+
+use super::{assignment, is_pure, parenthesize, temporaries_declaration, variable, NameGenerator};
+
+/// Replaces `?.` and `?[` by explicit null checks, so that `a?.b` becomes
+/// `(a == null ? null : a.b)`. An object that could have side effects, like `f()` in `f()?.b`,
+/// is evaluated once into a global declared at the start of the file:
+/// `((__object0 = f()) == null ? null : __object0.b)`. An optional chain assigned to, which
+/// `validate` rejects, is not lowered.
+pub fn lower_optional_chaining(file: &mut File) {
+    let mut lowering = OptionalChainingLowering {
+        names: NameGenerator::new(file),
+        temporaries: Vec::new(),
+        target: false,
+    };
+    lowering.visit_file(file);
+    if !lowering.temporaries.is_empty() {
+        file.statements.insert(0, temporaries_declaration(lowering.temporaries));
+    }
+}
+
+struct OptionalChainingLowering {
+    names: NameGenerator,
+    /// The globals holding the objects evaluated once
+    temporaries: Vec<String>,
+    /// Whether the next expression visited is assigned to, as the first child of an assignment
+    /// or update
+    target: bool,
+}
+
+impl VisitorMut for OptionalChainingLowering {
+    fn visit_expression(&mut self, expression: &mut Expression) {
+        let target = std::mem::take(&mut self.target);
+        self.target = matches!(expression, Expression::Assign(_) | Expression::PreUpdate(_) | Expression::PostUpdate(_));
+        walk_expression(self, expression);
+        self.target = false;
+
+        if let Expression::Member(member) = expression {
+            if !target && member.right.iter().any(|right| right.node.is_optional()) {
+                let member = std::mem::replace(member, Member {
+                    left: PrimaryExpr::Literal(Literal::Null),
+                    right: Vec::new(),
+                });
+                *expression = self.lower_member(member);
+            }
+        }
+    }
+}
+
+impl OptionalChainingLowering {
+    fn lower_member(&mut self, member: Member) -> Expression {
+        let Some(index) = member.right.iter().rposition(|right| right.node.is_optional()) else {
+            return Expression::Member(member);
+        };
+
+        let Member { left, mut right } = member;
+        let mut rest = right.split_off(index);
+        let object = match right.is_empty() {
+            true => Expression::Primary(left),
+            false => self.lower_member(Member { left, right }),
+        };
+
+        let first = rest[0].node.as_mut();
+        *first = match std::mem::replace(first, MemberRight::NotNull) {
+            MemberRight::OptionalDot(name) => MemberRight::Dot(name),
+            MemberRight::OptionalBracket(index) => MemberRight::Bracket(index),
+            right => right,
+        };
+        // The object is only evaluated once when it could have side effects
+        let (tested, object) = match is_pure(&object) {
+            true => (object.clone(), object),
+            false => {
+                let temporary = self.names.fresh("__object");
+                self.temporaries.push(temporary.clone());
+                (parenthesize(assignment(variable(&temporary), object)), variable(&temporary))
+            }
+        };
+        let access = match object {
+            Expression::Primary(left) => Member { left, right: rest },
+            Expression::Member(mut member) => {
+                member.right.extend(rest);
+                member
+            }
+            object => Member {
+                left: PrimaryExpr::ParenthesizedExpr(MetaNode::new(object)),
+                right: rest,
+            },
+        };
+
+        let is_null = Expression::Relation(RelationExpr::new(
+            tested,
+            vec![(MetaNode::spaced(RelationOp::Eq), MetaNode::new(null()))],
+        ));
+        let mut condition = MetaNode::spaced(is_null);
+        condition.pre_comments.clear();
+        let mut false_expr = MetaNode::spaced(Expression::Member(access));
+        false_expr.post_comments.clear();
+
+        Expression::Primary(PrimaryExpr::ParenthesizedExpr(MetaNode::new(Expression::TernaryExpression(
+            TernaryExpression {
+                condition,
+                true_expr: MetaNode::spaced(null()),
+                false_expr,
+            },
+        ))))
+    }
+}
+
+fn null() -> Expression {
+    Expression::Primary(PrimaryExpr::Literal(Literal::Null))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::parse_file;
+
+    use super::*;
+
+    fn lower(input: &str) -> String {
+        let mut file = parse_file(input);
+        lower_optional_chaining(&mut file);
+        let output = file.to_string();
+        assert_eq!(parse_file(&output).to_string(), output);
+        output
+    }
+
+    #[test]
+    fn test_lower_optional_chaining() {
+        assert_eq!(lower("var x = a?.b.c;"), "var x = (a == null ? null : a.b.c);");
+        assert_eq!(lower("var x = a.b?[0]!;"), "var x = (a.b == null ? null : a.b[0]!);");
+        assert_eq!(lower("var x = this.a?[i];"), "var x = (this.a == null ? null : this.a[i]);");
+        assert_eq!(
+            lower("f(a?.b?.c?.d);"),
+            "global __object0, __object1;\nf(((__object1 = ((__object0 = (a == null ? null : a.b)) == null ? null : __object0.c)) \
+             == null ? null : __object1.d));"
+        );
+        // Calls are only made once
+        assert_eq!(
+            lower("function g() { return f()?.x; }"),
+            "global __object0;\nfunction g() { return ((__object0 = f()) == null ? null : __object0.x); }"
+        );
+        assert_eq!(lower("var x = a.b;"), "var x = a.b;");
+        // Left for validation to report
+        assert_eq!(
+            lower("a?.b = c?.d; a?[f()?.e]++;"),
+            "global __object0;\na?.b = (c == null ? null : c.d); a?[((__object0 = f()) == null ? null : __object0.e)]++;"
+        );
+    }
+}
@@ -0,0 +1,69 @@
+use crate::ast::{visit_mut::*, *};
+
+use super::parenthesize;
+
+/// Replaces every template string by the concatenation of its parts, so that `` `a${b}` ``
+/// becomes `('a' + b)`.
+pub fn lower_templates(file: &mut File) {
+    TemplateLowering.visit_file(file);
+}
+
+struct TemplateLowering;
+
+impl VisitorMut for TemplateLowering {
+    fn visit_primary(&mut self, primary: &mut PrimaryExpr) {
+        walk_primary(self, primary);
+
+        if let PrimaryExpr::Literal(Literal::Template(template)) = primary {
+            *primary = concatenate(std::mem::take(&mut template.parts));
+        }
+    }
+}
+
+fn concatenate(parts: Vec<TemplatePart>) -> PrimaryExpr {
+    let mut operands: Vec<_> = parts.into_iter()
+        .map(|part| match part {
+            TemplatePart::Hole(expression) => parenthesize(*expression.node),
+            text => string(&text.decoded().unwrap_or_default()),
+        })
+        .collect();
+
+    // The first operand has to be a string for `+` to concatenate
+    if !matches!(operands.first(), Some(Expression::Primary(PrimaryExpr::Literal(Literal::String(_))))) {
+        operands.insert(0, string(""));
+    }
+    if operands.len() == 1 {
+        let Some(Expression::Primary(primary)) = operands.pop() else { unreachable!() };
+        return primary;
+    }
+
+    let mut operands = operands.into_iter();
+    let left = operands.next().unwrap();
+    let right = operands.map(|operand| (MetaNode::spaced(AddOp::Add), MetaNode::new(operand))).collect();
+    PrimaryExpr::ParenthesizedExpr(MetaNode::new(Expression::Add(AddExpr::new(left, right))))
+}
+
+fn string(value: &str) -> Expression {
+    Expression::Primary(PrimaryExpr::Literal(Literal::String(StringLiteral::new(value, QuoteType::Single))))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::parse_file;
+
+    use super::*;
+
+    fn lower(input: &str) -> String {
+        let mut file = parse_file(input);
+        lower_templates(&mut file);
+        file.to_string()
+    }
+
+    #[test]
+    fn test_lower_templates() {
+        assert_eq!(lower("var a = `x${b}y's${c - 1}`;"), "var a = ('x' + b + 'y\\'s' + (c - 1));");
+        assert_eq!(lower("debug(`${a}${b}`);"), "debug(('' + a + b));");
+        assert_eq!(lower("var a = `plain` + `${`n${1}`}`;"), "var a = 'plain' + ('' + ('n' + 1));");
+        assert_eq!(lower("var a = ``;"), "var a = '';");
+    }
+}
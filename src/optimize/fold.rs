@@ -0,0 +1,681 @@
+use std::collections::HashMap;
+
+use crate::analysis::{evaluate_with, Constant, Diagnostic, Operator};
+use crate::ast::{visit_mut::*, *};
+
+/// Replaces the operators applied to literals and to constants of known value with their result,
+/// and removes the `if` branches and `while` loops ruled out by a constant condition. Folded
+/// integers keep the format of the literal they come from, and comments are never dropped.
+///
+/// Divisions by zero and conditions that are always true are reported.
+pub fn fold_constants(file: &mut File) -> Vec<Diagnostic> {
+    let mut folder = Folder {
+        // The globals, then the top-level variables
+        scopes: vec![HashMap::new(), HashMap::new()],
+        statement: None,
+        diagnostics: Vec::new(),
+    };
+    folder.declare_globals(file);
+    folder.visit_file(file);
+    folder.diagnostics
+}
+
+struct Folder {
+    /// The value of the constants in scope, `None` for names hiding one
+    scopes: Vec<HashMap<String, Option<Constant>>>,
+    /// Location of the innermost statement, as expressions have none
+    statement: Option<Location>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+type Trivia = Vec<CommentOrWhitespace>;
+
+impl Folder {
+    fn declare_globals(&mut self, file: &File) {
+        for statement in &file.statements {
+            if let GlobalStatement::GlobalDefinition(definition) = statement.node.as_ref() {
+                for (name, value) in &definition.values {
+                    let value = match (&definition.const_, value) {
+                        (Some(_), Some(value)) => self.evaluate(&value.node),
+                        _ => None,
+                    };
+                    self.scopes[0].insert(name.node.name.clone(), value);
+                }
+            }
+        }
+    }
+
+    fn declare(&mut self, name: &str, value: Option<Constant>) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.to_string(), value);
+        }
+    }
+
+    fn lookup(&self, name: &str) -> Option<Constant> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(name)).cloned().flatten()
+    }
+
+    fn evaluate(&self, expression: &Expression) -> Option<Constant> {
+        evaluate_with(expression, &|name| self.lookup(name))
+    }
+
+    fn scoped(&mut self, f: impl FnOnce(&mut Self)) {
+        self.scopes.push(HashMap::new());
+        f(self);
+        self.scopes.pop();
+    }
+
+    /// Named functions and classes only see the globals, not the top-level variables.
+    fn isolated(&mut self, f: impl FnOnce(&mut Self)) {
+        let outer = self.scopes.split_off(1);
+        self.scoped(f);
+        self.scopes.extend(outer);
+    }
+
+    fn report(&mut self, message: &str) {
+        self.diagnostics.push(Diagnostic::warning(message, self.statement));
+    }
+
+    /// Folds the operands of `expression`, then `expression` itself if it is constant. Gives back
+    /// the whitespace that ended a replaced operand, for the caller to put after `expression`.
+    /// Parentheses are only removed around a `nested` operand.
+    fn fold(&mut self, expression: &mut Expression, nested: bool) -> Trivia {
+        let mut trailing = self.fold_operands(expression, nested);
+        let replaceable = match expression {
+            Expression::Unary(unary) => !matches!(
+                (unary.op.node.as_ref(), unary.expr.as_ref()),
+                (UnaryOp::Neg, Expression::Primary(PrimaryExpr::Literal(Literal::Number(_))))
+            ),
+            Expression::LogicalOr(_)
+            | Expression::LogicalXor(_)
+            | Expression::LogicalAnd(_)
+            | Expression::Relation(_)
+            | Expression::Shift(_)
+            | Expression::BinOr(_)
+            | Expression::BinXor(_)
+            | Expression::BinAnd(_)
+            | Expression::Add(_)
+            | Expression::Mult(_) => true,
+            _ => false,
+        };
+        if !replaceable {
+            return trailing;
+        }
+        let Some(folded) = self.evaluate(expression).and_then(|value| literal(&value, expression)) else {
+            return trailing;
+        };
+
+        // Comments are kept by leaving the expression as it is
+        if has_comments(expression) || trailing.iter().any(is_comment) {
+            return trailing;
+        }
+        let mut removed = trailing_trivia(expression);
+        removed.append(&mut trailing);
+        *expression = folded;
+        // Out of an operand, there is no node left to hold the whitespace
+        match nested {
+            true => removed,
+            false => Vec::new(),
+        }
+    }
+
+    fn fold_operands(&mut self, expression: &mut Expression, nested: bool) -> Trivia {
+        match expression {
+            Expression::LogicalOr(chain) => self.fold_chain(chain),
+            Expression::LogicalXor(chain) => self.fold_chain(chain),
+            Expression::LogicalAnd(chain) => self.fold_chain(chain),
+            Expression::Relation(chain) => self.fold_chain(chain),
+            Expression::Shift(chain) => self.fold_chain(chain),
+            Expression::BinOr(chain) => self.fold_chain(chain),
+            Expression::BinXor(chain) => self.fold_chain(chain),
+            Expression::BinAnd(chain) => self.fold_chain(chain),
+            Expression::Add(chain) => self.fold_chain(chain),
+            Expression::Mult(chain) => {
+                for (op, right) in &chain.right {
+                    if matches!(op.node.as_ref(), MultOp::Div | MultOp::Mod)
+                        && self.evaluate(&right.node).is_some_and(|value| value.is_zero())
+                    {
+                        self.report("division by zero");
+                    }
+                }
+                self.fold_chain(chain)
+            }
+            Expression::Unary(unary) => self.fold(&mut unary.expr, true),
+            Expression::TernaryExpression(ternary) => {
+                for part in [&mut ternary.condition, &mut ternary.true_expr, &mut ternary.false_expr] {
+                    let mut trailing = self.fold(&mut part.node, true);
+                    trailing.append(&mut part.post_comments);
+                    part.post_comments = trailing;
+                }
+                let Some(condition) = self.evaluate(&ternary.condition.node) else { return Vec::new() };
+                if condition.is_truthy() {
+                    self.report("condition is always true");
+                }
+                let (kept, dropped) = match condition.is_truthy() {
+                    true => (&mut ternary.true_expr, &ternary.false_expr),
+                    false => (&mut ternary.false_expr, &ternary.true_expr),
+                };
+                let comments = [&ternary.condition, dropped].iter().any(|part| {
+                    part.pre_comments.iter().chain(&part.post_comments).any(is_comment) || has_comments(&part.node)
+                });
+                if comments || kept.pre_comments.iter().chain(&kept.post_comments).any(is_comment) {
+                    return Vec::new();
+                }
+                // Only the whitespace after the false branch ends the whole expression
+                let mut trailing = std::mem::take(&mut kept.post_comments);
+                if condition.is_truthy() {
+                    take_trailing_whitespace(&mut kept.node);
+                    trailing.clear();
+                }
+                *expression = std::mem::replace(kept.node.as_mut(), Expression::Primary(PrimaryExpr::Literal(Literal::Null)));
+                trailing
+            }
+            Expression::Primary(PrimaryExpr::ParenthesizedExpr(inner)) => {
+                let mut trailing = self.fold(&mut inner.node, true);
+                trailing.append(&mut inner.post_comments);
+                inner.post_comments = trailing;
+
+                // A negative number stays in parentheses, `a - (-1)` would become `a - -1` or `a--1`
+                let unwrap = nested
+                    && matches!(inner.node.as_ref(), Expression::Primary(PrimaryExpr::Literal(_)))
+                    && !inner.pre_comments.iter().chain(&inner.post_comments).any(is_comment);
+                if unwrap {
+                    *expression = std::mem::replace(inner.node.as_mut(), Expression::Primary(PrimaryExpr::Literal(Literal::Null)));
+                }
+                Vec::new()
+            }
+            _ => {
+                walk_expression(self, expression);
+                Vec::new()
+            }
+        }
+    }
+
+    /// Folds the operands of a chain, then its constant prefix: `1 + 2 + a` gives `3 + a`.
+    fn fold_chain<Op: Operator + ParseInto, E: ParseInto>(&mut self, chain: &mut BinExpr<Op, E>) -> Trivia {
+        let trailing = self.fold(&mut chain.left, true);
+        if let Some((op, _)) = chain.right.first_mut() {
+            prepend(&mut op.pre_comments, trailing);
+        }
+        for (_, right) in &mut chain.right {
+            let trailing = self.fold(&mut right.node, true);
+            prepend(&mut right.post_comments, trailing);
+        }
+
+        let Some(mut value) = self.evaluate(&chain.left) else { return Vec::new() };
+        let mut folded = 0;
+        for (op, right) in &chain.right {
+            match self.evaluate(&right.node).and_then(|right| op.node.apply(&value, &right)) {
+                Some(result) => value = result,
+                None => break,
+            }
+            folded += 1;
+        }
+        // A fully constant chain is replaced as a whole by the caller
+        if folded == 0 || folded == chain.right.len() {
+            return Vec::new();
+        }
+        let Some(literal) = literal(&value, &chain.left) else { return Vec::new() };
+
+        let prefix = chain.right[..folded].iter().any(|(op, right)| {
+            op.pre_comments.iter().chain(&op.post_comments).chain(&right.pre_comments).chain(&right.post_comments).any(is_comment)
+                || has_comments(&right.node)
+        });
+        if prefix || has_comments(&chain.left) {
+            return Vec::new();
+        }
+        // The whitespace before the first remaining operator is the one after the folded prefix
+        let (_, last) = &chain.right[folded - 1];
+        let whitespace = [trailing_trivia(&last.node), last.post_comments.clone()].concat();
+        *chain.left = literal;
+        chain.right.drain(..folded);
+        prepend(&mut chain.right[0].0.pre_comments, whitespace);
+        Vec::new()
+    }
+
+    /// Replaces a statement by what its constant condition leaves of it, gives `false` when nothing is.
+    fn simplify(&mut self, statement: &mut MetaNode<Statement>) -> bool {
+        let remaining = match statement.node.as_mut() {
+            Statement::IfStatement(if_statement) => match self.evaluate(&if_statement.condition.node.expression.node) {
+                Some(condition) if condition.is_truthy() => Some(&mut if_statement.block),
+                Some(_) => if_statement.else_block.as_mut(),
+                None => return true,
+            },
+            Statement::WhileStatement(while_statement) => match self.evaluate(&while_statement.condition.node) {
+                Some(condition) if !condition.is_truthy() => None,
+                _ => return true,
+            },
+            _ => return true,
+        };
+        let Some(remaining) = remaining else { return false };
+
+        // Only the comments around the branch move with it, its whitespace was meant for the `if`
+        let pre_comments: Trivia = remaining.pre_comments.drain(..).filter(is_comment).collect();
+        let post_comments: Trivia = remaining.post_comments.drain(..).filter(is_comment).collect();
+        let node = std::mem::replace(remaining.node.as_mut(), Statement::Empty(Semi(false)));
+        *statement.node = node;
+        statement.pre_comments.extend(pre_comments);
+        prepend(&mut statement.post_comments, post_comments);
+        true
+    }
+
+    fn check_condition(&mut self, condition: &Expression, loop_: bool) {
+        // `while (true)` is how an infinite loop is written
+        let literal = matches!(unparenthesized(condition), Expression::Primary(PrimaryExpr::Literal(Literal::Boolean(true))));
+        if loop_ && literal {
+            return;
+        }
+        if self.evaluate(condition).is_some_and(|value| value.is_truthy()) {
+            self.report("condition is always true");
+        }
+    }
+
+    fn visit_statement_node(&mut self, statement: &mut MetaNode<Statement>) {
+        let outer = std::mem::replace(&mut self.statement, statement.location);
+        match statement.node.as_ref() {
+            Statement::IfStatement(if_statement) => self.check_condition(&if_statement.condition.node.expression.node, false),
+            Statement::WhileStatement(while_statement) => self.check_condition(&while_statement.condition.node, true),
+            Statement::DoWhileStatement(do_while) => self.check_condition(&do_while.condition.node, true),
+            Statement::ForStatement(ForStatement { for_header, .. }) => {
+                if let ForHeader::ForIter(ForIter { condition: Some(condition), .. }) = for_header.node.as_ref() {
+                    self.check_condition(&condition.node, true);
+                }
+            }
+            _ => {}
+        }
+
+        match statement.node.as_ref() {
+            Statement::ForStatement(_) => self.scoped(|folder| walk_statement(folder, statement)),
+            _ => walk_statement(self, statement),
+        }
+        // A branch emptied by the removal of its only statement
+        match statement.node.as_mut() {
+            Statement::IfStatement(if_statement) => {
+                if_statement.else_block.iter_mut().chain([&mut if_statement.block]).for_each(fill);
+            }
+            Statement::WhileStatement(WhileStatement { block, .. })
+            | Statement::ForStatement(ForStatement { block, .. })
+            | Statement::DoWhileStatement(DoWhileStatement { statement: block, .. }) => fill(block),
+            _ => {}
+        }
+        if !self.simplify(statement) {
+            *statement.node = Statement::Empty(Semi(false));
+        }
+        self.statement = outer;
+    }
+}
+
+impl VisitorMut for Folder {
+    fn visit_file(&mut self, file: &mut File) {
+        walk_file(self, file);
+        file.statements.retain(|statement| !matches!(statement.node.as_ref(), GlobalStatement::Statement(statement) if is_removed(statement)));
+    }
+
+    fn visit_global_statement(&mut self, statement: &mut MetaNode<GlobalStatement>) {
+        let GlobalStatement::Statement(node) = statement.node.as_mut() else {
+            return walk_global_statement(self, statement);
+        };
+        let mut wrapped = MetaNode {
+            node: Box::new(std::mem::replace(node, Statement::Empty(Semi(false)))),
+            pre_comments: Vec::new(),
+            post_comments: Vec::new(),
+            location: statement.location,
+        };
+        self.visit_statement_node(&mut wrapped);
+        statement.pre_comments.append(&mut wrapped.pre_comments);
+        prepend(&mut statement.post_comments, wrapped.post_comments);
+        *statement.node = GlobalStatement::Statement(*wrapped.node);
+    }
+
+    fn visit_function_definition(&mut self, function: &mut FunctionDefinition) {
+        self.isolated(|folder| walk_function_definition(folder, function));
+    }
+
+    fn visit_class(&mut self, class: &mut Class) {
+        self.isolated(|folder| walk_class(folder, class));
+    }
+
+    fn visit_method(&mut self, method: &mut Method) {
+        self.scoped(|folder| walk_method(folder, method));
+    }
+
+    fn visit_constructor(&mut self, constructor: &mut Constructor) {
+        self.scoped(|folder| walk_constructor(folder, constructor));
+    }
+
+    fn visit_anonymous_function(&mut self, function: &mut AnonymousFunction) {
+        self.scoped(|folder| walk_anonymous_function(folder, function));
+    }
+
+    fn visit_block(&mut self, block: &mut BlockStatement) {
+        self.scoped(|folder| walk_block(folder, block));
+        if let BlockStatement::StatementList(statements) = block {
+            statements.retain(|statement| !is_removed(&statement.node));
+        }
+    }
+
+    fn visit_catch(&mut self, catch: &mut CatchClause) {
+        self.scoped(|folder| {
+            if let Some(binding) = &catch.binding {
+                folder.declare(&binding.node.name.node.name, None);
+            }
+            walk_catch(folder, catch)
+        });
+    }
+
+    fn visit_statement(&mut self, statement: &mut MetaNode<Statement>) {
+        self.visit_statement_node(statement);
+    }
+
+    fn visit_parameter(&mut self, parameter: &mut MetaNode<Parameter>) {
+        walk_parameter(self, parameter);
+        self.declare(&parameter.node.name.node.name, None);
+    }
+
+    fn visit_var_declaration(&mut self, declaration: &mut VarDeclaration) {
+        walk_var_declaration(self, declaration);
+        let constant = declaration.type_.node.is_constant();
+        for (name, value) in &declaration.values {
+            let value = match (constant, value) {
+                (true, Some(value)) => self.evaluate(&value.node),
+                _ => None,
+            };
+            self.declare(&name.node.name, value);
+        }
+    }
+
+    fn visit_expression(&mut self, expression: &mut Expression) {
+        self.fold(expression, false);
+    }
+}
+
+/// Whether a statement was removed, and should be taken out of its block.
+fn is_removed(statement: &Statement) -> bool {
+    matches!(statement, Statement::Empty(Semi(false)))
+}
+
+/// Gives an empty statement to a body that lost its only statement.
+fn fill(body: &mut MetaNode<Statement>) {
+    if is_removed(&body.node) {
+        *body.node = Statement::Empty(Semi(true));
+    }
+}
+
+fn is_comment(trivia: &CommentOrWhitespace) -> bool {
+    !matches!(trivia, CommentOrWhitespace::Whitespace(_))
+}
+
+fn prepend(trivia: &mut Trivia, mut before: Trivia) {
+    before.append(trivia);
+    *trivia = before;
+}
+
+fn unparenthesized(expression: &Expression) -> &Expression {
+    match expression {
+        Expression::Primary(PrimaryExpr::ParenthesizedExpr(inner)) => unparenthesized(&inner.node),
+        expression => expression,
+    }
+}
+
+/// Whether comments are found within a constant expression.
+fn has_comments(expression: &Expression) -> bool {
+    fn chain<Op: ParseInto, E: ParseInto>(chain: &BinExpr<Op, E>) -> bool {
+        has_comments(&chain.left)
+            || chain.right.iter().any(|(op, right)| {
+                op.pre_comments.iter().chain(&op.post_comments).chain(&right.pre_comments).chain(&right.post_comments).any(is_comment)
+                    || has_comments(&right.node)
+            })
+    }
+    let meta = |node: &MetaNode<Expression>| {
+        node.pre_comments.iter().chain(&node.post_comments).any(is_comment) || has_comments(&node.node)
+    };
+
+    match expression {
+        Expression::LogicalOr(expression) => chain(expression),
+        Expression::LogicalXor(expression) => chain(expression),
+        Expression::LogicalAnd(expression) => chain(expression),
+        Expression::Relation(expression) => chain(expression),
+        Expression::Shift(expression) => chain(expression),
+        Expression::BinOr(expression) => chain(expression),
+        Expression::BinXor(expression) => chain(expression),
+        Expression::BinAnd(expression) => chain(expression),
+        Expression::Add(expression) => chain(expression),
+        Expression::Mult(expression) => chain(expression),
+        Expression::Unary(unary) => unary.op.pre_comments.iter().chain(&unary.op.post_comments).any(is_comment) || has_comments(&unary.expr),
+        Expression::TernaryExpression(ternary) => meta(&ternary.condition) || meta(&ternary.true_expr) || meta(&ternary.false_expr),
+        Expression::Primary(PrimaryExpr::ParenthesizedExpr(inner)) => meta(inner),
+        expression => trailing_trivia(expression).iter().any(is_comment),
+    }
+}
+
+/// The whitespace and comments after the last token of a constant expression.
+fn trailing_trivia(expression: &Expression) -> Trivia {
+    fn chain<Op: ParseInto, E: ParseInto>(chain: &BinExpr<Op, E>) -> Trivia {
+        match chain.right.last() {
+            Some((_, right)) => trailing_trivia(&right.node).into_iter().chain(right.post_comments.clone()).collect(),
+            None => trailing_trivia(&chain.left),
+        }
+    }
+
+    match expression {
+        Expression::LogicalOr(expression) => chain(expression),
+        Expression::LogicalXor(expression) => chain(expression),
+        Expression::LogicalAnd(expression) => chain(expression),
+        Expression::Relation(expression) => chain(expression),
+        Expression::Shift(expression) => chain(expression),
+        Expression::BinOr(expression) => chain(expression),
+        Expression::BinXor(expression) => chain(expression),
+        Expression::BinAnd(expression) => chain(expression),
+        Expression::Add(expression) => chain(expression),
+        Expression::Mult(expression) => chain(expression),
+        Expression::Unary(unary) => trailing_trivia(&unary.expr),
+        Expression::TernaryExpression(ternary) => {
+            trailing_trivia(&ternary.false_expr.node).into_iter().chain(ternary.false_expr.post_comments.clone()).collect()
+        }
+        Expression::Primary(PrimaryExpr::IdentifierOrMember(identifier)) => match identifier.node.as_ref() {
+            IdentifierOrMember::Identifier(name) => name.post_comments.iter().chain(&identifier.post_comments).cloned().collect(),
+            _ => identifier.post_comments.clone(),
+        },
+        _ => Vec::new(),
+    }
+}
+
+/// Removes the whitespace after the last token of an expression, when it belongs to a name or a chain.
+fn take_trailing_whitespace(expression: &mut Expression) {
+    fn chain<Op: ParseInto, E: ParseInto>(chain: &mut BinExpr<Op, E>) {
+        match chain.right.last_mut() {
+            Some((_, right)) => {
+                right.post_comments.retain(is_comment);
+                take_trailing_whitespace(&mut right.node);
+            }
+            None => take_trailing_whitespace(&mut chain.left),
+        }
+    }
+
+    match expression {
+        Expression::LogicalOr(expression) => chain(expression),
+        Expression::LogicalXor(expression) => chain(expression),
+        Expression::LogicalAnd(expression) => chain(expression),
+        Expression::Relation(expression) => chain(expression),
+        Expression::Shift(expression) => chain(expression),
+        Expression::BinOr(expression) => chain(expression),
+        Expression::BinXor(expression) => chain(expression),
+        Expression::BinAnd(expression) => chain(expression),
+        Expression::Add(expression) => chain(expression),
+        Expression::Mult(expression) => chain(expression),
+        Expression::Unary(unary) => take_trailing_whitespace(&mut unary.expr),
+        Expression::Primary(PrimaryExpr::IdentifierOrMember(identifier)) => {
+            identifier.post_comments.retain(is_comment);
+            if let IdentifierOrMember::Identifier(name) = identifier.node.as_mut() {
+                name.post_comments.retain(is_comment);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The literal giving `value`, written like the first literal of `like`.
+fn literal(value: &Constant, like: &Expression) -> Option<Expression> {
+    let literal = |literal| Expression::Primary(PrimaryExpr::Literal(literal));
+    let negative = |number: NumberLiteral| {
+        Expression::Unary(UnaryExpr::new(MetaNode::new(UnaryOp::Neg), literal(Literal::Number(number))))
+    };
+
+    Some(match value {
+        Constant::Null => literal(Literal::Null),
+        Constant::Boolean(value) => literal(Literal::Boolean(*value)),
+        Constant::Integer(value) => {
+            let number = integer(value.unsigned_abs(), first_number(like))?;
+            match *value < 0 {
+                true => negative(number),
+                false => literal(Literal::Number(number)),
+            }
+        }
+        Constant::Real(value) => {
+            let number = real(value.abs())?;
+            match value.is_sign_negative() {
+                true => negative(number),
+                false => literal(Literal::Number(number)),
+            }
+        }
+        Constant::String(value) => {
+            let quote_type = first_string(like).map_or(QuoteType::Single, |string| string.quote_type);
+            literal(Literal::String(StringLiteral::new(value, quote_type)))
+        }
+    })
+}
+
+fn integer(value: u64, like: Option<&NumberLiteral>) -> Option<NumberLiteral> {
+    let value = i64::try_from(value).ok()?;
+    let (format, raw) = match like {
+        Some(NumberLiteral { format: NumberFormat::Hexadecimal, raw, .. }) => {
+            let digits = match raw[2..].chars().any(|c| c.is_ascii_uppercase()) {
+                true => format!("{:X}", value),
+                false => format!("{:x}", value),
+            };
+            (NumberFormat::Hexadecimal, format!("{}{}", &raw[..2], digits))
+        }
+        Some(NumberLiteral { format: NumberFormat::Octal, raw, .. }) => (NumberFormat::Octal, format!("{}{:o}", &raw[..2], value)),
+        Some(NumberLiteral { format: NumberFormat::Binary, raw, .. }) => (NumberFormat::Binary, format!("{}{:b}", &raw[..2], value)),
+        _ => (NumberFormat::Decimal, value.to_string()),
+    };
+    Some(NumberLiteral { value: NumberValue::Integer(value), format, raw })
+}
+
+fn real(value: f64) -> Option<NumberLiteral> {
+    if !value.is_finite() {
+        return None;
+    }
+    let mut raw = value.to_string();
+    if !raw.contains('.') {
+        raw.push_str(".0");
+    }
+    Some(NumberLiteral { value: NumberValue::Float(value), format: NumberFormat::Decimal, raw })
+}
+
+fn first_literal(expression: &Expression) -> Option<&Literal> {
+    match expression {
+        Expression::LogicalOr(chain) => first_literal(&chain.left),
+        Expression::LogicalXor(chain) => first_literal(&chain.left),
+        Expression::LogicalAnd(chain) => first_literal(&chain.left),
+        Expression::Relation(chain) => first_literal(&chain.left),
+        Expression::Shift(chain) => first_literal(&chain.left),
+        Expression::BinOr(chain) => first_literal(&chain.left),
+        Expression::BinXor(chain) => first_literal(&chain.left),
+        Expression::BinAnd(chain) => first_literal(&chain.left),
+        Expression::Add(chain) => first_literal(&chain.left),
+        Expression::Mult(chain) => first_literal(&chain.left),
+        Expression::Unary(unary) => first_literal(&unary.expr),
+        Expression::Primary(PrimaryExpr::ParenthesizedExpr(inner)) => first_literal(&inner.node),
+        Expression::Primary(PrimaryExpr::Literal(literal)) => Some(literal),
+        _ => None,
+    }
+}
+
+fn first_number(expression: &Expression) -> Option<&NumberLiteral> {
+    match first_literal(expression)? {
+        Literal::Number(number) => Some(number),
+        _ => None,
+    }
+}
+
+fn first_string(expression: &Expression) -> Option<&StringLiteral> {
+    match first_literal(expression)? {
+        Literal::String(string) => Some(string),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::parse_file;
+
+    use super::*;
+
+    fn fold(input: &str) -> (String, Vec<String>) {
+        let mut file = parse_file(input);
+        let diagnostics = fold_constants(&mut file);
+        let output = file.to_string();
+        assert_eq!(parse_file(&output).to_string(), output);
+        (output, diagnostics.iter().map(|diagnostic| diagnostic.message.clone()).collect())
+    }
+
+    #[test]
+    fn test_fold_expressions() {
+        assert_eq!(fold("var a = 1 + 2 * 3;").0, "var a = 7;");
+        assert_eq!(fold("var a = (1 + 2) * b - 3;").0, "var a = 3 * b - 3;");
+        assert_eq!(fold("var a = 1 + 2 + b;").0, "var a = 3 + b;");
+        assert_eq!(fold("var a = 1 - 4;").0, "var a = -3;");
+        assert_eq!(fold("var a = b-(1-4);").0, "var a = b-(-3);");
+        assert_eq!(fold("var a = 10 / 4 + 'x' + \"y\";").0, "var a = 2.5 + 'x' + \"y\";");
+        assert_eq!(fold("var a = \"x\" + 'y';").0, "var a = \"xy\";");
+        assert_eq!(fold("var a = 1 < 2 and !(3 > 4);").0, "var a = true;");
+        assert_eq!(fold("var a = 2 > 1 ? b : c;").0, "var a = b;");
+    }
+
+    #[test]
+    fn test_keep_format() {
+        assert_eq!(fold("var a = 0xF0 | 0x0F;").0, "var a = 0xFF;");
+        assert_eq!(fold("var a = 0b1010 >> 1;").0, "var a = 0b101;");
+        assert_eq!(fold("var a = 0o7 + 1;").0, "var a = 0o10;");
+        assert_eq!(fold("var a = 1.5 * 2;").0, "var a = 3.0;");
+    }
+
+    #[test]
+    fn test_keep_trivia() {
+        assert_eq!(fold("var a = 1 + 2 > b;").0, "var a = 3 > b;");
+        assert_eq!(fold("var a = 1 + 2 // three\n;").0, "var a = 1 + 2 // three\n;");
+        assert_eq!(fold("var a = (1 /* one */) + 2 + b;").0, "var a = (1 /* one */) + 2 + b;");
+    }
+
+    #[test]
+    fn test_constants() {
+        assert_eq!(fold("const N = 4; var a = N * 2 + b;").0, "const N = 4; var a = 8 + b;");
+        assert_eq!(fold("global const N = 4; function f() { return N << 1; }").0, "global const N = 4; function f() { return 8; }");
+        // Top-level variables are not visible in functions, and parameters hide constants
+        assert_eq!(fold("const N = 4; function f() { return N + 1; }").0, "const N = 4; function f() { return N + 1; }");
+        assert_eq!(fold("const N = 4; class A { x = N + 1; m() { return N + 1; } }").0, "const N = 4; class A { x = N + 1; m() { return N + 1; } }");
+        assert_eq!(fold("const N = 4; var f = function() { return N + 1; };").0, "const N = 4; var f = function() { return 5; };");
+        assert_eq!(fold("const N = 4; function f(N) { return N + 1; }").0, "const N = 4; function f(N) { return N + 1; }");
+        assert_eq!(fold("const N = 4; { var N = 1; N + 1; }").0, "const N = 4; { var N = 1; N + 1; }");
+    }
+
+    #[test]
+    fn test_simplify_statements() {
+        assert_eq!(fold("if (true) { a(); } else { b(); }").0, "{ a(); }");
+        assert_eq!(fold("a(); if (1 > 2) { a(); } else b();").0, "a(); b();");
+        assert_eq!(fold("a();\nif (false) {\n  b();\n}\nc();").0, "a();\nc();");
+        assert_eq!(fold("while (false) a(); b();").0, "b();");
+        assert_eq!(fold("if (a) while (0) b();").0, "if (a) ;");
+        assert_eq!(fold("while (true) { a(); }").0, "while (true) { a(); }");
+    }
+
+    #[test]
+    fn test_diagnostics() {
+        assert_eq!(fold("var a = b / (1 - 1);").1, vec!["division by zero"]);
+        assert_eq!(fold("var a = b % 0;").1, vec!["division by zero"]);
+        assert_eq!(fold("if (1 < 2) a();").1, vec!["condition is always true"]);
+        assert_eq!(fold("const DEBUG = 1; while (DEBUG) a();").1, vec!["condition is always true"]);
+        assert!(fold("while (true) a(); for (;;) b(); if (a) b();").1.is_empty());
+    }
+}
@@ -0,0 +1,446 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    analysis::{resolve_include, Binding, BindingKind, Project, Resolution, ROOT_SCOPE, TOP_LEVEL_SCOPE},
+    ast::{scan::advance, visit::*, *},
+    syntax::{lex, TokenKind},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MinifyOptions {
+    /// Gives the variables and parameters that are not seen from other files the shortest
+    /// names available
+    pub rename_locals: bool,
+    /// Replaces each `include` with the minified file it includes, the first time it is met
+    pub inline_includes: bool,
+    /// Records where the pieces of the output come from
+    pub position_map: bool,
+}
+
+impl Default for MinifyOptions {
+    fn default() -> Self {
+        MinifyOptions { rename_locals: true, inline_includes: true, position_map: false }
+    }
+}
+
+/// Where a run of the output was copied from. The text of a run is copied as is, so the
+/// positions inside it follow.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mapping {
+    /// The offset of the run in the output
+    pub generated: usize,
+    pub file: String,
+    pub original: Position,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PositionMap {
+    /// Sorted by offset in the output
+    pub mappings: Vec<Mapping>,
+}
+
+impl PositionMap {
+    /// The file and position the output at `offset` comes from.
+    pub fn original(&self, offset: usize) -> Option<(&str, Position)> {
+        let index = self.mappings.partition_point(|mapping| mapping.generated <= offset).checked_sub(1)?;
+        let mapping = &self.mappings[index];
+        let shift = offset - mapping.generated;
+        let position = Position {
+            offset: mapping.original.offset + shift,
+            line: mapping.original.line,
+            column: mapping.original.column + shift as u32,
+        };
+        Some((&mapping.file, position))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Minified {
+    pub code: String,
+    pub map: Option<PositionMap>,
+}
+
+/// Makes the entry file of a project as short as possible: comments and whitespace are
+/// removed, except for the spaces keeping two tokens apart, and the semicolons right before a
+/// `}` or at the end are dropped. Strings are kept as they are written.
+pub fn minify(project: &Project, options: MinifyOptions) -> Minified {
+    let files: Vec<&File> = project.files.iter().map(|source| &source.file).collect();
+    let resolution = Resolution::resolve_files(&files);
+
+    let mut semis = HashSet::new();
+    for (index, file) in files.iter().enumerate() {
+        let mut collector = SemiCollector { semis: Vec::new() };
+        collector.visit_file(file);
+        semis.extend(collector.semis.into_iter().map(|offset| (index, offset)));
+    }
+
+    let mut minifier = Minifier {
+        project,
+        options,
+        renames: match options.rename_locals {
+            true => short_names(&resolution),
+            false => HashMap::new(),
+        },
+        semis,
+        emitted: HashSet::new(),
+        code: String::new(),
+        mappings: Vec::new(),
+        gap: false,
+        run: false,
+        semi: false,
+    };
+    minifier.file(0);
+    Minified {
+        code: minifier.code,
+        map: options.position_map.then_some(PositionMap { mappings: minifier.mappings }),
+    }
+}
+
+/// The variables and parameters declared below the top level are renamed, by file and offset
+/// of their declaration and uses.
+fn short_names(resolution: &Resolution) -> HashMap<(usize, usize), String> {
+    let renamed = |binding: &Binding| {
+        matches!(binding.kind, BindingKind::Variable | BindingKind::Constant | BindingKind::Parameter)
+            && binding.scope != ROOT_SCOPE
+            && binding.scope != TOP_LEVEL_SCOPE
+    };
+
+    // Names that keep their meaning everywhere
+    let mut reserved: HashSet<&str> = KEYWORDS.iter().copied().collect();
+    reserved.extend(resolution.bindings.iter().filter(|binding| !renamed(binding)).map(|binding| binding.name.as_str()));
+    reserved.extend(resolution.unresolved().map(|reference| reference.name.as_str()));
+
+    // A scope comes after its parent, and does not reuse the names given there
+    let mut given: Vec<Vec<String>> = vec![Vec::new(); resolution.scopes.len()];
+    let mut names = HashMap::new();
+    for scope in 0..resolution.scopes.len() {
+        let mut taken: HashSet<String> = HashSet::new();
+        let mut parent = resolution.scopes[scope].parent;
+        while let Some(ancestor) = parent {
+            taken.extend(given[ancestor].iter().cloned());
+            parent = resolution.scopes[ancestor].parent;
+        }
+
+        let mut next = 0;
+        for &binding in &resolution.scopes[scope].bindings {
+            if !renamed(&resolution.bindings[binding]) {
+                continue;
+            }
+            let name = loop {
+                let name = short_name(next);
+                next += 1;
+                if !reserved.contains(name.as_str()) && !taken.contains(&name) {
+                    break name;
+                }
+            };
+            taken.insert(name.clone());
+            given[scope].push(name.clone());
+
+            let declaration = &resolution.bindings[binding];
+            let uses = resolution.references_to(binding).map(|reference| (reference.file, reference.location));
+            for (file, location) in std::iter::once((declaration.file, declaration.location)).chain(uses) {
+                if let Some(location) = location {
+                    names.insert((file, location.start.offset), name.clone());
+                }
+            }
+        }
+    }
+    names
+}
+
+/// `a` to `Z`, then `aa`, `ab`...
+fn short_name(mut index: usize) -> String {
+    const FIRST: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+    const NEXT: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789_";
+    let mut name = vec![FIRST[index % FIRST.len()]];
+    index /= FIRST.len();
+    while index > 0 {
+        index -= 1;
+        name.push(NEXT[index % NEXT.len()]);
+        index /= NEXT.len();
+    }
+    String::from_utf8(name).unwrap()
+}
+
+fn is_word(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$' || !c.is_ascii()
+}
+
+/// Whether `before` and `after` would read as another token once put together. The `?` and
+/// `:` of a ternary cannot be followed by a letter either.
+fn needs_space(before: char, after: char) -> bool {
+    (is_word(before) && is_word(after))
+        || (before == after && !"()[]{},;:".contains(before))
+        || (before == '/' && after == '*')
+        || (before == '?' && after == '.')
+        || ((before == '?' || before == ':') && is_word(after))
+}
+
+struct Minifier<'a> {
+    project: &'a Project,
+    options: MinifyOptions,
+    renames: HashMap<(usize, usize), String>,
+    /// The semicolons ending a statement, which can be left out
+    semis: HashSet<(usize, usize)>,
+    /// The files already in the output
+    emitted: HashSet<usize>,
+    code: String,
+    mappings: Vec<Mapping>,
+    /// Whether comments or whitespace were skipped since the last token
+    gap: bool,
+    /// Whether the next text continues the current run of copied text
+    run: bool,
+    /// A semicolon written only if something other than `}` follows
+    semi: bool,
+}
+
+impl Minifier<'_> {
+    fn file(&mut self, index: usize) {
+        self.emitted.insert(index);
+        let source = &self.project.files[index];
+        let text = source.file.to_string();
+
+        // The includes replaced by the files they include
+        let mut inlined = HashMap::new();
+        if self.options.inline_includes {
+            for statement in &source.file.statements {
+                let (GlobalStatement::IncludeStatement(include), Some(location)) = (statement.node.as_ref(), statement.location) else {
+                    continue;
+                };
+                let path = resolve_include(&source.path, &include.path.node.decoded());
+                if let Some(included) = self.project.files.iter().position(|file| file.path == path) {
+                    inlined.insert(location.start.offset, (location.end.offset, included));
+                }
+            }
+        }
+
+        let mut offset = 0;
+        let mut position = Position { offset: 0, line: 1, column: 1 };
+        // The end of the include being replaced
+        let mut skipped = 0;
+        self.gap = true;
+        self.run = false;
+        for (kind, length) in lex(&text) {
+            let start = offset;
+            let token = &text[start..start + length];
+            let token_position = position;
+            offset += length;
+            position = advance(position, token);
+            if start < skipped {
+                continue;
+            }
+            if kind.is_trivia() {
+                self.gap = true;
+                continue;
+            }
+            match inlined.get(&start) {
+                Some(&(end, included)) => {
+                    if !self.emitted.contains(&included) {
+                        self.file(included);
+                    }
+                    self.gap = true;
+                    self.run = false;
+                    skipped = end;
+                }
+                None if token == ";" && self.semis.contains(&(index, start)) => {
+                    self.flush_semi(None);
+                    self.semi = true;
+                    self.run = false;
+                }
+                None => match self.renames.get(&(index, start)).cloned() {
+                    Some(name) if kind == TokenKind::Identifier => {
+                        self.run = false;
+                        self.copy(&name, index, token_position);
+                        self.run = false;
+                    }
+                    _ => self.copy(token, index, token_position),
+                },
+            }
+        }
+        self.gap = true;
+        self.run = false;
+    }
+
+    /// Writes the semicolon put aside unless `next` closes the block.
+    fn flush_semi(&mut self, next: Option<char>) {
+        if std::mem::take(&mut self.semi) && next != Some('}') {
+            self.code.push(';');
+        }
+    }
+
+    fn copy(&mut self, text: &str, file: usize, position: Position) {
+        let Some(first) = text.chars().next() else { return };
+        self.flush_semi(Some(first));
+        if std::mem::take(&mut self.gap) {
+            self.run = false;
+            if self.code.chars().next_back().is_some_and(|last| needs_space(last, first)) {
+                self.code.push(' ');
+            }
+        }
+        if !self.run && self.options.position_map {
+            self.mappings.push(Mapping {
+                generated: self.code.len(),
+                file: self.project.files[file].path.clone(),
+                original: position,
+            });
+        }
+        // Each line of the text starts a new run, so that columns stay right
+        let mut start = position;
+        for (index, line) in text.split_inclusive('\n').enumerate() {
+            if index > 0 && self.options.position_map {
+                self.mappings.push(Mapping {
+                    generated: self.code.len(),
+                    file: self.project.files[file].path.clone(),
+                    original: start,
+                });
+            }
+            self.code.push_str(line);
+            start = advance(start, line);
+        }
+        self.run = true;
+    }
+}
+
+/// Finds the semicolons ending a statement, whose statement also ends without them.
+struct SemiCollector {
+    semis: Vec<usize>,
+}
+
+impl SemiCollector {
+    fn add(&mut self, semi: &MetaNode<Semi>) {
+        if let (true, Some(location)) = (semi.node.0, semi.location) {
+            self.semis.push(location.start.offset);
+        }
+    }
+
+    fn statement(&mut self, statement: &Statement) {
+        match statement {
+            Statement::ExpressionStatement(statement) => self.add(&statement.semi),
+            Statement::VarDeclaration(statement) => self.add(&statement.semi),
+            Statement::DoWhileStatement(statement) => self.add(&statement.semi),
+            Statement::ReturnStatement(statement) => self.add(&statement.semi),
+            Statement::BreakStatement(BreakStatement::Break(semi) | BreakStatement::Continue(semi)) => self.add(semi),
+            Statement::ThrowStatement(statement) => self.add(&statement.semi),
+            _ => {}
+        }
+    }
+}
+
+impl<'ast> Visitor<'ast> for SemiCollector {
+    fn visit_global_statement(&mut self, statement: &'ast MetaNode<GlobalStatement>) {
+        if let GlobalStatement::Statement(statement) = statement.node.as_ref() {
+            self.statement(statement);
+        }
+        walk_global_statement(self, statement);
+    }
+
+    fn visit_include(&mut self, include: &'ast IncludeStatement) {
+        self.add(&include.semi);
+    }
+
+    fn visit_global_definition(&mut self, definition: &'ast GlobalDefinition) {
+        self.add(&definition.semi);
+        walk_global_definition(self, definition);
+    }
+
+    fn visit_field(&mut self, field: &'ast Field) {
+        self.add(&field.semi);
+        walk_field(self, field);
+    }
+
+    fn visit_statement(&mut self, statement: &'ast MetaNode<Statement>) {
+        self.statement(&statement.node);
+        walk_statement(self, statement);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::project::tests::project;
+
+    use super::*;
+
+    fn minified(files: &[(&str, &str)], options: MinifyOptions) -> String {
+        let code = minify(&project(files), options).code;
+        let (_, file) = File::parse(Span::new_extra(&code, "")).unwrap();
+        assert_eq!(file.to_string(), code);
+        code
+    }
+
+    const KEEP_NAMES: MinifyOptions = MinifyOptions { rename_locals: false, inline_includes: false, position_map: false };
+
+    #[test]
+    fn test_separators() {
+        let source = "// AI\nvar a = 1 ; /* two */ var b = a - -a + 1;\nif (a instanceof Array) { return a ; }\n\
+            var s = 'a  b' + `x ${ a  +  1 } y`;\nvar c = a ? .5 : a ?. b;\nvar d = a ? b : {x: c};";
+        assert_eq!(
+            minified(&[("main", source)], KEEP_NAMES),
+            "var a=1;var b=a- -a+1;if(a instanceof Array){return a}var s='a  b'+`x ${a+1} y`;var c=a? .5: a?.b;var d=a? b:{x: c}"
+        );
+        // Already minified
+        let once = minified(&[("main", source)], MinifyOptions::default());
+        assert_eq!(minified(&[("main", &once)], MinifyOptions::default()), once);
+    }
+
+    #[test]
+    fn test_semicolons() {
+        let source = "function f() { var x = 1; if (x) { return; } ; while (x) { x--; break; } return x; }\n\
+            do { f(); } while (false);\nclass A { integer x; y = 2; m(); }";
+        assert_eq!(
+            minified(&[("main", source)], KEEP_NAMES),
+            "function f(){var x=1;if(x){return};while(x){x--;break}return x}do{f()}while(false);\
+             class A{integer x;y=2;m();}"
+        );
+    }
+
+    #[test]
+    fn test_rename_locals() {
+        let source = "var total = 1;\nfunction f(first, second) { var sum = first + second; \
+            for (var i = 0; i < 2; i++) { sum += i + total; } return function(x) { return x + sum; }; }\n\
+            class A { m(value) { var a = value; return a; } }";
+        assert_eq!(
+            minified(&[("main", source)], MinifyOptions::default()),
+            "var total=1;function f(a,b){var c=a+b;for(var d=0;d<2;d++){c+=d+total}\
+             return function(d){return d+c}}class A{m(a){var b=a;return b}}"
+        );
+        // Names used but not declared are kept free
+        assert_eq!(
+            minified(&[("main", "function f(x) { return a + x; }")], MinifyOptions::default()),
+            "function f(b){return a+b}"
+        );
+    }
+
+    #[test]
+    fn test_includes() {
+        let files = [
+            ("main", "include('lib');\ninclude('util');\nf(1);"),
+            ("lib", "include('util');\nfunction f(x) { return g(x); }"),
+            ("util", "function g(y) { return y; }"),
+        ];
+        assert_eq!(
+            minified(&files, MinifyOptions::default()),
+            "function g(a){return a}function f(a){return g(a)}f(1)"
+        );
+        assert_eq!(
+            minified(&files, MinifyOptions { inline_includes: false, ..Default::default() }),
+            "include('lib');include('util');f(1)"
+        );
+    }
+
+    #[test]
+    fn test_position_map() {
+        let files = [("main", "include('lib');\n\nvar value = 12;  // twelve\nreturn value;"), ("lib", "var x = 'a\nb';")];
+        let options = MinifyOptions { position_map: true, ..Default::default() };
+        let minified = minify(&project(&files), options);
+        assert_eq!(minified.code, "var x='a\nb';var value=12;return value");
+        let map = minified.map.unwrap();
+        let at = |offset| map.original(offset).map(|(file, position)| (file.to_string(), position.line, position.column));
+        assert_eq!(at(0), Some(("lib".to_string(), 1, 1)));
+        assert_eq!(at(7), Some(("lib".to_string(), 1, 10)));
+        assert_eq!(at(9), Some(("lib".to_string(), 2, 1)));
+        assert_eq!(at(16), Some(("main".to_string(), 3, 5)));
+        assert_eq!(at(21), Some(("main".to_string(), 3, 11)));
+        assert_eq!(at(22), Some(("main".to_string(), 3, 13)));
+        assert_eq!(at(32), Some(("main".to_string(), 4, 8)));
+    }
+}
@@ -0,0 +1,7 @@
+//! Passes making a file smaller or cheaper to run without changing what it does.
+
+pub mod fold;
+pub mod minify;
+
+pub use fold::*;
+pub use minify::*;
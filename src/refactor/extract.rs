@@ -0,0 +1,282 @@
+use crate::{analysis::*, ast::{scan::code_end, visit::*, *}, lint::Edit};
+
+use super::{check_name, code, statement_lists, ListedStatement, RefactorError};
+
+/// Moves the statements between the offsets `start` and `end` of `file` into a new function
+/// called `name`, added at the end of the file, and calls it in their place. The variables the
+/// statements use from around them become parameters. The one they declare or change for the
+/// code around them is returned and assigned by the call.
+///
+/// The statements keep their text, comments included, and are only indented again.
+pub fn extract_function(file: &File, start: usize, end: usize, name: &str) -> Result<Vec<Edit>, RefactorError> {
+    check_name(name)?;
+    let source = file.to_string();
+    let resolution = Resolution::resolve(file);
+    let used = resolution.bindings.iter().any(|binding| binding.name == name)
+        || resolution.references.iter().any(|reference| reference.name == name);
+    if used {
+        return Err(RefactorError::NameTaken(name.to_string()));
+    }
+
+    let statements = selected_statements(file, &source, start.min(source.len()), end.min(source.len()))?;
+    let mut checker = ExtractChecker { loops: 0, functions: 0, error: None };
+    for statement in &statements {
+        match statement.node {
+            Some(node) => checker.check(node),
+            None => return Err(RefactorError::CannotExtract("a declaration of the top level")),
+        }
+    }
+    if let Some(error) = checker.error {
+        return Err(RefactorError::CannotExtract(error));
+    }
+
+    let (range_start, _) = code(&source, statements[0].location);
+    let (_, range_end) = code(&source, statements[statements.len() - 1].location);
+    let within = |location: Option<Location>| {
+        location.is_some_and(|location| range_start <= location.start.offset && location.end.offset <= range_end)
+    };
+
+    // Globals, functions and classes are visible from the new function as well
+    let mut parameters: Vec<BindingId> = Vec::new();
+    let mut references: Vec<_> = resolution.references.iter().filter(|reference| within(reference.location)).collect();
+    references.sort_by_key(|reference| reference.location.map(|location| location.start));
+    for reference in references {
+        if let Some(binding) = reference.binding {
+            let declaration = &resolution.bindings[binding];
+            if declaration.scope != ROOT_SCOPE && !within(declaration.location) && !parameters.contains(&binding) {
+                parameters.push(binding);
+            }
+        }
+    }
+
+    let mut results: Vec<BindingId> = (0..resolution.bindings.len())
+        .filter(|&binding| {
+            within(resolution.bindings[binding].location)
+                && resolution.references_to(binding).any(|reference| !within(reference.location))
+        })
+        .collect();
+    results.extend(parameters.iter().copied().filter(|&binding| {
+        let references: Vec<_> = resolution.references_to(binding).collect();
+        references.iter().any(|reference| within(reference.location) && reference.access != Access::Read)
+            && references.iter().any(|reference| !within(reference.location) && reference.access != Access::Write)
+    }));
+    if results.len() > 1 {
+        let names = results.iter().map(|&binding| resolution.bindings[binding].name.clone()).collect();
+        return Err(RefactorError::TooManyResults(names));
+    }
+
+    let arguments: Vec<_> = parameters.iter().map(|&binding| resolution.bindings[binding].name.as_str()).collect();
+    let call = format!("{}({})", name, arguments.join(", "));
+    let (call, returned) = match results.first() {
+        None => (format!("{};", call), None),
+        Some(&binding) => {
+            let binding = &resolution.bindings[binding];
+            match within(binding.location) {
+                true => {
+                    let type_ = declared_type(&statements, binding.location).ok_or(RefactorError::InvalidSelection)?;
+                    let (type_start, type_end) = code(&source, type_);
+                    (format!("{} {} = {};", &source[type_start..type_end], binding.name, call), Some(&binding.name))
+                }
+                false => (format!("{} = {};", binding.name, call), Some(&binding.name)),
+            }
+        }
+    };
+
+    let unit = indent_unit(&source);
+    let line_start = source[..range_start].rfind('\n').map_or(0, |newline| newline + 1);
+    let base = match source[line_start..range_start].trim().is_empty() {
+        true => &source[line_start..range_start],
+        false => "",
+    };
+    let mut body = String::new();
+    for (index, line) in source[range_start..range_end].split('\n').enumerate() {
+        let line = match index {
+            0 => line,
+            _ => line.strip_prefix(base).unwrap_or_else(|| line.trim_start()),
+        };
+        if !line.trim().is_empty() {
+            body.push_str(unit);
+            body.push_str(line);
+        }
+        body.push('\n');
+    }
+    if let Some(returned) = returned {
+        body.push_str(&format!("{}return {};\n", unit, returned));
+    }
+    let function = format!("function {}({}) {{\n{}}}", name, arguments.join(", "), body);
+    let function = match source.ends_with('\n') {
+        true => format!("\n{}\n", function),
+        false => format!("\n\n{}", function),
+    };
+
+    Ok(vec![
+        Edit { start: range_start, end: range_end, text: call },
+        Edit { start: source.len(), end: source.len(), text: function },
+    ])
+}
+
+/// The statements of the first list having some statements in the selection and none cut by
+/// it. Only comments and whitespace may be selected around them.
+fn selected_statements<'a>(file: &'a File, source: &str, start: usize, end: usize) -> Result<Vec<ListedStatement<'a>>, RefactorError> {
+    for list in statement_lists(file) {
+        let mut selected = Vec::new();
+        let mut cut = false;
+        for statement in list {
+            let (statement_start, statement_end) = code(source, statement.location);
+            if start <= statement_start && statement_end <= end {
+                selected.push(statement);
+            } else if statement_start < end && start < statement_end {
+                cut = true;
+            }
+        }
+        if selected.is_empty() || cut {
+            continue;
+        }
+
+        let (first, _) = code(source, selected[0].location);
+        let (_, last) = code(source, selected[selected.len() - 1].location);
+        return match code_end(&source[start..first]) == 0 && code_end(&source[last..end]) == 0 {
+            true => Ok(selected),
+            false => Err(RefactorError::InvalidSelection),
+        };
+    }
+    Err(RefactorError::InvalidSelection)
+}
+
+/// Where the type of the variable declared at `name` is written, `var` included.
+fn declared_type(statements: &[ListedStatement], name: Option<Location>) -> Option<Location> {
+    statements.iter().find_map(|statement| match statement.node {
+        Some(Statement::VarDeclaration(declaration)) => {
+            let declaration = &declaration.var_declaration.node;
+            declaration.values.iter()
+                .any(|(declared, _)| declared.location == name)
+                .then_some(declaration.type_.location)
+                .flatten()
+        }
+        _ => None,
+    })
+}
+
+/// The indentation of the first indented line, a tab if there is none.
+fn indent_unit(source: &str) -> &str {
+    source.lines()
+        .find_map(|line| {
+            let code = line.trim_start();
+            let indent = &line[..line.len() - code.len()];
+            (!indent.is_empty() && !code.is_empty()).then_some(indent)
+        })
+        .unwrap_or("\t")
+}
+
+/// Finds what only makes sense where the statements are.
+struct ExtractChecker {
+    loops: usize,
+    functions: usize,
+    error: Option<&'static str>,
+}
+
+impl ExtractChecker {
+    fn check(&mut self, statement: &Statement) {
+        match statement {
+            Statement::ReturnStatement(_) if self.functions == 0 => {
+                self.error.get_or_insert("`return`");
+            }
+            Statement::BreakStatement(_) if self.loops == 0 && self.functions == 0 => {
+                self.error.get_or_insert("`break` or `continue` leaving the statements");
+            }
+            _ => {}
+        }
+        let is_loop = matches!(
+            statement,
+            Statement::WhileStatement(_) | Statement::DoWhileStatement(_) | Statement::ForStatement(_)
+        );
+        self.loops += is_loop as usize;
+        walk_statement_node(self, statement);
+        self.loops -= is_loop as usize;
+    }
+}
+
+impl<'ast> Visitor<'ast> for ExtractChecker {
+    fn visit_statement(&mut self, statement: &'ast MetaNode<Statement>) {
+        self.check(&statement.node);
+    }
+
+    fn visit_anonymous_function(&mut self, function: &'ast AnonymousFunction) {
+        self.functions += 1;
+        walk_anonymous_function(self, function);
+        self.functions -= 1;
+    }
+
+    fn visit_primary(&mut self, primary: &'ast PrimaryExpr) {
+        if let PrimaryExpr::IdentifierOrMember(object) = primary {
+            if !matches!(object.node.as_ref(), IdentifierOrMember::Identifier(_)) {
+                self.error.get_or_insert("`this`, `super` or `class`");
+            }
+        }
+        walk_primary(self, primary);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{lint::apply_fixes, lint::Fix, utils::parse_file};
+
+    use super::*;
+
+    /// Extracts the statements from the first `from` to the end of the first `to` after it.
+    fn extracted(source: &str, from: &str, to: &str, name: &str) -> Result<String, String> {
+        let start = source.find(from).unwrap();
+        let end = start + source[start..].find(to).unwrap() + to.len();
+        let edits = extract_function(&parse_file(source), start, end, name).map_err(|error| error.to_string())?;
+        Ok(apply_fixes(source, &[Fix { location: None, edits }]))
+    }
+
+    #[test]
+    fn test_extract_declaration() {
+        let source = "var a = 1;\nvar b = 2;\n// sum\nvar c = a + /* both */ b; // c\nprint(c);";
+        assert_eq!(
+            extracted(source, "// sum", "b;", "sum").unwrap(),
+            "var a = 1;\nvar b = 2;\n// sum\nvar c = sum(a, b); // c\nprint(c);\n\n\
+            function sum(a, b) {\n\tvar c = a + /* both */ b;\n\treturn c;\n}"
+        );
+    }
+
+    #[test]
+    fn test_extract_changed_variable() {
+        let source = "function f(n) {\n    var total = 0;\n    for (var i = 0; i < n; i++) {\n        \
+            total += i; // add\n\n        debug(total);\n    }\n    return total;\n}\n";
+        assert_eq!(
+            extracted(source, "total +=", "debug(total);", "step").unwrap(),
+            "function f(n) {\n    var total = 0;\n    for (var i = 0; i < n; i++) {\n        \
+            total = step(total, i);\n    }\n    return total;\n}\n\n\
+            function step(total, i) {\n    total += i; // add\n\n    debug(total);\n    return total;\n}\n"
+        );
+        // Nothing to give back when the variable is not used afterwards
+        assert_eq!(
+            extracted("var a = 1;\nwhile (a) { a = 0; }\n", "while", "}", "loop").unwrap(),
+            "var a = 1;\nloop(a);\n\nfunction loop(a) {\n\twhile (a) { a = 0; }\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_refused() {
+        let source = "var a = 1; var b = 2;\nfunction f() { if (a) { return 1; } }";
+        assert_eq!(extracted(source, "if", "}", "g").unwrap_err(), "`return` cannot be moved to another function");
+        assert_eq!(extracted(source, "= 1", "2;", "g").unwrap_err(), "the selection does not cover whole statements of a single block");
+        assert_eq!(extracted(source, "var", "} }", "g").unwrap_err(), "a declaration of the top level cannot be moved to another function");
+        assert_eq!(extracted(source, "var", "2;", "f").unwrap_err(), "`f` is already used in this file");
+        assert_eq!(extracted(source, "var", "2;", "for").unwrap_err(), "`for` is a keyword");
+        assert_eq!(
+            extracted("var a = 1; var b = 2; a++; b++; a + b;", "a++", "b++;", "g").unwrap_err(),
+            "the statements change `a`, `b` which are used afterwards, a function returns a single value"
+        );
+        assert_eq!(
+            extracted("while (true) { break; }", "break", ";", "g").unwrap_err(),
+            "`break` or `continue` leaving the statements cannot be moved to another function"
+        );
+        assert_eq!(
+            extracted("class A { m() { this.x = 1; } }", "this", ";", "g").unwrap_err(),
+            "`this`, `super` or `class` cannot be moved to another function"
+        );
+    }
+}
@@ -0,0 +1,259 @@
+use crate::{analysis::*, ast::{scan::code_start, visit::*, *}, lint::Edit};
+
+use super::{code, statement_lists, RefactorError};
+
+/// Replaces the single read of the variable named at `offset` with its value, and removes its
+/// declaration. The value is only moved where it gives the same result: a value made of
+/// literals, variables and operators when none of its variables is assigned after the
+/// declaration, any other value when it is the last one declared and is read by the next
+/// statement of the same block, which is not a loop. Every variable of the value has to name
+/// the same declaration where it is used.
+///
+/// The value keeps its text, comments included, and is put in parentheses only when the
+/// operators around the use bind tighter.
+pub fn inline_variable(file: &File, offset: usize) -> Result<Vec<Edit>, RefactorError> {
+    let source = file.to_string();
+    let resolution = Resolution::resolve(file);
+    let contains = |location: Option<Location>| {
+        location.is_some_and(|location| location.start.offset <= offset && offset <= location.end.offset)
+    };
+    let binding = (0..resolution.bindings.len())
+        .find(|&binding| contains(resolution.bindings[binding].location))
+        .or_else(|| resolution.references.iter().find(|reference| contains(reference.location))?.binding)
+        .filter(|&binding| matches!(resolution.bindings[binding].kind, BindingKind::Variable | BindingKind::Constant))
+        .ok_or(RefactorError::NoVariable)?;
+    let name = resolution.bindings[binding].name.clone();
+    let declared = resolution.bindings[binding].location;
+
+    let references: Vec<_> = resolution.references_to(binding).collect();
+    let use_ = match references[..] {
+        [use_] if use_.access == Access::Read => use_,
+        _ => return Err(RefactorError::NotSingleUse(name)),
+    };
+    let use_location = use_.location.ok_or(RefactorError::NoVariable)?;
+
+    let lists = statement_lists(file);
+    let (list, index, declaration) = lists.iter()
+        .find_map(|list| {
+            list.iter().enumerate().find_map(|(index, statement)| match statement.node {
+                Some(Statement::VarDeclaration(declaration))
+                    if declaration.var_declaration.node.values.iter().any(|(declared_name, _)| declared_name.location == declared) =>
+                {
+                    Some((list, index, &declaration.var_declaration.node))
+                }
+                _ => None,
+            })
+        })
+        .ok_or(RefactorError::NoVariable)?;
+    let entry = declaration.values.iter().position(|(declared_name, _)| declared_name.location == declared).unwrap();
+    let value = declaration.values[entry].1.as_ref()
+        .and_then(|value| Some((value, value.location?)))
+        .ok_or_else(|| RefactorError::NoValue(name.clone()));
+    let (value, value_location) = value?;
+    let (value_start, value_end) = code(&source, value_location);
+
+    let read: Vec<_> = resolution.references.iter()
+        .filter(|reference| {
+            reference.location.is_some_and(|location| value_start <= location.start.offset && location.end.offset <= value_end)
+        })
+        .collect();
+    for reference in &read {
+        if resolution.lookup(use_.scope, &reference.name) != reference.binding {
+            let reason = format!("`{}` names something else where it is used", reference.name);
+            return Err(RefactorError::ValueChanges { name, reason });
+        }
+    }
+
+    let mut simple = SimpleValue(true);
+    simple.visit_expression(&value.node);
+    if simple.0 {
+        for reference in read {
+            let Some(read) = reference.binding else { continue };
+            let assigned = resolution.references_to(read).any(|other| {
+                other.access != Access::Read && other.location.is_some_and(|location| location.start.offset > value_end)
+            });
+            if assigned {
+                let reason = format!("`{}` is assigned after the declaration", reference.name);
+                return Err(RefactorError::ValueChanges { name, reason });
+            }
+        }
+    } else {
+        let next = list.get(index + 1).filter(|next| {
+            next.location.start.offset <= use_location.start.offset
+                && use_location.end.offset <= next.location.end.offset
+                && !matches!(
+                    next.node,
+                    Some(Statement::WhileStatement(_) | Statement::DoWhileStatement(_) | Statement::ForStatement(_))
+                )
+        });
+        if next.is_none() || use_.scope != resolution.bindings[binding].scope || entry + 1 != declaration.values.len() {
+            let reason = "its value may have effects and is not read right after it".to_string();
+            return Err(RefactorError::ValueChanges { name, reason });
+        }
+    }
+
+    let mut context = UseContext { location: use_location, required: 0, found: None };
+    context.visit_file(file);
+    let mut text = source[value_start..value_end].to_string();
+    if value.node.precedence() < context.found.unwrap_or(0) {
+        text = format!("({})", text);
+    }
+
+    let (name_start, _) = code(&source, declared.ok_or(RefactorError::NoVariable)?);
+    let (start, end) = match (entry, declaration.values.len()) {
+        (_, 1) => {
+            let (start, end) = code(&source, list[index].location);
+            whole_line(&source, start, end)
+        }
+        // From the comma before it
+        (0, _) => {
+            let next = declaration.values[1].0.location.ok_or(RefactorError::NoVariable)?;
+            (name_start, next.start.offset)
+        }
+        (entry, _) => {
+            let (previous_name, previous_value) = &declaration.values[entry - 1];
+            let previous = previous_value.as_ref().map_or(previous_name.location, |value| value.location);
+            let (_, previous_end) = code(&source, previous.ok_or(RefactorError::NoVariable)?);
+            (previous_end + code_start(&source[previous_end..name_start]), value_end)
+        }
+    };
+
+    Ok(vec![
+        Edit { start, end, text: String::new() },
+        Edit { start: use_location.start.offset, end: use_location.end.offset, text },
+    ])
+}
+
+/// The whole line of `start..end` when nothing else is on it, else the range followed by its
+/// spaces.
+fn whole_line(source: &str, start: usize, end: usize) -> (usize, usize) {
+    let line_start = source[..start].rfind('\n').map_or(0, |newline| newline + 1);
+    let line_end = source[end..].find('\n').map_or(source.len(), |newline| end + newline + 1);
+    if source[line_start..start].trim().is_empty() && source[end..line_end].trim().is_empty() {
+        return (line_start, line_end);
+    }
+    let rest = &source[end..];
+    (start, end + rest.len() - rest.trim_start_matches([' ', '\t']).len())
+}
+
+/// Whether an expression only reads variables, with no call, assignment or member access
+/// whose result could change before the use.
+struct SimpleValue(bool);
+
+impl<'ast> Visitor<'ast> for SimpleValue {
+    fn visit_expression(&mut self, expression: &'ast Expression) {
+        match expression {
+            Expression::Assign(_) | Expression::PreUpdate(_) | Expression::PostUpdate(_) | Expression::Member(_) => {
+                self.0 = false
+            }
+            Expression::Unary(unary) if matches!(unary.op.node.as_ref(), UnaryOp::New) => self.0 = false,
+            _ => walk_expression(self, expression),
+        }
+    }
+}
+
+/// Finds the precedence an expression needs to replace the name at `location` without
+/// parentheses.
+struct UseContext {
+    location: Location,
+    /// Required by the expression being visited of its operands
+    required: u8,
+    found: Option<u8>,
+}
+
+impl<'ast> Visitor<'ast> for UseContext {
+    fn visit_expression(&mut self, expression: &'ast Expression) {
+        if let Some(name) = expression.as_identifier() {
+            if name.location == Some(self.location) {
+                self.found = Some(self.required);
+            }
+            return;
+        }
+
+        let outer = self.required;
+        match expression {
+            Expression::Assign(assign) => {
+                self.visit_expression(&assign.left);
+                self.required = 0;
+                for (_, value) in &assign.right {
+                    self.visit_expression(&value.node);
+                }
+            }
+            // Arguments, indexes, elements and bodies stand on their own
+            Expression::Member(_) | Expression::Primary(_) | Expression::AnonyFunc(_) => {
+                self.required = 0;
+                walk_expression(self, expression);
+            }
+            expression => {
+                self.required = expression.precedence() + 1;
+                walk_expression(self, expression);
+            }
+        }
+        self.required = outer;
+    }
+
+    fn visit_primary(&mut self, primary: &'ast PrimaryExpr) {
+        // The object of a member
+        if let PrimaryExpr::IdentifierOrMember(object) = primary {
+            if let IdentifierOrMember::Identifier(name) = object.node.as_ref() {
+                if name.location == Some(self.location) {
+                    self.found = Some(Expression::Member(Member { left: primary.clone(), right: Vec::new() }).precedence());
+                }
+            }
+        }
+        walk_primary(self, primary);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{lint::apply_fixes, lint::Fix, utils::parse_file};
+
+    use super::*;
+
+    /// Inlines the variable named at the first `at`.
+    fn inlined(source: &str, at: &str) -> Result<String, String> {
+        let edits = inline_variable(&parse_file(source), source.find(at).unwrap()).map_err(|error| error.to_string())?;
+        Ok(apply_fixes(source, &[Fix { location: None, edits }]))
+    }
+
+    #[test]
+    fn test_inline() {
+        assert_eq!(
+            inlined("var a = 2;\n// b\nvar b = a /* three */ * 3;\ndebug(b + 1);\n", "b =").unwrap(),
+            "var a = 2;\n// b\ndebug(a /* three */ * 3 + 1);\n"
+        );
+        assert_eq!(inlined("var b = 1 + 2; /* keep */\ndebug(-b);", "b)").unwrap(), "/* keep */\ndebug(-(1 + 2));");
+        assert_eq!(inlined("var a = 1, b = a || c, d;\nreturn b.x;", "b =").unwrap(), "var a = 1, d;\nreturn (a || c).x;");
+        assert_eq!(inlined("var b = 2, c = 1;\nreturn b ? c : 0;", "b ?").unwrap(), "var c = 1;\nreturn 2 ? c : 0;");
+        assert_eq!(inlined("var a = 1;\nvar b = f();\ndebug(b);", "b =").unwrap(), "var a = 1;\ndebug(f());");
+    }
+
+    #[test]
+    fn test_refused() {
+        assert_eq!(inlined("var a = 1; a; a;", "a =").unwrap_err(), "`a` has to be read once and never assigned to be inlined");
+        assert_eq!(inlined("var a = 1; a = 2;", "a =").unwrap_err(), "`a` has to be read once and never assigned to be inlined");
+        assert_eq!(inlined("var a; debug(a);", "a;").unwrap_err(), "`a` is declared without a value");
+        assert_eq!(inlined("function f(a) { return a; }", "a)").unwrap_err(), "there is no variable to inline here");
+        assert_eq!(
+            inlined("var a = 1; var b = a + 1; a = 3; debug(b);", "b =").unwrap_err(),
+            "`b` cannot be inlined: `a` is assigned after the declaration"
+        );
+        assert_eq!(
+            inlined("var b = f(); g(); debug(b);", "b =").unwrap_err(),
+            "`b` cannot be inlined: its value may have effects and is not read right after it"
+        );
+        assert_eq!(
+            inlined("var b = f(); while (c) { debug(b); }", "b =").unwrap_err(),
+            "`b` cannot be inlined: its value may have effects and is not read right after it"
+        );
+        assert_eq!(
+            inlined("var a = 1; var b = a; var g = function(a) { return b; };", "b =").unwrap_err(),
+            "`b` cannot be inlined: `a` names something else where it is used"
+        );
+        assert_eq!(
+            inlined("var b = abs(1); var g = function(abs) { return b; };", "b =").unwrap_err(),
+            "`b` cannot be inlined: `abs` names something else where it is used"
+        );
+    }
+}
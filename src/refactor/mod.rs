@@ -0,0 +1,150 @@
+//! Changes to the source of a project that keep what it does, given as text edits so that the
+//! rest of the files, comments included, stays as it was written.
+
+pub mod rename;
+pub mod extract;
+pub mod inline;
+
+pub use rename::*;
+pub use extract::*;
+pub use inline::*;
+
+use std::fmt::Display;
+
+use crate::{ast::{scan::code_end, visit::*, *}, lint::{apply_fixes, Edit, Fix}};
+
+/// A place in one of the files of a project.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourcePosition<'a> {
+    pub path: &'a str,
+    pub offset: usize,
+}
+
+/// The edits to make to one file of a project.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileEdits {
+    pub path: String,
+    pub edits: Vec<Edit>,
+}
+
+impl FileEdits {
+    pub fn apply(&self, source: &str) -> String {
+        apply_fixes(source, &[Fix { location: None, edits: self.edits.clone() }])
+    }
+}
+
+/// Why a name cannot be given to a new or renamed symbol.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NameError {
+    Keyword(String),
+    Invalid(String),
+}
+
+impl Display for NameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NameError::Keyword(name) => write!(f, "`{}` is a keyword", name),
+            NameError::Invalid(name) => write!(f, "`{}` is not a valid name", name),
+        }
+    }
+}
+
+/// The name has to read back as a variable.
+pub(crate) fn check_name(name: &str) -> Result<(), NameError> {
+    if KEYWORDS.contains(&name) {
+        return Err(NameError::Keyword(name.to_string()));
+    }
+    match Expression::parse(Span::new_extra(name, "")) {
+        Ok((rest, expression)) if rest.is_empty() && expression.node.as_identifier().is_some() => Ok(()),
+        _ => Err(NameError::Invalid(name.to_string())),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RefactorError {
+    InvalidName(NameError),
+    /// The file already uses the name for something else
+    NameTaken(String),
+    InvalidSelection,
+    /// Something the selected statements do that a function cannot
+    CannotExtract(&'static str),
+    /// The variables changed by the selected statements and used after them
+    TooManyResults(Vec<String>),
+    NoVariable,
+    NoValue(String),
+    NotSingleUse(String),
+    /// The value of the variable may give something else where it is used
+    ValueChanges { name: String, reason: String },
+}
+
+impl Display for RefactorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RefactorError::InvalidName(error) => write!(f, "{}", error),
+            RefactorError::NameTaken(name) => write!(f, "`{}` is already used in this file", name),
+            RefactorError::InvalidSelection => write!(f, "the selection does not cover whole statements of a single block"),
+            RefactorError::CannotExtract(what) => write!(f, "{} cannot be moved to another function", what),
+            RefactorError::TooManyResults(names) => {
+                let names: Vec<_> = names.iter().map(|name| format!("`{}`", name)).collect();
+                write!(f, "the statements change {} which are used afterwards, a function returns a single value", names.join(", "))
+            }
+            RefactorError::NoVariable => write!(f, "there is no variable to inline here"),
+            RefactorError::NoValue(name) => write!(f, "`{}` is declared without a value", name),
+            RefactorError::NotSingleUse(name) => write!(f, "`{}` has to be read once and never assigned to be inlined", name),
+            RefactorError::ValueChanges { name, reason } => write!(f, "`{}` cannot be inlined: {}", name, reason),
+        }
+    }
+}
+
+impl std::error::Error for RefactorError {}
+
+impl From<NameError> for RefactorError {
+    fn from(error: NameError) -> Self {
+        RefactorError::InvalidName(error)
+    }
+}
+
+/// The text of a node in `source`, without the comments its last tokens took along.
+pub(crate) fn code(source: &str, location: Location) -> (usize, usize) {
+    let start = location.start.offset;
+    (start, start + code_end(&source[start..location.end.offset]))
+}
+
+/// A statement of a list, with where it is. Statements of the top level that are not plain
+/// statements, like functions, have no node.
+pub(crate) struct ListedStatement<'a> {
+    pub location: Location,
+    pub node: Option<&'a Statement>,
+}
+
+/// Every list of statements of a file, the top level first, then the blocks in the order they
+/// are found.
+pub(crate) fn statement_lists(file: &File) -> Vec<Vec<ListedStatement<'_>>> {
+    let mut collector = ListCollector { lists: Vec::new() };
+    collector.lists.push(file.statements.iter()
+        .filter_map(|statement| {
+            let node = match statement.node.as_ref() {
+                GlobalStatement::Statement(node) => Some(node),
+                _ => None,
+            };
+            Some(ListedStatement { location: statement.location?, node })
+        })
+        .collect());
+    collector.visit_file(file);
+    collector.lists
+}
+
+struct ListCollector<'a> {
+    lists: Vec<Vec<ListedStatement<'a>>>,
+}
+
+impl<'a> Visitor<'a> for ListCollector<'a> {
+    fn visit_block(&mut self, block: &'a BlockStatement) {
+        if let BlockStatement::StatementList(statements) = block {
+            self.lists.push(statements.iter()
+                .filter_map(|statement| Some(ListedStatement { location: statement.location?, node: Some(&statement.node) }))
+                .collect());
+        }
+        walk_block(self, block);
+    }
+}
@@ -0,0 +1,459 @@
+use std::fmt::Display;
+
+use crate::{analysis::*, ast::{visit::*, *}, lint::Edit};
+
+use super::{check_name, FileEdits, NameError, SourcePosition};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RenameError {
+    NoSymbol,
+    InvalidName(NameError),
+    /// The scope or the class hierarchy already declares the name
+    Redeclared { name: String, path: String, location: Option<Location> },
+    /// A declaration between a use and the renamed one would take the use
+    Shadowed { name: String, path: String, location: Option<Location> },
+    /// A use of another declaration would refer to the renamed one
+    Captured { name: String, path: String, location: Option<Location> },
+    /// The member name is also accessed on an object of unknown type, which may be the renamed member
+    Untyped { name: String, path: String, location: Option<Location> },
+}
+
+impl Display for RenameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let at = |path: &str, location: &Option<Location>| match location {
+            Some(location) => format!("{}:{}", path, location.start),
+            None => path.to_string(),
+        };
+        match self {
+            RenameError::NoSymbol => write!(f, "there is no symbol to rename here"),
+            RenameError::InvalidName(error) => write!(f, "{}", error),
+            RenameError::Redeclared { name, path, location } => {
+                write!(f, "`{}` is already declared at {}", name, at(path, location))
+            }
+            RenameError::Shadowed { name, path, location } => {
+                write!(f, "the declaration of `{}` at {} would hide the renamed symbol", name, at(path, location))
+            }
+            RenameError::Captured { name, path, location } => {
+                write!(f, "the use of `{}` at {} would refer to the renamed symbol", name, at(path, location))
+            }
+            RenameError::Untyped { name, path, location } => {
+                write!(f, "`{}` is accessed at {} on an object of unknown type", name, at(path, location))
+            }
+        }
+    }
+}
+
+impl std::error::Error for RenameError {}
+
+/// Renames the local, parameter, global, function, class, field or method named at `position`,
+/// with the edits to make to every file of the project using it. Overriding methods are renamed
+/// along with the one they override.
+///
+/// The rename is refused when the new name would take the place of another symbol for any of
+/// the uses, or give another symbol's uses to the renamed one. Members accessed on anything else
+/// than `this`, `super`, `class` or a class name have no known type, so a member is not renamed
+/// when its name is accessed that way anywhere in the project.
+pub fn rename(project: &Project, position: SourcePosition, new_name: &str) -> Result<Vec<FileEdits>, RenameError> {
+    check_name(new_name).map_err(RenameError::InvalidName)?;
+
+    let files: Vec<&File> = project.files.iter().map(|source| &source.file).collect();
+    let resolution = Resolution::resolve_files(&files);
+    let (table, _) = ClassTable::build(project);
+    let (occurrences, untyped) = occurrences(project, &resolution, &table);
+
+    let file = project.files.iter().position(|source| source.path == position.path);
+    let symbol = occurrences.iter()
+        .find(|occurrence| {
+            Some(occurrence.file) == file
+                && occurrence.location.start.offset <= position.offset
+                && position.offset <= occurrence.location.end.offset
+        })
+        .map(|occurrence| occurrence.symbol.clone())
+        .ok_or(RenameError::NoSymbol)?;
+
+    let old_name = match &symbol {
+        Symbol::Binding(binding) => resolution.bindings[*binding].name.as_str(),
+        Symbol::Member { name, .. } => name,
+    };
+    if old_name == new_name {
+        return Ok(Vec::new());
+    }
+    match &symbol {
+        Symbol::Binding(binding) => check_binding(project, &resolution, *binding, new_name)?,
+        Symbol::Member { class, name, .. } => {
+            if let Some(access) = untyped.iter().find(|access| access.name == *name) {
+                return Err(RenameError::Untyped {
+                    name: name.to_string(),
+                    path: project.files[access.file].path.clone(),
+                    location: access.location,
+                });
+            }
+            check_member(&table, class, new_name)?
+        }
+    }
+
+    let mut edits = Vec::new();
+    for (index, source) in project.files.iter().enumerate() {
+        let mut file_edits: Vec<Edit> = occurrences.iter()
+            .filter(|occurrence| occurrence.file == index && occurrence.symbol == symbol)
+            .map(|occurrence| Edit {
+                start: occurrence.location.start.offset,
+                end: occurrence.location.end.offset,
+                text: new_name.to_string(),
+            })
+            .collect();
+        file_edits.sort_by_key(|edit| edit.start);
+        file_edits.dedup();
+        if !file_edits.is_empty() {
+            edits.push(FileEdits { path: source.path.clone(), edits: file_edits });
+        }
+    }
+    Ok(edits)
+}
+
+fn check_binding(project: &Project, resolution: &Resolution, renamed: BindingId, name: &str) -> Result<(), RenameError> {
+    let binding = &resolution.bindings[renamed];
+    let named = |scope: ScopeId| {
+        resolution.scopes[scope].bindings.iter().copied().find(|&other| resolution.bindings[other].name == name)
+    };
+    let place = |other: BindingId| {
+        let other = &resolution.bindings[other];
+        (project.files[other.file].path.clone(), other.location)
+    };
+
+    if let Some(other) = named(binding.scope) {
+        let (path, location) = place(other);
+        return Err(RenameError::Redeclared { name: name.to_string(), path, location });
+    }
+
+    for reference in resolution.references_to(renamed) {
+        let mut scope = Some(reference.scope);
+        while let Some(current) = scope.filter(|&scope| scope != binding.scope) {
+            if let Some(other) = named(current) {
+                let (path, location) = place(other);
+                return Err(RenameError::Shadowed { name: name.to_string(), path, location });
+            }
+            scope = resolution.scopes[current].parent;
+        }
+    }
+
+    let sees = |from: ScopeId, scope: ScopeId| {
+        std::iter::successors(Some(from), |&current| resolution.scopes[current].parent).any(|current| current == scope)
+    };
+    for reference in resolution.references.iter().filter(|reference| reference.name == name) {
+        // Names of the builtins are not declared and get captured as well
+        let outer = reference.binding.is_none_or(|other| {
+            let scope = resolution.bindings[other].scope;
+            scope != binding.scope && sees(binding.scope, scope)
+        });
+        if outer && sees(reference.scope, binding.scope) {
+            let path = project.files[reference.file].path.clone();
+            return Err(RenameError::Captured { name: name.to_string(), path, location: reference.location });
+        }
+    }
+    Ok(())
+}
+
+/// A member of the same name in a class above or below the renamed one would be overridden or
+/// overriding.
+fn check_member(table: &ClassTable, class: &str, name: &str) -> Result<(), RenameError> {
+    let mut related: Vec<_> = table.classes()
+        .filter(|entry| table.is_subclass_of(entry.name(), class) || table.is_subclass_of(class, entry.name()))
+        .collect();
+    related.sort_by_key(|entry| (entry.file, entry.name()));
+
+    for entry in related {
+        let declared = entry.class.members().find_map(|member| match member {
+            ClassMember::Field(field) if field.name.node.name == name => Some(&field.name),
+            ClassMember::Method(method) if method.name.node.name == name => Some(&method.name),
+            _ => None,
+        });
+        if let Some(declared) = declared {
+            return Err(RenameError::Redeclared {
+                name: name.to_string(),
+                path: entry.file.to_string(),
+                location: declared.location,
+            });
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Symbol<'a> {
+    Binding(BindingId),
+    /// A field or method, known by the highest class declaring it so that overrides go together
+    Member { class: &'a str, name: &'a str, static_: bool },
+}
+
+/// A place where a symbol is named.
+struct Occurrence<'a> {
+    symbol: Symbol<'a>,
+    file: usize,
+    location: Location,
+}
+
+/// A member accessed on an object of unknown type.
+struct UntypedAccess<'a> {
+    name: &'a str,
+    file: usize,
+    location: Option<Location>,
+}
+
+/// Every name of the project that can be renamed, and the member accesses that cannot be told
+/// apart from any member of the same name.
+fn occurrences<'a>(project: &'a Project, resolution: &Resolution, table: &ClassTable<'a>) -> (Vec<Occurrence<'a>>, Vec<UntypedAccess<'a>>) {
+    let mut occurrences = Vec::new();
+    for (id, binding) in resolution.bindings.iter().enumerate() {
+        if let Some(location) = binding.location {
+            occurrences.push(Occurrence { symbol: Symbol::Binding(id), file: binding.file, location });
+        }
+    }
+    for reference in &resolution.references {
+        if let (Some(binding), Some(location)) = (reference.binding, reference.location) {
+            occurrences.push(Occurrence { symbol: Symbol::Binding(binding), file: reference.file, location });
+        }
+    }
+
+    let mut untyped = Vec::new();
+    for (file, source) in project.files.iter().enumerate() {
+        let mut collector = MemberCollector {
+            resolution,
+            table,
+            file,
+            class: None,
+            occurrences: Vec::new(),
+            untyped: Vec::new(),
+        };
+        collector.visit_file(&source.file);
+        occurrences.extend(collector.occurrences);
+        untyped.extend(collector.untyped);
+    }
+    (occurrences, untyped)
+}
+
+/// Finds the class names used as types and the members of the classes, which the scopes do not
+/// know about.
+struct MemberCollector<'r, 't, 'a> {
+    resolution: &'r Resolution,
+    table: &'t ClassTable<'a>,
+    file: usize,
+    class: Option<&'a str>,
+    occurrences: Vec<Occurrence<'a>>,
+    untyped: Vec<UntypedAccess<'a>>,
+}
+
+impl<'a> MemberCollector<'_, '_, 'a> {
+    fn push(&mut self, symbol: Symbol<'a>, name: &MetaNode<Identifier>) {
+        if let Some(location) = name.location {
+            self.occurrences.push(Occurrence { symbol, file: self.file, location });
+        }
+    }
+
+    fn class_name(&mut self, name: &MetaNode<Identifier>) {
+        let binding = self.resolution.lookup(ROOT_SCOPE, &name.node.name)
+            .filter(|&binding| self.resolution.bindings[binding].kind == BindingKind::Class);
+        if let Some(binding) = binding {
+            self.push(Symbol::Binding(binding), name);
+        }
+    }
+
+    fn member(&mut self, owner: &'a str, name: &'a MetaNode<Identifier>, static_: bool) {
+        let declares = |class: &Class| {
+            class.members().any(|member| match member {
+                ClassMember::Field(field) => field.name.node.name == name.node.name && field.visibility.node.is_static() == static_,
+                ClassMember::Method(method) => method.name.node.name == name.node.name && method.visibility.node.is_static() == static_,
+                _ => false,
+            })
+        };
+        let class = self.table.ancestors(owner)
+            .filter(|entry| declares(entry.class))
+            .last()
+            .map_or(owner, |entry| entry.name());
+        self.push(Symbol::Member { class, name: &name.node.name, static_ }, name);
+    }
+
+    fn is_class(&self, name: &MetaNode<Identifier>) -> bool {
+        name.location
+            .and_then(|location| self.resolution.reference_at(self.file, location.start.offset)?.binding)
+            .is_some_and(|binding| self.resolution.bindings[binding].kind == BindingKind::Class)
+    }
+}
+
+impl<'a> Visitor<'a> for MemberCollector<'_, '_, 'a> {
+    fn visit_class(&mut self, class: &'a Class) {
+        if let Some(extends) = &class.extends {
+            self.class_name(extends);
+        }
+        let outer = self.class.replace(&class.name.node.name);
+        walk_class(self, class);
+        self.class = outer;
+    }
+
+    fn visit_field(&mut self, field: &'a Field) {
+        if let Some(class) = self.class {
+            self.member(class, &field.name, field.visibility.node.is_static());
+        }
+        walk_field(self, field);
+    }
+
+    fn visit_method(&mut self, method: &'a Method) {
+        if let Some(class) = self.class {
+            self.member(class, &method.name, method.visibility.node.is_static());
+        }
+        walk_method(self, method);
+    }
+
+    fn visit_type(&mut self, type_: &'a MetaNode<Type>) {
+        self.class_name(&type_.node.type_);
+        for generic in &type_.node.generics {
+            self.visit_type(generic);
+        }
+        if let Some(alternative) = &type_.node.alternative {
+            self.visit_type(alternative);
+        }
+    }
+
+    fn visit_member(&mut self, member: &'a Member) {
+        for (index, right) in member.right.iter().enumerate() {
+            let (MemberRight::Dot(name) | MemberRight::OptionalDot(name)) = right.node.as_ref() else {
+                continue;
+            };
+            let IdentifierOrMember::Identifier(name) = name.node.as_ref() else {
+                continue;
+            };
+            // Only the first access on `this`, `super`, `class` or a class name has a known object
+            let resolved = match (&member.left, index) {
+                (PrimaryExpr::IdentifierOrMember(object), 0) => match object.node.as_ref() {
+                    IdentifierOrMember::Class => {
+                        self.class.and_then(|class| self.table.find_member(class, &name.node.name, true))
+                    }
+                    IdentifierOrMember::Identifier(class) if !self.is_class(class) => None,
+                    object => self.table.resolve_access(self.class, object, &name.node.name).ok(),
+                },
+                _ => None,
+            };
+            match resolved {
+                Some(resolved) => {
+                    let static_ = resolved.member.visibility().is_static();
+                    self.member(resolved.owner.name(), name, static_);
+                }
+                None => self.untyped.push(UntypedAccess { name: &name.node.name, file: self.file, location: name.location }),
+            }
+        }
+        walk_member(self, member);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::project::tests::project;
+
+    use super::*;
+
+    /// Renames the symbol at the first `at` of the file `path`, giving the files that changed.
+    fn renamed(files: &[(&str, &str)], path: &str, at: &str, new_name: &str) -> Result<Vec<(String, String)>, RenameError> {
+        let source = files.iter().find(|(name, _)| *name == path).unwrap().1;
+        let position = SourcePosition { path, offset: source.find(at).unwrap() };
+        let edits = rename(&project(files), position, new_name)?;
+        Ok(edits.iter()
+            .map(|file_edits| {
+                let source = files.iter().find(|(name, _)| *name == file_edits.path).unwrap().1;
+                (file_edits.path.clone(), file_edits.apply(source))
+            })
+            .collect())
+    }
+
+    fn renamed_main(source: &str, at: &str, new_name: &str) -> String {
+        renamed(&[("main", source)], "main", at, new_name).unwrap()[0].1.clone()
+    }
+
+    fn refused(source: &str, at: &str, new_name: &str) -> String {
+        renamed(&[("main", source)], "main", at, new_name).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn test_rename_locals() {
+        assert_eq!(
+            renamed_main("function f(a) { var b = a; /* a */ return b + a; }", "a)", "x"),
+            "function f(x) { var b = x; /* a */ return b + x; }"
+        );
+        assert_eq!(renamed_main("var a = 1; var o = {a: a};", "a =", "b"), "var b = 1; var o = {a: b};");
+        assert_eq!(
+            renamed_main("var a = 1; var f = x => a + x; for (var k : var v in a) { v; }", "v in", "value"),
+            "var a = 1; var f = x => a + x; for (var k : var value in a) { value; }"
+        );
+    }
+
+    #[test]
+    fn test_rename_across_includes() {
+        let files = [
+            ("main", "include('lib'); var v = f(); A x = new A(); global A g;"),
+            ("lib", "function f() { return 1; } class A { static A make() { return new A(); } } class B extends A {}"),
+        ];
+        assert_eq!(renamed(&files, "main", "f()", "g2").unwrap(), [
+            ("main".to_string(), "include('lib'); var v = g2(); A x = new A(); global A g;".to_string()),
+            ("lib".to_string(), "function g2() { return 1; } class A { static A make() { return new A(); } } class B extends A {}".to_string()),
+        ]);
+        assert_eq!(renamed(&files, "lib", "A {", "Point").unwrap(), [
+            ("main".to_string(), "include('lib'); var v = f(); Point x = new Point(); global Point g;".to_string()),
+            ("lib".to_string(), "function f() { return 1; } class Point { static Point make() { return new Point(); } } class B extends Point {}".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn test_rename_members() {
+        let source = "class A { x; m() { return this.x; } static s() { return class.s; } } \
+            class B extends A { m() { return super.m() + this.x + A.s(); } }";
+        assert_eq!(
+            renamed_main(source, "m() { return super", "run"),
+            "class A { x; run() { return this.x; } static s() { return class.s; } } \
+            class B extends A { run() { return super.run() + this.x + A.s(); } }"
+        );
+        assert_eq!(
+            renamed_main(source, "x +", "y"),
+            "class A { y; m() { return this.y; } static s() { return class.s; } } \
+            class B extends A { m() { return super.m() + this.y + A.s(); } }"
+        );
+        assert_eq!(
+            renamed_main(source, "s()", "t"),
+            "class A { x; m() { return this.x; } static t() { return class.t; } } \
+            class B extends A { m() { return super.m() + this.x + A.t(); } }"
+        );
+    }
+
+    #[test]
+    fn test_refusals() {
+        assert_eq!(refused("var a;", "a;", "while"), "`while` is a keyword");
+        for keyword in ["throw", "catch", "final"] {
+            assert_eq!(refused("var a = 1; debug(a);", "a =", keyword), format!("`{}` is a keyword", keyword));
+        }
+        assert_eq!(refused("var a;", "a;", "1a"), "`1a` is not a valid name");
+        assert_eq!(refused("var a;", "a;", "this"), "`this` is not a valid name");
+        assert_eq!(refused("var a;", "var", "b"), "there is no symbol to rename here");
+        assert_eq!(refused("var a; var b;", "a;", "b"), "`b` is already declared at main:1:12");
+        assert_eq!(
+            refused("var a = 1; { var b = 2; a; }", "a =", "b"),
+            "the declaration of `b` at main:1:18 would hide the renamed symbol"
+        );
+        assert_eq!(
+            refused("var a = 1; { var b = 2; a; }", "b =", "a"),
+            "the use of `a` at main:1:25 would refer to the renamed symbol"
+        );
+        assert_eq!(
+            refused("function f() { return abs(1); }", "f()", "abs"),
+            "the use of `abs` at main:1:23 would refer to the renamed symbol"
+        );
+        assert_eq!(refused("class A { x; } class B extends A { y; }", "y", "x"), "`x` is already declared at main:1:11");
+        assert_eq!(
+            refused("class A { x; m() { return this.x; } } function f(o) { return o.x; }", "x;", "y"),
+            "`x` is accessed at main:1:64 on an object of unknown type"
+        );
+        assert_eq!(
+            refused("class A { m() { return this.m; } } var a = [new A()]; a[0]?.m();", "m()", "n"),
+            "`m` is accessed at main:1:61 on an object of unknown type"
+        );
+        // The name is free where the symbol is used
+        assert_eq!(renamed_main("var a = 1; function f() { var b; }", "a =", "b"), "var b = 1; function f() { var b; }");
+    }
+}
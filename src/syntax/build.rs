@@ -0,0 +1,240 @@
+use std::{collections::HashMap, ops::Range, rc::Rc};
+
+use crate::ast::{visit::*, *};
+
+use super::{lex, GreenElement, GreenNode, GreenToken, NodeKind, SyntaxNode, TokenKind};
+
+/// The syntax tree of `source`, with a node at each range of `file` parsed from it.
+pub(super) fn build(file: &File, source: &str) -> SyntaxNode {
+    let tokens = lex(source);
+
+    let mut collector = Collector { ranges: Vec::new() };
+    collector.visit_file(file);
+    let mut ranges = collector.ranges;
+    ranges.extend(groups(source, &tokens));
+    // Outer nodes first, and the typed AST before the groups it starts with
+    ranges.sort_by_key(|(_, range)| (range.start, std::cmp::Reverse(range.end)));
+
+    let mut interner = HashMap::new();
+    let mut stack: Vec<(NodeKind, usize, Vec<GreenElement>)> = vec![(NodeKind::File, source.len(), Vec::new())];
+    let mut ranges = ranges.into_iter().peekable();
+    let mut offset = 0;
+    // The end of the last token that is not trivia
+    let mut code_end = 0;
+    for (kind, length) in tokens {
+        while stack.len() > 1 && stack.last().unwrap().1 <= offset {
+            close(&mut stack);
+        }
+        // A node starts at its first token that is not trivia
+        while let Some((node, range)) = ranges.next_if(|(_, range)| !kind.is_trivia() && range.start <= offset) {
+            // The ranges starting inside a token or crossing their parent have no node
+            if range.start >= code_end && range.end > offset && range.end <= stack.last().unwrap().1 {
+                stack.push((node, range.end, Vec::new()));
+            }
+        }
+        if !kind.is_trivia() {
+            code_end = offset + length;
+        }
+        let text = &source[offset..offset + length];
+        let token = interner.entry((kind, text))
+            .or_insert_with(|| Rc::new(GreenToken { kind, text: text.to_string() }))
+            .clone();
+        stack.last_mut().unwrap().2.push(GreenElement::Token(token));
+        offset += length;
+    }
+    while stack.len() > 1 {
+        close(&mut stack);
+    }
+    let (kind, _, children) = stack.pop().unwrap();
+    SyntaxNode::new_root(Rc::new(GreenNode::new(kind, children)))
+}
+
+/// Ends the last node of the stack. The locations of the typed AST can end after the comments
+/// of their last child, which go to the parent instead.
+fn close(stack: &mut Vec<(NodeKind, usize, Vec<GreenElement>)>) {
+    let (kind, _, mut children) = stack.pop().unwrap();
+    let code = children.iter()
+        .rposition(|child| !matches!(child, GreenElement::Token(token) if token.kind.is_trivia()))
+        .map_or(0, |index| index + 1);
+    let trailing = children.split_off(code);
+    let parent = &mut stack.last_mut().unwrap().2;
+    if !children.is_empty() {
+        parent.push(GreenElement::Node(Rc::new(GreenNode::new(kind, children))));
+    }
+    parent.extend(trailing);
+}
+
+/// The matching brackets and the template strings of the tokens.
+fn groups(source: &str, tokens: &[(TokenKind, usize)]) -> Vec<(NodeKind, Range<usize>)> {
+    let mut groups = Vec::new();
+    let mut open: Vec<(NodeKind, usize)> = Vec::new();
+    let mut offset = 0;
+    for &(kind, length) in tokens {
+        let start = offset;
+        offset += length;
+        if kind != TokenKind::Punctuation {
+            continue;
+        }
+        let text = &source[start..offset];
+        let top = open.last().map(|(kind, _)| *kind);
+        let opening = match text {
+            "(" => Some(NodeKind::Parens),
+            "[" | "?[" => Some(NodeKind::Brackets),
+            "{" => Some(NodeKind::Braces),
+            "${" => Some(NodeKind::TemplateHole),
+            "`" if top != Some(NodeKind::Template) => Some(NodeKind::Template),
+            _ => None,
+        };
+        if let Some(kind) = opening {
+            open.push((kind, start));
+            continue;
+        }
+        let closing = match text {
+            "`" => NodeKind::Template,
+            ")" => NodeKind::Parens,
+            "]" => NodeKind::Brackets,
+            "}" if top == Some(NodeKind::TemplateHole) => NodeKind::TemplateHole,
+            "}" => NodeKind::Braces,
+            _ => continue,
+        };
+        // An unmatched closing bracket stays a plain token
+        if let Some(index) = open.iter().rposition(|(kind, _)| *kind == closing) {
+            let (_, group_start) = open[index];
+            open.truncate(index);
+            groups.push((closing, group_start..offset));
+        }
+    }
+    groups
+}
+
+fn location_range<T>(node: &MetaNode<T>) -> Option<Range<usize>> {
+    node.location.map(|location| location.start.offset..location.end.offset)
+}
+
+/// The ranges of the nodes of the typed AST given a node of their own.
+struct Collector {
+    ranges: Vec<(NodeKind, Range<usize>)>,
+}
+
+impl Collector {
+    fn push<T>(&mut self, kind: NodeKind, node: &MetaNode<T>) {
+        if let Some(range) = location_range(node) {
+            self.ranges.push((kind, range));
+        }
+    }
+
+    /// An expression over `range`. The operands the parser gives no location lie between the
+    /// edges of their expression and the operators next to them.
+    fn expression(&mut self, expression: &Expression, range: Option<Range<usize>>) {
+        if let Some(range) = &range {
+            self.ranges.push((NodeKind::Expression, range.clone()));
+        }
+        let start = range.as_ref().map(|range| range.start);
+        let end = range.as_ref().map(|range| range.end);
+        let between = |from: Option<usize>, to: Option<usize>| Some(from?..to?);
+        let before = |op: Option<Range<usize>>| between(start, op.map(|op| op.start));
+        match expression {
+            Expression::Assign(assign) => {
+                self.expression(&assign.left, before(assign.right.first().and_then(|(op, _)| location_range(op))));
+                for (_, right) in &assign.right {
+                    self.visit_expression_node(right);
+                }
+            }
+            Expression::LogicalOr(e) => self.binary(&e.left, &e.right, start),
+            Expression::LogicalXor(e) => self.binary(&e.left, &e.right, start),
+            Expression::LogicalAnd(e) => self.binary(&e.left, &e.right, start),
+            Expression::Relation(e) => self.binary(&e.left, &e.right, start),
+            Expression::InstanceOf(e) => self.binary(&e.left, &e.right, start),
+            Expression::Shift(e) => self.binary(&e.left, &e.right, start),
+            Expression::BinOr(e) => self.binary(&e.left, &e.right, start),
+            Expression::BinXor(e) => self.binary(&e.left, &e.right, start),
+            Expression::BinAnd(e) => self.binary(&e.left, &e.right, start),
+            Expression::Add(e) => self.binary(&e.left, &e.right, start),
+            Expression::Mult(e) => self.binary(&e.left, &e.right, start),
+            Expression::TypeConversion(conversion) => {
+                self.expression(&conversion.expression, before(location_range(&conversion.as_)));
+                self.visit_type(&conversion.type_);
+            }
+            Expression::PreUpdate(update) => {
+                self.expression(&update.expr, between(location_range(&update.op).map(|op| op.end), end));
+            }
+            Expression::Unary(unary) => {
+                self.expression(&unary.expr, between(location_range(&unary.op).map(|op| op.end), end));
+            }
+            Expression::PostUpdate(update) => self.expression(&update.expr, before(location_range(&update.op))),
+            _ => walk_expression(self, expression),
+        }
+    }
+
+    fn binary<Op: ParseInto>(&mut self, left: &Expression, right: &[(MetaNode<Op>, MetaNode<Expression>)], start: Option<usize>) {
+        let op = right.first().and_then(|(op, _)| location_range(op));
+        self.expression(left, start.zip(op).map(|(start, op)| start..op.start));
+        for (_, right) in right {
+            self.visit_expression_node(right);
+        }
+    }
+}
+
+impl<'ast> Visitor<'ast> for Collector {
+    fn visit_global_statement(&mut self, statement: &'ast MetaNode<GlobalStatement>) {
+        self.push(NodeKind::GlobalStatement, statement);
+        walk_global_statement(self, statement);
+    }
+
+    fn visit_class(&mut self, class: &'ast Class) {
+        if let ClassBody::Members(members) = class.body.node.as_ref() {
+            for member in members {
+                self.push(NodeKind::ClassMember, member);
+            }
+        }
+        walk_class(self, class);
+    }
+
+    fn visit_parameter(&mut self, parameter: &'ast MetaNode<Parameter>) {
+        self.push(NodeKind::Parameter, parameter);
+        walk_parameter(self, parameter);
+    }
+
+    fn visit_statement(&mut self, statement: &'ast MetaNode<Statement>) {
+        self.push(NodeKind::Statement, statement);
+        walk_statement(self, statement);
+    }
+
+    fn visit_for_header(&mut self, header: &'ast ForHeader) {
+        if let ForHeader::ForIter(iter) = header {
+            if let Some(init) = &iter.init {
+                if let VarDecOrExpr::Expression(expression) = init.node.as_ref() {
+                    self.expression(expression, location_range(init));
+                    for expression in iter.condition.iter().chain(&iter.increment) {
+                        self.visit_expression_node(expression);
+                    }
+                    return;
+                }
+            }
+        }
+        walk_for_header(self, header);
+    }
+
+    fn visit_expression_node(&mut self, expression: &'ast MetaNode<Expression>) {
+        self.expression(&expression.node, location_range(expression));
+    }
+
+    fn visit_expression(&mut self, expression: &'ast Expression) {
+        self.expression(expression, None);
+    }
+
+    fn visit_identifier(&mut self, identifier: &'ast MetaNode<Identifier>) {
+        self.push(NodeKind::Name, identifier);
+    }
+
+    fn visit_type(&mut self, type_: &'ast MetaNode<Type>) {
+        self.push(NodeKind::Type, type_);
+        self.visit_identifier(&type_.node.type_);
+        for generic in &type_.node.generics {
+            self.visit_type(generic);
+        }
+        if let Some(alternative) = &type_.node.alternative {
+            self.visit_type(alternative);
+        }
+    }
+}
@@ -0,0 +1,149 @@
+use crate::ast::{comment::parse_comment_or_whitespace, scan::string_length, Span, KEYWORDS};
+
+use super::TokenKind;
+
+/// The operators of more than one character, the longest first.
+const OPERATORS: [&str; 27] = [
+    ">>>=", ">>>", "<<=", ">>=", "<<", ">>", "<=", ">=", "==", "!=", "&&", "||", "++", "--",
+    "+=", "-=", "*=", "/=", "%=", "&=", "^=", "|=", "=>", "->", "?.", "?[", "${",
+];
+
+#[derive(Clone, Copy)]
+enum Mode {
+    Code { braces: usize },
+    Template,
+}
+
+/// Splits `source` into tokens covering all of it, by kind and length. Every character ends up
+/// in a token: what cannot start one is a punctuation of its own.
+pub fn lex(source: &str) -> Vec<(TokenKind, usize)> {
+    let mut tokens = Vec::new();
+    let mut modes = vec![Mode::Code { braces: 0 }];
+    let mut offset = 0;
+    while let Some(c) = source[offset..].chars().next() {
+        let rest = &source[offset..];
+        let mode = *modes.last().unwrap();
+        let (kind, length) = match mode {
+            Mode::Template => match c {
+                '`' => {
+                    modes.pop();
+                    (TokenKind::Punctuation, 1)
+                }
+                '$' if rest.starts_with("${") => {
+                    modes.push(Mode::Code { braces: 0 });
+                    (TokenKind::Punctuation, 2)
+                }
+                _ => (TokenKind::TemplateText, template_text_length(rest)),
+            },
+            Mode::Code { .. } if c.is_whitespace() => {
+                (TokenKind::Whitespace, rest.find(|c: char| !c.is_whitespace()).unwrap_or(rest.len()))
+            }
+            Mode::Code { .. } if rest.starts_with("//") || rest.starts_with("/*") => {
+                let (after, _) = parse_comment_or_whitespace(Span::new_extra(rest, "")).expect("trivia never fails");
+                match (after.location_offset(), rest.starts_with("//")) {
+                    // An unterminated `/*` is not a comment
+                    (0, _) => (TokenKind::Punctuation, 1),
+                    (length, true) => (TokenKind::LineComment, length),
+                    (length, false) => (TokenKind::BlockComment, length),
+                }
+            }
+            Mode::Code { .. } if c.is_ascii_digit() => (TokenKind::Number, number_length(rest)),
+            Mode::Code { .. } if is_word(c) => {
+                let length = rest.find(|c| !is_word(c)).unwrap_or(rest.len());
+                match KEYWORDS.contains(&&rest[..length]) {
+                    true => (TokenKind::Keyword, length),
+                    false => (TokenKind::Identifier, length),
+                }
+            }
+            Mode::Code { .. } if c == '\'' || c == '"' => (TokenKind::String, string_length(rest, c)),
+            Mode::Code { braces } => {
+                let length = OPERATORS.iter().find(|operator| rest.starts_with(*operator)).map_or(c.len_utf8(), |operator| operator.len());
+                match &rest[..length] {
+                    "`" => modes.push(Mode::Template),
+                    "{" | "${" => *modes.last_mut().unwrap() = Mode::Code { braces: braces + 1 },
+                    "}" if braces == 0 && modes.len() > 1 => {
+                        modes.pop();
+                    }
+                    "}" => *modes.last_mut().unwrap() = Mode::Code { braces: braces.saturating_sub(1) },
+                    _ => {}
+                }
+                (TokenKind::Punctuation, length)
+            }
+        };
+        tokens.push((kind, length));
+        offset += length;
+    }
+    tokens
+}
+
+fn is_word(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$' || !c.is_ascii()
+}
+
+/// The length of the text of a template string starting `source`, up to a hole or its end.
+fn template_text_length(source: &str) -> usize {
+    let mut chars = source.char_indices().peekable();
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '`' => return index,
+            '$' if source[index..].starts_with("${") => return index,
+            _ => {}
+        }
+    }
+    source.len()
+}
+
+/// The length of the number starting `source`: its digits, letters and underscores, a dot
+/// followed by a digit, and the sign of a decimal exponent.
+fn number_length(source: &str) -> usize {
+    let bytes = source.as_bytes();
+    let decimal = !(source.len() > 1 && bytes[0] == b'0' && bytes[1].is_ascii_alphabetic());
+    let mut length = 0;
+    while length < bytes.len() {
+        let c = bytes[length];
+        let next = bytes.get(length + 1);
+        length += match c {
+            c if c.is_ascii_alphanumeric() || c == b'_' => 1,
+            b'.' if next.is_some_and(u8::is_ascii_digit) => 1,
+            b'+' | b'-' if decimal && matches!(bytes[length - 1], b'e' | b'E') => 1,
+            _ => break,
+        };
+    }
+    length
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(source: &str) -> Vec<(TokenKind, &str)> {
+        let mut offset = 0;
+        lex(source).into_iter()
+            .map(|(kind, length)| {
+                offset += length;
+                (kind, &source[offset - length..offset])
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_lex() {
+        use TokenKind::*;
+        assert_eq!(tokens("var x >>>= 1.5e-3; // end"), [
+            (Keyword, "var"), (Whitespace, " "), (Identifier, "x"), (Whitespace, " "), (Punctuation, ">>>="),
+            (Whitespace, " "), (Number, "1.5e-3"), (Punctuation, ";"), (Whitespace, " "), (LineComment, "// end"),
+        ]);
+        assert_eq!(tokens("`a\\` ${ {b: '}'} }c`"), [
+            (Punctuation, "`"), (TemplateText, "a\\` "), (Punctuation, "${"), (Whitespace, " "), (Punctuation, "{"),
+            (Identifier, "b"), (Punctuation, ":"), (Whitespace, " "), (String, "'}'"), (Punctuation, "}"),
+            (Whitespace, " "), (Punctuation, "}"), (TemplateText, "c"), (Punctuation, "`"),
+        ]);
+        assert_eq!(tokens("0x1e+2 /* a"), [
+            (Number, "0x1e"), (Punctuation, "+"), (Number, "2"), (Whitespace, " "), (Punctuation, "/"),
+            (Punctuation, "*"), (Whitespace, " "), (Identifier, "a"),
+        ]);
+    }
+}
@@ -0,0 +1,400 @@
+//! A lossless concrete syntax tree, where every byte of the source is in exactly one token,
+//! comments and whitespace included, so that the tree prints back the exact source. `parse`
+//! builds it from the source along with the typed AST, whose nodes then read their tokens, text
+//! and syntax node from it through their locations.
+//!
+//! The green tree holds the kinds and texts only, and shares identical tokens. The red tree
+//! wraps it with offsets and parents, and is what editor features walk. Its nodes are the
+//! statements, class members, parameters, expressions down to their operands, names and types
+//! of the typed AST, along with the bracket pairs and template strings of the source.
+
+mod build;
+mod lexer;
+
+pub use lexer::lex;
+
+use std::{fmt::Display, ops::Range, rc::Rc};
+
+use crate::{analysis::{parse_source, ProjectError}, ast::{File, MetaNode}};
+
+/// A file parsed along with the syntax tree of its source.
+#[derive(Debug, Clone)]
+pub struct Parse {
+    pub file: File,
+    pub syntax: SyntaxNode,
+}
+
+/// Parses `source`, read from `path`, into its typed AST and its syntax tree.
+pub fn parse(path: &str, source: &str) -> Result<Parse, ProjectError> {
+    let file = parse_source(path, source)?;
+    let syntax = build::build(&file, source);
+    Ok(Parse { file, syntax })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TokenKind {
+    Whitespace,
+    LineComment,
+    BlockComment,
+    Identifier,
+    Keyword,
+    Number,
+    /// A string between single or double quotes
+    String,
+    /// The text between the holes of a template string
+    TemplateText,
+    Punctuation,
+}
+
+impl TokenKind {
+    pub fn is_trivia(self) -> bool {
+        matches!(self, TokenKind::Whitespace | TokenKind::LineComment | TokenKind::BlockComment)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NodeKind {
+    File,
+    GlobalStatement,
+    Statement,
+    ClassMember,
+    Parameter,
+    /// An expression of the typed AST, down to its operands
+    Expression,
+    /// An identifier of the typed AST
+    Name,
+    Type,
+    /// From `(` to `)`
+    Parens,
+    /// From `[` or `?[` to `]`
+    Brackets,
+    /// From `{` to `}`
+    Braces,
+    /// From `` ` `` to `` ` ``
+    Template,
+    /// From `${` to `}`
+    TemplateHole,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GreenToken {
+    pub kind: TokenKind,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GreenNode {
+    pub kind: NodeKind,
+    /// The length of its text
+    pub width: usize,
+    pub children: Vec<GreenElement>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum GreenElement {
+    Node(Rc<GreenNode>),
+    Token(Rc<GreenToken>),
+}
+
+impl GreenNode {
+    pub fn new(kind: NodeKind, children: Vec<GreenElement>) -> Self {
+        let width = children.iter().map(GreenElement::width).sum();
+        GreenNode { kind, width, children }
+    }
+}
+
+impl GreenElement {
+    pub fn width(&self) -> usize {
+        match self {
+            GreenElement::Node(node) => node.width,
+            GreenElement::Token(token) => token.text.len(),
+        }
+    }
+}
+
+/// A node of the green tree at its place in the file.
+#[derive(Debug, Clone)]
+pub struct SyntaxNode(Rc<NodeData>);
+
+#[derive(Debug)]
+struct NodeData {
+    green: Rc<GreenNode>,
+    offset: usize,
+    parent: Option<SyntaxNode>,
+}
+
+/// A token of the green tree at its place in the file.
+#[derive(Debug, Clone)]
+pub struct SyntaxToken {
+    green: Rc<GreenToken>,
+    offset: usize,
+    parent: SyntaxNode,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyntaxElement {
+    Node(SyntaxNode),
+    Token(SyntaxToken),
+}
+
+impl SyntaxNode {
+    pub fn new_root(green: Rc<GreenNode>) -> Self {
+        SyntaxNode(Rc::new(NodeData { green, offset: 0, parent: None }))
+    }
+
+    pub fn kind(&self) -> NodeKind {
+        self.0.green.kind
+    }
+
+    pub fn green(&self) -> &Rc<GreenNode> {
+        &self.0.green
+    }
+
+    pub fn range(&self) -> Range<usize> {
+        self.0.offset..self.0.offset + self.0.green.width
+    }
+
+    pub fn parent(&self) -> Option<&SyntaxNode> {
+        self.0.parent.as_ref()
+    }
+
+    /// The node itself, then its parent up to the root.
+    pub fn ancestors(&self) -> impl Iterator<Item = SyntaxNode> {
+        std::iter::successors(Some(self.clone()), |node| node.parent().cloned())
+    }
+
+    pub fn children_with_tokens(&self) -> Vec<SyntaxElement> {
+        let mut offset = self.0.offset;
+        self.0.green.children.iter()
+            .map(|child| {
+                let start = offset;
+                offset += child.width();
+                match child {
+                    GreenElement::Node(green) => SyntaxElement::Node(SyntaxNode(Rc::new(NodeData {
+                        green: green.clone(),
+                        offset: start,
+                        parent: Some(self.clone()),
+                    }))),
+                    GreenElement::Token(green) => SyntaxElement::Token(SyntaxToken {
+                        green: green.clone(),
+                        offset: start,
+                        parent: self.clone(),
+                    }),
+                }
+            })
+            .collect()
+    }
+
+    pub fn children(&self) -> Vec<SyntaxNode> {
+        self.children_with_tokens().into_iter()
+            .filter_map(|child| match child {
+                SyntaxElement::Node(node) => Some(node),
+                SyntaxElement::Token(_) => None,
+            })
+            .collect()
+    }
+
+    /// Every token under the node, in source order.
+    pub fn tokens(&self) -> Vec<SyntaxToken> {
+        let mut tokens = Vec::new();
+        for child in self.children_with_tokens() {
+            match child {
+                SyntaxElement::Node(node) => tokens.extend(node.tokens()),
+                SyntaxElement::Token(token) => tokens.push(token),
+            }
+        }
+        tokens
+    }
+
+    /// The token containing `offset`, the one starting there when two tokens meet.
+    pub fn token_at(&self, offset: usize) -> Option<SyntaxToken> {
+        for child in self.children_with_tokens() {
+            match child {
+                SyntaxElement::Node(node) if node.range().contains(&offset) => return node.token_at(offset),
+                SyntaxElement::Token(token) if token.range().contains(&offset) => return Some(token),
+                _ => {}
+            }
+        }
+        None
+    }
+
+    /// The deepest node containing all of `range`.
+    pub fn covering(&self, range: Range<usize>) -> Option<SyntaxNode> {
+        if range.start < self.range().start || range.end > self.range().end {
+            return None;
+        }
+        Some(self.children().into_iter()
+            .find_map(|child| child.covering(range.clone()))
+            .unwrap_or_else(|| self.clone()))
+    }
+
+    pub fn text(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl PartialEq for SyntaxNode {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0.green, &other.0.green) && self.0.offset == other.0.offset
+    }
+}
+
+impl Eq for SyntaxNode {}
+
+impl Display for SyntaxNode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fn write(node: &GreenNode, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            for child in &node.children {
+                match child {
+                    GreenElement::Node(node) => write(node, f)?,
+                    GreenElement::Token(token) => write!(f, "{}", token.text)?,
+                }
+            }
+            Ok(())
+        }
+        write(&self.0.green, f)
+    }
+}
+
+impl SyntaxToken {
+    pub fn kind(&self) -> TokenKind {
+        self.green.kind
+    }
+
+    pub fn text(&self) -> &str {
+        &self.green.text
+    }
+
+    pub fn range(&self) -> Range<usize> {
+        self.offset..self.offset + self.green.text.len()
+    }
+
+    pub fn parent(&self) -> &SyntaxNode {
+        &self.parent
+    }
+}
+
+impl PartialEq for SyntaxToken {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.green, &other.green) && self.offset == other.offset
+    }
+}
+
+impl Eq for SyntaxToken {}
+
+impl Display for SyntaxToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.green.text)
+    }
+}
+
+impl<Node> MetaNode<Node> {
+    /// The deepest node of the syntax tree `root` containing this node, comments excluded.
+    pub fn syntax(&self, root: &SyntaxNode) -> Option<SyntaxNode> {
+        let tokens = self.tokens(root);
+        root.covering(tokens.first()?.range().start..tokens.last()?.range().end)
+    }
+
+    /// The tokens of this node in the syntax tree `root`, without the comments after it.
+    pub fn tokens(&self, root: &SyntaxNode) -> Vec<SyntaxToken> {
+        let Some(location) = self.location else { return Vec::new() };
+        let range = location.start.offset..location.end.offset;
+        let Some(node) = root.covering(range.clone()) else { return Vec::new() };
+        let mut tokens: Vec<_> = node.tokens().into_iter()
+            .filter(|token| token.range().start >= range.start && token.range().end <= range.end)
+            .collect();
+        // Its location can end after the comments of its last child
+        while tokens.last().is_some_and(|token| token.kind().is_trivia()) {
+            tokens.pop();
+        }
+        tokens
+    }
+
+    /// The source text of this node in the syntax tree `root`, without the comments around it.
+    pub fn text(&self, root: &SyntaxNode) -> String {
+        self.tokens(root).iter().map(SyntaxToken::text).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outline(node: &SyntaxNode, depth: usize, lines: &mut Vec<String>) {
+        lines.push(format!("{}{:?} {:?}", "  ".repeat(depth), node.kind(), node.text()));
+        for child in node.children() {
+            outline(&child, depth + 1, lines);
+        }
+    }
+
+    #[test]
+    fn test_lossless() {
+        let source = "include('lib'); // lib\n\n/** Doc */\nclass A extends B {\n\tpublic integer x = 1;\n\tf(@a, b = [1, 2]) { return `${a}${f(`${b}`)}`; }\n}\n\
+            var  m=[1:'a'] /* map */\nfunction g(Array<integer | real>? c) => string { if (c) { c += 1 } return c }\n// end";
+        let root = parse("main", source).unwrap().syntax;
+        assert_eq!(root.to_string(), source);
+        assert_eq!(root.range(), 0..source.len());
+        let tokens: String = root.tokens().iter().map(SyntaxToken::text).collect();
+        assert_eq!(tokens, source);
+
+        let mut lines = Vec::new();
+        outline(&root, 0, &mut lines);
+        let count = |kind: &str| lines.iter().filter(|line| line.trim_start().starts_with(kind)).count();
+        assert_eq!((count("Template "), count("TemplateHole "), count("ClassMember "), count("Type ")), (2, 3, 2, 5));
+    }
+
+    #[test]
+    fn test_structure() {
+        let root = parse("main", "// a\nfunction  f(integer x){return(x+1) ;}\n").unwrap().syntax;
+        let mut lines = Vec::new();
+        outline(&root, 0, &mut lines);
+        assert_eq!(lines, [
+            "File \"// a\\nfunction  f(integer x){return(x+1) ;}\\n\"",
+            "  GlobalStatement \"function  f(integer x){return(x+1) ;}\"",
+            "    Name \"f\"",
+            "    Parens \"(integer x)\"",
+            "      Parameter \"integer x\"",
+            "        Type \"integer\"",
+            "          Name \"integer\"",
+            "        Name \"x\"",
+            "    Braces \"{return(x+1) ;}\"",
+            "      Statement \"return(x+1) ;\"",
+            "        Expression \"(x+1)\"",
+            "          Parens \"(x+1)\"",
+            "            Expression \"x+1\"",
+            "              Expression \"x\"",
+            "                Name \"x\"",
+            "              Expression \"1\"",
+        ]);
+    }
+
+    #[test]
+    fn test_tokens() {
+        let source = "var total = f(a, b); // sum\n";
+        let Parse { file, syntax: root } = parse("main", source).unwrap();
+
+        let token = root.token_at(source.find("a,").unwrap()).unwrap();
+        assert_eq!((token.kind(), token.text(), token.range()), (TokenKind::Identifier, "a", 14..15));
+        let kinds: Vec<_> = token.parent().ancestors().map(|node| node.kind()).collect();
+        assert_eq!(kinds, [
+            NodeKind::Name, NodeKind::Expression, NodeKind::Parens, NodeKind::Expression, NodeKind::GlobalStatement, NodeKind::File,
+        ]);
+        assert_eq!(root.token_at(source.len() - 2).unwrap().kind(), TokenKind::LineComment);
+        assert_eq!(root.token_at(source.len()), None);
+
+        // The punctuation the typed AST leaves out is still there
+        let statement = &file.statements[0];
+        let texts: Vec<_> = statement.tokens(&root).iter()
+            .filter(|token| !token.kind().is_trivia())
+            .map(|token| token.text().to_string())
+            .collect();
+        assert_eq!(texts, ["var", "total", "=", "f", "(", "a", ",", "b", ")", ";"]);
+        assert_eq!(statement.syntax(&root).unwrap().kind(), NodeKind::GlobalStatement);
+        assert_eq!(statement.text(&root), "var total = f(a, b);");
+
+        // Identical tokens share their green token
+        let commas: Vec<_> = parse("main", "f(a, a);").unwrap().syntax.tokens().into_iter().filter(|token| token.text() == "a").collect();
+        assert!(Rc::ptr_eq(&commas[0].green, &commas[1].green));
+        assert_ne!(commas[0], commas[1]);
+    }
+}
@@ -9,7 +9,7 @@ use nom::{
     IResult,
 };
 
-use crate::ast::{ParseInto, Span};
+use crate::ast::{File, ParseInto, Span};
 
 pub fn kw<'a>(keyword: &'a str) -> impl FnMut(Span<'a>) -> IResult<Span<'a>, Span<'a>> {
     recognize(pair(tag(keyword), not(alt((alphanumeric1, tag("_"))))))
@@ -24,4 +24,12 @@ pub fn test_remains_same<T: ParseInto<Output = U>, U: Display + Debug>(input: &s
 
     assert!(rem.is_empty(), "Expected no remaining input for {}, got {:?}", input, rem);
     assert_eq!(result.to_string(), expected, "Expected {}, got {}", expected, result);
-}
\ No newline at end of file
+}
+
+pub fn parse_file(input: &str) -> File {
+    let input = Span::new_extra(input, "test_input");
+    let parse_result = File::parse(input);
+
+    assert!(parse_result.is_ok(), "Expected successful parse for {}, got {:?}", input, parse_result);
+    *parse_result.unwrap().1.node
+}